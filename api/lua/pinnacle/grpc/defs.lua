@@ -99,6 +99,9 @@ local pinnacle_output_v0alpha1_Transform = {
 ---@field scale number?
 ---@field transform pinnacle.output.v0alpha1.Transform?
 ---@field serial integer?
+---@field keyboard_focus_stack_window_ids integer[]?
+---@field powered boolean?
+---@field vrr boolean?
 
 -- Window
 
@@ -128,6 +131,7 @@ local pinnacle_output_v0alpha1_Transform = {
 ---@class pinnacle.window.v0alpha1.MoveToTagRequest
 ---@field window_id integer?
 ---@field tag_id integer?
+---@field follow boolean?
 
 ---@class pinnacle.window.v0alpha1.SetTagRequest
 ---@field window_id integer?
@@ -178,6 +182,8 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 ---@field classes string[]?
 ---@field titles string[]?
 ---@field tags integer[]?
+---@field class_regexes string[]?
+---@field title_regexes string[]?
 
 ---@class pinnacle.window.v0alpha1.WindowRule
 ---@field output string?
@@ -188,6 +194,9 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 ---@field y integer?
 ---@field width integer?
 ---@field height integer?
+---@field fullscreen_output string?
+---@field opacity number?
+---@field maximize_covers_exclusive_zones boolean?
 
 -- Tag
 
@@ -221,6 +230,10 @@ local pinnacle_window_v0alpha1_FullscreenOrMaximized = {
 ---@field name string?
 ---@field output_name string?
 ---@field window_ids integer[]?
+---@field layout string?
+---@field inner_gap integer?
+---@field outer_gap integer?
+---@field master_factor number?
 
 -- Input
 
@@ -231,12 +244,31 @@ local pinnacle_input_v0alpha1_Modifier = {
     MODIFIER_CTRL = 2,
     MODIFIER_ALT = 3,
     MODIFIER_SUPER = 4,
+    MODIFIER_HYPER = 5,
+    MODIFIER_META = 6,
+    MODIFIER_LEFT_SHIFT = 7,
+    MODIFIER_RIGHT_SHIFT = 8,
+    MODIFIER_LEFT_CTRL = 9,
+    MODIFIER_RIGHT_CTRL = 10,
+    MODIFIER_LEFT_ALT = 11,
+    MODIFIER_RIGHT_ALT = 12,
+    MODIFIER_LEFT_SUPER = 13,
+    MODIFIER_RIGHT_SUPER = 14,
+    MODIFIER_LEFT_HYPER = 15,
+    MODIFIER_RIGHT_HYPER = 16,
+    MODIFIER_LEFT_META = 17,
+    MODIFIER_RIGHT_META = 18,
 }
 
 ---@class pinnacle.input.v0alpha1.SetKeybindRequest
 ---@field modifiers pinnacle.input.v0alpha1.Modifier[]?
 ---@field raw_code integer?
 ---@field xkb_name string?
+---@field condition pinnacle.input.v0alpha1.KeybindCondition?
+
+---@class pinnacle.input.v0alpha1.KeybindCondition
+---@field tag_id integer?
+---@field layout string?
 
 ---@class pinnacle.input.v0alpha1.SetKeybindResponse
 
@@ -327,6 +359,7 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 ---@field stderr string?
 ---@field exit_code integer?
 ---@field exit_message string?
+---@field spawned boolean?
 
 ---@class pinnacle.process.v0alpha1.SetEnvRequest
 ---@field key string?
@@ -338,6 +371,7 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 ---@field request_id integer?
 ---@field output_name string?
 ---@field geometries pinnacle.v0alpha1.Geometry[]?
+---@field master_factor number?
 
 ---@class pinnacle.layout.v0alpha1.LayoutRequest.ExplicitLayout
 ---@field output_name string?
@@ -353,6 +387,8 @@ local pinnacle_input_v0alpha1_SetLibinputSettingRequest_TapButtonMap = {
 ---@field tag_ids integer[]?
 ---@field output_width integer?
 ---@field output_height integer?
+---@field master_factor number?
+---@field layout string?
 
 -- Render
 
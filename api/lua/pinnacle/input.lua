@@ -14,6 +14,20 @@ local modifier_values = {
     ctrl = 2,
     alt = 3,
     super = 4,
+    hyper = 5,
+    meta = 6,
+    left_shift = 7,
+    right_shift = 8,
+    left_ctrl = 9,
+    right_ctrl = 10,
+    left_alt = 11,
+    right_alt = 12,
+    left_super = 13,
+    right_super = 14,
+    left_hyper = 15,
+    right_hyper = 16,
+    left_meta = 17,
+    right_meta = 18,
 }
 
 local mouse_button_values = {
@@ -10,6 +10,8 @@ local mfloor = math.floor
 ---@field tags TagHandle[]
 ---@field output_width integer
 ---@field output_height integer
+---@field master_factor number? The master factor of the first focused tag, if it was set
+---@field layout string? The name of the layout the first focused tag uses, if it was set
 
 ---A layout generator.
 ---@class LayoutGenerator
@@ -120,7 +122,7 @@ function MasterStack:layout(args)
     rect = rect:split_at("vertical", 0, outer_gaps)
     rect = rect:split_at("vertical", width - outer_gaps, outer_gaps)
 
-    local master_factor = math.max(math.min(self.master_factor, 0.9), 0.1)
+    local master_factor = math.max(math.min(args.master_factor or self.master_factor, 0.9), 0.1)
     if win_count <= self.master_count then
         master_factor = 1
     end
@@ -1001,6 +1003,8 @@ function layout.set_manager(manager)
             tags = tag_handles,
             output_width = response.output_width,
             output_height = response.output_height,
+            master_factor = response.master_factor,
+            layout = response.layout,
         }
 
         local a = manager:get_active(args)
@@ -1011,11 +1015,18 @@ function layout.set_manager(manager)
             os.exit(1)
         end
 
+        -- Let relative master factor changes start from the factor this layout used
+        local layout_master_factor = nil
+        if type(a.master_factor) == "number" then
+            layout_master_factor = math.max(math.min(a.master_factor, 0.9), 0.1)
+        end
+
         local body = protobuf.encode(".pinnacle.layout.v0alpha1.LayoutRequest", {
             geometries = {
                 request_id = request_id,
                 geometries = geos,
                 output_name = response.output_name,
+                master_factor = layout_master_factor,
             },
         })
 
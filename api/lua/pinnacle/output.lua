@@ -893,6 +893,8 @@ end
 ---@field transform Transform?
 ---@field serial integer?
 ---@field keyboard_focus_stack WindowHandle[]
+---@field powered boolean?
+---@field vrr boolean?
 
 ---Get all properties of this output.
 ---
@@ -1067,6 +1069,15 @@ function OutputHandle:serial()
     return self:props().serial
 end
 
+---Get whether this output is powered on.
+---
+---Shorthand for `handle:props().powered`.
+---
+---@return boolean?
+function OutputHandle:powered()
+    return self:props().powered
+end
+
 ---Get this output's keyboard focus stack.
 ---
 ---This includes *all* windows on the output, even those on inactive tags.
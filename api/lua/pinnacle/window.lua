@@ -132,6 +132,8 @@ end
 ---@field classes string[]?
 ---@field titles string[]?
 ---@field tags TagHandle[]?
+---@field class_regexes string[]? Regexes matched against the class (the app id for Wayland windows)
+---@field title_regexes string[]? Regexes matched against the title
 
 ---@class WindowRule
 ---@field output OutputHandle?
@@ -369,15 +371,13 @@ end
 ---       v +y
 ---```
 ---
----*Tiled windows will not reflect these changes.*
----This method only applies to this window's floating geometry.
+---Tiled windows are made floating at the new geometry.
+---Widths and heights that aren't positive are rejected.
 ---
 ---### Example
 ---```lua
 ---local focused = Window.get_focused()
 ---if focused then
----    focused:set_floating(true)                     -- `set_geometry` only applies to floating geometry.
----
 ---    focused:set_geometry({ x = 50, y = 300 })      -- Move this window to (50, 300)
 ---    focused:set_geometry({ y = 0, height = 1080 }) -- Move this window to y = 0 and make its height 1080 pixels
 ---    focused:set_geometry({})                       -- Do nothing useful
@@ -545,6 +545,25 @@ function WindowHandle:move_to_tag(tag)
     client.unary_request(window_service.MoveToTag, { window_id = self.id, tag_id = tag.id })
 end
 
+---Move this window to the specified tag, then switch to that tag and focus this window.
+---
+---### Example
+---```lua
+--- -- Assume the focused output has the tag "Tag"
+---local focused = Window.get_focused()
+---if focused then
+---    focused:move_to_tag_and_follow(Tag.get("Tag"))
+---end
+---```
+---
+---@param tag TagHandle The tag to move this window to
+function WindowHandle:move_to_tag_and_follow(tag)
+    client.unary_request(
+        window_service.MoveToTag,
+        { window_id = self.id, tag_id = tag.id, follow = true }
+    )
+end
+
 ---Tag or untag the given tag on this window.
 ---
 ---### Example
@@ -46,7 +46,7 @@ async fn main() {
 
     // `mod_key + right click` starts resizing a window
     input.mousebind([mod_key], MouseButton::Right, MouseEdge::Press, || {
-        window.begin_resize(MouseButton::Right);
+        window.begin_resize(MouseButton::Right, None);
     });
 
     //------------------------
@@ -190,7 +190,6 @@ async fn main() {
         };
 
         layout_requester.cycle_layout_forward(&first_active_tag);
-        layout_requester.request_layout_on_output(&focused_op);
     });
 
     // `mod_key + shift + space` cycles to the previous layout
@@ -204,7 +203,6 @@ async fn main() {
         };
 
         layout_requester_clone.cycle_layout_backward(&first_active_tag);
-        layout_requester_clone.request_layout_on_output(&focused_op);
     });
 
     //------------------------
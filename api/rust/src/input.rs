@@ -8,22 +8,41 @@
 //! methods for setting key- and mousebinds, changing xkeyboard settings, and more.
 //! View the struct's documentation for more information.
 
+use std::sync::OnceLock;
+
 use futures::{future::BoxFuture, FutureExt, StreamExt};
 use num_enum::TryFromPrimitive;
 use pinnacle_api_defs::pinnacle::input::{
     self,
     v0alpha1::{
+        inject_input_request,
         input_service_client::InputServiceClient,
+        set_click_focus_request::Buttons,
+        set_keybind_sequence_request,
         set_libinput_setting_request::{CalibrationMatrix, Setting},
-        SetKeybindRequest, SetLibinputSettingRequest, SetMousebindRequest, SetRepeatRateRequest,
-        SetXkbConfigRequest,
+        set_scroll_tag_switch_request::Regions,
+        ClearBindsRequest, GetDevicesRequest, GetPointerInfoRequest, GetPointerLocationRequest,
+        GetPointerMotionStatsRequest, GetRepeatRateRequest, InjectInputRequest,
+        SetClickFocusRequest, SetFocusFollowsMouseDelayRequest, SetFocusFollowsMouseRequest,
+        SetHideCursorAfterRequest, SetIdleNotificationRequest, SetKeybindRequest,
+        SetKeybindSequenceRequest, SetLibinputSettingRequest, SetMousebindRequest,
+        SetPointerPredictionRequest, SetRepeatRateRequest, SetScrollInversionRequest,
+        SetScrollTagSwitchRequest, SetTabletMappingRequest, SetXkbConfigRequest, WarpCursorRequest,
     },
 };
 use tokio::sync::mpsc::UnboundedSender;
 use tonic::transport::Channel;
 use xkbcommon::xkb::Keysym;
 
-use crate::block_on_tokio;
+use crate::{
+    block_on_tokio,
+    output::OutputHandle,
+    signal::{InputSignal, SignalHandle},
+    tag::TagHandle,
+    util::Geometry,
+    window::WindowHandle,
+    ApiModules,
+};
 
 use self::libinput::LibinputSetting;
 
@@ -49,6 +68,9 @@ pub enum MouseButton {
 }
 
 /// Keyboard modifiers.
+///
+/// The non-sided modifiers match keys on either side of the keyboard, while
+/// the `Left*` and `Right*` variants only match keys on that side.
 #[repr(i32)]
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, TryFromPrimitive)]
 pub enum Mod {
@@ -58,8 +80,36 @@ pub enum Mod {
     Ctrl,
     /// The alt key
     Alt,
-    /// The super key, aka meta, win, mod4
+    /// The super key, aka win, mod4
     Super,
+    /// The hyper key
+    Hyper,
+    /// The meta key
+    Meta,
+    /// The left shift key
+    LeftShift,
+    /// The right shift key
+    RightShift,
+    /// The left ctrl key
+    LeftCtrl,
+    /// The right ctrl key
+    RightCtrl,
+    /// The left alt key
+    LeftAlt,
+    /// The right alt key
+    RightAlt,
+    /// The left super key
+    LeftSuper,
+    /// The right super key
+    RightSuper,
+    /// The left hyper key
+    LeftHyper,
+    /// The right hyper key
+    RightHyper,
+    /// The left meta key
+    LeftMeta,
+    /// The right meta key
+    RightMeta,
 }
 
 /// Press or release.
@@ -89,6 +139,158 @@ pub struct XkbConfig {
     pub options: Option<&'static str>,
 }
 
+/// Settings for focusing windows by clicking on them.
+///
+/// Fields left as `None` will not be changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ClickFocus {
+    /// Whether or not clicking on a window focuses it
+    pub enabled: Option<bool>,
+    /// The buttons that focus the window under the pointer.
+    ///
+    /// An empty list means all buttons do.
+    pub buttons: Option<Vec<MouseButton>>,
+    /// Whether to focus on press or release
+    pub edge: Option<MouseEdge>,
+    /// Whether or not focusing a window by clicking also raises it
+    pub raise: Option<bool>,
+    /// Whether or not clicking on the background keeps the current focus
+    /// instead of unfocusing all windows
+    pub keep_focus_on_background: Option<bool>,
+}
+
+/// Settings for switching to the next or previous tag by scrolling vertically.
+///
+/// Scrolls that switch tags are not sent to clients.
+///
+/// Fields left as `None` will not be changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ScrollTagSwitch {
+    /// Whether or not scrolling over the background switches tags
+    pub on_background: Option<bool>,
+    /// Regions, relative to the top left of each output, where scrolling switches tags
+    /// regardless of what is under the pointer
+    pub regions: Option<Vec<Geometry>>,
+}
+
+/// A condition on when a keybind set with [`Input::keybind_when`] is active.
+///
+/// All set fields must be met.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeybindCondition {
+    /// The tag that must be active
+    pub tag: Option<TagHandle>,
+    /// The name of the layout that the first active tag on the focused output must use
+    pub layout: Option<String>,
+}
+
+/// Options for a keybind sequence set with [`Input::keybind_sequence_with`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeybindSequenceOptions {
+    /// How long to wait for the next key in milliseconds, defaulting to 1000
+    pub timeout_ms: Option<u32>,
+    /// Whether the held back keys are sent to clients when the sequence is cancelled or times
+    /// out instead of being swallowed
+    pub forward_unmatched: bool,
+}
+
+/// The current state of the keyboard's modifiers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModifierState {
+    /// The modifiers currently held down.
+    ///
+    /// These are never sided.
+    pub mods: Vec<Mod>,
+    /// Whether or not caps lock is on
+    pub caps_lock: bool,
+    /// Whether or not num lock is on
+    pub num_lock: bool,
+}
+
+/// A capability an input device has.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, TryFromPrimitive)]
+pub enum DeviceCapability {
+    /// The device has keys
+    Keyboard = 1,
+    /// The device moves the pointer
+    Pointer,
+    /// The device is a touchscreen
+    Touch,
+    /// The device is a tablet tool
+    TabletTool,
+    /// The device is a tablet pad
+    TabletPad,
+    /// The device supports gestures
+    Gesture,
+    /// The device has switches, like a lid switch
+    Switch,
+}
+
+/// Information about the pointer and what's under it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointerInfo {
+    /// The pointer's x-coordinate in the global logical space
+    pub x: f64,
+    /// The pointer's y-coordinate in the global logical space
+    pub y: f64,
+    /// The output the pointer is on
+    pub output: Option<OutputHandle>,
+    /// The window under the pointer
+    pub window: Option<WindowHandle>,
+    /// The namespace of the layer surface under the pointer
+    pub layer_namespace: Option<String>,
+}
+
+/// Recent pointer motion before and after acceleration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointerMotionStats {
+    /// The distance the pointer moved before acceleration
+    pub raw_distance: f64,
+    /// The distance the pointer moved after acceleration
+    pub accelerated_distance: f64,
+    /// `accelerated_distance / raw_distance`, or `None` if the pointer hasn't moved recently
+    pub ratio: Option<f64>,
+}
+
+/// A connected input device.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InputDevice {
+    /// The device's id.
+    ///
+    /// This is its system name, like `"event5"`, and is unique among connected devices.
+    pub id: String,
+    /// The name of the device
+    pub name: String,
+    /// The device's USB vendor id
+    pub vendor_id: u32,
+    /// The device's USB product id
+    pub product_id: u32,
+    /// What this device is capable of
+    pub capabilities: Vec<DeviceCapability>,
+}
+
+impl InputDevice {
+    pub(crate) fn from_api(device: input::v0alpha1::Device) -> Self {
+        Self {
+            id: device.id().to_string(),
+            name: device.name().to_string(),
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
+            capabilities: device
+                .capabilities
+                .into_iter()
+                .flat_map(DeviceCapability::try_from)
+                .collect(),
+        }
+    }
+
+    /// Returns whether or not this device has the given capability.
+    pub fn has_capability(&self, capability: DeviceCapability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
 /// The `Input` struct.
 ///
 /// This struct contains methods that allow you to set key- and mousebinds,
@@ -97,6 +299,7 @@ pub struct XkbConfig {
 pub struct Input {
     channel: Channel,
     fut_sender: UnboundedSender<BoxFuture<'static, ()>>,
+    api: OnceLock<ApiModules>,
 }
 
 impl Input {
@@ -107,9 +310,14 @@ impl Input {
         Self {
             channel,
             fut_sender,
+            api: OnceLock::new(),
         }
     }
 
+    pub(crate) fn finish_init(&self, api: ApiModules) {
+        self.api.set(api).unwrap();
+    }
+
     fn create_input_client(&self) -> InputServiceClient<Channel> {
         InputServiceClient::new(self.channel.clone())
     }
@@ -156,11 +364,67 @@ impl Input {
         &self,
         mods: impl IntoIterator<Item = Mod>,
         key: impl Key + Send + 'static,
+        action: impl FnMut() + Send + 'static,
+    ) {
+        self.set_keybind(mods, key, None, action);
+    }
+
+    /// Set a keybind that is only active when `condition` is met.
+    ///
+    /// This allows the same key to do different things depending on, for example, the active tag
+    /// or layout. When the condition isn't met, the key falls back to a keybind set with
+    /// [`Input::keybind`], if any.
+    ///
+    /// If called with an already set keybind with the same condition, it gets replaced.
+    ///
+    /// See [`Input::keybind`] for the other arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::{KeybindCondition, Mod};
+    ///
+    /// // `Super + Return` spawns a terminal, except on tag "web" where it spawns a browser
+    /// input.keybind([Mod::Super], "Return", || {
+    ///     process.spawn(["alacritty"]);
+    /// });
+    ///
+    /// input.keybind_when(
+    ///     [Mod::Super],
+    ///     "Return",
+    ///     KeybindCondition {
+    ///         tag: tag.get("web"),
+    ///         ..Default::default()
+    ///     },
+    ///     || {
+    ///         process.spawn(["firefox"]);
+    ///     },
+    /// );
+    /// ```
+    pub fn keybind_when(
+        &self,
+        mods: impl IntoIterator<Item = Mod>,
+        key: impl Key + Send + 'static,
+        condition: KeybindCondition,
+        action: impl FnMut() + Send + 'static,
+    ) {
+        self.set_keybind(mods, key, Some(condition), action);
+    }
+
+    fn set_keybind(
+        &self,
+        mods: impl IntoIterator<Item = Mod>,
+        key: impl Key + Send + 'static,
+        condition: Option<KeybindCondition>,
         mut action: impl FnMut() + Send + 'static,
     ) {
         let mut client = self.create_input_client();
 
         let modifiers = mods.into_iter().map(|modif| modif as i32).collect();
+        let condition = condition.map(|cond| input::v0alpha1::KeybindCondition {
+            tag_id: cond.tag.map(|tag| tag.id),
+            layout: cond.layout,
+        });
 
         self.fut_sender
             .send(
@@ -171,6 +435,7 @@ impl Input {
                             key: Some(input::v0alpha1::set_keybind_request::Key::RawCode(
                                 key.into_keysym().raw(),
                             )),
+                            condition,
                         })
                         .await
                         .unwrap()
@@ -186,6 +451,173 @@ impl Input {
             .unwrap();
     }
 
+    /// Set a keybind sequence.
+    ///
+    /// A sequence is a list of keys, each with its own modifiers, that must be pressed one after
+    /// another, like `Super + w` then `h`. Once the first key is pressed, the next one must be
+    /// pressed within a second or the sequence is cancelled. Keys that are part of a started
+    /// sequence are held back from clients. A key that doesn't continue it cancels it and is
+    /// handled as usual.
+    ///
+    /// Keybinds set with [`Input::keybind`] take priority over the first key of a sequence.
+    /// If one sequence is the start of another, the shorter one always wins.
+    ///
+    /// If called with an already set sequence, it gets replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::Mod;
+    ///
+    /// // `Super + w` then `h` focuses the window to the left
+    /// input.keybind_sequence([(vec![Mod::Super], 'w'), (vec![], 'h')], || {
+    ///     // ...
+    /// });
+    /// ```
+    pub fn keybind_sequence<M, K>(
+        &self,
+        chords: impl IntoIterator<Item = (M, K)>,
+        action: impl FnMut() + Send + 'static,
+    ) where
+        M: IntoIterator<Item = Mod>,
+        K: Key,
+    {
+        self.keybind_sequence_with(chords, KeybindSequenceOptions::default(), action);
+    }
+
+    /// Set a keybind sequence with the given options.
+    ///
+    /// See [`Input::keybind_sequence`] for the other arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::{KeybindSequenceOptions, Mod};
+    ///
+    /// // Give two seconds to press `c`, sending `Ctrl + x` on to the window if it isn't pressed
+    /// input.keybind_sequence_with(
+    ///     [(vec![Mod::Ctrl], 'x'), (vec![Mod::Ctrl], 'c')],
+    ///     KeybindSequenceOptions {
+    ///         timeout_ms: Some(2000),
+    ///         forward_unmatched: true,
+    ///     },
+    ///     || {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn keybind_sequence_with<M, K>(
+        &self,
+        chords: impl IntoIterator<Item = (M, K)>,
+        options: KeybindSequenceOptions,
+        mut action: impl FnMut() + Send + 'static,
+    ) where
+        M: IntoIterator<Item = Mod>,
+        K: Key,
+    {
+        let mut client = self.create_input_client();
+
+        let chords = chords
+            .into_iter()
+            .map(|(mods, key)| set_keybind_sequence_request::Chord {
+                modifiers: mods.into_iter().map(|modif| modif as i32).collect(),
+                key: Some(set_keybind_sequence_request::chord::Key::RawCode(
+                    key.into_keysym().raw(),
+                )),
+            })
+            .collect();
+
+        self.fut_sender
+            .send(
+                async move {
+                    let mut stream = match client
+                        .set_keybind_sequence(SetKeybindSequenceRequest {
+                            chords,
+                            timeout_ms: options.timeout_ms,
+                            forward_unmatched: Some(options.forward_unmatched),
+                        })
+                        .await
+                    {
+                        Ok(stream) => stream.into_inner(),
+                        Err(status) => {
+                            eprintln!("ERROR: {status}");
+                            return;
+                        }
+                    };
+
+                    while let Some(Ok(_response)) = stream.next().await {
+                        action();
+                        tokio::task::yield_now().await;
+                    }
+                }
+                .boxed(),
+            )
+            .unwrap();
+    }
+
+    /// Run a closure after a period without input activity.
+    ///
+    /// `action` runs every time `timeout_ms` milliseconds pass without keyboard, pointer,
+    /// or other input activity. Use [`Input::on_resume`] to react to activity afterwards.
+    ///
+    /// Timeouts keep counting while switched to another tty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Lock the screen after 5 minutes of inactivity
+    /// input.on_idle(5 * 60 * 1000, || {
+    ///     process.spawn(["swaylock"]);
+    /// });
+    /// ```
+    pub fn on_idle(&self, timeout_ms: u32, mut action: impl FnMut() + Send + 'static) {
+        let mut client = self.create_input_client();
+
+        self.fut_sender
+            .send(
+                async move {
+                    let mut stream = match client
+                        .set_idle_notification(SetIdleNotificationRequest {
+                            timeout_ms: Some(timeout_ms),
+                        })
+                        .await
+                    {
+                        Ok(stream) => stream.into_inner(),
+                        Err(status) => {
+                            eprintln!("ERROR: {status}");
+                            return;
+                        }
+                    };
+
+                    while let Some(Ok(_response)) = stream.next().await {
+                        action();
+                        tokio::task::yield_now().await;
+                    }
+                }
+                .boxed(),
+            )
+            .unwrap();
+    }
+
+    /// Run a closure when there's input activity after any [`Input::on_idle`] timeout elapsed.
+    ///
+    /// This is a shorthand for connecting to [`InputSignal::Resumed`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.on_idle(10 * 60 * 1000, || {
+    ///     output.get_focused().unwrap().set_powered(false);
+    /// });
+    ///
+    /// input.on_resume(|| {
+    ///     output.get_focused().unwrap().set_powered(true);
+    /// });
+    /// ```
+    pub fn on_resume(&self, action: impl FnMut() + Send + 'static) -> SignalHandle {
+        self.connect_signal(InputSignal::Resumed(Box::new(action)))
+    }
+
     /// Set a mousebind.
     ///
     /// If called with an already set mousebind, it gets replaced.
@@ -242,6 +674,30 @@ impl Input {
             .unwrap();
     }
 
+    /// Remove all keybinds, keybind sequences, and mousebinds.
+    ///
+    /// This lets you set your binds again without reloading the whole config, so state held by
+    /// the config, like a [`CyclingLayoutManager`][crate::layout::CyclingLayoutManager], is kept.
+    /// Bind actions that were already triggered still run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn set_binds(input: &Input) {
+    ///     // Set keybinds and mousebinds here
+    /// }
+    ///
+    /// set_binds(input);
+    ///
+    /// // Later, after changing what `set_binds` does
+    /// input.clear_binds();
+    /// set_binds(input);
+    /// ```
+    pub fn clear_binds(&self) {
+        let mut client = self.create_input_client();
+        block_on_tokio(client.clear_binds(ClearBindsRequest {})).unwrap();
+    }
+
     /// Set the xkeyboard config.
     ///
     /// This allows you to set several xkeyboard options like `layout` and `rules`.
@@ -285,6 +741,9 @@ impl Input {
     /// // Set keyboard to repeat after holding down for half a second,
     /// // and repeat once every 25ms (40 times a second)
     /// input.set_repeat_rate(25, 500);
+    ///
+    /// // Disable key repeat
+    /// input.set_repeat_rate(0, 500);
     /// ```
     pub fn set_repeat_rate(&self, rate: i32, delay: i32) {
         let mut client = self.create_input_client();
@@ -296,6 +755,352 @@ impl Input {
         .unwrap();
     }
 
+    /// Get the keyboard's repeat rate and delay, in that order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (rate, delay) = input.repeat_rate();
+    /// ```
+    pub fn repeat_rate(&self) -> (i32, i32) {
+        block_on_tokio(self.repeat_rate_async())
+    }
+
+    /// The async version of [`Input::repeat_rate`].
+    pub async fn repeat_rate_async(&self) -> (i32, i32) {
+        let mut client = self.create_input_client();
+
+        let response = client
+            .get_repeat_rate(GetRepeatRateRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        (response.rate(), response.delay())
+    }
+
+    /// Set how clicking on windows focuses them.
+    ///
+    /// By default, pressing any button over a window focuses and raises it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::{ClickFocus, MouseButton, MouseEdge};
+    ///
+    /// // Only focus with the left mouse button on release, and don't raise windows
+    /// input.set_click_focus(ClickFocus {
+    ///     buttons: Some(vec![MouseButton::Left]),
+    ///     edge: Some(MouseEdge::Release),
+    ///     raise: Some(false),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn set_click_focus(&self, click_focus: ClickFocus) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_click_focus(SetClickFocusRequest {
+            enabled: click_focus.enabled,
+            buttons: click_focus.buttons.map(|buttons| Buttons {
+                buttons: buttons.into_iter().map(|button| button as u32).collect(),
+            }),
+            edge: click_focus.edge.map(|edge| edge as i32),
+            raise: click_focus.raise,
+            keep_focus_on_background: click_focus.keep_focus_on_background,
+        }))
+        .unwrap();
+    }
+
+    /// Set whether moving the pointer over a window focuses it.
+    ///
+    /// Focus doesn't move while a layer surface like a launcher or a popup has
+    /// keyboard focus, or while a fullscreen window is focused. To keep sweeping
+    /// the pointer across windows from focusing each one, focus changes at most
+    /// once every 50 milliseconds.
+    ///
+    /// This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_focus_follows_mouse(true);
+    /// ```
+    pub fn set_focus_follows_mouse(&self, enabled: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_focus_follows_mouse(SetFocusFollowsMouseRequest {
+            enabled: Some(enabled),
+        }))
+        .unwrap();
+    }
+
+    /// Set how long, in milliseconds, the pointer needs to stay over a window
+    /// before focus follows it.
+    ///
+    /// Moving the pointer off the window before then cancels the focus change,
+    /// so crossing windows on the way to another one doesn't focus them.
+    /// This only has an effect with [`Input::set_focus_follows_mouse`] enabled.
+    ///
+    /// The default of 0 focuses windows instantly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_focus_follows_mouse(true);
+    /// input.set_focus_follows_mouse_delay(150);
+    /// ```
+    pub fn set_focus_follows_mouse_delay(&self, delay_ms: u32) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(
+            client.set_focus_follows_mouse_delay(SetFocusFollowsMouseDelayRequest {
+                delay_ms: Some(delay_ms),
+            }),
+        )
+        .unwrap();
+    }
+
+    /// Set where scrolling switches tags.
+    ///
+    /// This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::ScrollTagSwitch;
+    /// use pinnacle_api::util::Geometry;
+    ///
+    /// // Switch tags when scrolling over the background or the top 32 pixels of an output
+    /// input.set_scroll_tag_switch(ScrollTagSwitch {
+    ///     on_background: Some(true),
+    ///     regions: Some(vec![Geometry { x: 0, y: 0, width: 10000, height: 32 }]),
+    /// });
+    /// ```
+    pub fn set_scroll_tag_switch(&self, scroll_tag_switch: ScrollTagSwitch) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_scroll_tag_switch(SetScrollTagSwitchRequest {
+            on_background: scroll_tag_switch.on_background,
+            regions: scroll_tag_switch.regions.map(|regions| {
+                Regions {
+                    regions: regions
+                        .into_iter()
+                        .map(|geo| pinnacle_api_defs::pinnacle::v0alpha1::Geometry {
+                            x: Some(geo.x),
+                            y: Some(geo.y),
+                            width: Some(geo.width as i32),
+                            height: Some(geo.height as i32),
+                        })
+                        .collect(),
+                }
+            }),
+        }))
+        .unwrap();
+    }
+
+    /// Set whether horizontal and vertical scrolling are inverted.
+    ///
+    /// This applies to all devices on top of [`LibinputSetting::NaturalScroll`],
+    /// for both wheel clicks and continuous scrolling.
+    ///
+    /// Neither axis is inverted by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Flip only horizontal scrolling
+    /// input.set_scroll_inversion(true, false);
+    /// ```
+    pub fn set_scroll_inversion(&self, horizontal: bool, vertical: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_scroll_inversion(SetScrollInversionRequest {
+            horizontal: Some(horizontal),
+            vertical: Some(vertical),
+        }))
+        .unwrap();
+    }
+
+    /// Set whether the cursor is drawn ahead of the pointer based on its recent motion.
+    ///
+    /// This can make the cursor feel more responsive at the cost of it sometimes
+    /// overshooting when the pointer stops. Only where the cursor is drawn is affected;
+    /// clients always receive the real pointer location.
+    ///
+    /// This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.set_pointer_prediction(true);
+    /// ```
+    pub fn set_pointer_prediction(&self, enabled: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_pointer_prediction(SetPointerPredictionRequest {
+            enabled: Some(enabled),
+        }))
+        .unwrap();
+    }
+
+    /// Hide the cursor after `hide_cursor_after_ms` milliseconds without pointer activity.
+    ///
+    /// The cursor is shown again as soon as the pointer moves. It isn't hidden
+    /// while dragging. Pass `None` to never hide the cursor, which is the default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Hide the cursor after 5 seconds
+    /// input.set_hide_cursor_after(5000);
+    ///
+    /// // Never hide the cursor
+    /// input.set_hide_cursor_after(None);
+    /// ```
+    pub fn set_hide_cursor_after(&self, hide_cursor_after_ms: impl Into<Option<u32>>) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_hide_cursor_after(SetHideCursorAfterRequest {
+            hide_cursor_after_ms: hide_cursor_after_ms.into(),
+        }))
+        .unwrap();
+    }
+
+    /// Inject a press or release of the key with the given evdev keycode,
+    /// as if it came from a keyboard.
+    ///
+    /// This is for testing configs headlessly. It only works when Pinnacle is run with
+    /// `PINNACLE_ENABLE_INPUT_INJECTION` set; otherwise an error is printed.
+    ///
+    /// Keycodes are those from `linux/input-event-codes.h`, so the key that types "a"
+    /// on a US layout is 30.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Type "a"
+    /// input.inject_key(30, true);
+    /// input.inject_key(30, false);
+    /// ```
+    pub fn inject_key(&self, key_code: u32, pressed: bool) {
+        self.inject_input(inject_input_request::Input::Key(
+            inject_input_request::Key {
+                key_code: Some(key_code),
+                pressed: Some(pressed),
+            },
+        ));
+    }
+
+    /// Inject a press or release of a mouse button, as if it came from a pointer.
+    ///
+    /// This is for testing configs headlessly. It only works when Pinnacle is run with
+    /// `PINNACLE_ENABLE_INPUT_INJECTION` set; otherwise an error is printed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Click
+    /// input.inject_pointer_button(MouseButton::Left, true);
+    /// input.inject_pointer_button(MouseButton::Left, false);
+    /// ```
+    pub fn inject_pointer_button(&self, button: MouseButton, pressed: bool) {
+        self.inject_input(inject_input_request::Input::PointerButton(
+            inject_input_request::PointerButton {
+                button: Some(button as u32),
+                pressed: Some(pressed),
+            },
+        ));
+    }
+
+    /// Inject pointer motion to the given location in the global space,
+    /// as if it came from a pointer.
+    ///
+    /// Unlike [`Input::warp_cursor`], this goes through input handling like real
+    /// motion does, so it can trigger focus follows mouse.
+    ///
+    /// This is for testing configs headlessly. It only works when Pinnacle is run with
+    /// `PINNACLE_ENABLE_INPUT_INJECTION` set; otherwise an error is printed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.inject_pointer_motion(500.0, 300.0);
+    /// ```
+    pub fn inject_pointer_motion(&self, x: f64, y: f64) {
+        self.inject_input(inject_input_request::Input::PointerMotion(
+            inject_input_request::PointerMotion {
+                x: Some(x),
+                y: Some(y),
+            },
+        ));
+    }
+
+    fn inject_input(&self, input: inject_input_request::Input) {
+        let mut client = self.create_input_client();
+
+        if let Err(status) =
+            block_on_tokio(client.inject_input(InjectInputRequest { input: Some(input) }))
+        {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Map tablets onto a region of `output`.
+    ///
+    /// If `device` is `None`, this applies to all tablets that weren't mapped individually.
+    /// `region` is relative to the output; if it's `None`, the whole output is used.
+    /// By default tablets map onto the first output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::util::Geometry;
+    ///
+    /// // Map all tablets onto the left half of DP-1
+    /// if let Some(dp1) = output.get_by_name("DP-1") {
+    ///     input.map_tablet(None, &dp1, Some(Geometry { x: 0, y: 0, width: 1280, height: 1440 }));
+    /// }
+    /// ```
+    pub fn map_tablet(
+        &self,
+        device: Option<&InputDevice>,
+        output: &OutputHandle,
+        region: Option<Geometry>,
+    ) {
+        let mut client = self.create_input_client();
+
+        if let Err(status) = block_on_tokio(client.set_tablet_mapping(SetTabletMappingRequest {
+            device_id: device.map(|device| device.id.clone()),
+            output_name: Some(output.name.clone()),
+            region: region.map(|geo| pinnacle_api_defs::pinnacle::v0alpha1::Geometry {
+                x: Some(geo.x),
+                y: Some(geo.y),
+                width: Some(geo.width as i32),
+                height: Some(geo.height as i32),
+            }),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Remove a mapping set with [`Input::map_tablet`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.unmap_tablet(None);
+    /// ```
+    pub fn unmap_tablet(&self, device: Option<&InputDevice>) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.set_tablet_mapping(SetTabletMappingRequest {
+            device_id: device.map(|device| device.id.clone()),
+            output_name: None,
+            region: None,
+        }))
+        .unwrap();
+    }
+
     /// Set a libinput setting.
     ///
     /// From [freedesktop.org](https://www.freedesktop.org/wiki/Software/libinput/):
@@ -323,34 +1128,256 @@ impl Input {
     pub fn set_libinput_setting(&self, setting: LibinputSetting) {
         let mut client = self.create_input_client();
 
-        let setting = match setting {
-            LibinputSetting::AccelProfile(profile) => Setting::AccelProfile(profile as i32),
-            LibinputSetting::AccelSpeed(speed) => Setting::AccelSpeed(speed),
-            LibinputSetting::CalibrationMatrix(matrix) => {
-                Setting::CalibrationMatrix(CalibrationMatrix {
-                    matrix: matrix.to_vec(),
-                })
-            }
-            LibinputSetting::ClickMethod(method) => Setting::ClickMethod(method as i32),
-            LibinputSetting::DisableWhileTyping(disable) => Setting::DisableWhileTyping(disable),
-            LibinputSetting::LeftHanded(enable) => Setting::LeftHanded(enable),
-            LibinputSetting::MiddleEmulation(enable) => Setting::MiddleEmulation(enable),
-            LibinputSetting::RotationAngle(angle) => Setting::RotationAngle(angle),
-            LibinputSetting::ScrollButton(button) => Setting::RotationAngle(button),
-            LibinputSetting::ScrollButtonLock(enable) => Setting::ScrollButtonLock(enable),
-            LibinputSetting::ScrollMethod(method) => Setting::ScrollMethod(method as i32),
-            LibinputSetting::NaturalScroll(enable) => Setting::NaturalScroll(enable),
-            LibinputSetting::TapButtonMap(map) => Setting::TapButtonMap(map as i32),
-            LibinputSetting::TapDrag(enable) => Setting::TapDrag(enable),
-            LibinputSetting::TapDragLock(enable) => Setting::TapDragLock(enable),
-            LibinputSetting::Tap(enable) => Setting::Tap(enable),
-        };
+        block_on_tokio(client.set_libinput_setting(SetLibinputSettingRequest {
+            setting: Some(libinput_setting_to_api(setting)),
+            device_id: None,
+        }))
+        .unwrap();
+    }
+
+    /// Set a libinput setting for a single device.
+    ///
+    /// Unlike [`Input::set_libinput_setting`], this only applies to the given device.
+    /// The setting is applied again if a device with the same name and vendor and product ids
+    /// is connected later, and takes precedence over settings applied to all devices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::input::libinput::*;
+    /// use pinnacle_api::input::DeviceCapability;
+    ///
+    /// // Enable tap-to-click only on touchpads
+    /// for device in input.devices() {
+    ///     if device.has_capability(DeviceCapability::Gesture) {
+    ///         input.set_device_libinput_setting(&device, LibinputSetting::Tap(true));
+    ///     }
+    /// }
+    /// ```
+    pub fn set_device_libinput_setting(&self, device: &InputDevice, setting: LibinputSetting) {
+        let mut client = self.create_input_client();
 
         block_on_tokio(client.set_libinput_setting(SetLibinputSettingRequest {
-            setting: Some(setting),
+            setting: Some(libinput_setting_to_api(setting)),
+            device_id: Some(device.id.clone()),
+        }))
+        .unwrap();
+    }
+
+    /// Get all currently connected input devices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for device in input.devices() {
+    ///     println!("{} ({:04x}:{:04x})", device.name, device.vendor_id, device.product_id);
+    /// }
+    /// ```
+    pub fn devices(&self) -> Vec<InputDevice> {
+        block_on_tokio(self.devices_async())
+    }
+
+    /// The async version of [`Input::devices`].
+    pub async fn devices_async(&self) -> Vec<InputDevice> {
+        let mut client = self.create_input_client();
+
+        client
+            .get_devices(GetDevicesRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .devices
+            .into_iter()
+            .map(InputDevice::from_api)
+            .collect()
+    }
+
+    /// Move the pointer to (`x`, `y`) in the global logical space.
+    ///
+    /// The pointer is kept on an output, and the output it lands on is focused.
+    /// This does nothing if there are no outputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Move the pointer to the center of the focused window
+    /// if let Some(geo) = window.get_focused().and_then(|win| win.geometry()) {
+    ///     input.warp_cursor(
+    ///         geo.x as f64 + geo.width as f64 / 2.0,
+    ///         geo.y as f64 + geo.height as f64 / 2.0,
+    ///     );
+    /// }
+    /// ```
+    pub fn warp_cursor(&self, x: f64, y: f64) {
+        self.warp_cursor_inner(x, y, false);
+    }
+
+    /// Move the pointer by (`dx`, `dy`) from its current location.
+    ///
+    /// The pointer is kept on an output, and the output it lands on is focused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Nudge the pointer 10 pixels to the right
+    /// input.warp_cursor_relative(10.0, 0.0);
+    /// ```
+    pub fn warp_cursor_relative(&self, dx: f64, dy: f64) {
+        self.warp_cursor_inner(dx, dy, true);
+    }
+
+    fn warp_cursor_inner(&self, x: f64, y: f64, relative: bool) {
+        let mut client = self.create_input_client();
+
+        block_on_tokio(client.warp_cursor(WarpCursorRequest {
+            x: Some(x),
+            y: Some(y),
+            relative: Some(relative),
         }))
         .unwrap();
     }
+
+    /// Get the pointer's location in the global logical space.
+    ///
+    /// This is the same space window and output locations are in.
+    /// If you also need what's under the pointer, use [`Input::pointer_info`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (x, y) = input.pointer_location();
+    /// ```
+    pub fn pointer_location(&self) -> (f64, f64) {
+        block_on_tokio(self.pointer_location_async())
+    }
+
+    /// The async version of [`Input::pointer_location`].
+    pub async fn pointer_location_async(&self) -> (f64, f64) {
+        let mut client = self.create_input_client();
+
+        let response = client
+            .get_pointer_location(GetPointerLocationRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        (response.x(), response.y())
+    }
+
+    /// Get the pointer's location along with the output it's on and what's under it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let info = input.pointer_info();
+    ///
+    /// // Move the window under the pointer to tag 2
+    /// if let (Some(window), Some(output)) = (info.window, info.output) {
+    ///     if let Some(tag) = tag.get_on_output("2", &output) {
+    ///         window.move_to_tag(&tag);
+    ///     }
+    /// }
+    /// ```
+    pub fn pointer_info(&self) -> PointerInfo {
+        block_on_tokio(self.pointer_info_async())
+    }
+
+    /// The async version of [`Input::pointer_info`].
+    pub async fn pointer_info_async(&self) -> PointerInfo {
+        let mut client = self.create_input_client();
+        let api = self.api.get().unwrap();
+
+        let response = client
+            .get_pointer_info(GetPointerInfoRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        PointerInfo {
+            x: response.x(),
+            y: response.y(),
+            output: response.output_name.map(|name| api.output.new_handle(name)),
+            window: response.window_id.map(|id| api.window.new_handle(id)),
+            layer_namespace: response.layer_namespace,
+        }
+    }
+
+    /// Get how the pointer moved over roughly the last second, before and after acceleration.
+    ///
+    /// This is meant for tuning [`LibinputSetting::AccelProfile`] and
+    /// [`LibinputSetting::AccelSpeed`]. The stats are reset whenever a libinput setting is
+    /// applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if let Some(ratio) = input.pointer_motion_stats().ratio {
+    ///     println!("pointer motion is being multiplied by {ratio:.2}");
+    /// }
+    /// ```
+    pub fn pointer_motion_stats(&self) -> PointerMotionStats {
+        block_on_tokio(self.pointer_motion_stats_async())
+    }
+
+    /// The async version of [`Input::pointer_motion_stats`].
+    pub async fn pointer_motion_stats_async(&self) -> PointerMotionStats {
+        let mut client = self.create_input_client();
+
+        let response = client
+            .get_pointer_motion_stats(GetPointerMotionStatsRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        PointerMotionStats {
+            raw_distance: response.raw_distance(),
+            accelerated_distance: response.accelerated_distance(),
+            ratio: response.ratio,
+        }
+    }
+
+    /// Connect to an input signal.
+    ///
+    /// The compositor will fire off signals that your config can listen for and act upon.
+    /// You can pass in an [`InputSignal`] along with a callback and it will get run
+    /// with the necessary arguments every time a signal of that type is received.
+    pub fn connect_signal(&self, signal: InputSignal) -> SignalHandle {
+        let mut signal_state = block_on_tokio(self.api.get().unwrap().signal.write());
+
+        match signal {
+            InputSignal::DeviceAdded(f) => signal_state.input_device_added.add_callback(f),
+            InputSignal::DeviceRemoved(f) => signal_state.input_device_removed.add_callback(f),
+            InputSignal::ModifiersChanged(f) => {
+                signal_state.input_modifiers_changed.add_callback(f)
+            }
+            InputSignal::Resumed(f) => signal_state.input_resumed.add_callback(f),
+        }
+    }
+}
+
+fn libinput_setting_to_api(setting: LibinputSetting) -> Setting {
+    match setting {
+        LibinputSetting::AccelProfile(profile) => Setting::AccelProfile(profile as i32),
+        LibinputSetting::AccelSpeed(speed) => Setting::AccelSpeed(speed),
+        LibinputSetting::CalibrationMatrix(matrix) => {
+            Setting::CalibrationMatrix(CalibrationMatrix {
+                matrix: matrix.to_vec(),
+            })
+        }
+        LibinputSetting::ClickMethod(method) => Setting::ClickMethod(method as i32),
+        LibinputSetting::DisableWhileTyping(disable) => Setting::DisableWhileTyping(disable),
+        LibinputSetting::LeftHanded(enable) => Setting::LeftHanded(enable),
+        LibinputSetting::MiddleEmulation(enable) => Setting::MiddleEmulation(enable),
+        LibinputSetting::RotationAngle(angle) => Setting::RotationAngle(angle),
+        LibinputSetting::ScrollButton(button) => Setting::ScrollButton(button),
+        LibinputSetting::ScrollButtonLock(enable) => Setting::ScrollButtonLock(enable),
+        LibinputSetting::ScrollMethod(method) => Setting::ScrollMethod(method as i32),
+        LibinputSetting::NaturalScroll(enable) => Setting::NaturalScroll(enable),
+        LibinputSetting::TapButtonMap(map) => Setting::TapButtonMap(map as i32),
+        LibinputSetting::TapDrag(enable) => Setting::TapDrag(enable),
+        LibinputSetting::TapDragLock(enable) => Setting::TapDragLock(enable),
+        LibinputSetting::Tap(enable) => Setting::Tap(enable),
+    }
 }
 
 /// A trait that designates anything that can be converted into a [`Keysym`].
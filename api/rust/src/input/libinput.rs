@@ -73,6 +73,10 @@ pub enum LibinputSetting {
     /// Set the scroll button
     ScrollButton(u32),
     /// Set whether the scroll button should be a drag or toggle
+    ///
+    /// When enabled, clicking the scroll button once starts button scrolling
+    /// and clicking it again stops it, instead of having to hold it down.
+    /// This only has an effect with [`ScrollMethod::OnButtonDown`].
     ScrollButtonLock(bool),
     /// Set the [`ScrollMethod`]
     ScrollMethod(ScrollMethod),
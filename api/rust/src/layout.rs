@@ -97,8 +97,14 @@ impl Layout {
                         .collect(),
                     output_width: response.output_width.unwrap_or_default(),
                     output_height: response.output_height.unwrap_or_default(),
+                    master_factor: response.master_factor,
+                    layout: response.layout,
+                };
+                let (geos, master_factor) = {
+                    let mut manager = manager.lock().unwrap();
+                    let layout = manager.active_layout(&args);
+                    (layout.layout(&args), layout.master_factor())
                 };
-                let geos = manager.lock().unwrap().active_layout(&args).layout(&args);
                 from_client
                     .send(LayoutRequest {
                         body: Some(Body::Geometries(Geometries {
@@ -113,6 +119,7 @@ impl Layout {
                                     height: Some(geo.height as i32),
                                 })
                                 .collect(),
+                            master_factor,
                         })),
                     })
                     .unwrap();
@@ -138,6 +145,12 @@ pub struct LayoutArgs {
     pub output_width: u32,
     /// The height of the layout area, in pixels.
     pub output_height: u32,
+    /// The master factor of the first focused tag, if it was set with
+    /// [`TagHandle::set_master_factor`].
+    pub master_factor: Option<f32>,
+    /// The name of the layout the first focused tag uses, if it was set with
+    /// [`TagHandle::set_layout`].
+    pub layout: Option<String>,
 }
 
 /// Types that can manage layouts.
@@ -150,6 +163,26 @@ pub trait LayoutManager {
 pub trait LayoutGenerator {
     /// Generate a vector of [geometries][Geometry] using the given [`LayoutArgs`].
     fn layout(&self, args: &LayoutArgs) -> Vec<Geometry>;
+
+    /// The name of this layout.
+    ///
+    /// [`CyclingLayoutManager`] stores this on tags with [`TagHandle::set_layout`] so the
+    /// compositor and the rest of your config know which layout a tag uses.
+    ///
+    /// Defaults to `None`.
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    /// The master factor this layout uses when the tag doesn't have one.
+    ///
+    /// This is sent back to the compositor so [`TagHandle::adjust_master_factor`] on a tag
+    /// without a master factor starts from the factor the layout actually used.
+    ///
+    /// Defaults to `None`.
+    fn master_factor(&self) -> Option<f32> {
+        None
+    }
 }
 
 /// Gaps between windows.
@@ -207,22 +240,58 @@ impl CyclingLayoutManager {
 
     /// Cycle the layout forward on the given tag.
     pub fn cycle_layout_forward(&mut self, tag: &TagHandle) {
-        let index = self.tag_indices.entry(tag.id).or_default();
-        *index += 1;
-        if *index >= self.layouts.len() {
-            *index = 0;
+        let layout = tag.layout();
+        let new_layout = self.cycle(tag, layout.as_deref(), true);
+        if new_layout != layout {
+            tag.set_layout(new_layout);
         }
     }
 
     /// Cycle the layout backward on the given tag.
     pub fn cycle_layout_backward(&mut self, tag: &TagHandle) {
-        let index = self.tag_indices.entry(tag.id).or_default();
-        if let Some(i) = index.checked_sub(1) {
-            *index = i;
-        } else {
-            *index = self.layouts.len().saturating_sub(1);
+        let layout = tag.layout();
+        let new_layout = self.cycle(tag, layout.as_deref(), false);
+        if new_layout != layout {
+            tag.set_layout(new_layout);
         }
     }
+
+    /// Move the given tag to the next or previous layout, starting from the one named
+    /// `layout`, and return the name of the new layout.
+    fn cycle(&mut self, tag: &TagHandle, layout: Option<&str>, forward: bool) -> Option<String> {
+        let index = self.layout_index(tag, layout);
+        let index = if forward {
+            let index = index + 1;
+            if index >= self.layouts.len() {
+                0
+            } else {
+                index
+            }
+        } else {
+            index
+                .checked_sub(1)
+                .unwrap_or_else(|| self.layouts.len().saturating_sub(1))
+        };
+
+        self.tag_indices.insert(tag.id, index);
+        self.layouts.get(index).and_then(|layout| layout.name())
+    }
+
+    /// Get the index of the layout the given tag uses.
+    ///
+    /// This prefers `layout`, the layout name stored in the compositor, so that layouts
+    /// set with [`TagHandle::set_layout`] are picked up. Layouts without a name fall back
+    /// to the index this manager last set.
+    fn layout_index(&self, tag: &TagHandle, layout: Option<&str>) -> usize {
+        layout
+            .and_then(|name| {
+                self.layouts
+                    .iter()
+                    .position(|layout| layout.name().as_deref() == Some(name))
+            })
+            .or_else(|| self.tag_indices.get(&tag.id).copied())
+            .unwrap_or_default()
+    }
 }
 
 impl LayoutManager for CyclingLayoutManager {
@@ -231,8 +300,10 @@ impl LayoutManager for CyclingLayoutManager {
             return &NoopLayout;
         };
 
+        let index = self.layout_index(first_tag, args.layout.as_deref());
+
         self.layouts
-            .get(*self.tag_indices.entry(first_tag.id).or_default())
+            .get(index)
             .expect("no layouts in manager")
             .as_ref()
     }
@@ -285,15 +356,34 @@ impl<T> LayoutRequester<T> {
 
 impl LayoutRequester<CyclingLayoutManager> {
     /// Cycle the layout forward for the given tag.
+    ///
+    /// This relayouts the tag's output, so there's no need to request a layout afterwards.
     pub fn cycle_layout_forward(&self, tag: &TagHandle) {
-        let mut lock = self.manager.lock().unwrap();
-        lock.cycle_layout_forward(tag);
+        self.cycle_layout(tag, true);
     }
 
     /// Cycle the layout backward for the given tag.
+    ///
+    /// This relayouts the tag's output, so there's no need to request a layout afterwards.
     pub fn cycle_layout_backward(&mut self, tag: &TagHandle) {
-        let mut lock = self.manager.lock().unwrap();
-        lock.cycle_layout_backward(tag);
+        self.cycle_layout(tag, false);
+    }
+
+    fn cycle_layout(&self, tag: &TagHandle, forward: bool) {
+        // Talk to the compositor without holding the lock, as layout requests need it
+        let layout = tag.layout();
+        let new_layout = self
+            .manager
+            .lock()
+            .unwrap()
+            .cycle(tag, layout.as_deref(), forward);
+
+        if new_layout != layout {
+            // The compositor relayouts when a tag's layout changes
+            tag.set_layout(new_layout);
+        } else if let Some(output) = tag.output() {
+            self.request_layout_on_output(&output);
+        }
     }
 }
 
@@ -329,7 +419,8 @@ pub struct MasterStackLayout {
     pub gaps: Gaps,
     /// The proportion of the output the master area will take up.
     ///
-    /// This will be clamped between 0.1 and 0.9.
+    /// This will be clamped between 0.1 and 0.9. The master factor of the focused tag
+    /// takes priority if it was set.
     ///
     /// Defaults to 0.5
     pub master_factor: f32,
@@ -388,7 +479,9 @@ impl LayoutGenerator for MasterStackLayout {
         .0;
 
         let master_factor = if win_count > self.master_count {
-            self.master_factor.clamp(0.1, 0.9)
+            args.master_factor
+                .unwrap_or(self.master_factor)
+                .clamp(0.1, 0.9)
         } else {
             1.0
         };
@@ -512,6 +605,14 @@ impl LayoutGenerator for MasterStackLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<String> {
+        Some("master_stack".to_string())
+    }
+
+    fn master_factor(&self) -> Option<f32> {
+        Some(self.master_factor.clamp(0.1, 0.9))
+    }
 }
 
 /// A [`LayoutGenerator`] that lays out windows in a shrinking fashion
@@ -624,6 +725,10 @@ impl LayoutGenerator for DwindleLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<String> {
+        Some("dwindle".to_string())
+    }
 }
 
 /// A [`LayoutGenerator`] that lays out windows in a spiral.
@@ -746,6 +851,10 @@ impl LayoutGenerator for SpiralLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<String> {
+        Some("spiral".to_string())
+    }
 }
 
 /// Which corner the corner window will in.
@@ -960,6 +1069,10 @@ impl LayoutGenerator for CornerLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<String> {
+        Some("corner".to_string())
+    }
 }
 
 /// A [`LayoutGenerator`] that attempts to layout windows such that
@@ -1132,4 +1245,8 @@ impl LayoutGenerator for FairLayout {
 
         geos
     }
+
+    fn name(&self) -> Option<String> {
+        Some("fair".to_string())
+    }
 }
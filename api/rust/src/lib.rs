@@ -194,9 +194,11 @@ pub async fn connect(
     };
 
     window.finish_init(modules.clone());
+    input.finish_init(modules.clone());
     output.finish_init(modules.clone());
     tag.finish_init(modules.clone());
     layout.finish_init(modules.clone());
+    render.finish_init(modules.clone());
     signal.read().await.finish_init(modules.clone());
 
     Ok((modules, fut_recv))
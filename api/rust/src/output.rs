@@ -15,8 +15,12 @@ use futures::FutureExt;
 use pinnacle_api_defs::pinnacle::output::{
     self,
     v0alpha1::{
-        output_service_client::OutputServiceClient, set_scale_request::AbsoluteOrRelative,
-        SetLocationRequest, SetModeRequest, SetScaleRequest, SetTransformRequest,
+        output_service_client::OutputServiceClient, set_primary_request,
+        set_scale_request::AbsoluteOrRelative, CaptureRequest, FocusRelativeRequest,
+        GetEdidRequest, GetFocusedRequest, SetClearColorRequest, SetColorTemperatureRequest,
+        SetCustomModeRequest, SetFocusedRequest, SetLocationRequest, SetModeRequest,
+        SetPoweredRequest, SetPrimaryRequest, SetRenderNodeRequest, SetScaleRequest,
+        SetTransformRequest, SetVblankMissPolicyRequest, SetVrrRequest,
     },
 };
 use tonic::transport::Channel;
@@ -42,7 +46,9 @@ pub struct Output {
 impl Output {
     pub(crate) fn new(channel: Channel) -> Self {
         Self {
-            output_client: OutputServiceClient::new(channel.clone()),
+            // Captures send whole frames, which are bigger than the default 4 MB limit
+            output_client: OutputServiceClient::new(channel.clone())
+                .max_decoding_message_size(usize::MAX),
             api: OnceLock::new(),
         }
     }
@@ -110,7 +116,8 @@ impl Output {
 
     /// Get a handle to the focused output.
     ///
-    /// This is currently implemented as the one that has had the most recent pointer movement.
+    /// This is the one that has had the most recent pointer movement, unless focus was moved
+    /// with [`Output::set_focused`] or [`Output::focus_next`] since.
     ///
     /// # Examples
     ///
@@ -118,17 +125,39 @@ impl Output {
     /// let op = output.get_focused()?;
     /// ```
     pub fn get_focused(&self) -> Option<OutputHandle> {
-        self.get_all()
-            .into_iter()
-            .find(|output| matches!(output.props().focused, Some(true)))
+        block_on_tokio(self.get_focused_async())
     }
 
     /// The async version of [`Output::get_focused`].
     pub async fn get_focused_async(&self) -> Option<OutputHandle> {
-        self.get_all_async().await.batch_find(
-            |output| output.props_async().boxed(),
-            |props| props.focused.is_some_and(|focused| focused),
-        )
+        let mut client = self.output_client.clone();
+
+        client
+            .get_focused(GetFocusedRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .output_name
+            .map(|name| self.new_handle(name))
+    }
+
+    /// Focus the given output.
+    ///
+    /// New windows and tags will go to this output. The pointer is not moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.set_focused(&output.get_by_name("HDMI-1")?);
+    /// ```
+    pub fn set_focused(&self, output: &OutputHandle) {
+        let mut client = self.output_client.clone();
+
+        if let Err(status) = block_on_tokio(client.set_focused(SetFocusedRequest {
+            output_name: Some(output.name.clone()),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
     }
 
     /// Connect a closure to be run on all current and future outputs.
@@ -175,6 +204,49 @@ impl Output {
         }
     }
 
+    /// Run `on_connect` with every output that gets connected from now on.
+    ///
+    /// The callback runs once the output has been set up, so it can be given tags right away.
+    /// Like [`Output::connect_for_all`], it doesn't run with outputs that were unplugged and
+    /// replugged, as the compositor restores their tags and state itself. Unlike it,
+    /// `on_connect` doesn't run with outputs that are already connected.
+    ///
+    /// This is shorthand for connecting to [`OutputSignal::Connect`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Give new outputs their own tags
+    /// output.on_connect(|op| {
+    ///     let tags = tag.add(op, ["1", "2", "3"]);
+    ///     tags[0].set_active(true);
+    /// });
+    /// ```
+    pub fn on_connect(
+        &self,
+        on_connect: impl FnMut(&OutputHandle) + Send + 'static,
+    ) -> SignalHandle {
+        self.connect_signal(OutputSignal::Connect(Box::new(on_connect)))
+    }
+
+    /// Run `on_disconnect` with every output that gets disconnected from now on.
+    ///
+    /// This is shorthand for connecting to [`OutputSignal::Disconnect`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.on_disconnect(|op| {
+    ///     println!("{} was unplugged", op.name());
+    /// });
+    /// ```
+    pub fn on_disconnect(
+        &self,
+        on_disconnect: impl FnMut(&OutputHandle) + Send + 'static,
+    ) -> SignalHandle {
+        self.connect_signal(OutputSignal::Disconnect(Box::new(on_disconnect)))
+    }
+
     /// Declaratively setup outputs.
     ///
     /// This method allows you to specify [`OutputSetup`]s that will be applied to outputs already
@@ -221,6 +293,68 @@ impl Output {
         });
     }
 
+    /// Focus the next output.
+    ///
+    /// Outputs are cycled through in the order they were connected, wrapping around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.focus_next();
+    /// ```
+    pub fn focus_next(&self) {
+        self.focus_relative(1);
+    }
+
+    /// Focus the previous output.
+    ///
+    /// See [`Output::focus_next`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.focus_prev();
+    /// ```
+    pub fn focus_prev(&self) {
+        self.focus_relative(-1);
+    }
+
+    fn focus_relative(&self, offset: i32) {
+        let mut client = self.output_client.clone();
+
+        block_on_tokio(client.focus_relative(FocusRelativeRequest {
+            offset: Some(offset),
+        }))
+        .unwrap();
+    }
+
+    /// Set the primary output.
+    ///
+    /// The primary output is placed at (0, 0) whenever it connects, with all other outputs
+    /// moved to its right. This happens before any [`Output::setup_locs`] relayout, so
+    /// relative locations can still be used to arrange outputs around it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::OutputId;
+    ///
+    /// output.set_primary(OutputId::name("DP-2"));
+    /// ```
+    pub fn set_primary(&self, id: OutputId) {
+        let mut client = self.output_client.clone();
+
+        let output_id = match id {
+            OutputId::Name(name) => set_primary_request::OutputId::OutputName(name),
+            OutputId::Serial(serial) => set_primary_request::OutputId::Serial(serial.get()),
+        };
+
+        block_on_tokio(client.set_primary(SetPrimaryRequest {
+            output_id: Some(output_id),
+        }))
+        .unwrap();
+    }
+
     /// Specify locations for outputs and when they should be laid out.
     ///
     /// This method allows you to specify locations for outputs, either as a specific point
@@ -610,6 +744,22 @@ pub enum Alignment {
     RightAlignBottom,
 }
 
+/// What an output does when a frame misses its vblank while another render is pending.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum VblankMissPolicy {
+    /// Render again immediately after the late frame is presented.
+    ///
+    /// This is the default.
+    #[default]
+    CatchUp = 1,
+    /// Drop the pending render and render as close to the next vblank as possible.
+    ///
+    /// Under heavy GPU load, this keeps frames from piling up behind each other
+    /// and reduces latency.
+    Skip,
+}
+
 /// An output transform.
 ///
 /// This determines what orientation outputs will render at.
@@ -811,8 +961,144 @@ impl OutputHandle {
         .unwrap();
     }
 
+    /// Set this output to a custom mode.
+    ///
+    /// Unlike [`OutputHandle::set_mode`], the mode doesn't need to be one the output lists.
+    /// If it isn't, its timings are generated with the CVT formula, like the `cvt` utility
+    /// does. This is useful for displays whose EDID lists wrong modes.
+    ///
+    /// If `refresh_rate_millihertz` is `None`, 60Hz is used.
+    ///
+    /// If the display device rejects the mode, an error is printed and the output
+    /// is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.get_focused()?.set_custom_mode(2560, 1080, 75000);
+    /// ```
+    pub fn set_custom_mode(
+        &self,
+        pixel_width: u32,
+        pixel_height: u32,
+        refresh_rate_millihertz: impl Into<Option<u32>>,
+    ) {
+        let mut client = self.output_client.clone();
+        if let Err(status) = block_on_tokio(client.set_custom_mode(SetCustomModeRequest {
+            output_name: Some(self.name.clone()),
+            pixel_width: Some(pixel_width),
+            pixel_height: Some(pixel_height),
+            refresh_rate_millihz: refresh_rate_millihertz.into(),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Enable or disable variable refresh rate (adaptive sync) on this output.
+    ///
+    /// With it enabled, the display refreshes when a new frame is ready instead of
+    /// at a fixed rate, which smooths out games and video that can't keep up with it.
+    ///
+    /// If the display doesn't support it, an error is printed and nothing happens.
+    /// This does nothing when running in a window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// output.get_focused()?.set_vrr(true);
+    /// ```
+    pub fn set_vrr(&self, vrr: bool) {
+        let mut client = self.output_client.clone();
+        if let Err(status) = block_on_tokio(client.set_vrr(SetVrrRequest {
+            output_name: Some(self.name.clone()),
+            vrr: Some(vrr),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Render this output with the GPU that has the given render node,
+    /// like `/dev/dri/renderD129`, or with the primary GPU if `None`.
+    ///
+    /// This is useful on multi-GPU laptops, for example to have the integrated GPU
+    /// drive the internal panel while the dedicated one drives an external monitor.
+    /// The choice is kept for this output's name if it is reconnected.
+    /// Outputs can also be pinned on startup with the `PINNACLE_RENDER_NODES` environment
+    /// variable, e.g. `eDP-1=/dev/dri/renderD128,HDMI-A-1=/dev/dri/renderD129`.
+    ///
+    /// If the render node is unavailable, the primary GPU is used.
+    /// This only works when running on a TTY.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if let Some(output) = output.get_by_name("HDMI-A-1") {
+    ///     output.set_render_node("/dev/dri/renderD129");
+    /// }
+    /// ```
+    pub fn set_render_node(&self, render_node_path: impl Into<Option<&str>>) {
+        let mut client = self.output_client.clone();
+        if let Err(status) = block_on_tokio(client.set_render_node(SetRenderNodeRequest {
+            output_name: Some(self.name.clone()),
+            render_node_path: render_node_path.into().map(ToString::to_string),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Set this output's mode to the one with the refresh rate closest to
+    /// `refresh_rate_millihertz`, keeping the current resolution.
+    ///
+    /// This is useful when you don't know the exact refresh rates an output supports,
+    /// as they are often something like 59940 instead of 60000.
+    /// Use [`OutputHandle::modes`] to see all available modes.
+    ///
+    /// If the output has no current mode, this does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Pick the mode closest to 144Hz
+    /// output.get_focused()?.set_mode_by_refresh(144000);
+    /// ```
+    pub fn set_mode_by_refresh(&self, refresh_rate_millihertz: u32) {
+        let OutputProperties {
+            current_mode,
+            modes,
+            ..
+        } = self.props();
+
+        let Some(current_mode) = current_mode else {
+            return;
+        };
+
+        let closest_mode = modes
+            .into_iter()
+            .filter(|mode| {
+                mode.pixel_width == current_mode.pixel_width
+                    && mode.pixel_height == current_mode.pixel_height
+            })
+            .min_by_key(|mode| {
+                mode.refresh_rate_millihertz
+                    .abs_diff(refresh_rate_millihertz)
+            });
+
+        if let Some(mode) = closest_mode {
+            self.set_mode(
+                mode.pixel_width,
+                mode.pixel_height,
+                mode.refresh_rate_millihertz,
+            );
+        }
+    }
+
     /// Set this output's scaling factor.
     ///
+    /// Fractional scales like `1.25` or `1.5` are supported. Floating windows on this output
+    /// are repositioned to stay in the same relative place.
+    ///
+    /// The scale must be positive; other values are rejected and the scale is left unchanged.
+    ///
     /// # Examples
     ///
     /// ```
@@ -820,11 +1106,12 @@ impl OutputHandle {
     /// ```
     pub fn set_scale(&self, scale: f32) {
         let mut client = self.output_client.clone();
-        block_on_tokio(client.set_scale(SetScaleRequest {
+        if let Err(status) = block_on_tokio(client.set_scale(SetScaleRequest {
             output_name: Some(self.name.clone()),
             absolute_or_relative: Some(AbsoluteOrRelative::Absolute(scale)),
-        }))
-        .unwrap();
+        })) {
+            eprintln!("ERROR: {status}");
+        }
     }
 
     /// Increase this output's scaling factor by `increase_by`.
@@ -836,11 +1123,12 @@ impl OutputHandle {
     /// ```
     pub fn increase_scale(&self, increase_by: f32) {
         let mut client = self.output_client.clone();
-        block_on_tokio(client.set_scale(SetScaleRequest {
+        if let Err(status) = block_on_tokio(client.set_scale(SetScaleRequest {
             output_name: Some(self.name.clone()),
             absolute_or_relative: Some(AbsoluteOrRelative::Relative(increase_by)),
-        }))
-        .unwrap();
+        })) {
+            eprintln!("ERROR: {status}");
+        }
     }
 
     /// Decrease this output's scaling factor by `decrease_by`.
@@ -875,6 +1163,101 @@ impl OutputHandle {
         .unwrap();
     }
 
+    /// Turn this output on or off.
+    ///
+    /// While off, the monitor receives no frames and will usually go into standby.
+    /// This does nothing when running Pinnacle in a window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Turn off all monitors
+    /// for op in output.get_all() {
+    ///     op.set_powered(false);
+    /// }
+    /// ```
+    pub fn set_powered(&self, powered: bool) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_powered(SetPoweredRequest {
+            output_name: Some(self.name.clone()),
+            powered: Some(powered),
+        }))
+        .unwrap();
+    }
+
+    /// Tint this output to a color temperature, in Kelvin.
+    ///
+    /// Lower temperatures are warmer, e.g. 3500 K is a typical night light setting.
+    /// 6500 K is neutral and restores the original colors. Values are clamped to
+    /// 1000–10000 K.
+    ///
+    /// This is only supported when running Pinnacle on a TTY and on outputs that support
+    /// setting gamma. Otherwise, an error is printed and nothing happens.
+    ///
+    /// While a client like gammastep controls this output's gamma through
+    /// `wlr-gamma-control`, the temperature is applied once that client exits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Night light
+    /// output.get_focused()?.set_color_temperature(3500);
+    /// ```
+    pub fn set_color_temperature(&self, kelvin: u16) {
+        let mut client = self.output_client.clone();
+        if let Err(status) =
+            block_on_tokio(client.set_color_temperature(SetColorTemperatureRequest {
+                output_name: Some(self.name.clone()),
+                kelvin: Some(kelvin.into()),
+            }))
+        {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Set what this output does when a frame misses its vblank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::output::VblankMissPolicy;
+    ///
+    /// // Favor latency over smoothness under load
+    /// output.get_focused()?.set_vblank_miss_policy(VblankMissPolicy::Skip);
+    /// ```
+    pub fn set_vblank_miss_policy(&self, policy: VblankMissPolicy) {
+        let mut client = self.output_client.clone();
+        block_on_tokio(client.set_vblank_miss_policy(SetVblankMissPolicyRequest {
+            output_name: Some(self.name.clone()),
+            policy: Some(policy as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Set the color drawn behind everything on this output.
+    ///
+    /// Each component ranges from 0.0 to 1.0. The color while the session is locked
+    /// is unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Dark gray
+    /// output.get_focused()?.set_clear_color(0.1, 0.1, 0.1, 1.0);
+    /// ```
+    pub fn set_clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        let mut client = self.output_client.clone();
+        if let Err(status) = block_on_tokio(client.set_clear_color(SetClearColorRequest {
+            output_name: Some(self.name.clone()),
+            r: Some(r),
+            g: Some(g),
+            b: Some(b),
+            a: Some(a),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
     /// Get all properties of this output.
     ///
     /// # Examples
@@ -949,6 +1332,8 @@ impl OutputHandle {
                 .into_iter()
                 .map(|id| self.api.window.new_handle(id))
                 .collect(),
+            powered: response.powered,
+            vrr: response.vrr,
         }
     }
 
@@ -968,7 +1353,7 @@ impl OutputHandle {
 
     /// Get this output's model.
     ///
-    /// Shorthand for `self.props().make`.
+    /// Shorthand for `self.props().model`.
     pub fn model(&self) -> Option<String> {
         self.props().model
     }
@@ -1148,6 +1533,94 @@ impl OutputHandle {
         self.props_async().await.serial
     }
 
+    /// Get whether this output is powered on.
+    ///
+    /// Shorthand for `self.props().powered`
+    pub fn powered(&self) -> Option<bool> {
+        self.props().powered
+    }
+
+    /// The async version of [`OutputHandle::powered`].
+    pub async fn powered_async(&self) -> Option<bool> {
+        self.props_async().await.powered
+    }
+
+    /// Get whether variable refresh rate is enabled on this output.
+    ///
+    /// Shorthand for `self.props().vrr`
+    pub fn vrr(&self) -> Option<bool> {
+        self.props().vrr
+    }
+
+    /// The async version of [`OutputHandle::vrr`].
+    pub async fn vrr_async(&self) -> Option<bool> {
+        self.props_async().await.vrr
+    }
+
+    /// Get this output's raw EDID.
+    ///
+    /// Returns `None` if the output doesn't provide one, e.g. on the winit backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if let Some(edid) = output.get_focused()?.edid() {
+    ///     // Look up a color profile for this exact panel
+    /// }
+    /// ```
+    pub fn edid(&self) -> Option<Vec<u8>> {
+        block_on_tokio(self.edid_async())
+    }
+
+    /// The async version of [`OutputHandle::edid`].
+    pub async fn edid_async(&self) -> Option<Vec<u8>> {
+        let mut client = self.output_client.clone();
+        client
+            .get_edid(GetEdidRequest {
+                output_name: Some(self.name.clone()),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .edid
+    }
+
+    /// Capture the contents of this output.
+    ///
+    /// The capture is the size of this output's current mode in physical pixels,
+    /// with width and height swapped if the output is rotated 90 or 270 degrees.
+    ///
+    /// Returns an error if this output couldn't be captured, for example if it has
+    /// no mode or the session is locked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let capture = output.get_focused()?.capture(false)?;
+    /// println!("Captured {}x{} pixels", capture.width, capture.height);
+    /// ```
+    pub fn capture(&self, include_cursor: bool) -> Result<Capture, tonic::Status> {
+        block_on_tokio(self.capture_async(include_cursor))
+    }
+
+    /// The async version of [`OutputHandle::capture`].
+    pub async fn capture_async(&self, include_cursor: bool) -> Result<Capture, tonic::Status> {
+        let mut client = self.output_client.clone();
+        let response = client
+            .capture(CaptureRequest {
+                output_name: Some(self.name.clone()),
+                include_cursor: Some(include_cursor),
+            })
+            .await?
+            .into_inner();
+
+        Ok(Capture {
+            width: response.width.unwrap_or_default(),
+            height: response.height.unwrap_or_default(),
+            data: response.data.unwrap_or_default(),
+        })
+    }
+
     /// Get this output's keyboard focus stack.
     ///
     /// This will return the focus stack containing *all* windows on this output.
@@ -1182,6 +1655,17 @@ impl OutputHandle {
     }
 }
 
+/// The contents of an output, captured with [`OutputHandle::capture`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Capture {
+    /// The width of the capture, in physical pixels.
+    pub width: u32,
+    /// The height of the capture, in physical pixels.
+    pub height: u32,
+    /// RGBA pixels, top row first, with no padding between rows.
+    pub data: Vec<u8>,
+}
+
 /// A possible output pixel dimension and refresh rate configuration.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct Mode {
@@ -1200,11 +1684,15 @@ pub struct Mode {
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct OutputProperties {
     /// The make of the output.
+    ///
+    /// This is "Unknown" if the output has no EDID or it couldn't be parsed.
     pub make: Option<String>,
     /// The model of the output.
     ///
     /// This is something like "27GL83A" or whatever crap monitor manufacturers name their monitors
     /// these days.
+    ///
+    /// This is "Unknown" if the output has no EDID or it couldn't be parsed.
     pub model: Option<String>,
     /// The x position of the output in the global space.
     pub x: Option<i32>,
@@ -1240,4 +1728,8 @@ pub struct OutputProperties {
     pub serial: Option<u32>,
     /// This output's window keyboard focus stack.
     pub keyboard_focus_stack: Vec<WindowHandle>,
+    /// Whether this output is powered on.
+    pub powered: Option<bool>,
+    /// Whether variable refresh rate is enabled on this output.
+    pub vrr: Option<bool>,
 }
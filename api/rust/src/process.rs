@@ -96,11 +96,25 @@ impl Process {
 
     /// Spawn a process only if it isn't already running.
     ///
+    /// The compositor remembers the exact command lines spawned this way, so reloading
+    /// your config won't spawn them again while they're still running.
+    /// Processes spawned by other means are not considered.
+    ///
     /// This is useful for startup programs.
     ///
+    /// Returns whether or not the process was spawned.
+    ///
     /// See [`Process::spawn`] for details.
-    pub fn spawn_once(&self, args: impl IntoIterator<Item = impl Into<String>>) {
-        self.spawn_inner(args, true, None);
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if !process.spawn_once(["waybar"]) {
+    ///     println!("waybar is already running");
+    /// }
+    /// ```
+    pub fn spawn_once(&self, args: impl IntoIterator<Item = impl Into<String>>) -> bool {
+        self.spawn_inner(args, true, None)
     }
 
     /// Spawn a process only if it isn't already running with optional callbacks for its stdout,
@@ -108,21 +122,26 @@ impl Process {
     ///
     /// This is useful for startup programs.
     ///
-    /// See [`Process::spawn_with_callbacks`] for details.
+    /// Returns whether or not the process was spawned.
+    ///
+    /// See [`Process::spawn_once`] and [`Process::spawn_with_callbacks`] for details.
     pub fn spawn_once_with_callbacks(
         &self,
         args: impl IntoIterator<Item = impl Into<String>>,
         callbacks: SpawnCallbacks,
-    ) {
-        self.spawn_inner(args, true, Some(callbacks));
+    ) -> bool {
+        self.spawn_inner(args, true, Some(callbacks))
     }
 
+    /// Returns whether or not the process was spawned.
+    ///
+    /// Processes not spawned with `once` are assumed to have been spawned.
     fn spawn_inner(
         &self,
         args: impl IntoIterator<Item = impl Into<String>>,
         once: bool,
         callbacks: Option<SpawnCallbacks>,
-    ) {
+    ) -> bool {
         let mut client = self.create_process_client();
 
         let args = args.into_iter().map(Into::into).collect::<Vec<_>>();
@@ -135,6 +154,19 @@ impl Process {
 
         let mut stream = block_on_tokio(client.spawn(request)).unwrap().into_inner();
 
+        let spawned = if once {
+            block_on_tokio(stream.next())
+                .and_then(|response| response.ok())
+                .and_then(|response| response.spawned)
+                .unwrap_or_default()
+        } else {
+            true
+        };
+
+        if !spawned {
+            return false;
+        }
+
         self.fut_sender
             .send(
                 async move {
@@ -161,6 +193,8 @@ impl Process {
                 .boxed(),
             )
             .unwrap();
+
+        true
     }
 
     /// Set an environment variable for the compositor.
@@ -1,16 +1,21 @@
 //! Rendering management.
 
+use std::{sync::OnceLock, time::Duration};
+
 use pinnacle_api_defs::pinnacle::render::v0alpha1::{
-    render_service_client::RenderServiceClient, SetDownscaleFilterRequest, SetUpscaleFilterRequest,
+    get_render_elements_response, render_service_client::RenderServiceClient,
+    GetRenderElementsRequest, GetRenderStatsRequest, GetSchedulingStatsRequest,
+    SetDownscaleFilterRequest, SetUpscaleFilterRequest,
 };
 use tonic::transport::Channel;
 
-use crate::block_on_tokio;
+use crate::{block_on_tokio, output::OutputHandle, util::Geometry, ApiModules};
 
 /// A struct that allows you to manage rendering.
 #[derive(Debug, Clone)]
 pub struct Render {
     client: RenderServiceClient<Channel>,
+    api: OnceLock<ApiModules>,
 }
 
 /// What filter to use when scaling.
@@ -25,13 +30,79 @@ pub enum ScalingFilter {
     NearestNeighbor,
 }
 
+/// Render scheduling counters for an output.
+///
+/// All counters are cumulative from when the output was connected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulingStats {
+    /// The output these stats are for.
+    pub output: OutputHandle,
+    /// How many times a render was requested.
+    ///
+    /// Requests made while a render is already pending are coalesced,
+    /// so this being much higher than `renders` points to over-scheduling.
+    pub render_requests: u64,
+    /// How many renders actually ran.
+    pub renders: u64,
+    /// How many renders produced damage and queued a frame.
+    pub frames_queued: u64,
+    /// How many queued frames weren't presented by the following vblank.
+    ///
+    /// This is always 0 on the winit backend.
+    pub vblank_misses: u64,
+    /// The total time spent rendering.
+    pub render_time: Duration,
+}
+
+/// Timing for the last frame on an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    /// How long the last render took.
+    pub last_render_time: Duration,
+    /// How long ago the last vblank happened, or `None` if there hasn't been one yet.
+    ///
+    /// On the winit backend this is when the last frame was submitted.
+    pub since_last_vblank: Option<Duration>,
+    /// Whether the last render produced damage.
+    pub last_render_damaged: bool,
+}
+
+/// What a render element is drawing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderElementKind {
+    /// A Wayland surface, from a window, layer, or popup.
+    Surface,
+    /// The cursor.
+    Pointer,
+    /// A snapshot of a window taken while a layout is pending.
+    Snapshot,
+}
+
+/// A render element that would be drawn on an output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderElementInfo {
+    /// An opaque identifier, stable for as long as the element exists.
+    pub id: String,
+    /// What this element is drawing.
+    pub kind: Option<RenderElementKind>,
+    /// The element's geometry in physical pixels, relative to the output.
+    pub geometry: Geometry,
+    /// The element's alpha.
+    pub alpha: f32,
+}
+
 impl Render {
     pub(crate) fn new(channel: Channel) -> Self {
         Self {
             client: RenderServiceClient::new(channel),
+            api: OnceLock::new(),
         }
     }
 
+    pub(crate) fn finish_init(&self, api: ApiModules) {
+        self.api.set(api).unwrap();
+    }
+
     /// Set the upscaling filter that will be used for rendering.
     ///
     /// # Examples
@@ -65,4 +136,135 @@ impl Render {
         }))
         .unwrap();
     }
+
+    /// Get render scheduling counters for every output.
+    ///
+    /// This is meant for debugging stutter and performance issues.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for stats in render.scheduling_stats() {
+    ///     println!("{}: {} misses", stats.output.name(), stats.vblank_misses);
+    /// }
+    /// ```
+    pub fn scheduling_stats(&self) -> Vec<SchedulingStats> {
+        block_on_tokio(self.scheduling_stats_async())
+    }
+
+    /// The async version of [`Render::scheduling_stats`].
+    pub async fn scheduling_stats_async(&self) -> Vec<SchedulingStats> {
+        let mut client = self.client.clone();
+        let api = self.api.get().unwrap();
+        let response = client
+            .get_scheduling_stats(GetSchedulingStatsRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        response
+            .outputs
+            .into_iter()
+            .map(|stats| SchedulingStats {
+                output: api.output.new_handle(stats.output_name()),
+                render_requests: stats.render_requests(),
+                renders: stats.renders(),
+                frames_queued: stats.frames_queued(),
+                vblank_misses: stats.vblank_misses(),
+                render_time: Duration::from_micros(stats.render_time_micros()),
+            })
+            .collect()
+    }
+
+    /// Get timing for the last frame on `output`.
+    ///
+    /// Returns `None` if the output doesn't exist or isn't being rendered to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let op = output.get_focused()?;
+    /// if let Some(stats) = render.frame_stats(&op) {
+    ///     println!("last frame took {:?}", stats.last_render_time);
+    /// }
+    /// ```
+    pub fn frame_stats(&self, output: &OutputHandle) -> Option<FrameStats> {
+        block_on_tokio(self.frame_stats_async(output))
+    }
+
+    /// The async version of [`Render::frame_stats`].
+    pub async fn frame_stats_async(&self, output: &OutputHandle) -> Option<FrameStats> {
+        let mut client = self.client.clone();
+        let response = client
+            .get_render_stats(GetRenderStatsRequest {
+                output_name: Some(output.name.clone()),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        Some(FrameStats {
+            last_render_time: Duration::from_micros(response.last_render_time_micros?),
+            since_last_vblank: response.micros_since_last_vblank.map(Duration::from_micros),
+            last_render_damaged: response.last_render_damaged?,
+        })
+    }
+
+    /// Get the render elements that would be drawn on `output` this frame,
+    /// ordered from top to bottom.
+    ///
+    /// This is meant for debugging windows that don't show up or are layered wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let op = output.get_focused()?;
+    /// for elem in render.render_elements(&op) {
+    ///     println!("{:?} {} at {:?}", elem.kind, elem.id, elem.geometry);
+    /// }
+    /// ```
+    pub fn render_elements(&self, output: &OutputHandle) -> Vec<RenderElementInfo> {
+        block_on_tokio(self.render_elements_async(output))
+    }
+
+    /// The async version of [`Render::render_elements`].
+    pub async fn render_elements_async(&self, output: &OutputHandle) -> Vec<RenderElementInfo> {
+        let mut client = self.client.clone();
+        let response = client
+            .get_render_elements(GetRenderElementsRequest {
+                output_name: Some(output.name.clone()),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        response
+            .elements
+            .into_iter()
+            .map(|elem| {
+                let kind = match elem.kind() {
+                    get_render_elements_response::Kind::Unspecified => None,
+                    get_render_elements_response::Kind::Surface => Some(RenderElementKind::Surface),
+                    get_render_elements_response::Kind::Pointer => Some(RenderElementKind::Pointer),
+                    get_render_elements_response::Kind::Snapshot => {
+                        Some(RenderElementKind::Snapshot)
+                    }
+                };
+
+                let geo = elem.geometry.clone().unwrap_or_default();
+
+                RenderElementInfo {
+                    id: elem.id().to_string(),
+                    kind,
+                    geometry: Geometry {
+                        x: geo.x(),
+                        y: geo.y(),
+                        width: geo.width() as u32,
+                        height: geo.height() as u32,
+                    },
+                    alpha: elem.alpha(),
+                }
+            })
+            .collect()
+    }
 }
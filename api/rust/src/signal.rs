@@ -18,7 +18,8 @@ use std::{
 
 use futures::{future::BoxFuture, pin_mut, FutureExt};
 use pinnacle_api_defs::pinnacle::signal::v0alpha1::{
-    signal_service_client::SignalServiceClient, SignalRequest, StreamControl,
+    signal_service_client::SignalServiceClient, window_state_request_response::Kind, SignalRequest,
+    StreamControl,
 };
 use tokio::sync::{
     mpsc::{unbounded_channel, UnboundedSender},
@@ -28,7 +29,12 @@ use tokio_stream::{wrappers::UnboundedReceiverStream, StreamExt};
 use tonic::{transport::Channel, Streaming};
 
 use crate::{
-    block_on_tokio, output::OutputHandle, tag::TagHandle, window::WindowHandle, ApiModules,
+    block_on_tokio,
+    input::{InputDevice, Mod, ModifierState},
+    output::OutputHandle,
+    tag::TagHandle,
+    window::{StateRequest, WindowHandle},
+    ApiModules,
 };
 
 pub(crate) trait Signal {
@@ -142,7 +148,7 @@ signals! {
                 }
             },
         }
-        /// An output was connected.
+        /// An output was disconnected.
         ///
         /// Callbacks receive the disconnected output.
         OutputDisconnect = {
@@ -224,6 +230,89 @@ signals! {
                 if let Some(window_id) = response.window_id {
                     let handle = api.window.new_handle(window_id);
 
+                    for callback in callbacks {
+                        callback(&handle);
+                    }
+                }
+            },
+        }
+        /// A window requested to be fullscreened or maximized.
+        ///
+        /// Callbacks receive the window, what it requested, and the output it asked to be
+        /// fullscreened on, if any.
+        ///
+        /// While any callback is connected, these requests are held until one of them calls
+        /// [`WindowHandle::resolve_state_request`]. Requests that aren't resolved within
+        /// half a second are applied.
+        WindowStateRequest = {
+            enum_name = StateRequest,
+            callback_type = Box<dyn FnMut(&WindowHandle, StateRequest, Option<&OutputHandle>) + Send + 'static>,
+            client_request = window_state_request,
+            on_response = |response, callbacks, api| {
+                let request = match response.kind() {
+                    Kind::Fullscreen => StateRequest::Fullscreen,
+                    Kind::Maximized => StateRequest::Maximized,
+                    Kind::Unspecified => return,
+                };
+
+                if let Some(window_id) = response.window_id {
+                    let handle = api.window.new_handle(window_id);
+                    let output = response
+                        .output_name
+                        .map(|name| api.output.new_handle(name));
+
+                    for callback in callbacks {
+                        callback(&handle, request, output.as_ref());
+                    }
+                }
+            },
+        }
+        /// A window's title changed.
+        ///
+        /// Callbacks receive the window and its new title.
+        WindowTitleChanged = {
+            enum_name = TitleChanged,
+            callback_type = Box<dyn FnMut(&WindowHandle, &str) + Send + 'static>,
+            client_request = window_title_changed,
+            on_response = |response, callbacks, api| {
+                if let (Some(window_id), Some(title)) = (response.window_id, &response.title) {
+                    let handle = api.window.new_handle(window_id);
+
+                    for callback in callbacks {
+                        callback(&handle, title);
+                    }
+                }
+            },
+        }
+        /// A window's class changed.
+        ///
+        /// Callbacks receive the window and its new class. This is the app id for
+        /// Wayland windows and `WM_CLASS` for X11 windows.
+        WindowClassChanged = {
+            enum_name = ClassChanged,
+            callback_type = Box<dyn FnMut(&WindowHandle, &str) + Send + 'static>,
+            client_request = window_class_changed,
+            on_response = |response, callbacks, api| {
+                if let (Some(window_id), Some(class)) = (response.window_id, &response.class) {
+                    let handle = api.window.new_handle(window_id);
+
+                    for callback in callbacks {
+                        callback(&handle, class);
+                    }
+                }
+            },
+        }
+        /// A window received keyboard focus.
+        ///
+        /// Callbacks receive the newly focused window.
+        WindowFocused = {
+            enum_name = Focused,
+            callback_type = SingleWindowFn,
+            client_request = window_focused,
+            on_response = |response, callbacks, api| {
+                if let Some(window_id) = response.window_id {
+                    let handle = api.window.new_handle(window_id);
+
                     for callback in callbacks {
                         callback(&handle);
                     }
@@ -249,10 +338,84 @@ signals! {
             },
         }
     }
+    /// Signals relating to input events.
+    InputSignal => {
+        /// An input device was connected.
+        ///
+        /// Callbacks receive the connected device.
+        InputDeviceAdded = {
+            enum_name = DeviceAdded,
+            callback_type = SingleInputDeviceFn,
+            client_request = input_device_added,
+            on_response = |response, callbacks, _api| {
+                if let Some(device) = response.device {
+                    let device = InputDevice::from_api(device);
+
+                    for callback in callbacks {
+                        callback(&device);
+                    }
+                }
+            },
+        }
+        /// An input device was disconnected.
+        ///
+        /// Callbacks receive the disconnected device.
+        InputDeviceRemoved = {
+            enum_name = DeviceRemoved,
+            callback_type = SingleInputDeviceFn,
+            client_request = input_device_removed,
+            on_response = |response, callbacks, _api| {
+                if let Some(device) = response.device {
+                    let device = InputDevice::from_api(device);
+
+                    for callback in callbacks {
+                        callback(&device);
+                    }
+                }
+            },
+        }
+        /// The keyboard's modifiers changed.
+        ///
+        /// Callbacks receive the new modifier state.
+        InputModifiersChanged = {
+            enum_name = ModifiersChanged,
+            callback_type = Box<dyn FnMut(&ModifierState) + Send + 'static>,
+            client_request = input_modifiers_changed,
+            on_response = |response, callbacks, _api| {
+                let state = ModifierState {
+                    mods: response
+                        .modifiers
+                        .iter()
+                        .flat_map(|modifier| Mod::try_from(*modifier))
+                        .collect(),
+                    caps_lock: response.caps_lock(),
+                    num_lock: response.num_lock(),
+                };
+
+                for callback in callbacks {
+                    callback(&state);
+                }
+            },
+        }
+        /// There was input activity after an idle timeout elapsed.
+        ///
+        /// See [`Input::on_idle`][crate::input::Input::on_idle].
+        InputResumed = {
+            enum_name = Resumed,
+            callback_type = Box<dyn FnMut() + Send + 'static>,
+            client_request = input_resumed,
+            on_response = |_response, callbacks, _api| {
+                for callback in callbacks {
+                    callback();
+                }
+            },
+        }
+    }
 }
 
 pub(crate) type SingleOutputFn = Box<dyn FnMut(&OutputHandle) + Send + 'static>;
 pub(crate) type SingleWindowFn = Box<dyn FnMut(&WindowHandle) + Send + 'static>;
+pub(crate) type SingleInputDeviceFn = Box<dyn FnMut(&InputDevice) + Send + 'static>;
 
 pub(crate) struct SignalState {
     pub(crate) output_connect: SignalData<OutputConnect>,
@@ -262,8 +425,17 @@ pub(crate) struct SignalState {
 
     pub(crate) window_pointer_enter: SignalData<WindowPointerEnter>,
     pub(crate) window_pointer_leave: SignalData<WindowPointerLeave>,
+    pub(crate) window_state_request: SignalData<WindowStateRequest>,
+    pub(crate) window_title_changed: SignalData<WindowTitleChanged>,
+    pub(crate) window_class_changed: SignalData<WindowClassChanged>,
+    pub(crate) window_focused: SignalData<WindowFocused>,
 
     pub(crate) tag_active: SignalData<TagActive>,
+
+    pub(crate) input_device_added: SignalData<InputDeviceAdded>,
+    pub(crate) input_device_removed: SignalData<InputDeviceRemoved>,
+    pub(crate) input_modifiers_changed: SignalData<InputModifiersChanged>,
+    pub(crate) input_resumed: SignalData<InputResumed>,
 }
 
 impl std::fmt::Debug for SignalState {
@@ -285,7 +457,15 @@ impl SignalState {
             output_move: SignalData::new(client.clone(), fut_sender.clone()),
             window_pointer_enter: SignalData::new(client.clone(), fut_sender.clone()),
             window_pointer_leave: SignalData::new(client.clone(), fut_sender.clone()),
+            window_state_request: SignalData::new(client.clone(), fut_sender.clone()),
+            window_title_changed: SignalData::new(client.clone(), fut_sender.clone()),
+            window_class_changed: SignalData::new(client.clone(), fut_sender.clone()),
+            window_focused: SignalData::new(client.clone(), fut_sender.clone()),
             tag_active: SignalData::new(client.clone(), fut_sender.clone()),
+            input_device_added: SignalData::new(client.clone(), fut_sender.clone()),
+            input_device_removed: SignalData::new(client.clone(), fut_sender.clone()),
+            input_modifiers_changed: SignalData::new(client.clone(), fut_sender.clone()),
+            input_resumed: SignalData::new(client.clone(), fut_sender.clone()),
         }
     }
 
@@ -296,7 +476,15 @@ impl SignalState {
         self.output_move.api.set(api.clone()).unwrap();
         self.window_pointer_enter.api.set(api.clone()).unwrap();
         self.window_pointer_leave.api.set(api.clone()).unwrap();
+        self.window_state_request.api.set(api.clone()).unwrap();
+        self.window_title_changed.api.set(api.clone()).unwrap();
+        self.window_class_changed.api.set(api.clone()).unwrap();
+        self.window_focused.api.set(api.clone()).unwrap();
         self.tag_active.api.set(api.clone()).unwrap();
+        self.input_device_added.api.set(api.clone()).unwrap();
+        self.input_device_removed.api.set(api.clone()).unwrap();
+        self.input_modifiers_changed.api.set(api.clone()).unwrap();
+        self.input_resumed.api.set(api.clone()).unwrap();
     }
 
     pub(crate) fn shutdown(&mut self) {
@@ -306,7 +494,15 @@ impl SignalState {
         self.output_move.reset();
         self.window_pointer_enter.reset();
         self.window_pointer_leave.reset();
+        self.window_state_request.reset();
+        self.window_title_changed.reset();
+        self.window_class_changed.reset();
+        self.window_focused.reset();
         self.tag_active.reset();
+        self.input_device_added.reset();
+        self.input_device_removed.reset();
+        self.input_modifiers_changed.reset();
+        self.input_resumed.reset();
     }
 }
 
@@ -36,8 +36,10 @@ use pinnacle_api_defs::pinnacle::{
     tag::{
         self,
         v0alpha1::{
-            tag_service_client::TagServiceClient, AddRequest, RemoveRequest, SetActiveRequest,
-            SwitchToRequest,
+            set_master_factor_request, tag_service_client::TagServiceClient, AddRequest,
+            RemoveRequest, SetActiveRequest, SetDefaultsRequest, SetGapsRequest, SetLayoutRequest,
+            SetMasterFactorRequest, SetSwitchAnimationRequest, SwitchToPreviousRequest,
+            SwitchToRelativeRequest, SwitchToRequest,
         },
     },
     v0alpha1::SetOrToggle,
@@ -127,6 +129,154 @@ impl Tag {
             .collect()
     }
 
+    /// Set the tags that outputs are given when they connect for the first time.
+    ///
+    /// The first tag is made active. Outputs that are reconnected get their old tags back
+    /// instead. Currently connected outputs that don't have any tags get these immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Every new monitor gets tags 1-5, with 1 active
+    /// tag.set_defaults(["1", "2", "3", "4", "5"]);
+    /// ```
+    pub fn set_defaults(&self, tag_names: impl IntoIterator<Item = impl Into<String>>) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_defaults(SetDefaultsRequest {
+            tag_names: tag_names.into_iter().map(Into::into).collect(),
+        }))
+        .unwrap();
+    }
+
+    /// Set whether switching tags crossfades from the old tags' windows to the new ones.
+    ///
+    /// This is off by default for instant switches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.set_switch_animation(true);
+    /// ```
+    pub fn set_switch_animation(&self, enabled: bool) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_switch_animation(SetSwitchAnimationRequest {
+            enabled: Some(enabled),
+            duration_ms: None,
+        }))
+        .unwrap();
+    }
+
+    /// Set how long the tag switch animation lasts in milliseconds.
+    ///
+    /// The default is 200ms. This must be positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.set_switch_animation_duration(150);
+    /// ```
+    pub fn set_switch_animation_duration(&self, duration_ms: u32) {
+        let mut client = self.tag_client.clone();
+        if let Err(status) =
+            block_on_tokio(client.set_switch_animation(SetSwitchAnimationRequest {
+                enabled: None,
+                duration_ms: Some(duration_ms),
+            }))
+        {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Switch back to the tags that were active on `output` before the last change in active tags.
+    ///
+    /// Calling this repeatedly toggles between the two most recent sets of active tags.
+    /// If `output` is `None`, the focused output is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.switch_to();
+    /// tag.get("2")?.switch_to();
+    /// tag.switch_to_previous(None); // Displays tag "1"
+    /// tag.switch_to_previous(None); // Displays tag "2"
+    /// ```
+    pub fn switch_to_previous(&self, output: Option<&OutputHandle>) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.switch_to_previous(SwitchToPreviousRequest {
+            output_name: output.map(|output| output.name.clone()),
+        }))
+        .unwrap();
+    }
+
+    /// Switch to the tag after the active one on `output`, wrapping around.
+    ///
+    /// Tags are ordered in the order they were added. If `output` is `None`, the focused
+    /// output is used. This does nothing if the output has fewer than two tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.add(&output.get_focused()?, ["1", "2", "3"]);
+    /// tag.get("3")?.switch_to();
+    /// tag.switch_to_next(None); // Displays tag "1"
+    /// ```
+    pub fn switch_to_next(&self, output: Option<&OutputHandle>) {
+        self.switch_to_relative(output, 1, false);
+    }
+
+    /// Switch to the tag before the active one on `output`, wrapping around.
+    ///
+    /// Tags are ordered in the order they were added. If `output` is `None`, the focused
+    /// output is used. This does nothing if the output has fewer than two tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.add(&output.get_focused()?, ["1", "2", "3"]);
+    /// tag.get("1")?.switch_to();
+    /// tag.switch_to_prev(None); // Displays tag "3"
+    /// ```
+    pub fn switch_to_prev(&self, output: Option<&OutputHandle>) {
+        self.switch_to_relative(output, -1, false);
+    }
+
+    /// Switch to the next tag after the active one on `output` that has windows,
+    /// wrapping around.
+    ///
+    /// If no other tag has windows, the active tag stays active.
+    /// If `output` is `None`, the focused output is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.add(&output.get_focused()?, ["1", "2", "3"]);
+    /// // With windows only on tags "1" and "3"
+    /// tag.get("1")?.switch_to();
+    /// tag.switch_to_next_occupied(None); // Displays tag "3"
+    /// ```
+    pub fn switch_to_next_occupied(&self, output: Option<&OutputHandle>) {
+        self.switch_to_relative(output, 1, true);
+    }
+
+    /// Switch to the previous tag before the active one on `output` that has windows,
+    /// wrapping around.
+    ///
+    /// If no other tag has windows, the active tag stays active.
+    /// If `output` is `None`, the focused output is used.
+    pub fn switch_to_prev_occupied(&self, output: Option<&OutputHandle>) {
+        self.switch_to_relative(output, -1, true);
+    }
+
+    fn switch_to_relative(&self, output: Option<&OutputHandle>, offset: i32, only_occupied: bool) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.switch_to_relative(SwitchToRelativeRequest {
+            output_name: output.map(|output| output.name.clone()),
+            offset: Some(offset),
+            only_occupied: Some(only_occupied),
+        }))
+        .unwrap();
+    }
+
     /// Get handles to all tags across all outputs.
     ///
     /// # Examples
@@ -220,12 +370,18 @@ impl Tag {
 
     /// Remove the given tags from their outputs.
     ///
+    /// Windows that were only on removed tags are moved to their output's active tags.
+    /// If an output would be left without any tags, it gets the default tags set with
+    /// [`Tag::set_defaults`], or a single active tag named "1" if there are none.
+    ///
+    /// Removing a tag that was already removed does nothing.
+    ///
     /// # Examples
     ///
     /// ```
     /// let tags = tag.add(output.get_by_name("DP-1")?, ["1", "2", "Buckle", "Shoe"]);
     ///
-    /// tag.remove(tags); // "DP-1" no longer has any tags
+    /// tag.remove(tags); // "DP-1" now only has a fallback tag
     /// ```
     pub fn remove(&self, tags: impl IntoIterator<Item = TagHandle>) {
         let tag_ids = tags.into_iter().map(|handle| handle.id).collect::<Vec<_>>();
@@ -303,6 +459,9 @@ impl TagHandle {
     /// While inactive, windows with this tag will not be displayed unless they have other active
     /// tags.
     ///
+    /// Unlike [`TagHandle::toggle_active`], this sets a known state, and does nothing
+    /// if the tag is already in that state.
+    ///
     /// # Examples
     ///
     /// ```
@@ -354,6 +513,101 @@ impl TagHandle {
         .unwrap();
     }
 
+    /// Set the name of the layout this tag uses.
+    ///
+    /// Layouts are generated by your config, so the compositor only stores this name
+    /// for [`LayoutManager`][crate::layout::LayoutManager]s and other parts of your config
+    /// to read back with [`TagHandle::layout`]. Passing `None` clears it.
+    ///
+    /// This will cause the tag's output to be relaid out if the tag is active.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.set_layout(Some("my_custom_layout"));
+    /// ```
+    pub fn set_layout(&self, layout: Option<impl Into<String>>) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_layout(SetLayoutRequest {
+            tag_id: Some(self.id),
+            layout: layout.map(Into::into),
+        }))
+        .unwrap();
+    }
+
+    /// Set the gaps around tiled windows while this tag is active.
+    ///
+    /// `inner` is the gap between adjacent tiled windows and `outer` is the gap between
+    /// tiled windows and the edges of the output's usable area, both in logical pixels.
+    /// If multiple tags on an output are active, the first one's gaps are used.
+    /// Fullscreen and maximized windows don't get gaps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.set_gaps(8, 16);
+    /// ```
+    pub fn set_gaps(&self, inner: u32, outer: u32) {
+        let mut client = self.tag_client.clone();
+        block_on_tokio(client.set_gaps(SetGapsRequest {
+            tag_id: Some(self.id),
+            inner: Some(inner),
+            outer: Some(outer),
+        }))
+        .unwrap();
+    }
+
+    /// Set the proportion of the layout area the master area takes up while this tag is active.
+    ///
+    /// This is clamped between 0.1 and 0.9, and non-finite values are ignored. If multiple
+    /// tags on an output are active, the first one's factor is used. [`MasterStackLayout`]
+    /// uses this instead of its own `master_factor` once it's set.
+    ///
+    /// [`MasterStackLayout`]: crate::layout::MasterStackLayout
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// tag.get("1")?.set_master_factor(0.6);
+    /// ```
+    pub fn set_master_factor(&self, master_factor: f32) {
+        self.set_master_factor_inner(set_master_factor_request::AbsoluteOrRelative::Absolute(
+            master_factor,
+        ));
+    }
+
+    /// Change the master factor by `delta`.
+    ///
+    /// If the master factor was never set, this changes it from the factor the layout used
+    /// (see [`LayoutGenerator::master_factor`][crate::layout::LayoutGenerator::master_factor]),
+    /// or from 0.5 if the layout didn't report one.
+    /// See [`TagHandle::set_master_factor`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Grow the master area
+    /// tag.get("1")?.adjust_master_factor(0.05);
+    /// ```
+    pub fn adjust_master_factor(&self, delta: f32) {
+        self.set_master_factor_inner(set_master_factor_request::AbsoluteOrRelative::Relative(
+            delta,
+        ));
+    }
+
+    fn set_master_factor_inner(
+        &self,
+        absolute_or_relative: set_master_factor_request::AbsoluteOrRelative,
+    ) {
+        let mut client = self.tag_client.clone();
+        if let Err(status) = block_on_tokio(client.set_master_factor(SetMasterFactorRequest {
+            tag_id: Some(self.id),
+            absolute_or_relative: Some(absolute_or_relative),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
     /// Remove this tag from its output.
     ///
     /// # Examples
@@ -416,6 +670,9 @@ impl TagHandle {
                 .into_iter()
                 .map(|id| window.new_handle(id))
                 .collect(),
+            layout: response.layout,
+            inner_gap: response.inner_gap,
+            outer_gap: response.outer_gap,
         }
     }
 
@@ -467,6 +724,53 @@ impl TagHandle {
         self.props_async().await.windows
     }
 
+    /// Get the name of the layout this tag uses, if one was set.
+    ///
+    /// Shorthand for `self.props().layout`.
+    pub fn layout(&self) -> Option<String> {
+        self.props().layout
+    }
+
+    /// The async version of [`TagHandle::layout`].
+    pub async fn layout_async(&self) -> Option<String> {
+        self.props_async().await.layout
+    }
+
+    /// Get this tag's inner and outer gaps.
+    ///
+    /// Shorthand for `self.props().inner_gap` and `self.props().outer_gap`.
+    pub fn gaps(&self) -> Option<(u32, u32)> {
+        let props = self.props();
+        props.inner_gap.zip(props.outer_gap)
+    }
+
+    /// The async version of [`TagHandle::gaps`].
+    pub async fn gaps_async(&self) -> Option<(u32, u32)> {
+        let props = self.props_async().await;
+        props.inner_gap.zip(props.outer_gap)
+    }
+
+    /// Get this tag's master factor, if it was set.
+    ///
+    /// This isn't part of [`TagProperties`] so that they stay hashable.
+    pub fn master_factor(&self) -> Option<f32> {
+        block_on_tokio(self.master_factor_async())
+    }
+
+    /// The async version of [`TagHandle::master_factor`].
+    pub async fn master_factor_async(&self) -> Option<f32> {
+        let mut client = self.tag_client.clone();
+
+        client
+            .get_properties(tag::v0alpha1::GetPropertiesRequest {
+                tag_id: Some(self.id),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .master_factor
+    }
+
     /// Get this tag's raw compositor id.
     pub fn id(&self) -> u32 {
         self.id
@@ -484,4 +788,10 @@ pub struct TagProperties {
     pub output: Option<OutputHandle>,
     /// The windows that have this tag
     pub windows: Vec<WindowHandle>,
+    /// The name of the layout this tag uses
+    pub layout: Option<String>,
+    /// The gap between adjacent tiled windows
+    pub inner_gap: Option<u32>,
+    /// The gap between tiled windows and the edges of the output's usable area
+    pub outer_gap: Option<u32>,
 }
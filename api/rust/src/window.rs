@@ -22,9 +22,13 @@ use pinnacle_api_defs::pinnacle::{
         self,
         v0alpha1::{
             window_service_client::WindowServiceClient, AddWindowRuleRequest, CloseRequest,
-            GetRequest, MoveGrabRequest, MoveToTagRequest, RaiseRequest, ResizeGrabRequest,
-            SetFloatingRequest, SetFocusedRequest, SetFullscreenRequest, SetMaximizedRequest,
-            SetTagRequest,
+            CycleFocusRequest, FocusRequest, GetAllPropertiesRequest, GetRequest, LowerRequest,
+            MoveGrabRequest, MoveToOutputRequest, MoveToRelativeOutputRequest, MoveToTagRequest,
+            RaiseRequest, ResizeGrabRequest, ResolveStateRequestRequest, SetFloatingRequest,
+            SetFocusedRequest, SetFullscreenRequest, SetGeometryRequest,
+            SetKeepFloatingInBoundsRequest, SetMaximizedRequest, SetMinimizedRequest,
+            SetOpacityRequest, SetTagRequest, SwapRequest, ToggleScratchpadRequest,
+            ToggleScratchpadVisibleRequest,
         },
     },
 };
@@ -33,6 +37,7 @@ use tonic::transport::Channel;
 use crate::{
     block_on_tokio,
     input::MouseButton,
+    output::OutputHandle,
     signal::{SignalHandle, WindowSignal},
     tag::TagHandle,
     util::{Batch, Geometry},
@@ -43,6 +48,23 @@ use self::rules::{WindowRule, WindowRuleCondition};
 
 pub mod rules;
 
+bitflags::bitflags! {
+    /// The edges a window is resized from in [`Window::begin_resize`].
+    ///
+    /// Opposite edges like `TOP | BOTTOM` can't be combined.
+    #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+    pub struct ResizeEdges: u32 {
+        /// The top edge.
+        const TOP = 1;
+        /// The bottom edge.
+        const BOTTOM = 1 << 1;
+        /// The left edge.
+        const LEFT = 1 << 2;
+        /// The right edge.
+        const RIGHT = 1 << 3;
+    }
+}
+
 /// A struct containing methods that get [`WindowHandle`]s and move windows with the mouse.
 ///
 /// See [`WindowHandle`] for more information.
@@ -102,6 +124,12 @@ impl Window {
     ///
     /// This will begin resizing the window under the pointer using the specified [`MouseButton`].
     /// The button must be held down at the time this method is called for the resize to start.
+    /// Releasing it ends the resize.
+    ///
+    /// The window is resized from `edges`. If `edges` is `None`, the corner closest to the
+    /// pointer is used.
+    ///
+    /// Only floating windows can be resized this way; tiled windows are sized by the layout.
     ///
     /// This is intended to be used with [`Input::mousebind`][crate::input::Input::mousebind].
     ///
@@ -109,16 +137,110 @@ impl Window {
     ///
     /// ```
     /// use pinnacle_api::input::{Mod, MouseButton, MouseEdge};
+    /// use pinnacle_api::window::ResizeEdges;
     ///
-    /// // Set `Super + right click` to begin moving a window
+    /// // Set `Super + right click` to begin resizing a window from the nearest corner
     /// input.mousebind([Mod::Super], MouseButton::Right, MouseEdge::Press, || {
-    ///     window.begin_resize(MouseButton::Right);
+    ///     window.begin_resize(MouseButton::Right, None);
+    /// });
+    ///
+    /// // Set `Super + Shift + right click` to only resize a window's width
+    /// input.mousebind([Mod::Super, Mod::Shift], MouseButton::Right, MouseEdge::Press, || {
+    ///     window.begin_resize(MouseButton::Right, Some(ResizeEdges::RIGHT));
     /// });
     /// ```
-    pub fn begin_resize(&self, button: MouseButton) {
+    pub fn begin_resize(&self, button: MouseButton, edges: Option<ResizeEdges>) {
         let mut client = self.window_client.clone();
-        block_on_tokio(client.resize_grab(ResizeGrabRequest {
+        if let Err(status) = block_on_tokio(client.resize_grab(ResizeGrabRequest {
             button: Some(button as u32),
+            edges: edges.map(|edges| edges.bits()),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Set whether or not floating windows are kept within output bounds.
+    ///
+    /// When enabled, at least `margin` pixels of every floating window stay on some output.
+    /// Windows placed or moved further offscreen, or left offscreen when an output is moved,
+    /// resized, or disconnected, are moved back onto the nearest output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Keep at least 50 pixels of floating windows onscreen
+    /// window.keep_floating_in_bounds(true, 50);
+    /// ```
+    pub fn keep_floating_in_bounds(&self, enabled: bool, margin: u32) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(
+            client.set_keep_floating_in_bounds(SetKeepFloatingInBoundsRequest {
+                enabled: Some(enabled),
+                margin: Some(margin),
+            }),
+        )
+        .unwrap();
+    }
+
+    /// Summon the scratchpad on the focused output, or dismiss it if any of its windows
+    /// are already shown there.
+    ///
+    /// Summoned windows are centered on the output and focused.
+    /// See [`WindowHandle::toggle_scratchpad`] to add windows to the scratchpad.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.keybind([Mod::Super], '`', || {
+    ///     window.toggle_scratchpad_visible();
+    /// });
+    /// ```
+    pub fn toggle_scratchpad_visible(&self) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(client.toggle_scratchpad_visible(ToggleScratchpadVisibleRequest {}))
+            .unwrap();
+    }
+
+    /// Focus and raise the next window on the focused output, wrapping around.
+    ///
+    /// Windows are cycled most recently focused first. If `all_tags` is `false`,
+    /// only windows on the output's active tags are cycled through. Otherwise, windows
+    /// on all of the output's tags are, and the chosen window's tag is switched to
+    /// if it isn't active.
+    ///
+    /// Minimized windows and windows in the scratchpad are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.keybind([Mod::Alt], Keysym::Tab, || {
+    ///     window.cycle_focus_next(false);
+    /// });
+    /// ```
+    pub fn cycle_focus_next(&self, all_tags: bool) {
+        self.cycle_focus(1, all_tags);
+    }
+
+    /// Focus and raise the previous window on the focused output, wrapping around.
+    ///
+    /// See [`Window::cycle_focus_next`] for which windows are cycled through.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// input.keybind([Mod::Alt, Mod::Shift], Keysym::Tab, || {
+    ///     window.cycle_focus_prev(false);
+    /// });
+    /// ```
+    pub fn cycle_focus_prev(&self, all_tags: bool) {
+        self.cycle_focus(-1, all_tags);
+    }
+
+    fn cycle_focus(&self, offset: i32, all_tags: bool) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(client.cycle_focus(CycleFocusRequest {
+            offset: Some(offset),
+            all_tags: Some(all_tags),
         }))
         .unwrap();
     }
@@ -148,6 +270,48 @@ impl Window {
             .collect::<Vec<_>>()
     }
 
+    /// Get all windows along with their properties.
+    ///
+    /// This gets everything in one request, which is faster than calling
+    /// [`WindowHandle::props`] on every window from [`Window::get_all`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for (win, props) in window.get_all_with_props() {
+    ///     // Skip menus and tooltips
+    ///     if props.is_x11_override_redirect == Some(true) {
+    ///         continue;
+    ///     }
+    ///
+    ///     println!("{}: {:?}", win.id(), props.title);
+    /// }
+    /// ```
+    pub fn get_all_with_props(&self) -> Vec<(WindowHandle, WindowProperties)> {
+        block_on_tokio(self.get_all_with_props_async())
+    }
+
+    /// The async version of [`Window::get_all_with_props`].
+    pub async fn get_all_with_props_async(&self) -> Vec<(WindowHandle, WindowProperties)> {
+        let mut client = self.window_client.clone();
+        let api = self.api.get().unwrap();
+
+        client
+            .get_all_properties(GetAllPropertiesRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .windows
+            .into_iter()
+            .map(|win| {
+                (
+                    self.new_handle(win.window_id()),
+                    WindowProperties::from_response(win.properties.unwrap_or_default(), api),
+                )
+            })
+            .collect()
+    }
+
     /// Get the currently focused window.
     ///
     /// # Examples
@@ -176,11 +340,12 @@ impl Window {
     pub fn add_window_rule(&self, cond: WindowRuleCondition, rule: WindowRule) {
         let mut client = self.window_client.clone();
 
-        block_on_tokio(client.add_window_rule(AddWindowRuleRequest {
+        if let Err(status) = block_on_tokio(client.add_window_rule(AddWindowRuleRequest {
             cond: Some(cond.0),
             rule: Some(rule.0),
-        }))
-        .unwrap();
+        })) {
+            eprintln!("ERROR: {status}");
+        }
     }
 
     /// Connect to a window signal.
@@ -194,6 +359,10 @@ impl Window {
         match signal {
             WindowSignal::PointerEnter(f) => signal_state.window_pointer_enter.add_callback(f),
             WindowSignal::PointerLeave(f) => signal_state.window_pointer_leave.add_callback(f),
+            WindowSignal::StateRequest(f) => signal_state.window_state_request.add_callback(f),
+            WindowSignal::TitleChanged(f) => signal_state.window_title_changed.add_callback(f),
+            WindowSignal::ClassChanged(f) => signal_state.window_class_changed.add_callback(f),
+            WindowSignal::Focused(f) => signal_state.window_focused.add_callback(f),
         }
     }
 }
@@ -234,6 +403,17 @@ pub enum FullscreenOrMaximized {
     Maximized,
 }
 
+/// A state a window can request, which your config can allow or deny.
+///
+/// See [`WindowSignal::StateRequest`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum StateRequest {
+    /// The window wants to be fullscreen
+    Fullscreen,
+    /// The window wants to be maximized
+    Maximized,
+}
+
 /// Properties of a window.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct WindowProperties {
@@ -254,12 +434,56 @@ pub struct WindowProperties {
     pub fullscreen_or_maximized: Option<FullscreenOrMaximized>,
     /// All the tags on the window
     pub tags: Vec<TagHandle>,
+    /// Whether the window is an X11 override-redirect window, like a menu or tooltip
+    ///
+    /// These windows aren't managed by the layout.
+    pub is_x11_override_redirect: Option<bool>,
+    /// Whether the window is in the scratchpad
+    pub scratchpad: Option<bool>,
+    /// Whether the window is minimized
+    pub minimized: Option<bool>,
+}
+
+impl WindowProperties {
+    fn from_response(response: window::v0alpha1::GetPropertiesResponse, api: &ApiModules) -> Self {
+        let fullscreen_or_maximized = response
+            .fullscreen_or_maximized
+            .unwrap_or_default()
+            .try_into()
+            .ok();
+
+        let geometry = response.geometry.map(|geo| Geometry {
+            x: geo.x(),
+            y: geo.y(),
+            width: geo.width() as u32,
+            height: geo.height() as u32,
+        });
+
+        WindowProperties {
+            geometry,
+            class: response.class,
+            title: response.title,
+            focused: response.focused,
+            floating: response.floating,
+            fullscreen_or_maximized,
+            tags: response
+                .tag_ids
+                .into_iter()
+                .map(|id| api.tag.new_handle(id))
+                .collect(),
+            is_x11_override_redirect: response.x11_override_redirect,
+            scratchpad: response.scratchpad,
+            minimized: response.minimized,
+        }
+    }
 }
 
 impl WindowHandle {
     /// Send a close request to this window.
     ///
-    /// If the window is unresponsive, it may not close.
+    /// Wayland windows are sent an `xdg_toplevel.close` event and X11 windows are sent
+    /// `WM_DELETE_WINDOW`. The window is never forcefully killed, so if it is unresponsive
+    /// or decides to ignore the request, it may not close.
     ///
     /// # Examples
     ///
@@ -357,6 +581,74 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Set this window to minimized or not.
+    ///
+    /// Minimized windows are hidden and left out of the layout but still show up
+    /// in [`Window::get_all`]. Minimizing the focused window focuses the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Minimize the focused window.
+    /// window.get_focused()?.set_minimized(true);
+    /// ```
+    pub fn set_minimized(&self, set: bool) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(client.set_minimized(SetMinimizedRequest {
+            window_id: Some(self.id),
+            set_or_toggle: Some(match set {
+                true => SetOrToggle::Set,
+                false => SetOrToggle::Unset,
+            } as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Toggle this window between minimized and not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Toggle the focused window to and from minimized.
+    /// window.get_focused()?.toggle_minimized();
+    /// ```
+    pub fn toggle_minimized(&self) {
+        let mut client = self.window_client.clone();
+        block_on_tokio(client.set_minimized(SetMinimizedRequest {
+            window_id: Some(self.id),
+            set_or_toggle: Some(SetOrToggle::Toggle as i32),
+        }))
+        .unwrap();
+    }
+
+    /// Set this window's location and size.
+    ///
+    /// Tiled windows are made floating at the given geometry.
+    /// Fullscreen and maximized windows will only reflect this once they're neither.
+    ///
+    /// Widths and heights of zero are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Snap the focused window to the left half of a 1920x1080 output.
+    /// window.get_focused()?.set_geometry(0, 0, 960, 1080);
+    /// ```
+    pub fn set_geometry(&self, x: i32, y: i32, width: u32, height: u32) {
+        let mut client = self.window_client.clone();
+        if let Err(status) = block_on_tokio(client.set_geometry(SetGeometryRequest {
+            window_id: Some(self.id),
+            geometry: Some(pinnacle_api_defs::pinnacle::v0alpha1::Geometry {
+                x: Some(x),
+                y: Some(y),
+                width: Some(width as i32),
+                height: Some(height as i32),
+            }),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
     /// Set this window to floating or not.
     ///
     /// Floating windows will not be tiled and can be moved around and resized freely.
@@ -443,6 +735,73 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Raise this window and give it keyboard focus.
+    ///
+    /// If this window isn't on an active tag, this switches to its first tag first.
+    /// Override-redirect X11 windows can't be focused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Focus the first window with class "firefox"
+    /// if let Some(firefox) = window.get_all().into_iter().find(|win| win.class().as_deref() == Some("firefox")) {
+    ///     firefox.focus();
+    /// }
+    /// ```
+    pub fn focus(&self) {
+        self.focus_inner(true);
+    }
+
+    /// Raise this window and give it keyboard focus only if it's on an active tag.
+    ///
+    /// Unlike [`WindowHandle::focus`], this doesn't switch tags; focusing a window on an
+    /// inactive tag does nothing and logs a warning in the compositor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Focus the first window with class "firefox" if it's visible
+    /// if let Some(firefox) = window.get_all().into_iter().find(|win| win.class().as_deref() == Some("firefox")) {
+    ///     firefox.focus_if_visible();
+    /// }
+    /// ```
+    pub fn focus_if_visible(&self) {
+        self.focus_inner(false);
+    }
+
+    fn focus_inner(&self, follow: bool) {
+        let mut client = self.window_client.clone();
+        if let Err(status) = block_on_tokio(client.focus(FocusRequest {
+            window_id: Some(self.id),
+            follow: Some(follow),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Add this window to the scratchpad and hide it, or remove it from the scratchpad.
+    ///
+    /// Scratchpad windows float and aren't on any tag while hidden, so they stay around
+    /// when outputs disconnect. Show them with [`Window::toggle_scratchpad_visible`].
+    /// Removing a hidden window from the scratchpad puts it back on the focused output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Stash the focused window
+    /// if let Some(focused) = window.get_focused() {
+    ///     focused.toggle_scratchpad();
+    /// }
+    /// ```
+    pub fn toggle_scratchpad(&self) {
+        let mut client = self.window_client.clone();
+        if let Err(status) = block_on_tokio(client.toggle_scratchpad(ToggleScratchpadRequest {
+            window_id: Some(self.id),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
     /// Move this window to the given `tag`.
     ///
     /// This will remove all tags from this window then tag it with `tag`, essentially moving the
@@ -460,6 +819,87 @@ impl WindowHandle {
         block_on_tokio(client.move_to_tag(MoveToTagRequest {
             window_id: Some(self.id),
             tag_id: Some(tag.id),
+            follow: None,
+        }))
+        .unwrap();
+    }
+
+    /// Move this window to the given `tag`, then switch to that tag and focus this window.
+    ///
+    /// This is [`WindowHandle::move_to_tag`] followed by switching the view to `tag`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Move the focused window to tag "Code" on the focused output and go there with it
+    /// window.get_focused()?.move_to_tag_and_follow(&tag.get("Code")?);
+    /// ```
+    pub fn move_to_tag_and_follow(&self, tag: &TagHandle) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.move_to_tag(MoveToTagRequest {
+            window_id: Some(self.id),
+            tag_id: Some(tag.id),
+            follow: Some(true),
+        }))
+        .unwrap();
+    }
+
+    /// Move this window to the next output, placing it on that output's active tags.
+    ///
+    /// Outputs are cycled through in the order they were connected, wrapping around.
+    /// The window stays focused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.move_to_next_output();
+    /// ```
+    pub fn move_to_next_output(&self) {
+        self.move_to_relative_output(1);
+    }
+
+    /// Move this window to the previous output, placing it on that output's active tags.
+    ///
+    /// See [`WindowHandle::move_to_next_output`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.move_to_prev_output();
+    /// ```
+    pub fn move_to_prev_output(&self) {
+        self.move_to_relative_output(-1);
+    }
+
+    /// Move this window to `output`, placing it on that output's active tags.
+    ///
+    /// Floating windows keep their relative position on the output, and fullscreen and
+    /// maximized windows fill the new output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let dp2 = output.get_by_name("DP-2")?;
+    /// window.get_focused()?.move_to_output(&dp2);
+    /// ```
+    pub fn move_to_output(&self, output: &OutputHandle) {
+        let mut client = self.window_client.clone();
+
+        if let Err(status) = block_on_tokio(client.move_to_output(MoveToOutputRequest {
+            window_id: Some(self.id),
+            output_name: Some(output.name.clone()),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    fn move_to_relative_output(&self, offset: i32) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.move_to_relative_output(MoveToRelativeOutputRequest {
+            window_id: Some(self.id),
+            offset: Some(offset),
         }))
         .unwrap();
     }
@@ -491,6 +931,9 @@ impl WindowHandle {
 
     /// Toggle a tag on this window.
     ///
+    /// Windows can have any number of tags and will be displayed when any of them are active,
+    /// so this can be used to show a window on multiple tags at once.
+    ///
     /// # Examples
     ///
     /// ```
@@ -501,6 +944,10 @@ impl WindowHandle {
     ///
     /// focused.toggle_tag(&tg); // `focused` now has tag "Potato"
     /// focused.toggle_tag(&tg); // `focused` no longer has tag "Potato"
+    ///
+    /// // Show the focused window on both "main" and "media"
+    /// focused.move_to_tag(&tag.get("main")?);
+    /// focused.toggle_tag(&tag.get("media")?);
     /// ```
     pub fn toggle_tag(&self, tag: &TagHandle) {
         let mut client = self.window_client.clone();
@@ -513,6 +960,26 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Set this window's opacity.
+    ///
+    /// `opacity` is clamped between 0.0 (fully transparent) and 1.0 (fully opaque).
+    /// Fullscreen windows are always drawn fully opaque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.set_opacity(0.9);
+    /// ```
+    pub fn set_opacity(&self, opacity: f32) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.set_opacity(SetOpacityRequest {
+            window_id: Some(self.id),
+            opacity: Some(opacity),
+        }))
+        .unwrap();
+    }
+
     /// Raise this window.
     ///
     /// This will raise this window all the way to the top of the z-stack.
@@ -531,6 +998,80 @@ impl WindowHandle {
         .unwrap();
     }
 
+    /// Lower this window below all other floating windows.
+    ///
+    /// It stays above tiled windows. This does nothing if the window is tiled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// window.get_focused()?.lower();
+    /// ```
+    pub fn lower(&self) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.lower(LowerRequest {
+            window_id: Some(self.id),
+        }))
+        .unwrap();
+    }
+
+    /// Swap this window's position in the layout with `other`.
+    ///
+    /// Both windows must be tiled, not fullscreen, and on the same output. Otherwise,
+    /// an error is printed and nothing happens. Keyboard focus stays on whichever window
+    /// had it before the swap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Swap the focused window with the first window in the focus stack
+    /// let focused = window.get_focused()?;
+    /// let first = output.get_focused()?.keyboard_focus_stack_visible()[0].clone();
+    /// focused.swap(&first);
+    /// ```
+    pub fn swap(&self, other: &WindowHandle) {
+        let mut client = self.window_client.clone();
+
+        if let Err(status) = block_on_tokio(client.swap(SwapRequest {
+            window_id: Some(self.id),
+            other_window_id: Some(other.id),
+        })) {
+            eprintln!("ERROR: {status}");
+        }
+    }
+
+    /// Allow or deny this window's pending fullscreen or maximize request.
+    ///
+    /// This is meant to be called from a [`WindowSignal::StateRequest`] callback.
+    /// Does nothing if the window has no pending request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::signal::WindowSignal;
+    /// use pinnacle_api::window::StateRequest;
+    ///
+    /// // Always fullscreen videos on the TV
+    /// window.connect_signal(WindowSignal::StateRequest(Box::new(|win, request, _| {
+    ///     if request == StateRequest::Fullscreen && win.class().as_deref() == Some("mpv") {
+    ///         if let Some(tv) = output.get_by_name("HDMI-A-1") {
+    ///             win.move_to_tag(&tv.tags()[0]);
+    ///         }
+    ///     }
+    ///     win.resolve_state_request(true);
+    /// })));
+    /// ```
+    pub fn resolve_state_request(&self, allow: bool) {
+        let mut client = self.window_client.clone();
+
+        block_on_tokio(client.resolve_state_request(ResolveStateRequestRequest {
+            window_id: Some(self.id),
+            allow: Some(allow),
+        }))
+        .unwrap();
+    }
+
     /// Get all properties of this window.
     ///
     /// # Examples
@@ -569,32 +1110,7 @@ impl WindowHandle {
             }
         };
 
-        let fullscreen_or_maximized = response
-            .fullscreen_or_maximized
-            .unwrap_or_default()
-            .try_into()
-            .ok();
-
-        let geometry = response.geometry.map(|geo| Geometry {
-            x: geo.x(),
-            y: geo.y(),
-            width: geo.width() as u32,
-            height: geo.height() as u32,
-        });
-
-        WindowProperties {
-            geometry,
-            class: response.class,
-            title: response.title,
-            focused: response.focused,
-            floating: response.floating,
-            fullscreen_or_maximized,
-            tags: response
-                .tag_ids
-                .into_iter()
-                .map(|id| self.api.tag.new_handle(id))
-                .collect(),
-        }
+        WindowProperties::from_response(response, &self.api)
     }
 
     /// Get this window's location and size.
@@ -681,6 +1197,30 @@ impl WindowHandle {
         self.props_async().await.tags
     }
 
+    /// Get whether this window is in the scratchpad.
+    ///
+    /// Shorthand for `self.props().scratchpad`.
+    pub fn scratchpad(&self) -> Option<bool> {
+        self.props().scratchpad
+    }
+
+    /// The async version of [`scratchpad`][Self::scratchpad].
+    pub async fn scratchpad_async(&self) -> Option<bool> {
+        self.props_async().await.scratchpad
+    }
+
+    /// Get whether this window is minimized.
+    ///
+    /// Shorthand for `self.props().minimized`.
+    pub fn minimized(&self) -> Option<bool> {
+        self.props().minimized
+    }
+
+    /// The async version of [`minimized`][Self::minimized].
+    pub async fn minimized_async(&self) -> Option<bool> {
+        self.props_async().await.minimized
+    }
+
     /// Returns whether this window is on an active tag.
     pub fn is_on_active_tag(&self) -> bool {
         self.tags()
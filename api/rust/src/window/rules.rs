@@ -68,6 +68,18 @@
 //! Like `classes`, passing in multiple titles at the top level will cause the condition to always
 //! fail.
 //!
+//! ### `WindowRuleCondition::class_regexes` and `WindowRuleCondition::title_regexes`
+//! To match classes or titles by pattern instead of exactly, use
+//! [`WindowRuleCondition::class_regexes`] and [`WindowRuleCondition::title_regexes`].
+//! Patterns use the syntax of the [`regex`](https://docs.rs/regex) crate and match anywhere
+//! in the class or title unless anchored.
+//!
+//! ```
+//! let cond = WindowRuleCondition::new().title_regexes([r"^Picture-in-Picture$"]);
+//! ```
+//!
+//! For Wayland windows, the class is the app id.
+//!
 //! ### `WindowRuleCondition::tags`
 //! You can specify that the window needs to open on the given tags in order to apply a rule.
 //!
@@ -174,11 +186,15 @@
 //!
 //! There are several rules you can set currently.
 //!
+//! Rules are applied before the window is first laid out, so windows open directly
+//! where and how the rules say.
+//!
 //! ### [`WindowRule::output`]
-//! This will cause the window to open on the specified output.
+//! This will cause the window to open on the specified output's active tags.
 //!
 //! ### [`WindowRule::tags`]
-//! This will cause the window to open with the given tags.
+//! This will cause the window to open with the given tags. If `output` is also set,
+//! `tags` takes precedence.
 //!
 //! ### [`WindowRule::floating`]
 //! This will cause the window to open either floating or tiled.
@@ -327,6 +343,51 @@ impl WindowRuleCondition {
         self
     }
 
+    /// This condition requires that the window's class matches the given regexes.
+    ///
+    /// For Wayland windows, the class is the app id.
+    /// Patterns match anywhere in the class unless anchored with `^` and `$`.
+    /// Adding a rule with an invalid regex fails.
+    ///
+    /// Like [`WindowRuleCondition::classes`], *all* regexes must match in a top level
+    /// condition or inside of [`WindowRuleCondition::all`], and at least one must match
+    /// inside of [`WindowRuleCondition::any`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// // `cond` will be true for windows with classes like "org.gnome.Nautilus"
+    /// let cond = WindowRuleCondition::new().class_regexes([r"^org\.gnome\."]);
+    /// ```
+    pub fn class_regexes(mut self, regexes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.class_regexes = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// This condition requires that the window's title matches the given regexes.
+    ///
+    /// Patterns match anywhere in the title unless anchored with `^` and `$`.
+    /// Adding a rule with an invalid regex fails.
+    ///
+    /// Like [`WindowRuleCondition::titles`], *all* regexes must match in a top level
+    /// condition or inside of [`WindowRuleCondition::all`], and at least one must match
+    /// inside of [`WindowRuleCondition::any`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRuleCondition;
+    ///
+    /// // `cond` will be true for windows whose title ends with " - Mozilla Firefox"
+    /// let cond = WindowRuleCondition::new().title_regexes([r" - Mozilla Firefox$"]);
+    /// ```
+    pub fn title_regexes(mut self, regexes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.title_regexes = regexes.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// This condition requires that the window's is opened on the given tags.
     ///
     /// When used in a top level condition or inside of [`WindowRuleCondition::all`],
@@ -455,6 +516,59 @@ impl WindowRule {
         self
     }
 
+    /// This rule will move windows to the provided `output` whenever they become fullscreen.
+    ///
+    /// The window is placed on the output's active tags, and is moved back to the tags
+    /// it had before when it stops being fullscreen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRule;
+    ///
+    /// // Always fullscreen on the TV
+    /// let rule = WindowRule::new().fullscreen_output(&output.get_by_name("HDMI-A-1")?);
+    /// ```
+    pub fn fullscreen_output(mut self, output: &OutputHandle) -> Self {
+        self.0.fullscreen_output = Some(output.name.clone());
+        self
+    }
+
+    /// This rule will set the opacity of windows.
+    ///
+    /// `opacity` is clamped between 0.0 (fully transparent) and 1.0 (fully opaque).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRule;
+    ///
+    /// // Make windows slightly see-through
+    /// let rule = WindowRule::new().opacity(0.9);
+    /// ```
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.0.opacity = Some(opacity);
+        self
+    }
+
+    /// This rule will set whether maximized windows cover the whole output.
+    ///
+    /// By default, maximized windows leave room for the exclusive zones of layer surfaces
+    /// like panels. Fullscreen windows always cover the whole output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pinnacle_api::window::rules::WindowRule;
+    ///
+    /// // Maximize over panels
+    /// let rule = WindowRule::new().maximize_covers_exclusive_zones(true);
+    /// ```
+    pub fn maximize_covers_exclusive_zones(mut self, covers: bool) -> Self {
+        self.0.maximize_covers_exclusive_zones = Some(covers);
+        self
+    }
+
     /// This rule will force windows to open at a specific x-coordinate.
     ///
     /// This will only actually be visible if the window is also floating.
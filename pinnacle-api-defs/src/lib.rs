@@ -68,7 +68,15 @@ pub mod pinnacle {
                 OutputMoveRequest,
                 WindowPointerEnterRequest,
                 WindowPointerLeaveRequest,
-                TagActiveRequest
+                WindowStateRequestRequest,
+                WindowTitleChangedRequest,
+                WindowClassChangedRequest,
+                WindowFocusedRequest,
+                TagActiveRequest,
+                InputDeviceAddedRequest,
+                InputDeviceRemovedRequest,
+                InputModifiersChangedRequest,
+                InputResumedRequest
             );
         }
     }
@@ -2,32 +2,51 @@ pub mod layout;
 pub mod signal;
 pub mod window;
 
-use std::{ffi::OsString, pin::Pin, process::Stdio};
+use std::{
+    ffi::OsString, num::NonZeroU32, path::PathBuf, pin::Pin, process::Stdio, time::Duration,
+};
 
 use pinnacle_api_defs::pinnacle::{
     input::v0alpha1::{
-        input_service_server,
+        inject_input_request, input_service_server,
         set_libinput_setting_request::{AccelProfile, ClickMethod, ScrollMethod, TapButtonMap},
         set_mousebind_request::MouseEdge,
-        SetKeybindRequest, SetKeybindResponse, SetLibinputSettingRequest, SetMousebindRequest,
-        SetMousebindResponse, SetRepeatRateRequest, SetXkbConfigRequest,
+        ClearBindsRequest, GetDevicesRequest, GetDevicesResponse, GetPointerInfoRequest,
+        GetPointerInfoResponse, GetPointerLocationRequest, GetPointerLocationResponse,
+        GetPointerMotionStatsRequest, GetPointerMotionStatsResponse, GetRepeatRateRequest,
+        GetRepeatRateResponse, InjectInputRequest, Modifier, SetClickFocusRequest,
+        SetFocusFollowsMouseDelayRequest, SetFocusFollowsMouseRequest, SetHideCursorAfterRequest,
+        SetIdleNotificationRequest, SetIdleNotificationResponse, SetKeybindRequest,
+        SetKeybindResponse, SetKeybindSequenceRequest, SetKeybindSequenceResponse,
+        SetLibinputSettingRequest, SetMousebindRequest, SetMousebindResponse,
+        SetPointerPredictionRequest, SetRepeatRateRequest, SetScrollInversionRequest,
+        SetScrollTagSwitchRequest, SetTabletMappingRequest, SetXkbConfigRequest, WarpCursorRequest,
     },
     output::{
         self,
         v0alpha1::{
-            output_service_server, set_scale_request::AbsoluteOrRelative, SetLocationRequest,
-            SetModeRequest, SetScaleRequest, SetTransformRequest,
+            output_service_server, set_primary_request, set_scale_request::AbsoluteOrRelative,
+            CaptureRequest, CaptureResponse, FocusRelativeRequest, GetFocusedRequest,
+            GetFocusedResponse, SetClearColorRequest, SetColorTemperatureRequest,
+            SetCustomModeRequest, SetFocusedRequest, SetLocationRequest, SetModeRequest,
+            SetPoweredRequest, SetPrimaryRequest, SetRenderNodeRequest, SetScaleRequest,
+            SetTransformRequest, SetVblankMissPolicyRequest, SetVrrRequest,
         },
     },
     process::v0alpha1::{process_service_server, SetEnvRequest, SpawnRequest, SpawnResponse},
     render::v0alpha1::{
-        render_service_server, Filter, SetDownscaleFilterRequest, SetUpscaleFilterRequest,
+        get_render_elements_response, get_scheduling_stats_response, render_service_server, Filter,
+        GetRenderElementsRequest, GetRenderElementsResponse, GetRenderStatsRequest,
+        GetRenderStatsResponse, GetSchedulingStatsRequest, GetSchedulingStatsResponse,
+        SetDownscaleFilterRequest, SetUpscaleFilterRequest,
     },
     tag::{
         self,
         v0alpha1::{
-            tag_service_server, AddRequest, AddResponse, RemoveRequest, SetActiveRequest,
-            SwitchToRequest,
+            set_master_factor_request, tag_service_server, AddRequest, AddResponse, RemoveRequest,
+            SetActiveRequest, SetDefaultsRequest, SetGapsRequest, SetLayoutRequest,
+            SetMasterFactorRequest, SetSwitchAnimationRequest, SwitchToPreviousRequest,
+            SwitchToRelativeRequest, SwitchToRequest,
         },
     },
     v0alpha1::{
@@ -36,14 +55,14 @@ use pinnacle_api_defs::pinnacle::{
     },
 };
 use smithay::{
-    backend::renderer::TextureFilter,
+    backend::renderer::{element::Element, TextureFilter},
     input::keyboard::XkbConfig,
     output::Scale,
     reexports::{calloop, input as libinput},
+    utils::{Point, Rectangle},
 };
-use sysinfo::ProcessRefreshKind;
 use tokio::{
-    io::AsyncBufReadExt,
+    io::{AsyncBufReadExt, AsyncRead},
     sync::mpsc::{unbounded_channel, UnboundedSender},
     task::JoinHandle,
 };
@@ -54,11 +73,18 @@ use tracing::{debug, error, info, trace, warn};
 use crate::{
     backend::BackendData,
     config::ConnectorSavedState,
-    input::ModifierMask,
-    output::OutputName,
-    render::util::snapshot::capture_snapshots_on_output,
+    input::{
+        keybind_sequence::{KeybindSequence, DEFAULT_SEQUENCE_TIMEOUT},
+        libinput::DeviceIdentity,
+        tablet::TabletMapping,
+        Keybind, KeybindCondition, ModifierMask, ModifierSides,
+    },
+    layout::Gaps,
+    output::{OutputName, PrimaryOutput, VblankMissPolicy},
+    process,
+    render::{util::snapshot::capture_snapshots_on_output, OutputRenderElement},
     state::{State, WithState},
-    tag::{Tag, TagId},
+    tag::{Tag, TagId, DEFAULT_MASTER_FACTOR},
 };
 
 type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
@@ -231,6 +257,61 @@ pub struct InputService {
     sender: StateFnSender,
 }
 
+/// Split api modifiers into a [`ModifierMask`] and the [`ModifierSides`] they require.
+fn modifiers_from_api(modifiers: impl Iterator<Item = Modifier>) -> (ModifierMask, ModifierSides) {
+    modifiers.fold(
+        (ModifierMask::empty(), ModifierSides::empty()),
+        |(mask, sides), modifier| match modifier {
+            Modifier::Unspecified => (mask, sides),
+            Modifier::Shift => (mask | ModifierMask::SHIFT, sides),
+            Modifier::Ctrl => (mask | ModifierMask::CTRL, sides),
+            Modifier::Alt => (mask | ModifierMask::ALT, sides),
+            Modifier::Super => (mask | ModifierMask::SUPER, sides),
+            Modifier::Hyper => (mask | ModifierMask::HYPER, sides),
+            Modifier::Meta => (mask | ModifierMask::META, sides),
+            Modifier::LeftShift => (
+                mask | ModifierMask::SHIFT,
+                sides | ModifierSides::LEFT_SHIFT,
+            ),
+            Modifier::RightShift => (
+                mask | ModifierMask::SHIFT,
+                sides | ModifierSides::RIGHT_SHIFT,
+            ),
+            Modifier::LeftCtrl => (mask | ModifierMask::CTRL, sides | ModifierSides::LEFT_CTRL),
+            Modifier::RightCtrl => (mask | ModifierMask::CTRL, sides | ModifierSides::RIGHT_CTRL),
+            Modifier::LeftAlt => (mask | ModifierMask::ALT, sides | ModifierSides::LEFT_ALT),
+            Modifier::RightAlt => (mask | ModifierMask::ALT, sides | ModifierSides::RIGHT_ALT),
+            Modifier::LeftSuper => (
+                mask | ModifierMask::SUPER,
+                sides | ModifierSides::LEFT_SUPER,
+            ),
+            Modifier::RightSuper => (
+                mask | ModifierMask::SUPER,
+                sides | ModifierSides::RIGHT_SUPER,
+            ),
+            Modifier::LeftHyper => (
+                mask | ModifierMask::HYPER,
+                sides | ModifierSides::LEFT_HYPER,
+            ),
+            Modifier::RightHyper => (
+                mask | ModifierMask::HYPER,
+                sides | ModifierSides::RIGHT_HYPER,
+            ),
+            Modifier::LeftMeta => (mask | ModifierMask::META, sides | ModifierSides::LEFT_META),
+            Modifier::RightMeta => (mask | ModifierMask::META, sides | ModifierSides::RIGHT_META),
+        },
+    )
+}
+
+/// Get the keysym for an xkb keysym name or a single character.
+fn keysym_from_xkb_name(name: &str) -> xkbcommon::xkb::Keysym {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => xkbcommon::xkb::Keysym::from_char(ch),
+        _ => xkbcommon::xkb::keysym_from_name(name, xkbcommon::xkb::KEYSYM_NO_FLAGS),
+    }
+}
+
 impl InputService {
     pub fn new(sender: StateFnSender) -> Self {
         Self { sender }
@@ -240,7 +321,9 @@ impl InputService {
 #[tonic::async_trait]
 impl input_service_server::InputService for InputService {
     type SetKeybindStream = ResponseStream<SetKeybindResponse>;
+    type SetKeybindSequenceStream = ResponseStream<SetKeybindSequenceResponse>;
     type SetMousebindStream = ResponseStream<SetMousebindResponse>;
+    type SetIdleNotificationStream = ResponseStream<SetIdleNotificationResponse>;
 
     async fn set_keybind(
         &self,
@@ -248,24 +331,7 @@ impl input_service_server::InputService for InputService {
     ) -> Result<Response<Self::SetKeybindStream>, Status> {
         let request = request.into_inner();
 
-        // TODO: impl From<&[Modifier]> for ModifierMask
-        let modifiers = request
-            .modifiers()
-            .fold(ModifierMask::empty(), |acc, modifier| match modifier {
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Unspecified => acc,
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Shift => {
-                    acc | ModifierMask::SHIFT
-                }
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Ctrl => {
-                    acc | ModifierMask::CTRL
-                }
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Alt => {
-                    acc | ModifierMask::ALT
-                }
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Super => {
-                    acc | ModifierMask::SUPER
-                }
-            });
+        let (modifiers, sides) = modifiers_from_api(request.modifiers());
         let key = request
             .key
             .ok_or_else(|| Status::invalid_argument("no key specified"))?;
@@ -277,26 +343,90 @@ impl input_service_server::InputService for InputService {
                 xkbcommon::xkb::Keysym::new(num)
             }
             Key::XkbName(s) => {
-                if s.chars().count() == 1 {
-                    let Some(ch) = s.chars().next() else { unreachable!() };
-                    let keysym = xkbcommon::xkb::Keysym::from_char(ch);
-                    debug!("Set keybind: {:?}, {:?}", modifiers, keysym);
-                    keysym
-                } else {
-                    let keysym =
-                        xkbcommon::xkb::keysym_from_name(&s, xkbcommon::xkb::KEYSYM_NO_FLAGS);
-                    debug!("Set keybind: {:?}, {:?}", modifiers, keysym);
-                    keysym
-                }
+                let keysym = keysym_from_xkb_name(&s);
+                debug!("Set keybind: {:?}, {:?}", modifiers, keysym);
+                keysym
             }
         };
 
+        let condition = request.condition.map(|cond| KeybindCondition {
+            tag: cond.tag_id.map(TagId),
+            layout: cond.layout,
+        });
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            state
+                .pinnacle
+                .input_state
+                .add_keybind((modifiers, sides, keysym), Keybind { condition, sender });
+        })
+    }
+
+    async fn set_keybind_sequence(
+        &self,
+        request: Request<SetKeybindSequenceRequest>,
+    ) -> Result<Response<Self::SetKeybindSequenceStream>, Status> {
+        let request = request.into_inner();
+
+        debug!(request = ?request);
+
+        if request.chords.is_empty() {
+            return Err(Status::invalid_argument("no chords specified"));
+        }
+
+        use pinnacle_api_defs::pinnacle::input::v0alpha1::set_keybind_sequence_request::chord::Key;
+        let chords = request
+            .chords
+            .iter()
+            .map(|chord| {
+                let (modifiers, sides) = modifiers_from_api(chord.modifiers());
+                let keysym = match chord.key.as_ref() {
+                    Some(Key::RawCode(num)) => xkbcommon::xkb::Keysym::new(*num),
+                    Some(Key::XkbName(s)) => keysym_from_xkb_name(s),
+                    None => return Err(Status::invalid_argument("no key specified")),
+                };
+                Ok((modifiers, sides, keysym))
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let timeout = request
+            .timeout_ms
+            .map(|ms| Duration::from_millis(ms.into()))
+            .unwrap_or(DEFAULT_SEQUENCE_TIMEOUT);
+        let forward_unmatched = request.forward_unmatched.unwrap_or_default();
+
         run_server_streaming(&self.sender, move |state, sender| {
             state
                 .pinnacle
                 .input_state
-                .keybinds
-                .insert((modifiers, keysym), sender);
+                .keybind_sequences
+                .add(KeybindSequence {
+                    chords,
+                    timeout,
+                    forward_unmatched,
+                    sender,
+                });
+        })
+    }
+
+    async fn set_idle_notification(
+        &self,
+        request: Request<SetIdleNotificationRequest>,
+    ) -> Result<Response<Self::SetIdleNotificationStream>, Status> {
+        let request = request.into_inner();
+
+        let timeout_ms = request
+            .timeout_ms
+            .ok_or_else(|| Status::invalid_argument("no timeout specified"))?;
+
+        if timeout_ms == 0 {
+            return Err(Status::invalid_argument("timeout must be positive"));
+        }
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            state
+                .pinnacle
+                .add_idle_timeout(Duration::from_millis(timeout_ms.into()), sender);
         })
     }
 
@@ -308,23 +438,7 @@ impl input_service_server::InputService for InputService {
 
         debug!(request = ?request);
 
-        let modifiers = request
-            .modifiers()
-            .fold(ModifierMask::empty(), |acc, modifier| match modifier {
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Unspecified => acc,
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Shift => {
-                    acc | ModifierMask::SHIFT
-                }
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Ctrl => {
-                    acc | ModifierMask::CTRL
-                }
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Alt => {
-                    acc | ModifierMask::ALT
-                }
-                pinnacle_api_defs::pinnacle::input::v0alpha1::Modifier::Super => {
-                    acc | ModifierMask::SUPER
-                }
-            });
+        let (modifiers, sides) = modifiers_from_api(request.modifiers());
         let button = request
             .button
             .ok_or_else(|| Status::invalid_argument("no key specified"))?;
@@ -340,10 +454,20 @@ impl input_service_server::InputService for InputService {
                 .pinnacle
                 .input_state
                 .mousebinds
-                .insert((modifiers, button, edge), sender);
+                .insert((modifiers, sides, button, edge), sender);
         })
     }
 
+    async fn clear_binds(
+        &self,
+        _request: Request<ClearBindsRequest>,
+    ) -> Result<Response<()>, Status> {
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.clear_binds();
+        })
+        .await
+    }
+
     async fn set_xkb_config(
         &self,
         request: Request<SetXkbConfigRequest>,
@@ -378,7 +502,14 @@ impl input_service_server::InputService for InputService {
             .ok_or_else(|| Status::invalid_argument("no rate specified"))?;
         let delay = request
             .delay
-            .ok_or_else(|| Status::invalid_argument("no rate specified"))?;
+            .ok_or_else(|| Status::invalid_argument("no delay specified"))?;
+
+        // A rate of 0 disables repeat
+        if rate < 0 || delay < 0 {
+            return Err(Status::invalid_argument(
+                "rate and delay must not be negative",
+            ));
+        }
 
         run_unary_no_response(&self.sender, move |state| {
             if let Some(kb) = state.pinnacle.seat.get_keyboard() {
@@ -388,6 +519,23 @@ impl input_service_server::InputService for InputService {
         .await
     }
 
+    async fn get_repeat_rate(
+        &self,
+        _request: Request<GetRepeatRateRequest>,
+    ) -> Result<Response<GetRepeatRateResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let Some(kb) = state.pinnacle.seat.get_keyboard() else {
+                return GetRepeatRateResponse::default();
+            };
+
+            GetRepeatRateResponse {
+                rate: Some(kb.repeat_rate()),
+                delay: Some(kb.repeat_delay()),
+            }
+        })
+        .await
+    }
+
     async fn set_libinput_setting(
         &self,
         request: Request<SetLibinputSettingRequest>,
@@ -521,253 +669,673 @@ impl input_service_server::InputService for InputService {
             }),
         };
 
+        let device_id = request.device_id;
+
         run_unary_no_response(&self.sender, move |state| {
-            for device in state.pinnacle.input_state.libinput_devices.iter_mut() {
+            // Motion from before the change would skew the stats
+            state.pinnacle.input_state.pointer_motion_stats.reset();
+
+            let input_state = &mut state.pinnacle.input_state;
+
+            // Device settings are kept so they're applied again if the device is replugged
+            if let Some(device_id) = device_id {
+                let Some(device) = input_state
+                    .libinput_devices
+                    .iter_mut()
+                    .find(|device| device.sysname() == device_id)
+                else {
+                    warn!("set_libinput_setting: no device with id {device_id}");
+                    return;
+                };
+
                 apply_setting(device);
+
+                input_state
+                    .device_libinput_settings
+                    .entry(DeviceIdentity::from(&*device))
+                    .or_default()
+                    .insert(discriminant, apply_setting);
+                return;
             }
 
-            state
-                .pinnacle
-                .input_state
+            for device in input_state.libinput_devices.iter_mut() {
+                apply_setting(device);
+
+                // Don't override the setting on devices that have their own
+                if let Some(device_setting) = input_state
+                    .device_libinput_settings
+                    .get(&DeviceIdentity::from(&*device))
+                    .and_then(|settings| settings.get(&discriminant))
+                {
+                    device_setting(device);
+                }
+            }
+
+            input_state
                 .libinput_settings
                 .insert(discriminant, apply_setting);
         })
         .await
     }
-}
-
-pub struct ProcessService {
-    sender: StateFnSender,
-}
-
-impl ProcessService {
-    pub fn new(sender: StateFnSender) -> Self {
-        Self { sender }
-    }
-}
-
-#[tonic::async_trait]
-impl process_service_server::ProcessService for ProcessService {
-    type SpawnStream = ResponseStream<SpawnResponse>;
 
-    async fn spawn(
+    async fn set_click_focus(
         &self,
-        request: Request<SpawnRequest>,
-    ) -> Result<Response<Self::SpawnStream>, Status> {
-        debug!("ProcessService.spawn");
+        request: Request<SetClickFocusRequest>,
+    ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
-        let once = request.once();
-        let has_callback = request.has_callback();
-        let mut command = request.args.into_iter();
-        let arg0 = command
-            .next()
-            .ok_or_else(|| Status::invalid_argument("no args specified"))?;
+        let edge = request
+            .edge
+            .map(|edge| MouseEdge::try_from(edge).unwrap_or(MouseEdge::Unspecified));
 
-        run_server_streaming(&self.sender, move |state, sender| {
-            if once {
-                state
-                    .pinnacle
-                    .system_processes
-                    .refresh_processes_specifics(ProcessRefreshKind::new());
+        if let Some(MouseEdge::Unspecified) = edge {
+            return Err(Status::invalid_argument("press or release not specified"));
+        }
 
-                let compositor_pid = std::process::id();
-                let already_running = state
-                    .pinnacle
-                    .system_processes
-                    .processes_by_exact_name(&arg0)
-                    .any(|proc| {
-                        proc.parent()
-                            .is_some_and(|parent_pid| parent_pid.as_u32() == compositor_pid)
-                    });
+        run_unary_no_response(&self.sender, move |state| {
+            let click_focus = &mut state.pinnacle.input_state.click_focus;
 
-                if already_running {
-                    return;
-                }
+            if let Some(enabled) = request.enabled {
+                click_focus.enabled = enabled;
             }
-
-            let Ok(mut child) = tokio::process::Command::new(OsString::from(arg0.clone()))
-                .stdin(match has_callback {
-                    true => Stdio::piped(),
-                    false => Stdio::null(),
-                })
-                .stdout(match has_callback {
-                    true => Stdio::piped(),
-                    false => Stdio::null(),
-                })
-                .stderr(match has_callback {
-                    true => Stdio::piped(),
-                    false => Stdio::null(),
-                })
-                .args(command)
-                .spawn()
-            else {
-                warn!("Tried to run {arg0}, but it doesn't exist",);
-                return;
-            };
-
-            if !has_callback {
-                return;
+            if let Some(buttons) = request.buttons {
+                click_focus.buttons = buttons.buttons;
             }
+            if let Some(edge) = edge {
+                click_focus.edge = edge;
+            }
+            if let Some(raise) = request.raise {
+                click_focus.raise = raise;
+            }
+            if let Some(keep) = request.keep_focus_on_background {
+                click_focus.keep_focus_on_background = keep;
+            }
+        })
+        .await
+    }
 
-            let stdout = child.stdout.take();
-            let stderr = child.stderr.take();
-
-            if let Some(stdout) = stdout {
-                let sender = sender.clone();
-
-                let mut reader = tokio::io::BufReader::new(stdout).lines();
+    async fn set_focus_follows_mouse(
+        &self,
+        request: Request<SetFocusFollowsMouseRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
 
-                tokio::spawn(async move {
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        let response: Result<_, Status> = Ok(SpawnResponse {
-                            stdout: Some(line),
-                            ..Default::default()
-                        });
-
-                        // TODO: handle error
-                        match sender.send(response) {
-                            Ok(_) => (),
-                            Err(err) => {
-                                error!(err = ?err);
-                                break;
-                            }
-                        }
-                    }
-                });
-            }
+        let Some(enabled) = request.enabled else {
+            return Err(Status::invalid_argument("no enabled specified"));
+        };
 
-            if let Some(stderr) = stderr {
-                let sender = sender.clone();
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.focus_follows_mouse.enabled = enabled;
+        })
+        .await
+    }
 
-                let mut reader = tokio::io::BufReader::new(stderr).lines();
+    async fn set_focus_follows_mouse_delay(
+        &self,
+        request: Request<SetFocusFollowsMouseDelayRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
 
-                tokio::spawn(async move {
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        let response: Result<_, Status> = Ok(SpawnResponse {
-                            stderr: Some(line),
-                            ..Default::default()
-                        });
-
-                        // TODO: handle error
-                        match sender.send(response) {
-                            Ok(_) => (),
-                            Err(err) => {
-                                error!(err = ?err);
-                                break;
-                            }
-                        }
-                    }
-                });
-            }
+        let Some(delay_ms) = request.delay_ms else {
+            return Err(Status::invalid_argument("no delay specified"));
+        };
 
-            tokio::spawn(async move {
-                match child.wait().await {
-                    Ok(exit_status) => {
-                        let response = Ok(SpawnResponse {
-                            exit_code: exit_status.code(),
-                            exit_message: Some(exit_status.to_string()),
-                            ..Default::default()
-                        });
-                        // TODO: handle error
-                        let _ = sender.send(response);
-                    }
-                    Err(err) => warn!("child wait() err: {err}"),
-                }
-            });
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.input_state.focus_follows_mouse.delay =
+                Duration::from_millis(delay_ms.into());
         })
+        .await
     }
 
-    async fn set_env(&self, request: Request<SetEnvRequest>) -> Result<Response<()>, Status> {
+    async fn set_scroll_tag_switch(
+        &self,
+        request: Request<SetScrollTagSwitchRequest>,
+    ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
-        let key = request
-            .key
-            .ok_or_else(|| Status::invalid_argument("no key specified"))?;
-        let value = request
-            .value
-            .ok_or_else(|| Status::invalid_argument("no value specified"))?;
+        let regions = request.regions.map(|regions| {
+            regions
+                .regions
+                .into_iter()
+                .map(|geo| {
+                    Rectangle::from_loc_and_size((geo.x(), geo.y()), (geo.width(), geo.height()))
+                })
+                .collect::<Vec<_>>()
+        });
 
-        if key.is_empty() {
-            return Err(Status::invalid_argument("key was empty"));
-        }
+        run_unary_no_response(&self.sender, move |state| {
+            let scroll = &mut state.pinnacle.input_state.scroll_tag_switch;
 
-        if key.contains(['\0', '=']) {
-            return Err(Status::invalid_argument("key contained NUL or ="));
-        }
+            if let Some(on_background) = request.on_background {
+                scroll.on_background = on_background;
+            }
+            if let Some(regions) = regions {
+                scroll.regions = regions;
+            }
+        })
+        .await
+    }
 
-        if value.contains('\0') {
-            return Err(Status::invalid_argument("value contained NUL"));
-        }
+    async fn set_scroll_inversion(
+        &self,
+        request: Request<SetScrollInversionRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
 
-        std::env::set_var(key, value);
+        run_unary_no_response(&self.sender, move |state| {
+            let inversion = &mut state.pinnacle.input_state.scroll_inversion;
 
-        Ok(Response::new(()))
+            if let Some(horizontal) = request.horizontal {
+                inversion.horizontal = horizontal;
+            }
+            if let Some(vertical) = request.vertical {
+                inversion.vertical = vertical;
+            }
+        })
+        .await
     }
-}
 
-pub struct TagService {
-    sender: StateFnSender,
-}
+    async fn set_pointer_prediction(
+        &self,
+        request: Request<SetPointerPredictionRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
 
-impl TagService {
-    pub fn new(sender: StateFnSender) -> Self {
-        Self { sender }
+        let Some(enabled) = request.enabled else {
+            return Err(Status::invalid_argument("no enabled specified"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let prediction = &mut state.pinnacle.input_state.pointer_prediction;
+            prediction.enabled = enabled;
+            prediction.reset();
+        })
+        .await
     }
-}
 
-#[tonic::async_trait]
-impl tag_service_server::TagService for TagService {
-    async fn set_active(&self, request: Request<SetActiveRequest>) -> Result<Response<()>, Status> {
+    async fn set_hide_cursor_after(
+        &self,
+        request: Request<SetHideCursorAfterRequest>,
+    ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
-        let tag_id = TagId(
-            request
-                .tag_id
-                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
-        );
+        let timeout = request
+            .hide_cursor_after_ms
+            .filter(|ms| *ms > 0)
+            .map(|ms| Duration::from_millis(ms.into()));
 
-        let set_or_toggle = request.set_or_toggle();
+        run_unary_no_response(&self.sender, move |state| {
+            state.set_hide_cursor_after(timeout);
+        })
+        .await
+    }
 
-        if set_or_toggle == SetOrToggle::Unspecified {
-            return Err(Status::invalid_argument("unspecified set or toggle"));
+    async fn inject_input(
+        &self,
+        request: Request<InjectInputRequest>,
+    ) -> Result<Response<()>, Status> {
+        let enabled = run_unary(&self.sender, move |state| {
+            state.pinnacle.input_state.injection_enabled
+        })
+        .await?
+        .into_inner();
+
+        if !enabled {
+            return Err(Status::permission_denied(format!(
+                "input injection is disabled; start pinnacle with {} set to enable it",
+                crate::input::synthetic::INPUT_INJECTION_ENV
+            )));
         }
 
-        run_unary_no_response(&self.sender, move |state| {
-            let Some(tag) = tag_id.tag(&state.pinnacle) else {
-                return;
-            };
-
-            let Some(output) = tag.output(&state.pinnacle) else {
+        let input = request
+            .into_inner()
+            .input
+            .ok_or_else(|| Status::invalid_argument("no input specified"))?;
+
+        match input {
+            inject_input_request::Input::Key(inject_input_request::Key {
+                key_code: Some(key_code),
+                pressed: Some(pressed),
+            }) => {
+                run_unary_no_response(&self.sender, move |state| {
+                    state.inject_key(key_code, pressed);
+                })
+                .await
+            }
+            inject_input_request::Input::PointerButton(inject_input_request::PointerButton {
+                button: Some(button),
+                pressed: Some(pressed),
+            }) => {
+                run_unary_no_response(&self.sender, move |state| {
+                    state.inject_pointer_button(button, pressed);
+                })
+                .await
+            }
+            inject_input_request::Input::PointerMotion(inject_input_request::PointerMotion {
+                x: Some(x),
+                y: Some(y),
+            }) => {
+                run_unary_no_response(&self.sender, move |state| {
+                    state.inject_pointer_motion((x, y).into());
+                })
+                .await
+            }
+            _ => Err(Status::invalid_argument("input has missing fields")),
+        }
+    }
+
+    async fn set_tablet_mapping(
+        &self,
+        request: Request<SetTabletMappingRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let region = match request.region {
+            Some(geo) => {
+                if geo.width() <= 0 || geo.height() <= 0 {
+                    return Err(Status::invalid_argument("region must have a positive size"));
+                }
+                Some(Rectangle::from_loc_and_size(
+                    (geo.x(), geo.y()),
+                    (geo.width(), geo.height()),
+                ))
+            }
+            None => None,
+        };
+
+        let mapping = request.output_name.map(|output_name| TabletMapping {
+            output_name: OutputName(output_name),
+            region,
+        });
+
+        let device_id = request.device_id;
+
+        run_unary_no_response(&self.sender, move |state| {
+            let mappings = &mut state.pinnacle.input_state.tablet_mappings;
+            match mapping {
+                Some(mapping) => {
+                    mappings.insert(device_id, mapping);
+                }
+                None => {
+                    mappings.remove(&device_id);
+                }
+            }
+        })
+        .await
+    }
+
+    async fn get_devices(
+        &self,
+        _request: Request<GetDevicesRequest>,
+    ) -> Result<Response<GetDevicesResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let devices = state
+                .pinnacle
+                .input_state
+                .libinput_devices
+                .iter()
+                .map(crate::input::libinput::device_to_api)
+                .collect();
+
+            GetDevicesResponse { devices }
+        })
+        .await
+    }
+
+    async fn warp_cursor(
+        &self,
+        request: Request<WarpCursorRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let x = request
+            .x
+            .ok_or_else(|| Status::invalid_argument("no x specified"))?;
+        let y = request
+            .y
+            .ok_or_else(|| Status::invalid_argument("no y specified"))?;
+        let relative = request.relative();
+
+        if !x.is_finite() || !y.is_finite() {
+            return Err(Status::invalid_argument("x and y must be finite"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(pointer) = state.pinnacle.seat.get_pointer() else {
                 return;
             };
 
-            let snapshots = state.backend.with_renderer(|renderer| {
-                capture_snapshots_on_output(&mut state.pinnacle, renderer, &output, [])
-            });
+            let mut loc = Point::from((x, y));
+            if relative {
+                loc += pointer.current_location();
+            }
 
-            match set_or_toggle {
-                SetOrToggle::Set => tag.set_active(true, &mut state.pinnacle),
-                SetOrToggle::Unset => tag.set_active(false, &mut state.pinnacle),
-                SetOrToggle::Toggle => tag.set_active(!tag.active(), &mut state.pinnacle),
-                SetOrToggle::Unspecified => unreachable!(),
+            state.warp_cursor_clamped(loc);
+        })
+        .await
+    }
+
+    async fn get_pointer_location(
+        &self,
+        _request: Request<GetPointerLocationRequest>,
+    ) -> Result<Response<GetPointerLocationResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let Some(pointer) = state.pinnacle.seat.get_pointer() else {
+                return GetPointerLocationResponse::default();
+            };
+
+            let location = pointer.current_location();
+
+            GetPointerLocationResponse {
+                x: Some(location.x),
+                y: Some(location.y),
+            }
+        })
+        .await
+    }
+
+    async fn get_pointer_motion_stats(
+        &self,
+        _request: Request<GetPointerMotionStatsRequest>,
+    ) -> Result<Response<GetPointerMotionStatsResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let stats = &state.pinnacle.input_state.pointer_motion_stats;
+            let (raw_distance, accelerated_distance) = stats.totals();
+
+            GetPointerMotionStatsResponse {
+                raw_distance: Some(raw_distance),
+                accelerated_distance: Some(accelerated_distance),
+                ratio: stats.ratio(),
             }
+        })
+        .await
+    }
 
-            state.pinnacle.fixup_xwayland_window_layering();
+    async fn get_pointer_info(
+        &self,
+        _request: Request<GetPointerInfoRequest>,
+    ) -> Result<Response<GetPointerInfoResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let Some(pointer) = state.pinnacle.seat.get_pointer() else {
+                return GetPointerInfoResponse::default();
+            };
 
-            if let Some((fs_and_up_snapshots, under_fs_snapshots)) = snapshots {
-                output.with_state_mut(|op_state| {
-                    op_state.new_wait_layout_transaction(
-                        state.pinnacle.loop_handle.clone(),
-                        fs_and_up_snapshots,
-                        under_fs_snapshots,
-                    )
-                });
+            let location = pointer.current_location();
+
+            let output_name = state
+                .pinnacle
+                .space
+                .output_under(location)
+                .next()
+                .map(|output| output.name());
+
+            let focus = state
+                .pinnacle
+                .pointer_focus_target_under(location)
+                .map(|(focus, _)| focus);
+
+            let window_id = focus
+                .as_ref()
+                .and_then(|focus| focus.window_for(state))
+                .map(|win| win.with_state(|state| state.id.0));
+
+            let layer_namespace = focus
+                .as_ref()
+                .and_then(|focus| focus.layer_for(state))
+                .map(|layer| layer.namespace().to_string());
+
+            GetPointerInfoResponse {
+                x: Some(location.x),
+                y: Some(location.y),
+                output_name,
+                window_id,
+                layer_namespace,
             }
+        })
+        .await
+    }
+}
 
-            state.pinnacle.request_layout(&output);
-            state.update_keyboard_focus(&output);
-            state.schedule_render(&output);
+pub struct ProcessService {
+    sender: StateFnSender,
+}
+
+impl ProcessService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+/// How long to wait for a process's remaining output after it exits.
+const PROCESS_OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Send each line of a spawned process's output to the config.
+///
+/// Invalid UTF-8 is replaced instead of ending the stream, and a last line without
+/// a trailing newline is still sent when the pipe closes. `reader` is dropped, closing
+/// the pipe, once the output ends or the config stops listening.
+async fn forward_output_lines(
+    reader: impl AsyncRead + Unpin,
+    sender: UnboundedSender<Result<SpawnResponse, Status>>,
+    to_response: fn(String) -> SpawnResponse,
+) {
+    let mut reader = tokio::io::BufReader::new(reader);
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) => break,
+            Ok(_) => {
+                if buf.ends_with(b"\n") {
+                    buf.pop();
+                    if buf.ends_with(b"\r") {
+                        buf.pop();
+                    }
+                }
+
+                let line = String::from_utf8_lossy(&buf).into_owned();
+                if sender.send(Ok(to_response(line))).is_err() {
+                    break;
+                }
+            }
+            Err(err) => {
+                warn!("Failed to read process output: {err}");
+                break;
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl process_service_server::ProcessService for ProcessService {
+    type SpawnStream = ResponseStream<SpawnResponse>;
+
+    async fn spawn(
+        &self,
+        request: Request<SpawnRequest>,
+    ) -> Result<Response<Self::SpawnStream>, Status> {
+        debug!("ProcessService.spawn");
+        let request = request.into_inner();
+
+        let once = request.once();
+        let has_callback = request.has_callback();
+        let args = request.args;
+        let mut command = args.clone().into_iter();
+        let arg0 = command
+            .next()
+            .ok_or_else(|| Status::invalid_argument("no args specified"))?;
+
+        run_server_streaming(&self.sender, move |state, sender| {
+            if once && state.pinnacle.spawned_once_is_running(&args) {
+                let _ = sender.send(Ok(SpawnResponse {
+                    spawned: Some(false),
+                    ..Default::default()
+                }));
+                return;
+            }
+
+            let mut cmd = tokio::process::Command::new(OsString::from(arg0.clone()));
+            cmd.stdin(Stdio::null())
+                .stdout(match has_callback {
+                    true => Stdio::piped(),
+                    false => Stdio::null(),
+                })
+                .stderr(match has_callback {
+                    true => Stdio::piped(),
+                    false => Stdio::null(),
+                })
+                .args(command);
+            process::restore_fd_limit_on_spawn(&mut cmd);
+
+            let Ok(mut child) = cmd.spawn() else {
+                warn!("Tried to run {arg0}, but it doesn't exist",);
+                if once {
+                    let _ = sender.send(Ok(SpawnResponse {
+                        spawned: Some(false),
+                        ..Default::default()
+                    }));
+                }
+                return;
+            };
+
+            if once {
+                if let Some(pid) = child.id() {
+                    state
+                        .pinnacle
+                        .spawned_once
+                        .insert(args, sysinfo::Pid::from_u32(pid));
+                }
+                let _ = sender.send(Ok(SpawnResponse {
+                    spawned: Some(true),
+                    ..Default::default()
+                }));
+            }
+
+            if !has_callback {
+                return;
+            }
+
+            let stdout_task = child.stdout.take().map(|stdout| {
+                tokio::spawn(forward_output_lines(stdout, sender.clone(), |line| {
+                    SpawnResponse {
+                        stdout: Some(line),
+                        ..Default::default()
+                    }
+                }))
+            });
+
+            let stderr_task = child.stderr.take().map(|stderr| {
+                tokio::spawn(forward_output_lines(stderr, sender.clone(), |line| {
+                    SpawnResponse {
+                        stderr: Some(line),
+                        ..Default::default()
+                    }
+                }))
+            });
+
+            tokio::spawn(async move {
+                let exit_status = child.wait().await;
+
+                // Let output that was written right before exiting through first so the exit
+                // callback runs last. Don't wait forever, as processes the child spawned
+                // may still have the pipes open.
+                for task in [stdout_task, stderr_task].into_iter().flatten() {
+                    let _ = tokio::time::timeout(PROCESS_OUTPUT_DRAIN_TIMEOUT, task).await;
+                }
+
+                match exit_status {
+                    Ok(exit_status) => {
+                        let _ = sender.send(Ok(SpawnResponse {
+                            exit_code: exit_status.code(),
+                            exit_message: Some(exit_status.to_string()),
+                            ..Default::default()
+                        }));
+                    }
+                    Err(err) => warn!("child wait() err: {err}"),
+                }
+            });
+        })
+    }
+
+    async fn set_env(&self, request: Request<SetEnvRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let key = request
+            .key
+            .ok_or_else(|| Status::invalid_argument("no key specified"))?;
+        let value = request
+            .value
+            .ok_or_else(|| Status::invalid_argument("no value specified"))?;
+
+        if key.is_empty() {
+            return Err(Status::invalid_argument("key was empty"));
+        }
+
+        if key.contains(['\0', '=']) {
+            return Err(Status::invalid_argument("key contained NUL or ="));
+        }
+
+        if value.contains('\0') {
+            return Err(Status::invalid_argument("value contained NUL"));
+        }
+
+        std::env::set_var(key, value);
+
+        Ok(Response::new(()))
+    }
+}
+
+pub struct TagService {
+    sender: StateFnSender,
+}
+
+impl TagService {
+    pub fn new(sender: StateFnSender) -> Self {
+        Self { sender }
+    }
+}
+
+#[tonic::async_trait]
+impl tag_service_server::TagService for TagService {
+    async fn set_active(&self, request: Request<SetActiveRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+
+        let set_or_toggle = request.set_or_toggle();
+
+        if set_or_toggle == SetOrToggle::Unspecified {
+            return Err(Status::invalid_argument("unspecified set or toggle"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else {
+                return;
+            };
+
+            let Some(output) = tag.output(&state.pinnacle) else {
+                return;
+            };
+
+            let active = match set_or_toggle {
+                SetOrToggle::Set => true,
+                SetOrToggle::Unset => false,
+                SetOrToggle::Toggle => !tag.active(),
+                SetOrToggle::Unspecified => unreachable!(),
+            };
+
+            // Don't relayout if nothing changed
+            if tag.active() == active {
+                return;
+            }
+
+            state.change_active_tags(&output, |pinnacle| tag.set_active(active, pinnacle));
         })
         .await
     }
@@ -783,36 +1351,173 @@ impl tag_service_server::TagService for TagService {
 
         run_unary_no_response(&self.sender, move |state| {
             let Some(tag) = tag_id.tag(&state.pinnacle) else { return };
-            let Some(output) = tag.output(&state.pinnacle) else {
+            state.switch_to_tag(&tag);
+        })
+        .await
+    }
+
+    async fn switch_to_previous(
+        &self,
+        request: Request<SwitchToPreviousRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let output_name = request.output_name.map(OutputName);
+
+        run_unary_no_response(&self.sender, move |state| {
+            let output = match output_name {
+                Some(output_name) => output_name.output(&state.pinnacle),
+                None => state.pinnacle.focused_output().cloned(),
+            };
+
+            let Some(output) = output else { return };
+
+            state.switch_to_previous_tags(&output);
+        })
+        .await
+    }
+
+    async fn switch_to_relative(
+        &self,
+        request: Request<SwitchToRelativeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let offset = request
+            .offset
+            .ok_or_else(|| Status::invalid_argument("no offset specified"))?;
+        let only_occupied = request.only_occupied();
+        let output_name = request.output_name.map(OutputName);
+
+        run_unary_no_response(&self.sender, move |state| {
+            let output = match output_name {
+                Some(output_name) => output_name.output(&state.pinnacle),
+                None => state.pinnacle.focused_output().cloned(),
+            };
+
+            let Some(output) = output else { return };
+
+            if output.with_state(|state| state.tags.len() < 2) {
                 return;
+            }
+
+            let tag = if only_occupied {
+                state
+                    .pinnacle
+                    .occupied_tag_relative_to_active(&output, offset as isize)
+            } else {
+                state
+                    .pinnacle
+                    .tag_relative_to_active(&output, offset as isize)
             };
 
-            let snapshots = state.backend.with_renderer(|renderer| {
-                capture_snapshots_on_output(&mut state.pinnacle, renderer, &output, [])
-            });
+            if let Some(tag) = tag {
+                state.switch_to_tag(&tag);
+            }
+        })
+        .await
+    }
+
+    async fn set_layout(&self, request: Request<SetLayoutRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+
+        let layout = request.layout;
 
-            output.with_state(|op_state| {
-                for op_tag in op_state.tags.iter() {
-                    op_tag.set_active(false, &mut state.pinnacle);
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else { return };
+
+            tag.set_layout(layout);
+
+            // The config may pick a different layout generator now, so relayout
+            if tag.active() {
+                if let Some(output) = tag.output(&state.pinnacle) {
+                    state.pinnacle.request_layout(&output);
                 }
-                tag.set_active(true, &mut state.pinnacle);
-            });
+            }
+        })
+        .await
+    }
 
-            state.pinnacle.fixup_xwayland_window_layering();
+    async fn set_gaps(&self, request: Request<SetGapsRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
 
-            if let Some((fs_and_up_snapshots, under_fs_snapshots)) = snapshots {
-                output.with_state_mut(|op_state| {
-                    op_state.new_wait_layout_transaction(
-                        state.pinnacle.loop_handle.clone(),
-                        fs_and_up_snapshots,
-                        under_fs_snapshots,
-                    )
-                });
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+
+        let inner = request
+            .inner
+            .ok_or_else(|| Status::invalid_argument("no inner gap specified"))?;
+        let outer = request
+            .outer
+            .ok_or_else(|| Status::invalid_argument("no outer gap specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else { return };
+
+            tag.set_gaps(Gaps { inner, outer });
+
+            if tag.active() {
+                if let Some(output) = tag.output(&state.pinnacle) {
+                    state.pinnacle.request_layout(&output);
+                }
             }
+        })
+        .await
+    }
 
-            state.pinnacle.request_layout(&output);
-            state.update_keyboard_focus(&output);
-            state.schedule_render(&output);
+    async fn set_master_factor(
+        &self,
+        request: Request<SetMasterFactorRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let tag_id = TagId(
+            request
+                .tag_id
+                .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
+        );
+
+        let absolute_or_relative = request
+            .absolute_or_relative
+            .ok_or_else(|| Status::invalid_argument("no master factor specified"))?;
+
+        let factor = match absolute_or_relative {
+            set_master_factor_request::AbsoluteOrRelative::Absolute(factor)
+            | set_master_factor_request::AbsoluteOrRelative::Relative(factor) => factor,
+        };
+        if !factor.is_finite() {
+            return Err(Status::invalid_argument("master factor must be finite"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(tag) = tag_id.tag(&state.pinnacle) else { return };
+
+            let master_factor = match absolute_or_relative {
+                set_master_factor_request::AbsoluteOrRelative::Absolute(factor) => factor,
+                set_master_factor_request::AbsoluteOrRelative::Relative(delta) => {
+                    tag.master_factor()
+                        .or(tag.layout_master_factor())
+                        .unwrap_or(DEFAULT_MASTER_FACTOR)
+                        + delta
+                }
+            };
+
+            tag.set_master_factor(master_factor);
+
+            if tag.active() {
+                if let Some(output) = tag.output(&state.pinnacle) {
+                    state.pinnacle.request_layout(&output);
+                }
+            }
         })
         .await
     }
@@ -874,6 +1579,50 @@ impl tag_service_server::TagService for TagService {
         .await
     }
 
+    async fn set_defaults(
+        &self,
+        request: Request<SetDefaultsRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.default_tags = request.tag_names;
+
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                if output.with_state(|state| !state.tags.is_empty()) {
+                    continue;
+                }
+
+                state.pinnacle.add_default_tags(&output);
+                state.pinnacle.request_layout(&output);
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn set_switch_animation(
+        &self,
+        request: Request<SetSwitchAnimationRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        if request.duration_ms == Some(0) {
+            return Err(Status::invalid_argument("duration must be positive"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            if let Some(enabled) = request.enabled {
+                state.pinnacle.config.tag_switch_animation = enabled;
+            }
+            if let Some(duration_ms) = request.duration_ms {
+                state.pinnacle.config.tag_switch_animation_duration =
+                    Duration::from_millis(duration_ms.into());
+            }
+        })
+        .await
+    }
+
     // TODO: test
     async fn remove(&self, request: Request<RemoveRequest>) -> Result<Response<()>, Status> {
         let request = request.into_inner();
@@ -881,18 +1630,61 @@ impl tag_service_server::TagService for TagService {
         let tag_ids = request.tag_ids.into_iter().map(TagId);
 
         run_unary_no_response(&self.sender, move |state| {
+            // Ids of tags that were already removed don't resolve, so removing twice is a no-op
             let tags_to_remove = tag_ids
                 .flat_map(|id| id.tag(&state.pinnacle))
                 .collect::<Vec<_>>();
 
+            if tags_to_remove.is_empty() {
+                return;
+            }
+
+            // Windows that are only on removed tags get moved to their output's remaining tags
+            let orphaned_windows = state
+                .pinnacle
+                .windows
+                .iter()
+                .filter(|win| {
+                    win.with_state(|state| {
+                        !state.tags.is_empty()
+                            && state.tags.iter().all(|tag| tags_to_remove.contains(tag))
+                    })
+                })
+                .filter_map(|win| Some((win.clone(), win.output(&state.pinnacle)?)))
+                .collect::<Vec<_>>();
+
             for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
                 // TODO: seriously, convert state.tags into a hashset
-                output.with_state_mut(|state| {
+                let removed_last_tag = output.with_state_mut(|state| {
+                    let had_tags = !state.tags.is_empty();
                     for tag_to_remove in tags_to_remove.iter() {
                         state.tags.retain(|tag| tag != tag_to_remove);
                     }
+                    had_tags && state.tags.is_empty()
                 });
 
+                // Don't leave the output without tags, otherwise its windows would vanish
+                if removed_last_tag {
+                    state.pinnacle.add_default_tags(&output);
+                    if output.with_state(|state| state.tags.is_empty()) {
+                        let tag = Tag::new("1".to_string());
+                        tag.set_active(true, &mut state.pinnacle);
+                        output.with_state_mut(|state| state.tags.push(tag));
+                    }
+                }
+            }
+
+            for window in state.pinnacle.windows.iter() {
+                window.with_state_mut(|state| {
+                    state.tags.retain(|tag| !tags_to_remove.contains(tag));
+                });
+            }
+
+            for (window, output) in orphaned_windows {
+                window.place_on_output(&output);
+            }
+
+            for output in state.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
                 state.pinnacle.request_layout(&output);
                 state.schedule_render(&output);
             }
@@ -948,6 +1740,9 @@ impl tag_service_server::TagService for TagService {
                 .map(|output| output.name());
             let active = tag.as_ref().map(|tag| tag.active());
             let name = tag.as_ref().map(|tag| tag.name());
+            let layout = tag.as_ref().and_then(|tag| tag.layout());
+            let gaps = tag.as_ref().map(|tag| tag.gaps());
+            let master_factor = tag.as_ref().and_then(|tag| tag.master_factor());
             let window_ids = tag
                 .as_ref()
                 .map(|tag| {
@@ -969,6 +1764,10 @@ impl tag_service_server::TagService for TagService {
                 name,
                 output_name,
                 window_ids,
+                layout,
+                inner_gap: gaps.map(|gaps| gaps.inner),
+                outer_gap: gaps.map(|gaps| gaps.outer),
+                master_factor,
             }
         })
         .await
@@ -1039,6 +1838,7 @@ impl output_service_server::OutputService for OutputService {
                 .pinnacle
                 .change_output_state(&output, None, None, None, Some(loc));
             debug!("Mapping output {} to {loc:?}", output.name());
+            state.pinnacle.warn_on_output_overlap(&output);
             state.pinnacle.request_layout(&output);
         })
         .await
@@ -1057,19 +1857,119 @@ impl output_service_server::OutputService for OutputService {
                 return;
             };
 
-            // poor man's try v2
-            let Some(mode) = Some(request).and_then(|request| {
-                Some(smithay::output::Mode {
-                    size: (request.pixel_width? as i32, request.pixel_height? as i32).into(),
-                    refresh: request.refresh_rate_millihz? as i32,
-                })
-            }) else {
-                return;
+            // poor man's try v2
+            let Some(mode) = Some(request).and_then(|request| {
+                Some(smithay::output::Mode {
+                    size: (request.pixel_width? as i32, request.pixel_height? as i32).into(),
+                    refresh: request.refresh_rate_millihz? as i32,
+                })
+            }) else {
+                return;
+            };
+
+            if let Err(err) = state.resize_output(&output, mode) {
+                warn!("{err:#}");
+            }
+        })
+        .await
+    }
+
+    async fn set_custom_mode(
+        &self,
+        request: Request<SetCustomModeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        let (Some(pixel_width), Some(pixel_height)) = (request.pixel_width, request.pixel_height)
+        else {
+            return Err(Status::invalid_argument("no mode size specified"));
+        };
+
+        let (Ok(width), Ok(height)) = (u16::try_from(pixel_width), u16::try_from(pixel_height))
+        else {
+            return Err(Status::invalid_argument("mode size is too large"));
+        };
+
+        let refresh_rate_millihz = request.refresh_rate_millihz;
+
+        run_unary(&self.sender, move |state| {
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return Err(Status::not_found("output doesn't exist"));
+            };
+
+            state
+                .set_custom_output_mode(&output, width, height, refresh_rate_millihz)
+                .map_err(|err| Status::failed_precondition(format!("{err:#}")))
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
+    async fn set_render_node(
+        &self,
+        request: Request<SetRenderNodeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        let render_node_path = request.render_node_path.map(PathBuf::from);
+
+        run_unary(&self.sender, move |state| {
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return Err(Status::not_found("output doesn't exist"));
+            };
+
+            state
+                .set_output_render_node(&output, render_node_path)
+                .map_err(|err| Status::failed_precondition(format!("{err:#}")))
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
+    async fn set_vrr(&self, request: Request<SetVrrRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        let vrr = request
+            .vrr
+            .ok_or_else(|| Status::invalid_argument("no vrr specified"))?;
+
+        run_unary(&self.sender, move |state| {
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return Err(Status::not_found("output doesn't exist"));
             };
 
-            state.resize_output(&output, mode);
+            state
+                .backend
+                .set_output_vrr(&output, vrr)
+                .map_err(|err| Status::failed_precondition(format!("{err:#}")))?;
+
+            state.schedule_render(&output);
+
+            Ok(())
         })
-        .await
+        .await?
+        .into_inner()
+        .map(Response::new)
     }
 
     async fn set_scale(&self, request: Request<SetScaleRequest>) -> Result<Response<()>, Status> {
@@ -1083,6 +1983,16 @@ impl output_service_server::OutputService for OutputService {
             ));
         };
 
+        match absolute_or_relative {
+            AbsoluteOrRelative::Absolute(abs) if !abs.is_finite() || abs <= 0.0 => {
+                return Err(Status::invalid_argument("scale must be positive"));
+            }
+            AbsoluteOrRelative::Relative(rel) if !rel.is_finite() => {
+                return Err(Status::invalid_argument("scale change must be finite"));
+            }
+            _ => (),
+        }
+
         run_unary_no_response(&self.sender, move |state| {
             let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
                 return;
@@ -1157,12 +2067,222 @@ impl output_service_server::OutputService for OutputService {
             state
                 .pinnacle
                 .change_output_state(&output, None, Some(smithay_transform), None, None);
+            state.clamp_cursor_to_outputs();
             state.pinnacle.request_layout(&output);
             state.schedule_render(&output);
         })
         .await
     }
 
+    async fn set_powered(
+        &self,
+        request: Request<SetPoweredRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        let powered = request
+            .powered
+            .ok_or_else(|| Status::invalid_argument("no powered specified"))?;
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            state
+                .backend
+                .set_output_powered(&state.pinnacle.loop_handle, &output, powered);
+            state
+                .pinnacle
+                .output_management_manager_state
+                .schedule_refresh();
+        })
+        .await
+    }
+
+    async fn set_color_temperature(
+        &self,
+        request: Request<SetColorTemperatureRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        let kelvin = request
+            .kelvin
+            .ok_or_else(|| Status::invalid_argument("no kelvin specified"))?;
+        // The backend clamps this further
+        let kelvin = u16::try_from(kelvin).unwrap_or(u16::MAX);
+
+        run_unary(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return Err(Status::not_found("output doesn't exist"));
+            };
+
+            // A gamma control client owns the gamma until it lets go,
+            // so just remember the temperature for then
+            if !state
+                .pinnacle
+                .gamma_control_manager_state
+                .gamma_controls
+                .contains_key(&output)
+            {
+                state
+                    .backend
+                    .set_output_color_temperature(&output, kelvin)
+                    .map_err(|err| Status::failed_precondition(format!("{err:#}")))?;
+            }
+
+            output.with_state_mut(|state| state.color_temperature = Some(kelvin));
+
+            Ok(())
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
+    async fn set_vblank_miss_policy(
+        &self,
+        request: Request<SetVblankMissPolicyRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let policy = match request.policy() {
+            output::v0alpha1::VblankMissPolicy::Unspecified => {
+                return Err(Status::invalid_argument("policy was unspecified"));
+            }
+            output::v0alpha1::VblankMissPolicy::CatchUp => VblankMissPolicy::CatchUp,
+            output::v0alpha1::VblankMissPolicy::Skip => VblankMissPolicy::Skip,
+        };
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            output.with_state_mut(|state| state.vblank_miss_policy = policy);
+        })
+        .await
+    }
+
+    async fn set_clear_color(
+        &self,
+        request: Request<SetClearColorRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        let (Some(r), Some(g), Some(b), Some(a)) = (request.r, request.g, request.b, request.a)
+        else {
+            return Err(Status::invalid_argument("color components were null"));
+        };
+
+        if [r, g, b, a].iter().any(|c| !c.is_finite()) {
+            return Err(Status::invalid_argument("color components must be finite"));
+        }
+
+        let color = [r, g, b, a].map(|c| c.clamp(0.0, 1.0));
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return;
+            };
+
+            output.with_state_mut(|state| state.clear_color = Some(color));
+
+            state.backend.reset_buffers(&output);
+            state.schedule_render(&output);
+        })
+        .await
+    }
+
+    async fn focus_relative(
+        &self,
+        request: Request<FocusRelativeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(offset) = request.offset else {
+            return Err(Status::invalid_argument("no offset specified"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = state
+                .pinnacle
+                .focused_output()
+                .and_then(|op| state.pinnacle.output_relative_to(op, offset as isize))
+            else {
+                return;
+            };
+
+            state.pinnacle.output_focus_stack.set_focus(output.clone());
+            state.update_keyboard_focus(&output);
+        })
+        .await
+    }
+
+    async fn set_focused(
+        &self,
+        request: Request<SetFocusedRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(output_name) = request.output_name else {
+            return Err(Status::invalid_argument("output_name was null"));
+        };
+
+        run_unary(&self.sender, move |state| {
+            let Some(output) = OutputName(output_name).output(&state.pinnacle) else {
+                return Err(Status::not_found("output doesn't exist"));
+            };
+
+            state.pinnacle.output_focus_stack.set_focus(output.clone());
+            state.update_keyboard_focus(&output);
+
+            Ok(())
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
+    async fn set_primary(
+        &self,
+        request: Request<SetPrimaryRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let primary = match request.output_id {
+            Some(set_primary_request::OutputId::OutputName(name)) => {
+                Some(PrimaryOutput::Name(OutputName(name)))
+            }
+            Some(set_primary_request::OutputId::Serial(serial)) => Some(PrimaryOutput::Serial(
+                NonZeroU32::new(serial).ok_or_else(|| Status::invalid_argument("serial was 0"))?,
+            )),
+            None => None,
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            state.pinnacle.config.primary_output = primary;
+            state.pinnacle.anchor_primary_output();
+        })
+        .await
+    }
+
     async fn get(
         &self,
         _request: Request<output::v0alpha1::GetRequest>,
@@ -1180,6 +2300,18 @@ impl output_service_server::OutputService for OutputService {
         .await
     }
 
+    async fn get_focused(
+        &self,
+        _request: Request<GetFocusedRequest>,
+    ) -> Result<Response<GetFocusedResponse>, Status> {
+        run_unary(&self.sender, move |state| {
+            let output_name = state.pinnacle.focused_output().map(|op| op.name());
+
+            GetFocusedResponse { output_name }
+        })
+        .await
+    }
+
     async fn get_properties(
         &self,
         request: Request<output::v0alpha1::GetPropertiesRequest>,
@@ -1298,6 +2430,14 @@ impl output_service_server::OutputService for OutputService {
                 })
                 .unwrap_or_default();
 
+            let powered = output
+                .as_ref()
+                .map(|output| state.backend.output_powered(output));
+
+            let vrr = output
+                .as_ref()
+                .map(|output| state.backend.output_vrr(output));
+
             output::v0alpha1::GetPropertiesResponse {
                 make,
                 model,
@@ -1316,10 +2456,69 @@ impl output_service_server::OutputService for OutputService {
                 transform,
                 serial,
                 keyboard_focus_stack_window_ids,
+                powered,
+                vrr,
             }
         })
         .await
     }
+
+    async fn get_edid(
+        &self,
+        request: Request<output::v0alpha1::GetEdidRequest>,
+    ) -> Result<Response<output::v0alpha1::GetEdidResponse>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        run_unary(&self.sender, move |state| {
+            let edid = output_name
+                .output(&state.pinnacle)
+                .and_then(|output| output.with_state(|state| state.edid.clone()));
+
+            output::v0alpha1::GetEdidResponse { edid }
+        })
+        .await
+    }
+
+    async fn capture(
+        &self,
+        request: Request<CaptureRequest>,
+    ) -> Result<Response<CaptureResponse>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+        let include_cursor = request.include_cursor.unwrap_or_default();
+
+        let response = run_unary(&self.sender, move |state| {
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return Err(Status::not_found("output doesn't exist"));
+            };
+
+            let capture = state
+                .capture_output(&output, include_cursor)
+                .map_err(|err| Status::failed_precondition(format!("{err:#}")))?;
+
+            Ok(CaptureResponse {
+                width: Some(capture.width),
+                height: Some(capture.height),
+                stride: Some(capture.stride),
+                data: Some(capture.data),
+            })
+        })
+        .await?
+        .into_inner()?;
+
+        Ok(Response::new(response))
+    }
 }
 
 pub struct RenderService {
@@ -1383,4 +2582,118 @@ impl render_service_server::RenderService for RenderService {
         })
         .await
     }
+
+    async fn get_scheduling_stats(
+        &self,
+        _request: Request<GetSchedulingStatsRequest>,
+    ) -> Result<Response<GetSchedulingStatsResponse>, Status> {
+        run_unary(&self.sender, |state| {
+            let outputs = state
+                .pinnacle
+                .space
+                .outputs()
+                .filter_map(|output| {
+                    let stats = state.backend.render_stats(output)?;
+                    Some(get_scheduling_stats_response::OutputStats {
+                        output_name: Some(output.name()),
+                        render_requests: Some(stats.render_requests),
+                        renders: Some(stats.renders),
+                        frames_queued: Some(stats.frames_queued),
+                        vblank_misses: Some(stats.vblank_misses),
+                        render_time_micros: Some(stats.render_time.as_micros() as u64),
+                    })
+                })
+                .collect();
+
+            GetSchedulingStatsResponse { outputs }
+        })
+        .await
+    }
+
+    async fn get_render_stats(
+        &self,
+        request: Request<GetRenderStatsRequest>,
+    ) -> Result<Response<GetRenderStatsResponse>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        run_unary(&self.sender, move |state| {
+            let Some(stats) = output_name
+                .output(&state.pinnacle)
+                .and_then(|output| state.backend.render_stats(&output))
+            else {
+                return GetRenderStatsResponse::default();
+            };
+
+            GetRenderStatsResponse {
+                last_render_time_micros: Some(stats.last_render_time.as_micros() as u64),
+                micros_since_last_vblank: stats
+                    .last_vblank
+                    .map(|vblank| vblank.elapsed().as_micros() as u64),
+                last_render_damaged: Some(stats.last_render_damaged),
+            }
+        })
+        .await
+    }
+
+    async fn get_render_elements(
+        &self,
+        request: Request<GetRenderElementsRequest>,
+    ) -> Result<Response<GetRenderElementsResponse>, Status> {
+        let request = request.into_inner();
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        run_unary(&self.sender, move |state| {
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return GetRenderElementsResponse::default();
+            };
+
+            let scale = output.current_scale().fractional_scale().into();
+
+            let elements = state
+                .inspect_render_elements(&output)
+                .into_iter()
+                .map(|elem| {
+                    let kind = match &elem {
+                        OutputRenderElement::Surface(_) => {
+                            get_render_elements_response::Kind::Surface
+                        }
+                        OutputRenderElement::Pointer(_) => {
+                            get_render_elements_response::Kind::Pointer
+                        }
+                        OutputRenderElement::Snapshot(_) => {
+                            get_render_elements_response::Kind::Snapshot
+                        }
+                    };
+
+                    let geo = elem.geometry(scale);
+
+                    get_render_elements_response::RenderElement {
+                        id: Some(format!("{:?}", elem.id())),
+                        kind: Some(kind as i32),
+                        geometry: Some(pinnacle_api_defs::pinnacle::v0alpha1::Geometry {
+                            x: Some(geo.loc.x),
+                            y: Some(geo.loc.y),
+                            width: Some(geo.size.w),
+                            height: Some(geo.size.h),
+                        }),
+                        alpha: Some(elem.alpha()),
+                    }
+                })
+                .collect();
+
+            GetRenderElementsResponse { elements }
+        })
+        .await
+    }
 }
@@ -1,11 +1,16 @@
 use std::collections::VecDeque;
 
 use pinnacle_api_defs::pinnacle::signal::v0alpha1::{
-    signal_service_server, OutputConnectRequest, OutputConnectResponse, OutputDisconnectRequest,
-    OutputDisconnectResponse, OutputMoveRequest, OutputMoveResponse, OutputResizeRequest,
-    OutputResizeResponse, SignalRequest, StreamControl, TagActiveRequest, TagActiveResponse,
-    WindowPointerEnterRequest, WindowPointerEnterResponse, WindowPointerLeaveRequest,
-    WindowPointerLeaveResponse,
+    signal_service_server, InputDeviceAddedRequest, InputDeviceAddedResponse,
+    InputDeviceRemovedRequest, InputDeviceRemovedResponse, InputModifiersChangedRequest,
+    InputModifiersChangedResponse, InputResumedRequest, InputResumedResponse, OutputConnectRequest,
+    OutputConnectResponse, OutputDisconnectRequest, OutputDisconnectResponse, OutputMoveRequest,
+    OutputMoveResponse, OutputResizeRequest, OutputResizeResponse, SignalRequest, StreamControl,
+    TagActiveRequest, TagActiveResponse, WindowClassChangedRequest, WindowClassChangedResponse,
+    WindowFocusedRequest, WindowFocusedResponse, WindowPointerEnterRequest,
+    WindowPointerEnterResponse, WindowPointerLeaveRequest, WindowPointerLeaveResponse,
+    WindowStateRequestRequest, WindowStateRequestResponse, WindowTitleChangedRequest,
+    WindowTitleChangedResponse,
 };
 use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
 use tonic::{Request, Response, Status, Streaming};
@@ -28,9 +33,25 @@ pub struct SignalState {
         SignalData<WindowPointerEnterResponse, VecDeque<WindowPointerEnterResponse>>,
     pub window_pointer_leave:
         SignalData<WindowPointerLeaveResponse, VecDeque<WindowPointerLeaveResponse>>,
+    pub window_state_request:
+        SignalData<WindowStateRequestResponse, VecDeque<WindowStateRequestResponse>>,
+    pub window_title_changed:
+        SignalData<WindowTitleChangedResponse, VecDeque<WindowTitleChangedResponse>>,
+    pub window_class_changed:
+        SignalData<WindowClassChangedResponse, VecDeque<WindowClassChangedResponse>>,
+    pub window_focused: SignalData<WindowFocusedResponse, VecDeque<WindowFocusedResponse>>,
 
     // Tag
     pub tag_active: SignalData<TagActiveResponse, VecDeque<TagActiveResponse>>,
+
+    // Input
+    pub input_device_added:
+        SignalData<InputDeviceAddedResponse, VecDeque<InputDeviceAddedResponse>>,
+    pub input_device_removed:
+        SignalData<InputDeviceRemovedResponse, VecDeque<InputDeviceRemovedResponse>>,
+    pub input_modifiers_changed:
+        SignalData<InputModifiersChangedResponse, VecDeque<InputModifiersChangedResponse>>,
+    pub input_resumed: SignalData<InputResumedResponse, VecDeque<InputResumedResponse>>,
 }
 
 impl SignalState {
@@ -41,6 +62,14 @@ impl SignalState {
         self.output_move.disconnect();
         self.window_pointer_enter.disconnect();
         self.window_pointer_leave.disconnect();
+        self.window_state_request.disconnect();
+        self.window_title_changed.disconnect();
+        self.window_class_changed.disconnect();
+        self.window_focused.disconnect();
+        self.input_device_added.disconnect();
+        self.input_device_removed.disconnect();
+        self.input_modifiers_changed.disconnect();
+        self.input_resumed.disconnect();
     }
 }
 
@@ -94,6 +123,11 @@ impl<T, B: SignalBuffer<T>> SignalData<T, B> {
         }
     }
 
+    /// Returns whether a client is connected to this signal.
+    pub fn is_connected(&self) -> bool {
+        self.sender.is_some()
+    }
+
     pub fn connect(
         &mut self,
         sender: UnboundedSender<Result<T, Status>>,
@@ -191,9 +225,18 @@ impl signal_service_server::SignalService for SignalService {
 
     type WindowPointerEnterStream = ResponseStream<WindowPointerEnterResponse>;
     type WindowPointerLeaveStream = ResponseStream<WindowPointerLeaveResponse>;
+    type WindowStateRequestStream = ResponseStream<WindowStateRequestResponse>;
+    type WindowTitleChangedStream = ResponseStream<WindowTitleChangedResponse>;
+    type WindowClassChangedStream = ResponseStream<WindowClassChangedResponse>;
+    type WindowFocusedStream = ResponseStream<WindowFocusedResponse>;
 
     type TagActiveStream = ResponseStream<TagActiveResponse>;
 
+    type InputDeviceAddedStream = ResponseStream<InputDeviceAddedResponse>;
+    type InputDeviceRemovedStream = ResponseStream<InputDeviceRemovedResponse>;
+    type InputModifiersChangedStream = ResponseStream<InputModifiersChangedResponse>;
+    type InputResumedStream = ResponseStream<InputResumedResponse>;
+
     async fn output_connect(
         &self,
         request: Request<Streaming<OutputConnectRequest>>,
@@ -260,6 +303,50 @@ impl signal_service_server::SignalService for SignalService {
         })
     }
 
+    async fn window_state_request(
+        &self,
+        request: Request<Streaming<WindowStateRequestRequest>>,
+    ) -> Result<Response<Self::WindowStateRequestStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.window_state_request
+        })
+    }
+
+    async fn window_title_changed(
+        &self,
+        request: Request<Streaming<WindowTitleChangedRequest>>,
+    ) -> Result<Response<Self::WindowTitleChangedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.window_title_changed
+        })
+    }
+
+    async fn window_class_changed(
+        &self,
+        request: Request<Streaming<WindowClassChangedRequest>>,
+    ) -> Result<Response<Self::WindowClassChangedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.window_class_changed
+        })
+    }
+
+    async fn window_focused(
+        &self,
+        request: Request<Streaming<WindowFocusedRequest>>,
+    ) -> Result<Response<Self::WindowFocusedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.window_focused
+        })
+    }
+
     async fn tag_active(
         &self,
         request: Request<Streaming<TagActiveRequest>>,
@@ -270,4 +357,48 @@ impl signal_service_server::SignalService for SignalService {
             &mut state.pinnacle.signal_state.tag_active
         })
     }
+
+    async fn input_device_added(
+        &self,
+        request: Request<Streaming<InputDeviceAddedRequest>>,
+    ) -> Result<Response<Self::InputDeviceAddedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.input_device_added
+        })
+    }
+
+    async fn input_device_removed(
+        &self,
+        request: Request<Streaming<InputDeviceRemovedRequest>>,
+    ) -> Result<Response<Self::InputDeviceRemovedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.input_device_removed
+        })
+    }
+
+    async fn input_modifiers_changed(
+        &self,
+        request: Request<Streaming<InputModifiersChangedRequest>>,
+    ) -> Result<Response<Self::InputModifiersChangedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.input_modifiers_changed
+        })
+    }
+
+    async fn input_resumed(
+        &self,
+        request: Request<Streaming<InputResumedRequest>>,
+    ) -> Result<Response<Self::InputResumedStream>, Status> {
+        let in_stream = request.into_inner();
+
+        start_signal_stream(self.sender.clone(), in_stream, |state| {
+            &mut state.pinnacle.signal_state.input_resumed
+        })
+    }
 }
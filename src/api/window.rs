@@ -5,10 +5,14 @@ use pinnacle_api_defs::pinnacle::{
     window::{
         self,
         v0alpha1::{
-            window_service_server, AddWindowRuleRequest, CloseRequest, FullscreenOrMaximized,
-            MoveGrabRequest, MoveToTagRequest, RaiseRequest, ResizeGrabRequest, SetFloatingRequest,
-            SetFocusedRequest, SetFullscreenRequest, SetGeometryRequest, SetMaximizedRequest,
-            SetTagRequest, WindowRule, WindowRuleCondition,
+            get_all_properties_response, window_service_server, AddWindowRuleRequest, CloseRequest,
+            CycleFocusRequest, FocusRequest, FullscreenOrMaximized, LowerRequest, MoveGrabRequest,
+            MoveToOutputRequest, MoveToRelativeOutputRequest, MoveToTagRequest, RaiseRequest,
+            ResizeGrabRequest, ResolveStateRequestRequest, SetFloatingRequest, SetFocusedRequest,
+            SetFullscreenRequest, SetGeometryRequest, SetKeepFloatingInBoundsRequest,
+            SetMaximizedRequest, SetMinimizedRequest, SetOpacityRequest, SetTagRequest,
+            SwapRequest, ToggleScratchpadRequest, ToggleScratchpadVisibleRequest, WindowRule,
+            WindowRuleCondition,
         },
     },
 };
@@ -22,8 +26,15 @@ use tonic::{Request, Response, Status};
 use tracing::warn;
 
 use crate::{
-    output::OutputName, render::util::snapshot::capture_snapshots_on_output, state::WithState,
-    tag::TagId, window::window_state::WindowId,
+    output::OutputName,
+    render::util::snapshot::capture_snapshots_on_output,
+    state::{Pinnacle, WithState},
+    tag::TagId,
+    window::{
+        rules::RulePattern,
+        window_state::{FloatingOrTiled, WindowId},
+        WindowElement,
+    },
 };
 
 use super::{run_unary, run_unary_no_response, StateFnSender};
@@ -79,8 +90,15 @@ impl window_service_server::WindowService for WindowService {
         let width = geometry.width;
         let height = geometry.height;
 
+        if width.is_some_and(|width| width <= 0) || height.is_some_and(|height| height <= 0) {
+            return Err(Status::invalid_argument(
+                "width and height must be positive",
+            ));
+        }
+
         run_unary_no_response(&self.sender, move |state| {
-            let Some(window) = window_id.window(&state.pinnacle) else {
+            let pinnacle = &mut state.pinnacle;
+            let Some(window) = window_id.window(pinnacle) else {
                 return;
             };
 
@@ -99,16 +117,41 @@ impl window_service_server::WindowService for WindowService {
 
             let rect = Rectangle::from_loc_and_size(window_loc, window_size);
 
-            window.with_state_mut(|state| {
-                use crate::window::window_state::FloatingOrTiled;
-                state.floating_or_tiled = match state.floating_or_tiled {
-                    FloatingOrTiled::Floating(_) => FloatingOrTiled::Floating(rect),
-                    FloatingOrTiled::Tiled(_) => FloatingOrTiled::Tiled(Some(rect)),
+            let is_tiled = window.with_state(|state| state.floating_or_tiled.is_tiled());
+
+            if is_tiled {
+                // Tiled windows are floated at the new geometry, the same as
+                // toggling them to floating would
+                let snapshots = window.output(pinnacle).map(|output| {
+                    state.backend.with_renderer(|renderer| {
+                        capture_snapshots_on_output(pinnacle, renderer, &output, [window.clone()])
+                    })
+                });
+
+                window.with_state_mut(|state| {
+                    state.floating_or_tiled = FloatingOrTiled::Tiled(Some(rect));
+                });
+                window.toggle_floating();
+
+                if let (Some(output), Some((fs_and_up_snapshots, under_fs_snapshots))) =
+                    (window.output(pinnacle), snapshots.flatten())
+                {
+                    output.with_state_mut(|op_state| {
+                        op_state.new_wait_layout_transaction(
+                            pinnacle.loop_handle.clone(),
+                            fs_and_up_snapshots,
+                            under_fs_snapshots,
+                        )
+                    });
                 }
-            });
+            } else {
+                window.with_state_mut(|state| {
+                    state.floating_or_tiled = FloatingOrTiled::Floating(rect);
+                });
+            }
 
-            for output in state.pinnacle.space.outputs_for_element(&window) {
-                state.pinnacle.request_layout(&output);
+            for output in pinnacle.space.outputs_for_element(&window) {
+                pinnacle.request_layout(&output);
                 state.schedule_render(&output);
             }
         })
@@ -199,6 +242,47 @@ impl window_service_server::WindowService for WindowService {
         .await
     }
 
+    async fn set_minimized(
+        &self,
+        request: Request<SetMinimizedRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let set_or_toggle = request.set_or_toggle();
+
+        if set_or_toggle == SetOrToggle::Unspecified {
+            return Err(Status::invalid_argument("unspecified set or toggle"));
+        }
+
+        let minimized = match set_or_toggle {
+            SetOrToggle::Set => Some(true),
+            SetOrToggle::Unset => Some(false),
+            SetOrToggle::Toggle => None,
+            SetOrToggle::Unspecified => unreachable!(),
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            if window.is_x11_override_redirect() {
+                return;
+            }
+
+            let minimized =
+                minimized.unwrap_or_else(|| !window.with_state(|win_state| win_state.minimized));
+            state.set_window_minimized(&window, minimized);
+        })
+        .await
+    }
+
     async fn set_floating(
         &self,
         request: Request<SetFloatingRequest>,
@@ -341,6 +425,59 @@ impl window_service_server::WindowService for WindowService {
         .await
     }
 
+    async fn focus(&self, request: Request<FocusRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let follow = request.follow.unwrap_or(true);
+
+        run_unary(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return Err(Status::not_found("window doesn't exist"));
+            };
+
+            if window.is_x11_override_redirect() {
+                return Err(Status::failed_precondition(
+                    "override-redirect windows can't be focused",
+                ));
+            }
+
+            // Minimized windows get unminimized below, so only check the tags here
+            let on_active_tag =
+                window.with_state(|state| state.tags.iter().any(|tag| tag.active()));
+
+            if !on_active_tag {
+                if !follow {
+                    warn!("Not focusing window {window_id:?} as it isn't on an active tag");
+                    return Ok(());
+                }
+
+                let Some(tag) = window.with_state(|state| state.tags.first().cloned()) else {
+                    return Err(Status::failed_precondition("window has no tags"));
+                };
+                state.switch_to_tag(&tag);
+            }
+
+            state.set_window_minimized(&window, false);
+
+            let Some(output) = window.output(&state.pinnacle) else {
+                return Err(Status::failed_precondition("window isn't on an output"));
+            };
+
+            state.raise_and_focus_window(&window, &output);
+
+            Ok(())
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
     async fn move_to_tag(
         &self,
         request: Request<MoveToTagRequest>,
@@ -359,6 +496,8 @@ impl window_service_server::WindowService for WindowService {
                 .ok_or_else(|| Status::invalid_argument("no tag specified"))?,
         );
 
+        let follow = request.follow();
+
         run_unary_no_response(&self.sender, move |state| {
             let pinnacle = &mut state.pinnacle;
 
@@ -368,9 +507,11 @@ impl window_service_server::WindowService for WindowService {
 
             let Some(tag) = tag_id.tag(pinnacle) else { return };
 
-            let snapshots = window.output(pinnacle).map(|output| {
+            let source_output = window.output(pinnacle);
+
+            let snapshots = source_output.as_ref().map(|output| {
                 state.backend.with_renderer(|renderer| {
-                    capture_snapshots_on_output(pinnacle, renderer, &output, [window.clone()])
+                    capture_snapshots_on_output(pinnacle, renderer, output, [window.clone()])
                 })
             });
 
@@ -393,11 +534,97 @@ impl window_service_server::WindowService for WindowService {
             pinnacle.request_layout(&output);
             state.schedule_render(&output);
 
+            // The window left its old output, so that needs a relayout too
+            if let Some(source_output) = source_output.filter(|op| op != &output) {
+                state.pinnacle.request_layout(&source_output);
+                state.schedule_render(&source_output);
+            }
+
             state.pinnacle.fixup_xwayland_window_layering();
+
+            if follow {
+                state.switch_to_tag(&tag);
+                output.with_state_mut(|op_state| op_state.focus_stack.set_focus(window.clone()));
+                state.pinnacle.output_focus_stack.set_focus(output.clone());
+                state.update_keyboard_focus(&output);
+            }
+        })
+        .await
+    }
+
+    async fn move_to_relative_output(
+        &self,
+        request: Request<MoveToRelativeOutputRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let Some(offset) = request.offset else {
+            return Err(Status::invalid_argument("no offset specified"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            let Some(output) = window
+                .output(&state.pinnacle)
+                .and_then(|op| state.pinnacle.output_relative_to(&op, offset as isize))
+            else {
+                return;
+            };
+
+            state.move_window_to_output(&window, &output);
+
+            output.with_state_mut(|op_state| op_state.focus_stack.set_focus(window.clone()));
+            state.pinnacle.output_focus_stack.set_focus(output.clone());
+            state.update_keyboard_focus(&output);
         })
         .await
     }
 
+    async fn move_to_output(
+        &self,
+        request: Request<MoveToOutputRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let output_name = OutputName(
+            request
+                .output_name
+                .ok_or_else(|| Status::invalid_argument("no output specified"))?,
+        );
+
+        run_unary(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return Err(Status::not_found("window doesn't exist"));
+            };
+
+            let Some(output) = output_name.output(&state.pinnacle) else {
+                return Err(Status::not_found("output doesn't exist"));
+            };
+
+            state.move_window_to_output(&window, &output);
+
+            Ok(())
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
     async fn set_tag(&self, request: Request<SetTagRequest>) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
@@ -426,9 +653,11 @@ impl window_service_server::WindowService for WindowService {
             };
             let Some(tag) = tag_id.tag(pinnacle) else { return };
 
-            let snapshots = window.output(pinnacle).map(|output| {
+            let source_output = window.output(pinnacle);
+
+            let snapshots = source_output.as_ref().map(|output| {
                 state.backend.with_renderer(|renderer| {
-                    capture_snapshots_on_output(pinnacle, renderer, &output, [window.clone()])
+                    capture_snapshots_on_output(pinnacle, renderer, output, [window.clone()])
                 })
             });
 
@@ -466,11 +695,52 @@ impl window_service_server::WindowService for WindowService {
             pinnacle.request_layout(&output);
             state.schedule_render(&output);
 
+            // Tags from different outputs can be set on the same window,
+            // so the output the window was on may need a relayout as well
+            if let Some(source_output) = source_output.filter(|op| op != &output) {
+                state.pinnacle.request_layout(&source_output);
+                state.schedule_render(&source_output);
+            }
+
             state.pinnacle.fixup_xwayland_window_layering();
         })
         .await
     }
 
+    async fn set_opacity(
+        &self,
+        request: Request<SetOpacityRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let opacity = request
+            .opacity
+            .ok_or_else(|| Status::invalid_argument("no opacity specified"))?;
+
+        if opacity.is_nan() {
+            return Err(Status::invalid_argument("opacity was NaN"));
+        }
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            window.with_state_mut(|state| state.opacity = opacity.clamp(0.0, 1.0));
+
+            for output in state.pinnacle.space.outputs_for_element(&window) {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
     async fn raise(&self, request: Request<RaiseRequest>) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
@@ -492,6 +762,106 @@ impl window_service_server::WindowService for WindowService {
         .await
     }
 
+    async fn lower(&self, request: Request<LowerRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                warn!("`lower` was called on a nonexistent window");
+                return;
+            };
+
+            state.pinnacle.lower_window(window.clone());
+
+            if let Some(output) = window.output(&state.pinnacle) {
+                state.schedule_render(&output);
+            }
+        })
+        .await
+    }
+
+    async fn swap(&self, request: Request<SwapRequest>) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+        let other_window_id = WindowId(
+            request
+                .other_window_id
+                .ok_or_else(|| Status::invalid_argument("no other window specified"))?,
+        );
+
+        run_unary(&self.sender, move |state| {
+            let pinnacle = &mut state.pinnacle;
+
+            let (Some(window), Some(other)) =
+                (window_id.window(pinnacle), other_window_id.window(pinnacle))
+            else {
+                return Err(Status::not_found("window doesn't exist"));
+            };
+
+            if window == other {
+                return Ok(());
+            }
+
+            let is_swappable = |win: &WindowElement| {
+                win.with_state(|state| {
+                    state.floating_or_tiled.is_tiled()
+                        && !state.fullscreen_or_maximized.is_fullscreen()
+                })
+            };
+
+            if !is_swappable(&window) || !is_swappable(&other) {
+                return Err(Status::failed_precondition(
+                    "only tiled, non-fullscreen windows can be swapped",
+                ));
+            }
+
+            let Some(output) = window.output(pinnacle) else {
+                return Err(Status::failed_precondition("window is not on an output"));
+            };
+
+            if other.output(pinnacle).as_ref() != Some(&output) {
+                return Err(Status::failed_precondition(
+                    "windows are on different outputs",
+                ));
+            }
+
+            let snapshots = state.backend.with_renderer(|renderer| {
+                capture_snapshots_on_output(pinnacle, renderer, &output, [])
+            });
+
+            if let Some((fs_and_up_snapshots, under_fs_snapshots)) = snapshots {
+                output.with_state_mut(|op_state| {
+                    op_state.new_wait_layout_transaction(
+                        pinnacle.loop_handle.clone(),
+                        fs_and_up_snapshots,
+                        under_fs_snapshots,
+                    )
+                });
+            }
+
+            // Focus is tracked per window, so the focused window stays focused
+            // at its new position.
+            pinnacle.swap_window_positions(&window, &other);
+            state.schedule_render(&output);
+
+            Ok(())
+        })
+        .await?
+        .into_inner()
+        .map(Response::new)
+    }
+
     async fn move_grab(&self, request: Request<MoveGrabRequest>) -> Result<Response<()>, Status> {
         let request = request.into_inner();
 
@@ -537,6 +907,12 @@ impl window_service_server::WindowService for WindowService {
             .button
             .ok_or_else(|| Status::invalid_argument("no button specified"))?;
 
+        let requested_edges = request
+            .edges
+            .map(server::xdg_toplevel::ResizeEdge::try_from)
+            .transpose()
+            .map_err(|_| Status::invalid_argument("invalid resize edges"))?;
+
         run_unary_no_response(&self.sender, move |state| {
             let Some(pointer_loc) = state
                 .pinnacle
@@ -569,7 +945,7 @@ impl window_service_server::WindowService for WindowService {
             let full_width = window_x + window_width;
             let full_height = window_y + window_height;
 
-            let edges = match pointer_loc {
+            let edges = requested_edges.unwrap_or_else(|| match pointer_loc {
                 Point { x, y, .. }
                     if (window_x..=half_width).contains(&x)
                         && (window_y..=half_height).contains(&y) =>
@@ -595,7 +971,7 @@ impl window_service_server::WindowService for WindowService {
                     server::xdg_toplevel::ResizeEdge::BottomRight
                 }
                 _ => server::xdg_toplevel::ResizeEdge::None,
-            };
+            });
 
             state.resize_request_server(
                 &wl_surf,
@@ -608,6 +984,113 @@ impl window_service_server::WindowService for WindowService {
         .await
     }
 
+    async fn resolve_state_request(
+        &self,
+        request: Request<ResolveStateRequestRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        let Some(allow) = request.allow else {
+            return Err(Status::invalid_argument("no allow specified"));
+        };
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            state.resolve_state_request(&window, allow);
+        })
+        .await
+    }
+
+    async fn set_keep_floating_in_bounds(
+        &self,
+        request: Request<SetKeepFloatingInBoundsRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let Some(enabled) = request.enabled else {
+            return Err(Status::invalid_argument("no enabled specified"));
+        };
+
+        let margin = request.margin;
+
+        run_unary_no_response(&self.sender, move |state| {
+            let config = &mut state.pinnacle.config;
+            config.keep_floating_in_bounds = enabled;
+            if let Some(margin) = margin {
+                config.floating_bounds_margin = margin;
+            }
+
+            state.pinnacle.keep_floating_windows_in_bounds();
+        })
+        .await
+    }
+
+    async fn toggle_scratchpad(
+        &self,
+        request: Request<ToggleScratchpadRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let window_id = WindowId(
+            request
+                .window_id
+                .ok_or_else(|| Status::invalid_argument("no window specified"))?,
+        );
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(window) = window_id.window(&state.pinnacle) else {
+                return;
+            };
+
+            if window.is_x11_override_redirect() {
+                return;
+            }
+
+            state.toggle_window_scratchpad(&window);
+        })
+        .await
+    }
+
+    async fn toggle_scratchpad_visible(
+        &self,
+        _request: Request<ToggleScratchpadVisibleRequest>,
+    ) -> Result<Response<()>, Status> {
+        run_unary_no_response(&self.sender, move |state| {
+            state.toggle_scratchpad_visible();
+        })
+        .await
+    }
+
+    async fn cycle_focus(
+        &self,
+        request: Request<CycleFocusRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        let offset = request
+            .offset
+            .ok_or_else(|| Status::invalid_argument("no offset specified"))?;
+        let all_tags = request.all_tags();
+
+        run_unary_no_response(&self.sender, move |state| {
+            let Some(output) = state.pinnacle.focused_output().cloned() else {
+                return;
+            };
+
+            state.cycle_focus(&output, offset, all_tags);
+        })
+        .await
+    }
+
     async fn get(
         &self,
         _request: Request<window::v0alpha1::GetRequest>,
@@ -638,82 +1121,28 @@ impl window_service_server::WindowService for WindowService {
         );
 
         run_unary(&self.sender, move |state| {
-            let pinnacle = &state.pinnacle;
-            let window = window_id.window(pinnacle);
-
-            let width = window.as_ref().map(|win| win.geometry().size.w);
-
-            let height = window.as_ref().map(|win| win.geometry().size.h);
-
-            let x = window
-                .as_ref()
-                .and_then(|win| state.pinnacle.space.element_location(win))
-                .map(|loc| loc.x);
-
-            let y = window
-                .as_ref()
-                .and_then(|win| state.pinnacle.space.element_location(win))
-                .map(|loc| loc.y);
-
-            let geometry = if width.is_none() && height.is_none() && x.is_none() && y.is_none() {
-                None
-            } else {
-                Some(Geometry {
-                    x,
-                    y,
-                    width,
-                    height,
-                })
-            };
-
-            let class = window.as_ref().and_then(|win| win.class());
-            let title = window.as_ref().and_then(|win| win.title());
-
-            let focused = window.as_ref().and_then(|win| {
-                pinnacle
-                    .focused_output()
-                    .and_then(|output| pinnacle.focused_window(output))
-                    .map(|foc_win| win == foc_win)
-            });
-
-            let floating = window
-                .as_ref()
-                .map(|win| win.with_state(|state| state.floating_or_tiled.is_floating()));
-
-            let fullscreen_or_maximized = window
-                .as_ref()
-                .map(|win| win.with_state(|state| state.fullscreen_or_maximized))
-                .map(|fs_or_max| match fs_or_max {
-                    // TODO: from impl
-                    crate::window::window_state::FullscreenOrMaximized::Neither => {
-                        FullscreenOrMaximized::Neither
-                    }
-                    crate::window::window_state::FullscreenOrMaximized::Fullscreen => {
-                        FullscreenOrMaximized::Fullscreen
-                    }
-                    crate::window::window_state::FullscreenOrMaximized::Maximized => {
-                        FullscreenOrMaximized::Maximized
-                    }
-                } as i32);
+            let window = window_id.window(&state.pinnacle);
+            window_properties(&state.pinnacle, window.as_ref())
+        })
+        .await
+    }
 
-            let tag_ids = window
-                .as_ref()
-                .map(|win| {
-                    win.with_state(|state| {
-                        state.tags.iter().map(|tag| tag.id().0).collect::<Vec<_>>()
-                    })
+    async fn get_all_properties(
+        &self,
+        _request: Request<window::v0alpha1::GetAllPropertiesRequest>,
+    ) -> Result<Response<window::v0alpha1::GetAllPropertiesResponse>, Status> {
+        run_unary(&self.sender, move |state| {
+            let windows = state
+                .pinnacle
+                .windows
+                .iter()
+                .map(|win| get_all_properties_response::Window {
+                    window_id: Some(win.with_state(|state| state.id.0)),
+                    properties: Some(window_properties(&state.pinnacle, Some(win))),
                 })
-                .unwrap_or_default();
+                .collect();
 
-            window::v0alpha1::GetPropertiesResponse {
-                geometry,
-                class,
-                title,
-                focused,
-                floating,
-                fullscreen_or_maximized,
-                tag_ids,
-            }
+            window::v0alpha1::GetAllPropertiesResponse { windows }
         })
         .await
     }
@@ -726,8 +1155,9 @@ impl window_service_server::WindowService for WindowService {
 
         let cond = request
             .cond
-            .ok_or_else(|| Status::invalid_argument("no condition specified"))?
-            .into();
+            .ok_or_else(|| Status::invalid_argument("no condition specified"))?;
+
+        let cond = cond.try_into()?;
 
         let rule = request
             .rule
@@ -741,15 +1171,34 @@ impl window_service_server::WindowService for WindowService {
     }
 }
 
-impl From<WindowRuleCondition> for crate::window::rules::WindowRuleCondition {
-    fn from(cond: WindowRuleCondition) -> Self {
+/// Compile `patterns`, failing with `invalid_argument` on the first invalid one.
+fn compile_rule_patterns(patterns: Vec<String>) -> Result<Option<Vec<RulePattern>>, Status> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    patterns
+        .iter()
+        .map(|pattern| {
+            RulePattern::new(pattern).map_err(|err| {
+                Status::invalid_argument(format!("invalid regex `{pattern}`: {err}"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+impl TryFrom<WindowRuleCondition> for crate::window::rules::WindowRuleCondition {
+    type Error = Status;
+
+    fn try_from(cond: WindowRuleCondition) -> Result<Self, Status> {
         let cond_any = match cond.any.is_empty() {
             true => None,
             false => Some(
                 cond.any
                     .into_iter()
-                    .map(crate::window::rules::WindowRuleCondition::from)
-                    .collect::<Vec<_>>(),
+                    .map(crate::window::rules::WindowRuleCondition::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
             ),
         };
 
@@ -758,8 +1207,8 @@ impl From<WindowRuleCondition> for crate::window::rules::WindowRuleCondition {
             false => Some(
                 cond.all
                     .into_iter()
-                    .map(crate::window::rules::WindowRuleCondition::from)
-                    .collect::<Vec<_>>(),
+                    .map(crate::window::rules::WindowRuleCondition::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
             ),
         };
 
@@ -773,18 +1222,23 @@ impl From<WindowRuleCondition> for crate::window::rules::WindowRuleCondition {
             false => Some(cond.titles),
         };
 
+        let class_regex = compile_rule_patterns(cond.class_regexes)?;
+        let title_regex = compile_rule_patterns(cond.title_regexes)?;
+
         let tag = match cond.tags.is_empty() {
             true => None,
             false => Some(cond.tags.into_iter().map(TagId).collect::<Vec<_>>()),
         };
 
-        crate::window::rules::WindowRuleCondition {
+        Ok(crate::window::rules::WindowRuleCondition {
             cond_any,
             cond_all,
             class,
             title,
+            class_regex,
+            title_regex,
             tag,
-        }
+        })
     }
 }
 
@@ -820,6 +1274,9 @@ impl From<WindowRule> for crate::window::rules::WindowRule {
             })
         });
         let location = rule.x.and_then(|x| rule.y.map(|y| (x, y)));
+        let fullscreen_output = rule.fullscreen_output.map(OutputName);
+        let opacity = rule.opacity.filter(|opacity| !opacity.is_nan());
+        let maximize_covers_exclusive_zones = rule.maximize_covers_exclusive_zones;
 
         crate::window::rules::WindowRule {
             output,
@@ -828,6 +1285,88 @@ impl From<WindowRule> for crate::window::rules::WindowRule {
             fullscreen_or_maximized,
             size,
             location,
+            fullscreen_output,
+            opacity,
+            maximize_covers_exclusive_zones,
         }
     }
 }
+
+/// Get the properties of `window`, or empty properties if it doesn't exist.
+fn window_properties(
+    pinnacle: &Pinnacle,
+    window: Option<&WindowElement>,
+) -> window::v0alpha1::GetPropertiesResponse {
+    let width = window.map(|win| win.geometry().size.w);
+
+    let height = window.map(|win| win.geometry().size.h);
+
+    let x = window
+        .and_then(|win| pinnacle.space.element_location(win))
+        .map(|loc| loc.x);
+
+    let y = window
+        .and_then(|win| pinnacle.space.element_location(win))
+        .map(|loc| loc.y);
+
+    let geometry = if width.is_none() && height.is_none() && x.is_none() && y.is_none() {
+        None
+    } else {
+        Some(Geometry {
+            x,
+            y,
+            width,
+            height,
+        })
+    };
+
+    let class = window.and_then(|win| win.class());
+    let title = window.and_then(|win| win.title());
+
+    let focused = window.and_then(|win| {
+        pinnacle
+            .focused_output()
+            .and_then(|output| pinnacle.focused_window(output))
+            .map(|foc_win| win == foc_win)
+    });
+
+    let floating = window.map(|win| win.with_state(|state| state.floating_or_tiled.is_floating()));
+
+    let fullscreen_or_maximized = window
+        .map(|win| win.with_state(|state| state.fullscreen_or_maximized))
+        .map(|fs_or_max| match fs_or_max {
+            // TODO: from impl
+            crate::window::window_state::FullscreenOrMaximized::Neither => {
+                FullscreenOrMaximized::Neither
+            }
+            crate::window::window_state::FullscreenOrMaximized::Fullscreen => {
+                FullscreenOrMaximized::Fullscreen
+            }
+            crate::window::window_state::FullscreenOrMaximized::Maximized => {
+                FullscreenOrMaximized::Maximized
+            }
+        } as i32);
+
+    let tag_ids = window
+        .map(|win| {
+            win.with_state(|state| state.tags.iter().map(|tag| tag.id().0).collect::<Vec<_>>())
+        })
+        .unwrap_or_default();
+
+    let x11_override_redirect = window.map(|win| win.is_x11_override_redirect());
+    let scratchpad = window.map(|win| win.with_state(|state| state.scratchpad));
+    let minimized = window.map(|win| win.with_state(|state| state.minimized));
+
+    window::v0alpha1::GetPropertiesResponse {
+        geometry,
+        class,
+        title,
+        focused,
+        floating,
+        fullscreen_or_maximized,
+        tag_ids,
+        x11_override_redirect,
+        scratchpad,
+        minimized,
+    }
+}
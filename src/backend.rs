@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use smithay::{
     backend::{
@@ -11,7 +11,7 @@ use smithay::{
                 RenderElementStates,
             },
             gles::GlesRenderer,
-            ImportDma, Renderer, TextureFilter,
+            ImportDma, TextureFilter,
         },
     },
     delegate_dmabuf,
@@ -25,13 +25,13 @@ use smithay::{
     },
     input::pointer::CursorImageStatus,
     output::Output,
-    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    reexports::{calloop::LoopHandle, wayland_server::protocol::wl_surface::WlSurface},
     wayland::{
+        compositor::{with_surface_tree_downward, TraversalAction},
         dmabuf::{DmabufGlobal, DmabufHandler, DmabufState, ImportNotifier},
         fractional_scale::with_fractional_scale,
     },
 };
-use tracing::error;
 
 use crate::{
     state::{Pinnacle, State, SurfaceDmabufFeedback, WithState},
@@ -56,6 +56,34 @@ pub enum Backend {
     Dummy(Dummy),
 }
 
+/// Counters for diagnosing render scheduling on an output.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RenderStats {
+    /// How many times a render was requested.
+    ///
+    /// Requests made while a render is already pending are coalesced,
+    /// so this being much higher than `renders` points to over-scheduling.
+    pub render_requests: u64,
+    /// How many renders actually ran.
+    pub renders: u64,
+    /// How many renders produced damage and queued a frame.
+    pub frames_queued: u64,
+    /// How many queued frames weren't presented by the vblank after they were queued.
+    ///
+    /// This is always 0 on the winit backend.
+    pub vblank_misses: u64,
+    /// The total time spent rendering.
+    pub render_time: Duration,
+    /// How long the last render took.
+    pub last_render_time: Duration,
+    /// Whether the last render produced damage.
+    pub last_render_damaged: bool,
+    /// When the last vblank happened.
+    ///
+    /// On the winit backend this is when the last frame was submitted.
+    pub last_vblank: Option<Instant>,
+}
+
 pub(crate) struct UninitBackend<B> {
     pub(crate) seat_name: String,
     #[allow(clippy::complexity)]
@@ -65,11 +93,7 @@ pub(crate) struct UninitBackend<B> {
 impl Backend {
     pub fn set_upscale_filter(&mut self, filter: TextureFilter) {
         match self {
-            Backend::Winit(winit) => {
-                if let Err(err) = winit.backend.renderer().upscale_filter(filter) {
-                    error!("Failed to set winit upscale filter: {err}");
-                }
-            }
+            Backend::Winit(winit) => winit.upscale_filter = filter,
             Backend::Udev(udev) => udev.upscale_filter = filter,
             #[cfg(feature = "testing")]
             Backend::Dummy(_) => (),
@@ -78,17 +102,92 @@ impl Backend {
 
     pub fn set_downscale_filter(&mut self, filter: TextureFilter) {
         match self {
-            Backend::Winit(winit) => {
-                if let Err(err) = winit.backend.renderer().downscale_filter(filter) {
-                    error!("Failed to set winit upscale filter: {err}");
-                }
-            }
+            Backend::Winit(winit) => winit.downscale_filter = filter,
             Backend::Udev(udev) => udev.downscale_filter = filter,
             #[cfg(feature = "testing")]
             Backend::Dummy(_) => (),
         }
     }
 
+    /// Turn the given output on or off.
+    ///
+    /// This does nothing on the winit backend.
+    pub fn set_output_powered(
+        &mut self,
+        loop_handle: &LoopHandle<State>,
+        output: &Output,
+        powered: bool,
+    ) {
+        match self {
+            Backend::Winit(_) => (),
+            Backend::Udev(udev) => udev.set_output_powered(loop_handle, output, powered),
+            #[cfg(feature = "testing")]
+            Backend::Dummy(_) => (),
+        }
+    }
+
+    /// Returns whether the given output is powered on.
+    ///
+    /// Outputs are always on with the winit backend.
+    pub fn output_powered(&self, output: &Output) -> bool {
+        match self {
+            Backend::Winit(_) => true,
+            Backend::Udev(udev) => udev.output_powered(output),
+            #[cfg(feature = "testing")]
+            Backend::Dummy(_) => true,
+        }
+    }
+
+    /// Tint the given output to the given color temperature in Kelvin.
+    ///
+    /// Errors on the winit backend, as it can't change gamma.
+    pub fn set_output_color_temperature(
+        &mut self,
+        output: &Output,
+        kelvin: u16,
+    ) -> anyhow::Result<()> {
+        match self {
+            Backend::Winit(_) => {
+                anyhow::bail!("setting color temperature is not supported on the winit backend")
+            }
+            Backend::Udev(udev) => udev.set_color_temperature(output, kelvin),
+            #[cfg(feature = "testing")]
+            Backend::Dummy(_) => Ok(()),
+        }
+    }
+
+    /// Enable or disable variable refresh rate on the given output.
+    ///
+    /// This does nothing on the winit backend.
+    pub fn set_output_vrr(&mut self, output: &Output, vrr: bool) -> anyhow::Result<()> {
+        match self {
+            Backend::Winit(_) => Ok(()),
+            Backend::Udev(udev) => udev.set_vrr(output, vrr),
+            #[cfg(feature = "testing")]
+            Backend::Dummy(_) => Ok(()),
+        }
+    }
+
+    /// Returns whether variable refresh rate is enabled on the given output.
+    pub fn output_vrr(&self, output: &Output) -> bool {
+        match self {
+            Backend::Winit(_) => false,
+            Backend::Udev(udev) => udev.vrr(output),
+            #[cfg(feature = "testing")]
+            Backend::Dummy(_) => false,
+        }
+    }
+
+    /// Get the [`RenderStats`] for the given output, if the backend is rendering to it.
+    pub fn render_stats(&self, output: &Output) -> Option<RenderStats> {
+        match self {
+            Backend::Winit(winit) => (&winit.output == output).then_some(winit.render_stats),
+            Backend::Udev(udev) => udev.render_stats(output),
+            #[cfg(feature = "testing")]
+            Backend::Dummy(_) => None,
+        }
+    }
+
     pub fn seat_name(&self) -> String {
         match self {
             Backend::Winit(winit) => winit.seat_name(),
@@ -182,6 +281,7 @@ pub fn post_repaint(
     dmabuf_feedback: Option<SurfaceDmabufFeedback<'_>>,
     time: Duration,
     cursor_status: &CursorImageStatus,
+    dnd_icon: Option<&WlSurface>,
 ) {
     // let throttle = Some(Duration::from_secs(1));
     let throttle = Some(Duration::ZERO);
@@ -262,6 +362,39 @@ pub fn post_repaint(
         send_frames_surface_tree(surf, output, time, Some(Duration::ZERO), |_, _| None);
     }
 
+    if let Some(dnd_icon) = dnd_icon {
+        with_surface_tree_downward(
+            dnd_icon,
+            (),
+            |_, _, _| TraversalAction::DoChildren(()),
+            |surface, states, _| {
+                let primary_scanout_output = update_surface_primary_scanout_output(
+                    surface,
+                    output,
+                    states,
+                    render_element_states,
+                    default_primary_scanout_output_compare,
+                );
+
+                if let Some(output) = primary_scanout_output {
+                    with_fractional_scale(states, |fraction_scale| {
+                        fraction_scale
+                            .set_preferred_scale(output.current_scale().fractional_scale());
+                    });
+                }
+            },
+            |_, _, _| true,
+        );
+
+        send_frames_surface_tree(
+            dnd_icon,
+            output,
+            time,
+            Some(Duration::ZERO),
+            surface_primary_scanout_output,
+        );
+    }
+
     if let Some(lock_surface) = output.with_state(|state| state.lock_surface.clone()) {
         send_frames_surface_tree(
             lock_surface.wl_surface(),
@@ -130,6 +130,7 @@ impl Pinnacle {
         output.create_global::<State>(&self.display_handle);
 
         self.space.map_output(&output, (0, 0));
+        self.output_management_manager_state.schedule_refresh();
 
         self.signal_state.output_connect.signal(|buf| {
             buf.push_back(OutputConnectResponse {
@@ -140,6 +141,8 @@ impl Pinnacle {
 
     pub fn remove_output(&mut self, output: &Output) {
         self.space.unmap_output(output);
+        self.output_management_manager_state.schedule_refresh();
+        self.keep_floating_windows_in_bounds();
 
         self.signal_state.output_disconnect.signal(|buffer| {
             buffer.push_back(OutputDisconnectResponse {
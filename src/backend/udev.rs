@@ -2,14 +2,15 @@
 
 mod drm_util;
 mod gamma;
+mod vrr;
 
 use std::{
     collections::{HashMap, HashSet},
-    path::Path,
-    time::Duration,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
-use anyhow::{anyhow, ensure, Context};
+use anyhow::{anyhow, bail, ensure, Context};
 use pinnacle_api_defs::pinnacle::signal::v0alpha1::{
     OutputConnectResponse, OutputDisconnectResponse,
 };
@@ -54,15 +55,17 @@ use smithay::{
         layer_map_for_output,
         utils::{send_frames_surface_tree, OutputPresentationFeedback},
     },
-    input::pointer::CursorImageStatus,
+    input::pointer::{CursorIcon, CursorImageStatus},
     output::{Output, PhysicalProperties, Subpixel},
     reexports::{
         ash::vk::ExtPhysicalDeviceDrmFn,
         calloop::{
-            self, generic::Generic, Dispatcher, Idle, Interest, LoopHandle, PostAction,
-            RegistrationToken,
+            self,
+            generic::Generic,
+            timer::{TimeoutAction, Timer},
+            Dispatcher, Idle, Interest, LoopHandle, PostAction, RegistrationToken,
         },
-        drm::control::{connector, crtc, ModeTypeFlags},
+        drm::control::{self, connector, crtc, ModeTypeFlags},
         input::Libinput,
         rustix::fs::OFlags,
         wayland_protocols::wp::{
@@ -87,7 +90,7 @@ use tracing::{debug, error, info, trace, warn};
 use crate::{
     backend::Backend,
     config::ConnectorSavedState,
-    output::{BlankingState, OutputName},
+    output::{BlankingState, OutputName, VblankMissPolicy},
     render::{
         pointer::PointerElement, pointer_render_elements, take_presentation_feedback,
         OutputRenderElement, CLEAR_COLOR, CLEAR_COLOR_LOCKED,
@@ -97,7 +100,7 @@ use crate::{
 
 use self::drm_util::EdidInfo;
 
-use super::{BackendData, UninitBackend};
+use super::{BackendData, RenderStats, UninitBackend};
 
 const SUPPORTED_FORMATS: &[Fourcc] = &[
     Fourcc::Abgr2101010,
@@ -118,6 +121,12 @@ pub type UdevRenderer<'a> = MultiRenderer<
 type UdevRenderFrameResult<'a> =
     RenderFrameResult<'a, GbmBuffer, GbmFramebuffer, OutputRenderElement<UdevRenderer<'a>>>;
 
+/// The environment variable used to pin outputs to render nodes.
+///
+/// This is a comma-separated list of `<output name>=<render node path>`,
+/// for example `eDP-1=/dev/dri/renderD128,HDMI-A-1=/dev/dri/renderD129`.
+pub const RENDER_NODES_ENV: &str = "PINNACLE_RENDER_NODES";
+
 /// Udev state attached to each [`Output`].
 #[derive(Debug, PartialEq)]
 struct UdevOutputData {
@@ -134,10 +143,15 @@ pub struct Udev {
     display_handle: DisplayHandle,
     pub(super) dmabuf_state: Option<(DmabufState, DmabufGlobal)>,
     pub(super) primary_gpu: DrmNode,
+    /// Render nodes that outputs with the given names are pinned to.
+    ///
+    /// Outputs without an entry here are rendered by the primary GPU.
+    pinned_render_nodes: HashMap<OutputName, PathBuf>,
     allocator: Option<Box<dyn Allocator<Buffer = Dmabuf, Error = AnyError>>>,
     pub(super) gpu_manager: GpuManager<GbmGlesBackend<GlesRenderer, DrmDeviceFd>>,
     backends: HashMap<DrmNode, UdevBackendData>,
-    pointer_images: Vec<(xcursor::parser::Image, TextureBuffer<MultiTexture>)>,
+    /// Cached cursor textures along with the buffer scale they were imported with.
+    pointer_images: Vec<(xcursor::parser::Image, i32, TextureBuffer<MultiTexture>)>,
     pointer_element: PointerElement<MultiTexture>,
     pointer_image: crate::cursor::Cursor,
 
@@ -181,6 +195,10 @@ impl Udev {
             });
         info!("Using {} as primary gpu.", primary_gpu);
 
+        let pinned_render_nodes = std::env::var(RENDER_NODES_ENV)
+            .map(|value| drm_util::parse_pinned_render_nodes(&value))
+            .unwrap_or_default();
+
         let gpu_manager = GpuManager::new(GbmGlesBackend::default())?;
         // let gpu_manager = GpuManager::new(GbmGlesBackend::with_factory(|egl| {
         //     let ctx = EGLContext::new(egl)?;
@@ -225,6 +243,7 @@ impl Udev {
             dmabuf_state: None,
             session,
             primary_gpu,
+            pinned_render_nodes,
             gpu_manager,
             allocator: None,
             backends: HashMap::new(),
@@ -397,6 +416,16 @@ impl Udev {
                                                 }
                                             }
                                         }
+
+                                        // Reapply VRR, which is reset by other DRM masters
+                                        if surface.vrr {
+                                            if let Err(err) =
+                                                drm_util::set_vrr_enabled(&backend.drm, *crtc, true)
+                                            {
+                                                warn!("Failed to restore VRR: {err}");
+                                                surface.vrr = false;
+                                            }
+                                        }
                                     }
                                 }
 
@@ -509,7 +538,7 @@ impl Udev {
                         surface_data.dmabuf_feedback =
                             surface_data.dmabuf_feedback.take().or_else(|| {
                                 get_surface_dmabuf_feedback(
-                                    primary_gpu,
+                                    surface_data.render_gpu,
                                     surface_data.render_node,
                                     gpu_manager,
                                     &surface_data.compositor,
@@ -523,12 +552,79 @@ impl Udev {
         })
     }
 
+    /// Get the [`RenderStats`] for the surface rendering to the given output.
+    pub fn render_stats(&self, output: &Output) -> Option<RenderStats> {
+        let UdevOutputData { device_id, crtc } = output.user_data().get()?;
+
+        self.backends
+            .get(device_id)
+            .and_then(|device| device.surfaces.get(crtc))
+            .map(|surface| surface.render_stats)
+    }
+
+    /// Turn the given output on or off.
+    ///
+    /// Nothing is rendered to the output while it's off.
+    /// Turning it back on schedules a render so it shows something again.
+    pub fn set_output_powered(
+        &mut self,
+        loop_handle: &LoopHandle<State>,
+        output: &Output,
+        powered: bool,
+    ) {
+        let Some(surface) = render_surface_for_output(output, &mut self.backends) else {
+            return;
+        };
+
+        if surface.powered == powered {
+            return;
+        }
+
+        surface.powered = powered;
+
+        if powered {
+            if let Err(err) = surface.compositor.reset_state() {
+                warn!("Failed to reset state for {}: {err}", output.name());
+            }
+            self.schedule_render(loop_handle, output);
+        } else {
+            match std::mem::replace(&mut surface.render_state, RenderState::Idle) {
+                RenderState::Scheduled(idle) => idle.cancel(),
+                RenderState::Delayed(token) => loop_handle.remove(token),
+                RenderState::Idle | RenderState::WaitingForVblank { .. } => (),
+            }
+            surface.frame_queued_at = None;
+
+            if let Err(err) = surface.compositor.clear() {
+                warn!("Failed to power off {}: {err}", output.name());
+            }
+        }
+    }
+
+    /// Returns whether the given output is powered on.
+    pub fn output_powered(&self, output: &Output) -> bool {
+        let Some(UdevOutputData { device_id, crtc }) = output.user_data().get() else {
+            return false;
+        };
+
+        self.backends
+            .get(device_id)
+            .and_then(|device| device.surfaces.get(crtc))
+            .is_some_and(|surface| surface.powered)
+    }
+
     /// Schedule a new render that will cause the compositor to redraw everything.
     pub fn schedule_render(&mut self, loop_handle: &LoopHandle<State>, output: &Output) {
         let Some(surface) = render_surface_for_output(output, &mut self.backends) else {
             return;
         };
 
+        if !surface.powered {
+            return;
+        }
+
+        surface.render_stats.render_requests += 1;
+
         match &surface.render_state {
             RenderState::Idle => {
                 let output = output.clone();
@@ -541,7 +637,7 @@ impl Udev {
 
                 surface.render_state = RenderState::Scheduled(token);
             }
-            RenderState::Scheduled(_) => (),
+            RenderState::Scheduled(_) | RenderState::Delayed(_) => (),
             RenderState::WaitingForVblank { dirty: _ } => {
                 surface.render_state = RenderState::WaitingForVblank { dirty: true }
             }
@@ -609,11 +705,158 @@ impl State {
 
     /// Resize the output with the given mode.
     ///
+    /// On udev, the mode must be one of the connector's modes.
+    /// Returns an error without changing the output if it isn't or the DRM device rejects it.
+    ///
     /// TODO: This is in udev.rs but is also used in winit.rs.
     /// |     I've got no clue how to make things public without making a mess.
-    pub fn resize_output(&mut self, output: &Output, mode: smithay::output::Mode) {
+    pub fn resize_output(
+        &mut self,
+        output: &Output,
+        mode: smithay::output::Mode,
+    ) -> anyhow::Result<()> {
         if let Backend::Udev(udev) = &mut self.backend {
-            let drm_mode = udev.backends.iter().find_map(|(_, backend)| {
+            let drm_mode = udev
+                .connector_modes(output)
+                .into_iter()
+                .find(|m| smithay::output::Mode::from(*m) == mode)
+                .with_context(|| format!("output {} doesn't support {mode:?}", output.name()))?;
+
+            let render_surface = render_surface_for_output(output, &mut udev.backends)
+                .context("output has no render surface")?;
+
+            render_surface
+                .compositor
+                .use_mode(drm_mode)
+                .map_err(|err| anyhow!("failed to resize output {}: {err}", output.name()))?;
+        }
+
+        self.pinnacle
+            .change_output_state(output, Some(mode), None, None, None);
+
+        self.pinnacle.request_layout(output);
+        self.schedule_render(output);
+
+        Ok(())
+    }
+
+    /// Set the output to a mode it may not list.
+    ///
+    /// On udev, the connector's mode is used if it lists one with the given size and refresh
+    /// rate, otherwise one is generated with CVT. The refresh rate defaults to 60Hz.
+    ///
+    /// Returns an error without changing the output if the DRM device rejects the mode.
+    pub fn set_custom_output_mode(
+        &mut self,
+        output: &Output,
+        width: u16,
+        height: u16,
+        refresh_mhz: Option<u32>,
+    ) -> anyhow::Result<()> {
+        ensure!(width > 0 && height > 0, "mode size must be positive");
+
+        let refresh_mhz = refresh_mhz.unwrap_or(60_000);
+
+        let mode = if let Backend::Udev(udev) = &mut self.backend {
+            let listed_mode = udev.connector_modes(output).into_iter().find(|mode| {
+                let mode = smithay::output::Mode::from(*mode);
+                mode.size == (width as i32, height as i32).into()
+                    && mode.refresh == refresh_mhz as i32
+            });
+            let drm_mode = match listed_mode {
+                Some(mode) => mode,
+                None => drm_util::create_drm_mode(width, height, refresh_mhz)?,
+            };
+
+            let render_surface = render_surface_for_output(output, &mut udev.backends)
+                .context("output has no render surface")?;
+
+            render_surface
+                .compositor
+                .use_mode(drm_mode)
+                .map_err(|err| {
+                    anyhow!(
+                        "the DRM device rejected mode {width}x{height}@{}mHz: {err}",
+                        smithay::output::Mode::from(drm_mode).refresh
+                    )
+                })?;
+
+            smithay::output::Mode::from(drm_mode)
+        } else {
+            smithay::output::Mode {
+                size: (width as i32, height as i32).into(),
+                refresh: refresh_mhz as i32,
+            }
+        };
+
+        self.pinnacle
+            .change_output_state(output, Some(mode), None, None, None);
+
+        self.pinnacle.request_layout(output);
+        self.schedule_render(output);
+
+        Ok(())
+    }
+
+    /// Pin rendering for `output` to the render node at `path`, or unpin it
+    /// if `None`.
+    ///
+    /// If the node is unavailable, the output is rendered by the primary GPU.
+    /// The pin is kept for the output's name, so it also applies when it reconnects.
+    ///
+    /// Returns an error when not running on udev.
+    pub fn set_output_render_node(
+        &mut self,
+        output: &Output,
+        path: Option<PathBuf>,
+    ) -> anyhow::Result<()> {
+        let Backend::Udev(udev) = &mut self.backend else {
+            bail!("render nodes can only be selected on the udev backend");
+        };
+
+        let name = OutputName(output.name());
+        match path {
+            Some(path) => {
+                udev.pinned_render_nodes.insert(name.clone(), path);
+            }
+            None => {
+                udev.pinned_render_nodes.remove(&name);
+            }
+        }
+
+        let render_gpu = resolve_render_gpu(
+            &mut udev.gpu_manager,
+            udev.primary_gpu,
+            udev.pinned_render_nodes.get(&name),
+        );
+
+        let render_surface = render_surface_for_output(output, &mut udev.backends)
+            .context("output has no render surface")?;
+
+        if render_surface.render_gpu != render_gpu {
+            info!("Rendering {} with {render_gpu}", output.name());
+            render_surface.render_gpu = render_gpu;
+            render_surface.dmabuf_feedback = get_surface_dmabuf_feedback(
+                render_gpu,
+                render_surface.render_node,
+                &mut udev.gpu_manager,
+                &render_surface.compositor,
+            );
+            render_surface.compositor.reset_buffers();
+        }
+
+        self.schedule_render(output);
+
+        Ok(())
+    }
+}
+
+impl Udev {
+    /// Get the modes the connector driving `output` lists.
+    fn connector_modes(&self, output: &Output) -> Vec<control::Mode> {
+        self.backends
+            .values()
+            .find_map(|backend| {
                 backend
                     .drm_scanner
                     .crtcs()
@@ -623,33 +866,9 @@ impl State {
                             .get::<UdevOutputData>()
                             .is_some_and(|data| &data.crtc == handle)
                     })
-                    .and_then(|(info, _)| {
-                        info.modes()
-                            .iter()
-                            .find(|m| smithay::output::Mode::from(**m) == mode)
-                    })
-                    .copied()
-            });
-
-            if let Some(drm_mode) = drm_mode {
-                if let Some(render_surface) = render_surface_for_output(output, &mut udev.backends)
-                {
-                    match render_surface.compositor.use_mode(drm_mode) {
-                        Ok(()) => {
-                            self.pinnacle
-                                .change_output_state(output, Some(mode), None, None, None);
-                        }
-                        Err(err) => error!("Failed to resize output: {err}"),
-                    }
-                }
-            }
-        } else {
-            self.pinnacle
-                .change_output_state(output, Some(mode), None, None, None);
-        }
-
-        self.pinnacle.request_layout(output);
-        self.schedule_render(output);
+                    .map(|(info, _)| info.modes().to_vec())
+            })
+            .unwrap_or_default()
     }
 }
 
@@ -780,6 +999,13 @@ enum RenderState {
         #[allow(dead_code)] // TODO:
         Idle<'static>,
     ),
+    /// A render has been delayed until right before the next vblank
+    /// because the last frame missed its vblank.
+    Delayed(
+        /// The timer's token.
+        #[allow(dead_code)]
+        RegistrationToken,
+    ),
     /// A frame was rendered and scheduled and we are waiting for vblank.
     WaitingForVblank {
         /// A render was scheduled while waiting for vblank.
@@ -801,14 +1027,26 @@ struct RenderSurface {
     /// If this is equal to the primary gpu node then it does the rendering operations.
     /// If it's not it is the node the composited buffer ends up on.
     render_node: DrmNode,
+    /// The GPU that renders this output.
+    ///
+    /// This is the primary GPU unless the output is pinned to another render node.
+    render_gpu: DrmNode,
     /// The thing rendering elements and queueing frames.
     compositor: GbmDrmCompositor,
     dmabuf_feedback: Option<DrmSurfaceDmabufFeedback>,
     render_state: RenderState,
     screencopy_commit_state: ScreencopyCommitState,
+    render_stats: RenderStats,
+    /// When the frame currently waiting for vblank was queued.
+    frame_queued_at: Option<Instant>,
+    /// Whether the output is powered on. Nothing is rendered while it's off.
+    powered: bool,
 
     previous_gamma: Option<[Box<[u16]>; 3]>,
     pending_gamma_change: PendingGammaChange,
+
+    /// Whether variable refresh rate is enabled
+    vrr: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -951,6 +1189,19 @@ impl Udev {
             return;
         };
 
+        let output_name = format!(
+            "{}-{}",
+            connector.interface().as_str(),
+            connector.interface_id()
+        );
+
+        let render_gpu = resolve_render_gpu(
+            &mut self.gpu_manager,
+            self.primary_gpu,
+            self.pinned_render_nodes
+                .get(&OutputName(output_name.clone())),
+        );
+
         let mut renderer = self
             .gpu_manager
             .single_renderer(&device.render_node)
@@ -968,13 +1219,16 @@ impl Udev {
             connector.interface_id(),
         );
 
-        let mode_id = connector
-            .modes()
-            .iter()
-            .position(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
-            .unwrap_or(0);
+        let saved_mode = pinnacle
+            .config
+            .connector_saved_states
+            .get(&OutputName(output_name.clone()))
+            .and_then(|saved_state| saved_state.mode);
 
-        let drm_mode = connector.modes()[mode_id];
+        let Some(drm_mode) = select_mode(connector.modes(), saved_mode) else {
+            warn!("Connector {output_name} has no modes");
+            return;
+        };
         let wl_mode = smithay::output::Mode::from(drm_mode);
 
         let surface = match device
@@ -988,12 +1242,6 @@ impl Udev {
             }
         };
 
-        let output_name = format!(
-            "{}-{}",
-            connector.interface().as_str(),
-            connector.interface_id()
-        );
-
         let (make, model, serial) = EdidInfo::try_from_connector(&device.drm, connector.handle())
             .map(|info| (info.manufacturer, info.model, info.serial))
             .unwrap_or_else(|err| {
@@ -1001,6 +1249,11 @@ impl Udev {
                 ("Unknown".into(), "Unknown".into(), None)
             });
 
+        let edid = drm_util::get_edid(&device.drm, connector.handle()).unwrap_or_else(|err| {
+            warn!("Failed to get EDID: {err}");
+            None
+        });
+
         let (phys_w, phys_h) = connector.size().unwrap_or((0, 0));
 
         if pinnacle.space.outputs().any(|op| {
@@ -1022,17 +1275,33 @@ impl Udev {
         );
         let global = output.create_global::<State>(&self.display_handle);
 
-        output.with_state_mut(|state| state.serial = serial);
+        output.with_state_mut(|state| {
+            state.serial = serial;
+            state.edid = edid;
+        });
 
-        output.set_preferred(wl_mode);
+        let preferred_mode = connector
+            .modes()
+            .iter()
+            .find(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
+            .map(|mode| smithay::output::Mode::from(*mode))
+            .unwrap_or(wl_mode);
+        output.set_preferred(preferred_mode);
 
-        let modes = connector
+        let mut modes = Vec::<smithay::output::Mode>::new();
+        for mode in connector
             .modes()
             .iter()
             .cloned()
             .map(smithay::output::Mode::from)
-            .collect::<Vec<_>>();
+        {
+            // Modes that only differ in flags like interlacing look the same to clients
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
         output.with_state_mut(|state| state.modes = modes);
+        pinnacle.output_management_manager_state.schedule_refresh();
 
         let x = pinnacle.space.outputs().fold(0, |acc, o| {
             let Some(geo) = pinnacle.space.output_geometry(o) else {
@@ -1087,7 +1356,7 @@ impl Udev {
         };
 
         let dmabuf_feedback = get_surface_dmabuf_feedback(
-            self.primary_gpu,
+            render_gpu,
             device.render_node,
             &mut self.gpu_manager,
             &compositor,
@@ -1097,13 +1366,18 @@ impl Udev {
             display_handle: self.display_handle.clone(),
             device_id: node,
             render_node: device.render_node,
+            render_gpu,
             global: Some(global),
             compositor,
             dmabuf_feedback,
             render_state: RenderState::Idle,
             screencopy_commit_state: ScreencopyCommitState::default(),
+            render_stats: RenderStats::default(),
+            frame_queued_at: None,
+            powered: true,
             previous_gamma: None,
             pending_gamma_change: PendingGammaChange::Idle,
+            vrr: false,
         };
 
         device.surfaces.insert(crtc, surface);
@@ -1118,10 +1392,15 @@ impl Udev {
             .connector_saved_states
             .get(&OutputName(output.name()))
         {
-            let ConnectorSavedState { loc, tags, scale } = saved_state;
+            let ConnectorSavedState {
+                loc, tags, scale, ..
+            } = saved_state;
             output.with_state_mut(|state| state.tags.clone_from(tags));
             pinnacle.change_output_state(&output, None, None, *scale, Some(*loc));
         } else {
+            pinnacle.add_default_tags(&output);
+            pinnacle.anchor_primary_output();
+
             pinnacle.signal_state.output_connect.signal(|buffer| {
                 buffer.push_back(OutputConnectResponse {
                     output_name: Some(output.name()),
@@ -1167,6 +1446,7 @@ impl Udev {
                     loc: output.current_location(),
                     tags: output.with_state(|state| state.tags.clone()),
                     scale: Some(output.current_scale()),
+                    mode: output.current_mode(),
                 },
             );
 
@@ -1177,6 +1457,8 @@ impl Udev {
 
             pinnacle.space.unmap_output(&output);
             pinnacle.gamma_control_manager_state.output_removed(&output);
+            pinnacle.output_management_manager_state.schedule_refresh();
+            pinnacle.keep_floating_windows_in_bounds();
 
             pinnacle.signal_state.output_disconnect.signal(|buffer| {
                 buffer.push_back(OutputDisconnectResponse {
@@ -1239,6 +1521,28 @@ impl Udev {
             pinnacle.loop_handle.remove(backend_data.registration_token);
 
             tracing::debug!("Dropping device");
+
+            // Outputs pinned to this GPU can no longer render with it
+            for device in self.backends.values_mut() {
+                for surface in device.surfaces.values_mut() {
+                    if surface.render_gpu != backend_data.render_node {
+                        continue;
+                    }
+
+                    info!(
+                        "Render node {} was removed, falling back to primary gpu {}",
+                        backend_data.render_node, self.primary_gpu
+                    );
+                    surface.render_gpu = self.primary_gpu;
+                    surface.dmabuf_feedback = get_surface_dmabuf_feedback(
+                        self.primary_gpu,
+                        surface.render_node,
+                        &mut self.gpu_manager,
+                        &surface.compositor,
+                    );
+                    surface.compositor.reset_buffers();
+                }
+            }
         }
     }
 
@@ -1258,6 +1562,8 @@ impl Udev {
             return;
         };
 
+        surface.render_stats.last_vblank = Some(Instant::now());
+
         let output = if let Some(output) = pinnacle.space.outputs().find(|o| {
             let udev_op_data = o.user_data().get::<UdevOutputData>();
             udev_op_data
@@ -1323,12 +1629,47 @@ impl Udev {
         };
 
         let RenderState::WaitingForVblank { dirty } = surface.render_state else {
-            unreachable!();
+            // The output was powered off while this frame was in flight
+            return;
         };
 
+        let refresh_interval = output
+            .current_mode()
+            .map(|mode| Duration::from_secs_f64(1000f64 / mode.refresh as f64))
+            .unwrap_or_default();
+
+        let mut missed_vblank = false;
+        if let Some(queued_at) = surface.frame_queued_at.take() {
+            if queued_at.elapsed() > refresh_interval {
+                surface.render_stats.vblank_misses += 1;
+                missed_vblank = true;
+            }
+        }
+
         surface.render_state = RenderState::Idle;
 
-        if dirty {
+        // With VRR the display waits for the next frame, so there's no vblank to aim for
+        let skip = missed_vblank
+            && !surface.vrr
+            && output.with_state(|state| state.vblank_miss_policy) == VblankMissPolicy::Skip;
+
+        if dirty && skip {
+            // Rendering right away would likely miss again, so render as late as
+            // possible before the next vblank instead.
+            let delay = refresh_interval.saturating_sub(surface.render_stats.last_render_time);
+            let output = output.clone();
+            let token = pinnacle
+                .loop_handle
+                .insert_source(Timer::from_duration(delay), move |_, _, state| {
+                    state
+                        .backend
+                        .udev_mut()
+                        .render_surface(&mut state.pinnacle, &output);
+                    TimeoutAction::Drop
+                })
+                .expect("failed to insert render timer");
+            surface.render_state = RenderState::Delayed(token);
+        } else if dirty {
             self.schedule_render(&pinnacle.loop_handle, &output);
         } else {
             for window in pinnacle.windows.iter() {
@@ -1349,23 +1690,43 @@ impl Udev {
             return;
         };
 
-        assert!(matches!(surface.render_state, RenderState::Scheduled(_)));
+        assert!(matches!(
+            surface.render_state,
+            RenderState::Scheduled(_) | RenderState::Delayed(_)
+        ));
 
-        // TODO get scale from the rendersurface when supporting HiDPI
+        let render_start = Instant::now();
+
+        // Load the cursor at the nominal size times the output's scale so it stays the same
+        // logical size and isn't blurry on scaled outputs.
+        let cursor_scale = output.current_scale().integer_scale().max(1);
+        let cursor_icon = match &pinnacle.cursor_status {
+            CursorImageStatus::Named(icon) => *icon,
+            _ => CursorIcon::Default,
+        };
         let frame = self.pointer_image.get_image(
-            1,
-            // output.current_scale().integer_scale() as u32,
+            cursor_icon,
+            cursor_scale as u32,
             pinnacle.clock.now().into(),
         );
 
+        // The theme may not have an image at the requested size, so derive the buffer scale from
+        // the size of the image we actually got.
+        let buffer_scale = (frame.size as f64 / self.pointer_image.nominal_size() as f64)
+            .round()
+            .max(1.0) as i32;
+        let hotspot = Point::from((
+            frame.xhot as i32 / buffer_scale,
+            frame.yhot as i32 / buffer_scale,
+        ));
+
         let render_node = surface.render_node;
-        let primary_gpu = self.primary_gpu;
-        let mut renderer = if primary_gpu == render_node {
+        let render_gpu = surface.render_gpu;
+        let mut renderer = if render_gpu == render_node {
             self.gpu_manager.single_renderer(&render_node)
         } else {
             let format = surface.compositor.format();
-            self.gpu_manager
-                .renderer(&primary_gpu, &render_node, format)
+            self.gpu_manager.renderer(&render_gpu, &render_node, format)
         }
         .expect("failed to create MultiRenderer");
 
@@ -1375,15 +1736,13 @@ impl Udev {
         let pointer_images = &mut self.pointer_images;
         let pointer_image = pointer_images
             .iter()
-            .find_map(
-                |(image, texture)| {
-                    if image == &frame {
-                        Some(texture.clone())
-                    } else {
-                        None
-                    }
-                },
-            )
+            .find_map(|(image, scale, texture)| {
+                if image == &frame && *scale == buffer_scale {
+                    Some(texture.clone())
+                } else {
+                    None
+                }
+            })
             .unwrap_or_else(|| {
                 let texture = TextureBuffer::from_memory(
                     &mut renderer,
@@ -1391,23 +1750,20 @@ impl Udev {
                     Fourcc::Abgr8888,
                     (frame.width as i32, frame.height as i32),
                     false,
-                    1,
+                    buffer_scale,
                     Transform::Normal,
                     None,
                 )
                 .expect("Failed to import cursor bitmap");
-                pointer_images.push((frame, texture.clone()));
+                pointer_images.push((frame, buffer_scale, texture.clone()));
                 texture
             });
 
-        let pointer_location = pinnacle
-            .seat
-            .get_pointer()
-            .map(|ptr| ptr.current_location())
-            .unwrap_or((0.0, 0.0).into());
+        let pointer_location = pinnacle.cursor_render_location();
 
         // set cursor
-        self.pointer_element.set_texture(pointer_image.clone());
+        self.pointer_element
+            .set_texture(pointer_image.clone(), hotspot);
 
         // draw the cursor as relevant and
         // reset the cursor if the surface is no longer alive
@@ -1426,7 +1782,7 @@ impl Udev {
         }
 
         self.pointer_element
-            .set_status(pinnacle.cursor_status.clone());
+            .set_status(pinnacle.cursor_status_to_draw());
 
         let pending_screencopy_with_cursor =
             output.with_state(|state| state.screencopy.as_ref().map(|sc| sc.overlay_cursor()));
@@ -1525,8 +1881,22 @@ impl Udev {
             }
         });
 
+        // Keep rendering until a tag switch animation finishes
+        let tag_switch_animating = output.with_state_mut(|state| {
+            if state
+                .tag_switch_animation
+                .as_ref()
+                .is_some_and(|anim| anim.is_done())
+            {
+                state.tag_switch_animation.take();
+            }
+            state.tag_switch_animation.is_some()
+        });
+
         let clear_color = if pinnacle.lock_state.is_unlocked() {
-            CLEAR_COLOR
+            output
+                .with_state(|state| state.clear_color)
+                .unwrap_or(CLEAR_COLOR)
         } else {
             CLEAR_COLOR_LOCKED
         };
@@ -1568,6 +1938,7 @@ impl Udev {
                     }),
                 Duration::from(pinnacle.clock.now()),
                 &pinnacle.cursor_status,
+                pinnacle.dnd_icon.as_ref(),
             );
 
             let rendered = !render_frame_result.is_empty;
@@ -1588,17 +1959,85 @@ impl Udev {
             Ok(rendered)
         })();
 
+        surface.render_stats.last_render_damaged = matches!(result, Ok(true));
+
         match result {
-            Ok(true) => surface.render_state = RenderState::WaitingForVblank { dirty: false },
+            Ok(true) => {
+                surface.render_state = RenderState::WaitingForVblank { dirty: false };
+                surface.render_stats.frames_queued += 1;
+                surface.frame_queued_at = Some(Instant::now());
+            }
             Ok(false) | Err(_) => surface.render_state = RenderState::Idle,
         }
 
-        if render_after_transaction_finish {
+        surface.render_stats.last_render_time = render_start.elapsed();
+        surface.render_stats.renders += 1;
+        surface.render_stats.render_time += surface.render_stats.last_render_time;
+
+        if render_after_transaction_finish || tag_switch_animating {
             self.schedule_render(&pinnacle.loop_handle, output);
         }
     }
 }
 
+/// Get the render node at `pinned_path`, falling back to the primary GPU
+/// if there is no path or the node isn't available.
+fn resolve_render_gpu(
+    gpu_manager: &mut GpuManager<GbmGlesBackend<GlesRenderer, DrmDeviceFd>>,
+    primary_gpu: DrmNode,
+    pinned_path: Option<&PathBuf>,
+) -> DrmNode {
+    let Some(path) = pinned_path else {
+        return primary_gpu;
+    };
+
+    let node = DrmNode::from_path(path)
+        .ok()
+        .and_then(|node| node.node_with_type(NodeType::Render)?.ok())
+        .filter(|node| gpu_manager.single_renderer(node).is_ok());
+
+    match node {
+        Some(node) => node,
+        None => {
+            warn!(
+                "Render node {} is unavailable, falling back to primary gpu {primary_gpu}",
+                path.display()
+            );
+            primary_gpu
+        }
+    }
+}
+
+/// Choose the mode a newly connected connector should start with.
+///
+/// This is the saved mode if the connector was connected before and still supports it,
+/// otherwise the preferred mode. If no mode is marked as preferred, the one with the
+/// highest resolution and refresh rate is used, as the first mode is sometimes just a
+/// low-resolution fallback.
+fn select_mode(
+    modes: &[control::Mode],
+    saved_mode: Option<smithay::output::Mode>,
+) -> Option<control::Mode> {
+    saved_mode
+        .and_then(|saved_mode| {
+            modes
+                .iter()
+                .find(|mode| smithay::output::Mode::from(**mode) == saved_mode)
+        })
+        .or_else(|| {
+            modes
+                .iter()
+                .find(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
+        })
+        .or_else(|| {
+            modes.iter().max_by_key(|mode| {
+                let mode = smithay::output::Mode::from(**mode);
+                (mode.size.w * mode.size.h, mode.refresh)
+            })
+        })
+        .copied()
+}
+
 fn render_surface_for_output<'a>(
     output: &Output,
     backends: &'a mut HashMap<DrmNode, UdevBackendData>,
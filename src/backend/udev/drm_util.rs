@@ -1,6 +1,10 @@
-use std::num::NonZeroU32;
+use std::{collections::HashMap, num::NonZeroU32, path::PathBuf};
 
-use smithay::reexports::drm::control::{connector, property, Device, ResourceHandle};
+use anyhow::Context;
+
+use smithay::reexports::drm::control::{self, connector, crtc, property, Device, ResourceHandle};
+
+use crate::output::OutputName;
 
 // A bunch of this stuff is from cosmic-comp
 
@@ -16,26 +20,36 @@ impl EdidInfo {
         device: &impl Device,
         connector: connector::Handle,
     ) -> anyhow::Result<Self> {
-        let edid_prop = get_prop(device, connector, "EDID")?;
-        let edid_info = device.get_property(edid_prop)?;
+        let edid = get_edid(device, connector)?.ok_or_else(|| anyhow::anyhow!("No info"))?;
+        parse_edid(&edid)
+    }
+}
 
-        let mut info = Err(anyhow::anyhow!("No info"));
+/// Get the raw EDID blob of the given connector.
+///
+/// Returns `None` if the connector doesn't provide one.
+pub fn get_edid(
+    device: &impl Device,
+    connector: connector::Handle,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let edid_prop = get_prop(device, connector, "EDID")?;
+    let edid_info = device.get_property(edid_prop)?;
 
-        let props = device.get_properties(connector)?;
-        let (ids, vals) = props.as_props_and_values();
-        for (&id, &val) in ids.iter().zip(vals.iter()) {
-            if id == edid_prop {
-                if let property::Value::Blob(edid_blob) = edid_info.value_type().convert_value(val)
-                {
-                    let blob = device.get_property_blob(edid_blob)?;
-                    info = parse_edid(&blob);
+    let props = device.get_properties(connector)?;
+    let (ids, vals) = props.as_props_and_values();
+    for (&id, &val) in ids.iter().zip(vals.iter()) {
+        if id == edid_prop {
+            if let property::Value::Blob(edid_blob) = edid_info.value_type().convert_value(val) {
+                // A blob id of 0 means there is no EDID
+                if edid_blob != 0 {
+                    return Ok(Some(device.get_property_blob(edid_blob)?));
                 }
-                break;
             }
+            break;
         }
-
-        info
     }
+
+    Ok(None)
 }
 
 /// Minimally parse the model and manufacturer from the given EDID data buffer.
@@ -125,6 +139,138 @@ fn get_prop(
     anyhow::bail!("No prop found for {}", name)
 }
 
+/// Generate a mode for the given size and refresh rate using the VESA Coordinated Video
+/// Timings formula, the same way the `cvt` utility does without reduced blanking.
+///
+/// This allows setting modes that a connector doesn't list, for example when a display's
+/// EDID is wrong.
+///
+/// Returns an error if the refresh rate is 0 or the timings don't fit in a DRM mode.
+pub fn create_drm_mode(width: u16, height: u16, refresh_mhz: u32) -> anyhow::Result<control::Mode> {
+    const H_GRANULARITY: u32 = 8;
+    const MIN_V_PORCH: u32 = 3;
+    const MIN_V_BPORCH: u32 = 6;
+    const MIN_VSYNC_BP: f64 = 550.0;
+    const HSYNC_PERCENTAGE: u32 = 8;
+    const CLOCK_STEP: u32 = 250;
+    // (C - J) * K / 256 + J and M * K / 256 with the default blanking formula parameters
+    const C_PRIME: f64 = 30.0;
+    const M_PRIME: f64 = 300.0;
+
+    anyhow::ensure!(refresh_mhz > 0, "refresh rate must be positive");
+
+    let vrefresh = refresh_mhz as f64 / 1000.0;
+
+    let hdisplay = width as u32 - width as u32 % H_GRANULARITY;
+    let vdisplay = height as u32;
+
+    // The vsync width is determined by the aspect ratio
+    let vsync = match (hdisplay, vdisplay) {
+        (h, v) if v % 3 == 0 && v * 4 / 3 == h => 4,
+        (h, v) if v % 9 == 0 && v * 16 / 9 == h => 5,
+        (h, v) if v % 10 == 0 && v * 16 / 10 == h => 6,
+        (h, v) if v % 4 == 0 && v * 5 / 4 == h => 7,
+        (h, v) if v % 9 == 0 && v * 15 / 9 == h => 7,
+        _ => 10,
+    };
+
+    // Estimated horizontal period in microseconds
+    let hperiod = (1_000_000.0 / vrefresh - MIN_VSYNC_BP) / (vdisplay + MIN_V_PORCH) as f64;
+    anyhow::ensure!(
+        hperiod > 0.0,
+        "refresh rate of {refresh_mhz}mHz is too high to generate timings for"
+    );
+
+    let vsync_and_back_porch = ((MIN_VSYNC_BP / hperiod) as u32 + 1).max(vsync + MIN_V_BPORCH);
+    let vtotal = vdisplay + vsync_and_back_porch + MIN_V_PORCH;
+
+    let hblank_percentage = (C_PRIME - M_PRIME * hperiod / 1000.0).max(20.0);
+    let mut hblank = (hdisplay as f64 * hblank_percentage / (100.0 - hblank_percentage)) as u32;
+    hblank -= hblank % (2 * H_GRANULARITY);
+    let htotal = hdisplay + hblank;
+
+    let hsync_end = hdisplay + hblank / 2;
+    let mut hsync_start = hsync_end - htotal * HSYNC_PERCENTAGE / 100;
+    hsync_start += H_GRANULARITY - hsync_start % H_GRANULARITY;
+
+    let vsync_start = vdisplay + MIN_V_PORCH;
+    let vsync_end = vsync_start + vsync;
+
+    // In kHz
+    let mut clock = (htotal as f64 * 1000.0 / hperiod) as u32;
+    clock -= clock % CLOCK_STEP;
+
+    let actual_vrefresh = clock as f64 * 1000.0 / (htotal as u64 * vtotal as u64) as f64;
+
+    let mut name = [0; 32];
+    let name_str = format!("{hdisplay}x{vdisplay}");
+    for (c, byte) in name.iter_mut().zip(name_str.bytes()) {
+        *c = byte as std::ffi::c_char;
+    }
+
+    let timing = |value: u32| {
+        u16::try_from(value).with_context(|| {
+            format!("{hdisplay}x{vdisplay}@{refresh_mhz}mHz has timings that are too large")
+        })
+    };
+
+    Ok(control::Mode::from(drm_ffi::drm_mode_modeinfo {
+        clock,
+        hdisplay: timing(hdisplay)?,
+        hsync_start: timing(hsync_start)?,
+        hsync_end: timing(hsync_end)?,
+        htotal: timing(htotal)?,
+        hskew: 0,
+        vdisplay: timing(vdisplay)?,
+        vsync_start: timing(vsync_start)?,
+        vsync_end: timing(vsync_end)?,
+        vtotal: timing(vtotal)?,
+        vscan: 0,
+        vrefresh: actual_vrefresh.round() as u32,
+        flags: drm_ffi::DRM_MODE_FLAG_NHSYNC | drm_ffi::DRM_MODE_FLAG_PVSYNC,
+        type_: drm_ffi::DRM_MODE_TYPE_USERDEF,
+        name,
+    }))
+}
+
+/// Whether the display on the given connector supports variable refresh rate.
+pub fn vrr_capable(device: &impl Device, connector: connector::Handle) -> anyhow::Result<bool> {
+    let vrr_prop = get_prop(device, connector, "vrr_capable")?;
+
+    let props = device.get_properties(connector)?;
+    let (ids, vals) = props.as_props_and_values();
+    Ok(ids
+        .iter()
+        .zip(vals.iter())
+        .any(|(&id, &val)| id == vrr_prop && val == 1))
+}
+
+/// Enable or disable variable refresh rate on the given crtc.
+pub fn set_vrr_enabled(
+    device: &impl Device,
+    crtc: crtc::Handle,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    let vrr_prop = get_prop(device, crtc, "VRR_ENABLED")?;
+    device.set_property(crtc, vrr_prop, enabled as u64)?;
+    Ok(())
+}
+
+/// Parse a comma-separated list of `<output name>=<render node path>` pairs.
+///
+/// Malformed entries are skipped.
+pub fn parse_pinned_render_nodes(value: &str) -> HashMap<OutputName, PathBuf> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (name, path) = entry.split_once('=')?;
+            let (name, path) = (name.trim(), path.trim());
+            (!name.is_empty() && !path.is_empty())
+                .then(|| (OutputName(name.to_string()), PathBuf::from(path)))
+        })
+        .collect()
+}
+
 // You are damn right I copied everything from /usr/share/hwdata/pnp.ids and
 // stuck it in this function
 fn get_manufacturer(vendor: [char; 3]) -> String {
@@ -2689,3 +2835,91 @@ fn get_manufacturer(vendor: [char; 3]) -> String {
         _ => vendor.iter().collect(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinned_render_nodes_skip_malformed_entries() {
+        let nodes = parse_pinned_render_nodes(
+            "eDP-1=/dev/dri/renderD128, HDMI-A-1 = /dev/dri/renderD129,DP-1,=/dev/dri/renderD130",
+        );
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes.get(&OutputName("eDP-1".to_string())),
+            Some(&PathBuf::from("/dev/dri/renderD128"))
+        );
+        assert_eq!(
+            nodes.get(&OutputName("HDMI-A-1".to_string())),
+            Some(&PathBuf::from("/dev/dri/renderD129"))
+        );
+    }
+
+    #[test]
+    fn pinned_render_nodes_use_the_last_entry_for_an_output() {
+        assert!(parse_pinned_render_nodes("").is_empty());
+
+        let nodes = parse_pinned_render_nodes("DP-1=/dev/dri/renderD128,DP-1=/dev/dri/renderD129,");
+
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(
+            nodes.get(&OutputName("DP-1".to_string())),
+            Some(&PathBuf::from("/dev/dri/renderD129"))
+        );
+    }
+
+    #[test]
+    fn custom_modes_match_cvt() -> anyhow::Result<()> {
+        // From `cvt 1920 1080 60`, `cvt 2560 1440 60`, and `cvt 1280 1024 75`
+        let expected = [
+            (
+                (1920, 1080, 60_000),
+                173_000,
+                [1920, 2048, 2248, 2576, 1080, 1083, 1088, 1120],
+            ),
+            (
+                (2560, 1440, 60_000),
+                312_250,
+                [2560, 2752, 3024, 3488, 1440, 1443, 1448, 1493],
+            ),
+            (
+                (1280, 1024, 75_000),
+                138_750,
+                [1280, 1368, 1504, 1728, 1024, 1027, 1034, 1072],
+            ),
+        ];
+
+        for ((width, height, refresh_mhz), clock, timings) in expected {
+            let mode = create_drm_mode(width, height, refresh_mhz)?;
+            let (hdisplay, vdisplay) = mode.size();
+            let (hsync_start, hsync_end, htotal) = mode.hsync();
+            let (vsync_start, vsync_end, vtotal) = mode.vsync();
+
+            assert_eq!(mode.clock(), clock);
+            assert_eq!(
+                [
+                    hdisplay,
+                    hsync_start,
+                    hsync_end,
+                    htotal,
+                    vdisplay,
+                    vsync_start,
+                    vsync_end,
+                    vtotal
+                ],
+                timings
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn custom_modes_reject_bad_refresh_rates_and_sizes() {
+        assert!(create_drm_mode(1920, 1080, 0).is_err());
+        assert!(create_drm_mode(1920, 1080, 10_000_000).is_err());
+        assert!(create_drm_mode(u16::MAX, 1080, 60_000).is_err());
+    }
+}
@@ -100,6 +100,24 @@ impl Udev {
         Ok(())
     }
 
+    /// Tint the given output to the given color temperature in Kelvin.
+    ///
+    /// 6500 K restores the output's original gamma.
+    pub fn set_color_temperature(&mut self, output: &Output, kelvin: u16) -> anyhow::Result<()> {
+        let kelvin = kelvin.clamp(MIN_TEMPERATURE, MAX_TEMPERATURE);
+
+        if kelvin == NEUTRAL_TEMPERATURE {
+            return self.set_gamma(output, None);
+        }
+
+        let gamma_size = self.gamma_size(output)? as usize;
+        ensure!(gamma_size != 0, "setting gamma is not supported");
+
+        let [red, green, blue] = color_temperature_ramps(kelvin, gamma_size);
+
+        self.set_gamma(output, Some([&red[..], &green[..], &blue[..]]))
+    }
+
     pub fn gamma_size(&self, output: &Output) -> anyhow::Result<u32> {
         let UdevOutputData { device_id, crtc } = output
             .user_data()
@@ -116,3 +134,47 @@ impl Udev {
         Ok(crtc_info.gamma_length())
     }
 }
+
+const MIN_TEMPERATURE: u16 = 1000;
+const MAX_TEMPERATURE: u16 = 10000;
+const NEUTRAL_TEMPERATURE: u16 = 6500;
+
+/// Approximate the RGB whitepoint of a blackbody at `kelvin`, with each channel in 0.0..=1.0.
+///
+/// From https://tannerhelland.com/2012/09/18/convert-temperature-rgb-algorithm-code.html
+fn temperature_to_rgb(kelvin: u16) -> [f64; 3] {
+    let temp = kelvin as f64 / 100.0;
+
+    let red = if temp <= 66.0 {
+        255.0
+    } else {
+        329.698727446 * (temp - 60.0).powf(-0.1332047592)
+    };
+
+    let green = if temp <= 66.0 {
+        99.4708025861 * temp.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (temp - 60.0).powf(-0.0755148492)
+    };
+
+    let blue = if temp >= 66.0 {
+        255.0
+    } else if temp <= 19.0 {
+        0.0
+    } else {
+        138.5177312231 * (temp - 10.0).ln() - 305.0447927307
+    };
+
+    [red, green, blue].map(|channel| (channel / 255.0).clamp(0.0, 1.0))
+}
+
+/// Create linear gamma ramps of length `gamma_size`, scaled by the whitepoint of `kelvin`.
+fn color_temperature_ramps(kelvin: u16, gamma_size: usize) -> [Vec<u16>; 3] {
+    let denom = gamma_size.saturating_sub(1).max(1) as f64;
+
+    temperature_to_rgb(kelvin).map(|factor| {
+        (0..gamma_size)
+            .map(|i| (f64::from(u16::MAX) * i as f64 / denom * factor).round() as u16)
+            .collect()
+    })
+}
@@ -0,0 +1,62 @@
+//! Variable refresh rate.
+
+use anyhow::{ensure, Context};
+use smithay::{backend::session::Session, output::Output};
+
+use super::{drm_util, render_surface_for_output, Udev, UdevOutputData};
+
+impl Udev {
+    /// Enable or disable variable refresh rate on `output`.
+    ///
+    /// Errors if the display doesn't support it. While the session is inactive,
+    /// the change is applied when it's activated again.
+    pub fn set_vrr(&mut self, output: &Output, vrr: bool) -> anyhow::Result<()> {
+        let UdevOutputData { device_id, crtc } = output
+            .user_data()
+            .get()
+            .context("no udev output data for output")?;
+
+        let backend = self
+            .backends
+            .get(device_id)
+            .context("no udev backend data for output")?;
+
+        let connector = backend
+            .drm_scanner
+            .crtcs()
+            .find(|(_, handle)| handle == crtc)
+            .map(|(info, _)| info.handle())
+            .context("no connector for output")?;
+
+        if vrr {
+            let capable = drm_util::vrr_capable(&backend.drm, connector)?;
+            ensure!(
+                capable,
+                "{} doesn't support variable refresh rate",
+                output.name()
+            );
+        }
+
+        if self.session.is_active() {
+            drm_util::set_vrr_enabled(&backend.drm, *crtc, vrr)?;
+        }
+
+        render_surface_for_output(output, &mut self.backends)
+            .context("no render surface for output")?
+            .vrr = vrr;
+
+        Ok(())
+    }
+
+    /// Returns whether variable refresh rate is enabled on `output`.
+    pub fn vrr(&self, output: &Output) -> bool {
+        let Some(UdevOutputData { device_id, crtc }) = output.user_data().get() else {
+            return false;
+        };
+
+        self.backends
+            .get(device_id)
+            .and_then(|device| device.surfaces.get(crtc))
+            .is_some_and(|surface| surface.vrr)
+    }
+}
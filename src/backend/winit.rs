@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, ensure};
 use smithay::{
@@ -19,7 +19,12 @@ use smithay::{
     input::pointer::CursorImageStatus,
     output::{Output, Scale, Subpixel},
     reexports::{
-        calloop::{self, generic::Generic, Interest, LoopHandle, PostAction},
+        calloop::{
+            self,
+            generic::Generic,
+            timer::{TimeoutAction, Timer},
+            Interest, LoopHandle, PostAction,
+        },
         wayland_protocols::wp::presentation_time::server::wp_presentation_feedback,
         wayland_server::{
             protocol::{wl_shm, wl_surface::WlSurface},
@@ -44,7 +49,7 @@ use crate::{
     state::{Pinnacle, State, WithState},
 };
 
-use super::{Backend, BackendData, UninitBackend};
+use super::{Backend, BackendData, RenderStats, UninitBackend};
 
 const LOGO_BYTES: &[u8] = include_bytes!("../../resources/pinnacle_logo_icon.rgba");
 
@@ -54,7 +59,10 @@ pub struct Winit {
     pub dmabuf_state: (DmabufState, DmabufGlobal, Option<DmabufFeedback>),
     pub full_redraw: u8,
     output_render_scheduled: bool,
-    output: Output,
+    pub(super) upscale_filter: TextureFilter,
+    pub(super) downscale_filter: TextureFilter,
+    pub(super) render_stats: RenderStats,
+    pub(super) output: Output,
 }
 
 impl BackendData for Winit {
@@ -97,8 +105,8 @@ impl Winit {
         let physical_properties = smithay::output::PhysicalProperties {
             size: (0, 0).into(),
             subpixel: Subpixel::Unknown,
-            make: "Pinnacle".to_string(),
-            model: "Winit Window".to_string(),
+            make: "Unknown".to_string(),
+            model: "Unknown".to_string(),
         };
 
         let output = Output::new("Pinnacle Window".to_string(), physical_properties);
@@ -174,6 +182,9 @@ impl Winit {
             dmabuf_state,
             full_redraw: 0,
             output_render_scheduled: false,
+            upscale_filter: TextureFilter::Linear,
+            downscale_filter: TextureFilter::Linear,
+            render_stats: RenderStats::default(),
             output,
         };
 
@@ -200,6 +211,7 @@ impl Winit {
                                 size,
                                 refresh: 144_000,
                             };
+                            output.set_preferred(mode);
                             state.pinnacle.change_output_state(
                                 &output,
                                 Some(mode),
@@ -241,14 +253,40 @@ impl Winit {
     /// Schedule a render on the winit window.
     pub fn schedule_render(&mut self) {
         trace!("Scheduling winit render");
+        self.render_stats.render_requests += 1;
         self.output_render_scheduled = true;
     }
 
     /// Render the winit window if a render has been scheduled.
     pub fn render_if_scheduled(&mut self, pinnacle: &mut Pinnacle) {
         if self.output_render_scheduled {
+            let render_start = Instant::now();
             self.render_winit_window(pinnacle);
             self.output_render_scheduled = false;
+
+            // Keep rendering until a tag switch animation finishes
+            let tag_switch_animating = self.output.with_state_mut(|state| {
+                if state
+                    .tag_switch_animation
+                    .as_ref()
+                    .is_some_and(|anim| anim.is_done())
+                {
+                    state.tag_switch_animation.take();
+                }
+                state.tag_switch_animation.is_some()
+            });
+            if tag_switch_animating {
+                self.schedule_render();
+                // Wake the event loop up for the next frame
+                let _ = pinnacle.loop_handle.insert_source(
+                    Timer::from_duration(Duration::from_millis(16)),
+                    |_, _, _| TimeoutAction::Drop,
+                );
+            }
+
+            self.render_stats.last_render_time = render_start.elapsed();
+            self.render_stats.renders += 1;
+            self.render_stats.render_time += self.render_stats.last_render_time;
         }
     }
 
@@ -262,11 +300,12 @@ impl Winit {
             }
         }
 
-        let cursor_visible = !matches!(pinnacle.cursor_status, CursorImageStatus::Surface(_));
+        let cursor_visible = !matches!(pinnacle.cursor_status, CursorImageStatus::Surface(_))
+            && !pinnacle.cursor_hidden();
 
         let mut pointer_element = PointerElement::<GlesTexture>::new();
 
-        pointer_element.set_status(pinnacle.cursor_status.clone());
+        pointer_element.set_status(pinnacle.cursor_status_to_draw());
 
         // The z-index of these is determined by `state.fixup_z_layering()`, which is called at the end
         // of every event loop cycle
@@ -284,11 +323,7 @@ impl Winit {
             });
 
         if should_draw_cursor {
-            let pointer_location = pinnacle
-                .seat
-                .get_pointer()
-                .map(|ptr| ptr.current_location())
-                .unwrap_or((0.0, 0.0).into());
+            let pointer_location = pinnacle.cursor_render_location();
 
             let pointer_render_elements = pointer_render_elements(
                 &self.output,
@@ -362,8 +397,13 @@ impl Winit {
 
             let renderer = self.backend.renderer();
 
+            let _ = renderer.upscale_filter(self.upscale_filter);
+            let _ = renderer.downscale_filter(self.downscale_filter);
+
             let clear_color = if pinnacle.lock_state.is_unlocked() {
-                CLEAR_COLOR
+                self.output
+                    .with_state(|state| state.clear_color)
+                    .unwrap_or(CLEAR_COLOR)
             } else {
                 CLEAR_COLOR_LOCKED
             };
@@ -376,6 +416,8 @@ impl Winit {
                 })
         });
 
+        self.render_stats.last_render_damaged = false;
+
         match render_res {
             Ok(render_output_result) => {
                 if pinnacle.lock_state.is_unlocked() {
@@ -406,6 +448,9 @@ impl Winit {
                 }
 
                 self.backend.window().set_cursor_visible(cursor_visible);
+                if let CursorImageStatus::Named(icon) = &pinnacle.cursor_status {
+                    self.backend.window().set_cursor_icon(*icon);
+                }
 
                 let time = pinnacle.clock.now();
 
@@ -416,9 +461,14 @@ impl Winit {
                     None,
                     time.into(),
                     &pinnacle.cursor_status,
+                    pinnacle.dnd_icon.as_ref(),
                 );
 
                 if has_rendered {
+                    self.render_stats.frames_queued += 1;
+                    self.render_stats.last_render_damaged = true;
+                    self.render_stats.last_vblank = Some(Instant::now());
+
                     let mut output_presentation_feedback = take_presentation_feedback(
                         &self.output,
                         &pinnacle.space,
@@ -53,10 +53,26 @@ pub struct Cli {
     #[arg(long)]
     pub no_xwayland: bool,
 
+    /// Raise the soft open file descriptor limit to this value on startup
+    ///
+    /// If this exceeds the hard limit and the hard limit can't be raised,
+    /// the soft limit will be raised to the hard limit instead.
+    /// Defaults to 65536.
+    #[arg(long, value_name("LIMIT"))]
+    pub max_open_files: Option<u64>,
+
     /// Open the gRPC socket at the specified directory
     #[arg(short, long, value_name("DIR"), value_hint(ValueHint::DirPath))]
     pub socket_dir: Option<PathBuf>,
 
+    /// Listen for Wayland clients on a socket with this name instead of
+    /// picking the first free `wayland-N`
+    ///
+    /// The gRPC socket's name is derived from this, so this can be used to give a
+    /// nested instance predictable paths.
+    #[arg(long, value_name("NAME"))]
+    pub wayland_socket: Option<String>,
+
     /// Cli subcommands
     #[command(subcommand)]
     subcommand: Option<CliSubcommand>,
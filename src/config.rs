@@ -5,15 +5,17 @@ use crate::{
     },
     cli::Cli,
     input::ModifierMask,
-    output::OutputName,
+    output::{OutputName, PrimaryOutput},
+    process,
     state::Pinnacle,
-    tag::Tag,
+    tag::{switch_animation::DEFAULT_TAG_SWITCH_ANIMATION_DURATION, Tag},
     window::rules::{WindowRule, WindowRuleCondition},
 };
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     process::Stdio,
+    time::Duration,
 };
 
 use anyhow::Context;
@@ -30,7 +32,10 @@ use pinnacle_api_defs::pinnacle::{
 };
 use smithay::{
     input::keyboard::keysyms,
-    reexports::calloop::{self, channel::Event, LoopHandle, RegistrationToken},
+    reexports::{
+        calloop::{self, channel::Event, LoopHandle, RegistrationToken},
+        rustix::process::geteuid,
+    },
     utils::{Logical, Point},
 };
 use sysinfo::ProcessRefreshKind;
@@ -118,11 +123,15 @@ impl Metaconfig {
             std::env::set_current_dir(current_dir)?;
             socket_dir
         } else {
-            // Otherwise, use $XDG_RUNTIME_DIR. If that doesn't exist, use /tmp.
+            // Otherwise, use $XDG_RUNTIME_DIR. If that doesn't exist, use a per-user
+            // directory in /tmp so different users' instances don't collide.
             BaseDirectories::with_prefix("pinnacle")?
                 .get_runtime_directory()
                 .cloned()
-                .unwrap_or(PathBuf::from(DEFAULT_SOCKET_DIR))
+                .unwrap_or_else(|_| {
+                    PathBuf::from(DEFAULT_SOCKET_DIR)
+                        .join(format!("pinnacle-{}", geteuid().as_raw()))
+                })
         };
 
         Ok(ResolvedMetaconfig {
@@ -184,6 +193,8 @@ enum Modifier {
     Ctrl,
     Alt,
     Super,
+    Hyper,
+    Meta,
 }
 
 // TODO: refactor metaconfig input
@@ -197,6 +208,8 @@ impl From<Vec<self::Modifier>> for ModifierMask {
                 Modifier::Ctrl => mask |= ModifierMask::CTRL,
                 Modifier::Alt => mask |= ModifierMask::ALT,
                 Modifier::Super => mask |= ModifierMask::SUPER,
+                Modifier::Hyper => mask |= ModifierMask::HYPER,
+                Modifier::Meta => mask |= ModifierMask::META,
             }
         }
 
@@ -279,6 +292,10 @@ pub enum Key {
     Escape = keysyms::KEY_Escape,
 }
 
+/// The default number of pixels of a floating window that stay on an output
+/// when floating windows are kept in bounds.
+const DEFAULT_FLOATING_BOUNDS_MARGIN: u32 = 32;
+
 /// The current state of configuration.
 #[derive(Debug)]
 pub struct Config {
@@ -286,6 +303,18 @@ pub struct Config {
     pub window_rules: Vec<(WindowRuleCondition, WindowRule)>,
     /// Saved states when outputs are disconnected
     pub connector_saved_states: HashMap<OutputName, ConnectorSavedState>,
+    /// The output that gets anchored at (0, 0)
+    pub primary_output: Option<PrimaryOutput>,
+    /// Names of the tags given to outputs that connect without saved state
+    pub default_tags: Vec<String>,
+    /// Whether or not floating windows are kept within output bounds
+    pub keep_floating_in_bounds: bool,
+    /// How many pixels of a floating window stay on an output when it's kept in bounds
+    pub floating_bounds_margin: u32,
+    /// Whether or not switching tags crossfades between them
+    pub tag_switch_animation: bool,
+    /// How long tag switch animations last
+    pub tag_switch_animation_duration: Duration,
 
     pub config_join_handle: Option<JoinHandle<()>>,
     pub(crate) config_reload_on_crash_token: Option<RegistrationToken>,
@@ -311,6 +340,12 @@ impl Config {
         Config {
             window_rules: Vec::new(),
             connector_saved_states: HashMap::new(),
+            primary_output: None,
+            default_tags: Vec::new(),
+            keep_floating_in_bounds: false,
+            floating_bounds_margin: DEFAULT_FLOATING_BOUNDS_MARGIN,
+            tag_switch_animation: false,
+            tag_switch_animation_duration: DEFAULT_TAG_SWITCH_ANIMATION_DURATION,
             config_join_handle: None,
             config_reload_on_crash_token: None,
             shutdown_sender: None,
@@ -323,6 +358,12 @@ impl Config {
     pub(crate) fn clear(&mut self, loop_handle: &LoopHandle<State>) {
         self.window_rules.clear();
         self.connector_saved_states.clear();
+        self.primary_output = None;
+        self.default_tags.clear();
+        self.keep_floating_in_bounds = false;
+        self.floating_bounds_margin = DEFAULT_FLOATING_BOUNDS_MARGIN;
+        self.tag_switch_animation = false;
+        self.tag_switch_animation_duration = DEFAULT_TAG_SWITCH_ANIMATION_DURATION;
         if let Some(join_handle) = self.config_join_handle.take() {
             join_handle.abort();
         }
@@ -347,6 +388,8 @@ pub struct ConnectorSavedState {
     pub tags: Vec<Tag>,
     /// The output's previous scale
     pub scale: Option<smithay::output::Scale>,
+    /// The output's previous mode
+    pub mode: Option<smithay::output::Mode>,
 }
 
 /// Parse a metaconfig file in `config_dir`, if any.
@@ -502,6 +545,7 @@ impl Pinnacle {
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
                 .kill_on_drop(true);
+            process::restore_fd_limit_on_spawn(&mut cmd);
 
             let mut child = match cmd.spawn() {
                 Ok(child) => child,
@@ -568,7 +612,12 @@ impl Pinnacle {
 
         std::fs::create_dir_all(socket_dir)?;
 
-        let socket_name = format!("pinnacle-grpc-{}.sock", std::process::id());
+        // Namespace the socket by Wayland display as well as pid so that nested instances
+        // don't step on each other and it's obvious which socket belongs to which session.
+        let socket_name = match std::env::var("WAYLAND_DISPLAY") {
+            Ok(display) => format!("pinnacle-grpc-{display}-{}.sock", std::process::id()),
+            Err(_) => format!("pinnacle-grpc-{}.sock", std::process::id()),
+        };
 
         let socket_path = socket_dir.join(socket_name);
 
@@ -617,7 +666,10 @@ impl Pinnacle {
             .add_service(InputServiceServer::new(input_service))
             .add_service(ProcessServiceServer::new(process_service))
             .add_service(TagServiceServer::new(tag_service))
-            .add_service(OutputServiceServer::new(output_service))
+            .add_service(
+                // Captures send whole frames, which are bigger than the default 4 MB limit
+                OutputServiceServer::new(output_service).max_encoding_message_size(usize::MAX),
+            )
             .add_service(WindowServiceServer::new(window_service))
             .add_service(SignalServiceServer::new(signal_service))
             .add_service(LayoutServiceServer::new(layout_service))
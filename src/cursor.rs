@@ -1,13 +1,18 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::{io::Read, time::Duration};
+use std::{collections::HashMap, io::Read, time::Duration};
 
+use smithay::input::pointer::CursorIcon;
 use xcursor::{parser::Image, CursorTheme};
 
 static FALLBACK_CURSOR_DATA: &[u8] = include_bytes!("../resources/cursor.rgba");
 
 pub struct Cursor {
-    icons: Vec<Image>,
+    theme: CursorTheme,
+    /// Loaded cursor images for each requested icon.
+    ///
+    /// Icons the theme doesn't have map to the default icon's images.
+    icons: HashMap<CursorIcon, Vec<Image>>,
     size: u32,
 }
 
@@ -22,7 +27,7 @@ impl Cursor {
             .unwrap_or(24);
 
         let theme = CursorTheme::load(&name);
-        let icons = load_icon(&theme)
+        let default_icon = load_icon(&theme, CursorIcon::Default)
             .map_err(|err| tracing::warn!("Unable to load xcursor: {}, using fallback cursor", err))
             .unwrap_or_else(|_| {
                 vec![Image {
@@ -37,12 +42,40 @@ impl Cursor {
                 }]
             });
 
-        Cursor { icons, size }
+        Cursor {
+            theme,
+            icons: HashMap::from([(CursorIcon::Default, default_icon)]),
+            size,
+        }
+    }
+
+    /// The nominal size of the cursor at a scale of 1.
+    pub fn nominal_size(&self) -> u32 {
+        self.size
     }
 
-    pub fn get_image(&self, scale: u32, time: Duration) -> Image {
+    /// Get the image for `icon` at the given scale and time, loading it from the theme
+    /// if it hasn't been used yet.
+    ///
+    /// If the theme doesn't have `icon`, this returns the default cursor's image.
+    pub fn get_image(&mut self, icon: CursorIcon, scale: u32, time: Duration) -> Image {
         let size = self.size * scale;
-        frame(time.as_millis() as u32, size, &self.icons)
+        frame(time.as_millis() as u32, size, self.icons(icon))
+    }
+
+    fn icons(&mut self, icon: CursorIcon) -> &[Image] {
+        if !self.icons.contains_key(&icon) {
+            let images = load_icon(&self.theme, icon).unwrap_or_else(|err| {
+                tracing::debug!(
+                    "Unable to load cursor {}: {err}, using default",
+                    icon.name()
+                );
+                self.icons[&CursorIcon::Default].clone()
+            });
+            self.icons.insert(icon, images);
+        }
+
+        &self.icons[&icon]
     }
 }
 
@@ -74,16 +107,19 @@ fn frame(mut millis: u32, size: u32, images: &[Image]) -> Image {
 
 #[derive(thiserror::Error, Debug)]
 enum Error {
-    #[error("Theme has no default cursor")]
-    NoDefaultCursor,
+    #[error("Theme has no such cursor")]
+    NoCursor,
     #[error("Error opening xcursor file: {0}")]
     File(#[from] std::io::Error),
     #[error("Failed to parse XCursor file")]
     Parse,
 }
 
-fn load_icon(theme: &CursorTheme) -> Result<Vec<Image>, Error> {
-    let icon_path = theme.load_icon("default").ok_or(Error::NoDefaultCursor)?;
+fn load_icon(theme: &CursorTheme, icon: CursorIcon) -> Result<Vec<Image>, Error> {
+    let icon_path = std::iter::once(icon.name())
+        .chain(icon.alt_names().iter().copied())
+        .find_map(|name| theme.load_icon(name))
+        .ok_or(Error::NoCursor)?;
     let mut cursor_file = std::fs::File::open(icon_path)?;
     let mut cursor_data = Vec::new();
     cursor_file.read_to_end(&mut cursor_data)?;
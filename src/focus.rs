@@ -43,6 +43,89 @@ impl State {
                 SERIAL_COUNTER.next_serial(),
             );
     }
+
+    /// Raise `window` and give it keyboard focus, focusing `output` as well.
+    pub fn raise_and_focus_window(&mut self, window: &WindowElement, output: &Output) {
+        self.pinnacle.raise_window(window.clone(), true);
+        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+        self.pinnacle.output_focus_stack.set_focus(output.clone());
+        self.update_keyboard_focus(output);
+
+        for window in self.pinnacle.space.elements() {
+            if let Some(toplevel) = window.toplevel() {
+                toplevel.send_configure();
+            }
+        }
+
+        self.schedule_render(output);
+    }
+
+    /// Focus the window `offset` places away from the focused one on `output`, wrapping around.
+    ///
+    /// Windows are cycled through the output's focus stack, most recently focused first,
+    /// followed by any windows that were never focused in the order they were opened.
+    /// Only windows on the output's active tags are considered unless `all_tags` is set,
+    /// in which case the chosen window's tag is switched to if it isn't active. Minimized
+    /// and scratchpad windows are skipped.
+    pub fn cycle_focus(&mut self, output: &Output, offset: i32, all_tags: bool) {
+        let (output_tags, focus_stack) =
+            output.with_state(|state| (state.tags.clone(), state.focus_stack.stack.clone()));
+
+        let candidates = focus_stack
+            .iter()
+            .rev()
+            .chain(
+                self.pinnacle
+                    .windows
+                    .iter()
+                    .filter(|win| !focus_stack.contains(win)),
+            )
+            .filter(|win| !win.is_x11_override_redirect())
+            .filter(|win| win.with_state(|state| !state.minimized && !state.scratchpad))
+            .filter(|win| {
+                if all_tags {
+                    win.with_state(|state| state.tags.iter().any(|tag| output_tags.contains(tag)))
+                } else {
+                    win.is_on_active_tag_on_output(output)
+                }
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return;
+        }
+
+        let current = self
+            .pinnacle
+            .focused_window(output)
+            .and_then(|focused| candidates.iter().position(|win| *win == focused));
+
+        let len = candidates.len() as i64;
+        let index = match current {
+            Some(current) => current as i64 + offset as i64,
+            // Nothing is focused, so count from just past either end
+            None if offset > 0 => offset as i64 - 1,
+            None => len + offset as i64,
+        }
+        .rem_euclid(len);
+        let window = candidates[index as usize].clone();
+
+        if !window.is_on_active_tag_on_output(output) {
+            let tag = window.with_state(|state| {
+                state
+                    .tags
+                    .iter()
+                    .find(|tag| output_tags.contains(tag))
+                    .cloned()
+            });
+            if let Some(tag) = tag {
+                self.switch_to_tag(&tag);
+            }
+        }
+
+        self.raise_and_focus_window(&window, output);
+    }
 }
 
 impl Pinnacle {
@@ -85,6 +168,28 @@ impl Pinnacle {
         self.fixup_xwayland_window_layering();
     }
 
+    /// Lower a floating window below all other floating windows.
+    ///
+    /// It stays above tiled windows so it doesn't disappear behind them.
+    /// Tiled windows don't overlap and are left alone.
+    pub fn lower_window(&mut self, window: WindowElement) {
+        if !window.with_state(|state| state.floating_or_tiled.is_floating()) {
+            return;
+        }
+
+        self.z_index_stack.retain(|win| win != window);
+
+        let lowest_floating = self
+            .z_index_stack
+            .iter()
+            .position(|win| win.with_state(|state| state.floating_or_tiled.is_floating()))
+            .unwrap_or(self.z_index_stack.len());
+        self.z_index_stack.insert(lowest_floating, window);
+
+        self.fixup_z_layering();
+        self.fixup_xwayland_window_layering();
+    }
+
     /// Get the currently focused output, or the first mapped output if there is none, or None.
     pub fn focused_output(&self) -> Option<&Output> {
         self.output_focus_stack
@@ -15,7 +15,7 @@ use smithay::{
     },
     reexports::wayland_server::{backend::ObjectId, protocol::wl_surface::WlSurface},
     utils::{IsAlive, Serial},
-    wayland::seat::WaylandFocus,
+    wayland::{seat::WaylandFocus, shell::wlr_layer},
     xwayland::X11Surface,
 };
 
@@ -72,6 +72,12 @@ impl PointerFocusTarget {
         }
     }
 
+    /// Returns whether or not this is a layer surface on the background layer.
+    pub fn is_background_layer(&self, state: &State) -> bool {
+        self.layer_for(state)
+            .is_some_and(|layer| layer.layer() == wlr_layer::Layer::Background)
+    }
+
     pub fn popup_for(&self, state: &State) -> Option<PopupKind> {
         match self {
             PointerFocusTarget::WlSurface(surf) => state.pinnacle.popup_manager.find_popup(surf),
@@ -141,6 +141,33 @@ impl PointerGrab<State> for MoveSurfaceGrab {
                 }
             }
 
+            // If the window was dragged onto a different output, give it that output's tags
+            // so it doesn't stay attached to the old one.
+            let old_output = self.window.output(&state.pinnacle);
+            let new_output = state
+                .pinnacle
+                .space
+                .output_under(event.location)
+                .next()
+                .cloned();
+
+            if let Some(new_output) = new_output {
+                if old_output.as_ref() != Some(&new_output) {
+                    debug!(
+                        "Moving window from {:?} to {}",
+                        old_output.as_ref().map(|op| op.name()),
+                        new_output.name()
+                    );
+                    self.window.place_on_output(&new_output);
+
+                    if let Some(old_output) = old_output {
+                        state.pinnacle.request_layout(&old_output);
+                        state.schedule_render(&old_output);
+                    }
+                    state.pinnacle.request_layout(&new_output);
+                }
+            }
+
             let outputs = state.pinnacle.space.outputs_for_element(&self.window);
             for output in outputs {
                 state.schedule_render(&output);
@@ -184,7 +211,9 @@ impl PointerGrab<State> for MoveSurfaceGrab {
         &self.start_data
     }
 
-    fn unset(&mut self, _data: &mut State) {}
+    fn unset(&mut self, data: &mut State) {
+        data.pinnacle.keep_floating_window_in_bounds(&self.window);
+    }
 
     fn gesture_swipe_begin(
         &mut self,
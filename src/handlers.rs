@@ -5,14 +5,21 @@ pub mod window;
 mod xdg_shell;
 mod xwayland;
 
-use std::{mem, os::fd::OwnedFd, sync::Arc};
+use std::{collections::HashMap, mem, os::fd::OwnedFd, sync::Arc};
 
+use pinnacle_api_defs::pinnacle::signal::v0alpha1::WindowFocusedResponse;
 use smithay::{
-    backend::renderer::utils::{self, with_renderer_surface_state},
-    delegate_compositor, delegate_data_control, delegate_data_device, delegate_fractional_scale,
-    delegate_layer_shell, delegate_output, delegate_pointer_constraints, delegate_presentation,
-    delegate_primary_selection, delegate_relative_pointer, delegate_seat,
-    delegate_security_context, delegate_shm, delegate_viewporter, delegate_xwayland_shell,
+    backend::{
+        input::TabletToolDescriptor,
+        renderer::utils::{self, with_renderer_surface_state},
+    },
+    delegate_compositor, delegate_cursor_shape, delegate_data_control, delegate_data_device,
+    delegate_fractional_scale, delegate_idle_notify, delegate_input_method_manager,
+    delegate_keyboard_shortcuts_inhibit, delegate_layer_shell, delegate_output,
+    delegate_pointer_constraints, delegate_presentation, delegate_primary_selection,
+    delegate_relative_pointer, delegate_seat, delegate_security_context, delegate_shm,
+    delegate_single_pixel_buffer, delegate_tablet_manager, delegate_text_input_manager,
+    delegate_viewporter, delegate_virtual_keyboard_manager, delegate_xwayland_shell,
     desktop::{
         self, find_popup_root_surface, get_popup_toplevel_coords, layer_map_for_output, PopupKind,
         PopupManager, WindowSurfaceType,
@@ -21,7 +28,7 @@ use smithay::{
         pointer::{CursorImageStatus, PointerHandle},
         Seat, SeatHandler, SeatState,
     },
-    output::Output,
+    output::{Output, Scale},
     reexports::{
         calloop::Interest,
         wayland_protocols::xdg::shell::server::xdg_positioner::ConstraintAdjustment,
@@ -42,6 +49,12 @@ use smithay::{
         },
         dmabuf,
         fractional_scale::{self, FractionalScaleHandler},
+        idle_notify::{IdleNotifierHandler, IdleNotifierState},
+        input_method::{self, InputMethodHandler},
+        keyboard_shortcuts_inhibit::{
+            KeyboardShortcutsInhibitHandler, KeyboardShortcutsInhibitState,
+            KeyboardShortcutsInhibitor,
+        },
         output::OutputHandler,
         pointer_constraints::{with_pointer_constraint, PointerConstraintsHandler},
         seat::WaylandFocus,
@@ -64,6 +77,7 @@ use smithay::{
             xdg::{PopupSurface, XdgPopupSurfaceData, XdgToplevelSurfaceData},
         },
         shm::{ShmHandler, ShmState},
+        tablet_manager::TabletSeatHandler,
         xwayland_shell::{XWaylandShellHandler, XWaylandShellState},
     },
     xwayland::{X11Wm, XWaylandClientData},
@@ -72,12 +86,17 @@ use tracing::{error, trace, warn};
 
 use crate::{
     backend::Backend,
-    delegate_foreign_toplevel, delegate_gamma_control, delegate_screencopy,
+    delegate_foreign_toplevel, delegate_gamma_control, delegate_output_management,
+    delegate_screencopy,
     focus::{keyboard::KeyboardFocusTarget, pointer::PointerFocusTarget},
     handlers::xdg_shell::snapshot_pre_commit_hook,
     protocol::{
         foreign_toplevel::{self, ForeignToplevelHandler, ForeignToplevelManagerState},
         gamma_control::{GammaControlHandler, GammaControlManagerState},
+        output_management::{
+            ModeConfiguration, OutputConfiguration, OutputManagementHandler,
+            OutputManagementManagerState,
+        },
         screencopy::{Screencopy, ScreencopyHandler},
     },
     render::util::snapshot::capture_snapshots_on_output,
@@ -187,13 +206,11 @@ impl CompositorHandler for State {
                         tracing::debug!("Placing toplevel");
                         unmapped_window.place_on_output(&output);
 
-                        output.with_state_mut(|state| {
-                            state.focus_stack.set_focus(unmapped_window.clone())
+                        let snapshots = self.backend.with_renderer(|renderer| {
+                            capture_snapshots_on_output(&mut self.pinnacle, renderer, &output, [])
                         });
 
-                        Some(self.backend.with_renderer(|renderer| {
-                            capture_snapshots_on_output(&mut self.pinnacle, renderer, &output, [])
-                        }))
+                        Some((output, snapshots))
                     } else {
                         None
                     };
@@ -205,24 +222,34 @@ impl CompositorHandler for State {
 
                     self.pinnacle.raise_window(unmapped_window.clone(), true);
 
-                    self.pinnacle.apply_window_rules(&unmapped_window);
+                    // Window rules may move the window to different tags or another output,
+                    // so apply them before the window is focused and laid out.
+                    self.apply_window_rules(&unmapped_window);
+
+                    if let Some(output) = unmapped_window.output(&self.pinnacle) {
+                        output.with_state_mut(|state| {
+                            state.focus_stack.set_focus(unmapped_window.clone())
+                        });
 
-                    if let Some(focused_output) = self.pinnacle.focused_output().cloned() {
                         if unmapped_window.is_on_active_tag() {
-                            self.update_keyboard_focus(&focused_output);
+                            self.update_keyboard_focus(&output);
 
-                            if let Some((fs_and_up_snapshots, under_fs_snapshots)) =
-                                snapshots.flatten()
+                            if let Some((
+                                snapshot_output,
+                                Some((fs_and_up_snapshots, under_fs_snapshots)),
+                            )) = snapshots
                             {
-                                focused_output.with_state_mut(|state| {
-                                    state.new_wait_layout_transaction(
-                                        self.pinnacle.loop_handle.clone(),
-                                        fs_and_up_snapshots,
-                                        under_fs_snapshots,
-                                    )
-                                });
+                                if snapshot_output == output {
+                                    output.with_state_mut(|state| {
+                                        state.new_wait_layout_transaction(
+                                            self.pinnacle.loop_handle.clone(),
+                                            fs_and_up_snapshots,
+                                            under_fs_snapshots,
+                                        )
+                                    });
+                                }
                             }
-                            self.pinnacle.request_layout(&focused_output);
+                            self.pinnacle.request_layout(&output);
                         }
                     }
                 } else {
@@ -563,6 +590,26 @@ impl SeatHandler for State {
         });
         set_data_device_focus(&self.pinnacle.display_handle, seat, focus_client.clone());
         set_primary_focus(&self.pinnacle.display_handle, seat, focus_client);
+
+        let focused_window = match focused {
+            Some(KeyboardFocusTarget::Window(window)) => Some(window.with_state(|state| state.id)),
+            _ => None,
+        };
+
+        if focused_window != self.pinnacle.last_focused_window {
+            self.pinnacle.last_focused_window = focused_window;
+
+            if let Some(window_id) = focused_window {
+                self.pinnacle.signal_state.window_focused.signal(|buf| {
+                    buf.push_back(WindowFocusedResponse {
+                        window_id: Some(window_id.0),
+                    })
+                });
+            }
+        }
+
+        // Text inputs, and through them the active input method, are notified
+        // of the new focus by the keyboard handle itself.
     }
 }
 delegate_seat!(State);
@@ -583,6 +630,32 @@ delegate_output!(State);
 
 delegate_viewporter!(State);
 
+delegate_single_pixel_buffer!(State);
+
+impl KeyboardShortcutsInhibitHandler for State {
+    fn keyboard_shortcuts_inhibit_state(&mut self) -> &mut KeyboardShortcutsInhibitState {
+        &mut self.pinnacle.keyboard_shortcuts_inhibit_state
+    }
+
+    fn new_inhibitor(&mut self, inhibitor: KeyboardShortcutsInhibitor) {
+        // Restricted clients don't get to take over keybinds
+        let restricted = inhibitor
+            .wl_surface()
+            .client()
+            .and_then(|client| {
+                client
+                    .get_data::<ClientState>()
+                    .map(|state| state.is_restricted)
+            })
+            .unwrap_or(false);
+
+        if !restricted {
+            inhibitor.activate();
+        }
+    }
+}
+delegate_keyboard_shortcuts_inhibit!(State);
+
 impl FractionalScaleHandler for State {
     fn new_fractional_scale(&mut self, surface: WlSurface) {
         // comment yanked from anvil
@@ -762,13 +835,161 @@ impl GammaControlHandler for State {
             return;
         };
 
-        if let Err(err) = udev.set_gamma(output, None) {
+        // Go back to the config's color temperature if it set one
+        let res = match output.with_state(|state| state.color_temperature) {
+            Some(kelvin) => udev.set_color_temperature(output, kelvin),
+            None => udev.set_gamma(output, None),
+        };
+
+        if let Err(err) = res {
             warn!("Failed to set gamma for output {}: {err}", output.name());
         }
     }
 }
 delegate_gamma_control!(State);
 
+impl OutputManagementHandler for State {
+    fn output_management_manager_state(&mut self) -> &mut OutputManagementManagerState {
+        &mut self.pinnacle.output_management_manager_state
+    }
+
+    fn apply_output_configuration(
+        &mut self,
+        config: HashMap<Output, OutputConfiguration>,
+        test_only: bool,
+    ) -> bool {
+        let mut changes = Vec::new();
+
+        for (output, conf) in config {
+            let OutputConfiguration::Enabled {
+                mode,
+                position,
+                transform,
+                scale,
+            } = conf
+            else {
+                changes.push((output, None));
+                continue;
+            };
+
+            let mode = match mode {
+                Some(mode_conf) => {
+                    let Some(mode) = supported_mode(&output, mode_conf) else {
+                        warn!(
+                            "Output {} doesn't support mode {mode_conf:?}",
+                            output.name()
+                        );
+                        return false;
+                    };
+                    Some(mode)
+                }
+                None => None,
+            };
+
+            if let Some(scale) = scale.filter(|scale| !scale.is_finite() || *scale < 0.25) {
+                warn!("Output {} can't have a scale of {scale}", output.name());
+                return false;
+            }
+
+            changes.push((output, Some((mode, position, transform, scale))));
+        }
+
+        if test_only {
+            return true;
+        }
+
+        // Changing modes is the only thing that can fail, so do that first
+        // and undo it if any output fails so a failed configuration changes nothing
+        let mut old_modes = Vec::new();
+
+        for (output, change) in changes.iter() {
+            let Some((Some(mode), ..)) = change else {
+                continue;
+            };
+
+            let old_mode = output.current_mode();
+            if old_mode == Some(*mode) {
+                continue;
+            }
+
+            if let Err(err) = self.resize_output(output, *mode) {
+                warn!("{err:#}");
+
+                for (output, old_mode) in old_modes {
+                    if let Err(err) = self.resize_output(&output, old_mode) {
+                        warn!("Failed to restore mode: {err:#}");
+                    }
+                }
+
+                return false;
+            }
+
+            if let Some(old_mode) = old_mode {
+                old_modes.push((output.clone(), old_mode));
+            }
+        }
+
+        for (output, change) in changes {
+            let Some((_, position, transform, scale)) = change else {
+                self.backend
+                    .set_output_powered(&self.pinnacle.loop_handle, &output, false);
+                continue;
+            };
+
+            self.backend
+                .set_output_powered(&self.pinnacle.loop_handle, &output, true);
+
+            let scale = scale.map(Scale::Fractional);
+            self.pinnacle
+                .change_output_state(&output, None, transform, scale, position);
+
+            self.pinnacle.request_layout(&output);
+            self.schedule_render(&output);
+        }
+
+        self.pinnacle
+            .output_management_manager_state
+            .schedule_refresh();
+
+        self.clamp_cursor_to_outputs();
+
+        true
+    }
+}
+delegate_output_management!(State);
+
+/// How far a requested custom refresh rate, in millihertz, may be from a supported one.
+///
+/// Clients often round refresh rates, asking for 60000 for a 59940 mode.
+const CUSTOM_MODE_REFRESH_TOLERANCE: i32 = 500;
+
+/// Find the mode `output` supports that matches a client's requested mode.
+///
+/// Custom modes match the supported mode of the same size with the closest refresh
+/// rate within [`CUSTOM_MODE_REFRESH_TOLERANCE`], or the highest one if no rate was given.
+fn supported_mode(output: &Output, mode: ModeConfiguration) -> Option<smithay::output::Mode> {
+    let modes = output.with_state(|state| state.modes.clone());
+
+    match mode {
+        ModeConfiguration::Mode(mode) => modes.contains(&mode).then_some(mode),
+        ModeConfiguration::Custom {
+            size,
+            refresh: Some(refresh),
+        } => modes
+            .into_iter()
+            .filter(|mode| mode.size == size)
+            .filter(|mode| (mode.refresh - refresh).abs() <= CUSTOM_MODE_REFRESH_TOLERANCE)
+            .min_by_key(|mode| (mode.refresh - refresh).abs()),
+        ModeConfiguration::Custom {
+            size,
+            refresh: None,
+        } => modes
+            .into_iter()
+            .filter(|mode| mode.size == size)
+            .max_by_key(|mode| mode.refresh),
+    }
+}
+
 impl SecurityContextHandler for State {
     fn context_created(&mut self, source: SecurityContextListenerSource, context: SecurityContext) {
         self.pinnacle
@@ -815,6 +1036,9 @@ impl ForeignToplevelHandler for State {
             return;
         };
 
+        // Activating a minimized window brings it back
+        window.with_state_mut(|state| state.minimized = false);
+
         if !window.is_on_active_tag() {
             let new_active_tag =
                 window.with_state(|state| state.tags.iter().min_by_key(|tag| tag.id().0).cloned());
@@ -883,14 +1107,7 @@ impl ForeignToplevelHandler for State {
             return;
         };
 
-        window.with_state_mut(|state| state.minimized = true);
-
-        let Some(output) = window.output(&self.pinnacle) else {
-            return;
-        };
-
-        self.pinnacle.request_layout(&output);
-        self.schedule_render(&output);
+        self.set_window_minimized(&window, true);
     }
 
     fn unset_minimized(&mut self, wl_surface: WlSurface) {
@@ -898,14 +1115,7 @@ impl ForeignToplevelHandler for State {
             return;
         };
 
-        window.with_state_mut(|state| state.minimized = false);
-
-        let Some(output) = window.output(&self.pinnacle) else {
-            return;
-        };
-
-        self.pinnacle.request_layout(&output);
-        self.schedule_render(&output);
+        self.set_window_minimized(&window, false);
     }
 }
 delegate_foreign_toplevel!(State);
@@ -917,6 +1127,13 @@ impl XWaylandShellHandler for State {
 }
 delegate_xwayland_shell!(State);
 
+impl IdleNotifierHandler for State {
+    fn idle_notifier_state(&mut self) -> &mut IdleNotifierState<Self> {
+        &mut self.pinnacle.idle_notifier_state
+    }
+}
+delegate_idle_notify!(State);
+
 impl Pinnacle {
     fn position_popup(&self, popup: &PopupSurface) {
         trace!("State::position_popup");
@@ -1002,3 +1219,44 @@ impl Pinnacle {
         });
     }
 }
+
+impl InputMethodHandler for State {
+    fn new_popup(&mut self, surface: input_method::PopupSurface) {
+        if let Err(err) = self
+            .pinnacle
+            .popup_manager
+            .track_popup(PopupKind::from(surface))
+        {
+            warn!("Failed to track input method popup: {err}");
+        }
+    }
+
+    fn popup_repositioned(&mut self, _surface: input_method::PopupSurface) {}
+
+    fn dismiss_popup(&mut self, surface: input_method::PopupSurface) {
+        if let Some(parent) = surface.get_parent().map(|parent| parent.surface.clone()) {
+            let _ = PopupManager::dismiss_popup(&parent, &PopupKind::from(surface));
+        }
+    }
+
+    fn parent_geometry(&self, parent: &WlSurface) -> Rectangle<i32, Logical> {
+        self.pinnacle
+            .window_for_surface(parent)
+            .and_then(|win| self.pinnacle.space.element_geometry(&win))
+            .unwrap_or_default()
+    }
+}
+delegate_input_method_manager!(State);
+
+delegate_text_input_manager!(State);
+
+delegate_virtual_keyboard_manager!(State);
+
+impl TabletSeatHandler for State {
+    fn tablet_tool_image(&mut self, _tool: &TabletToolDescriptor, image: CursorImageStatus) {
+        self.pinnacle.cursor_status = image;
+    }
+}
+delegate_tablet_manager!(State);
+
+delegate_cursor_shape!(State);
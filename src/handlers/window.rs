@@ -1,9 +1,27 @@
+use std::time::Duration;
+
+use pinnacle_api_defs::pinnacle::signal::v0alpha1::{
+    window_state_request_response, WindowStateRequestResponse,
+};
+use smithay::{
+    output::Output,
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::{Logical, Point},
+};
+
 use crate::{
+    output::OutputName,
     render::util::snapshot::capture_snapshots_on_output,
     state::{State, WithState},
-    window::WindowElement,
+    window::{
+        window_state::{FloatingOrTiled, PendingStateRequest, StateRequest},
+        WindowElement,
+    },
 };
 
+/// How long to wait for the config to resolve a client's state request before applying it.
+const STATE_REQUEST_TIMEOUT: Duration = Duration::from_millis(500);
+
 impl State {
     pub fn set_window_maximized(&mut self, window: &WindowElement, maximized: bool) {
         let snapshots = window.output(&self.pinnacle).map(|output| {
@@ -37,6 +55,13 @@ impl State {
     }
 
     pub fn set_window_fullscreen(&mut self, window: &WindowElement, fullscreen: bool) {
+        let was_fullscreen =
+            window.with_state(|state| state.fullscreen_or_maximized.is_fullscreen());
+
+        if fullscreen && !was_fullscreen {
+            self.move_to_fullscreen_output(window);
+        }
+
         let snapshots = window.output(&self.pinnacle).map(|output| {
             self.backend.with_renderer(|renderer| {
                 capture_snapshots_on_output(&mut self.pinnacle, renderer, &output, [window.clone()])
@@ -65,5 +90,287 @@ impl State {
             self.pinnacle.request_layout(&output);
             self.schedule_render(&output);
         }
+
+        if !fullscreen && was_fullscreen {
+            self.restore_from_fullscreen_output(window);
+        }
+    }
+
+    /// Minimize or unminimize `window`.
+    ///
+    /// Minimized windows are hidden and left out of the layout as if they weren't on an
+    /// active tag. Minimizing the focused window moves focus to the next one, and
+    /// unminimizing a window raises and focuses it.
+    pub fn set_window_minimized(&mut self, window: &WindowElement, minimized: bool) {
+        if window.with_state(|state| state.minimized) == minimized {
+            return;
+        }
+
+        let snapshots = window.output(&self.pinnacle).map(|output| {
+            self.backend.with_renderer(|renderer| {
+                capture_snapshots_on_output(&mut self.pinnacle, renderer, &output, [window.clone()])
+            })
+        });
+
+        window.with_state_mut(|state| state.minimized = minimized);
+
+        let Some(output) = window.output(&self.pinnacle) else {
+            return;
+        };
+
+        if let Some((fs_and_up_snapshots, under_fs_snapshots)) = snapshots.flatten() {
+            output.with_state_mut(|op_state| {
+                op_state.new_wait_layout_transaction(
+                    self.pinnacle.loop_handle.clone(),
+                    fs_and_up_snapshots,
+                    under_fs_snapshots,
+                )
+            });
+        }
+
+        if !minimized && window.is_on_active_tag_on_output(&output) {
+            self.pinnacle.raise_window(window.clone(), true);
+            output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+        }
+
+        self.pinnacle.request_layout(&output);
+        self.update_keyboard_focus(&output);
+        self.schedule_render(&output);
+    }
+
+    /// Apply window rules to a new window.
+    ///
+    /// Windows that rules make fullscreen are moved to their fullscreen output the same way
+    /// as when the client requests fullscreen.
+    pub fn apply_window_rules(&mut self, window: &WindowElement) {
+        self.pinnacle.apply_window_rules(window);
+
+        if window.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
+            self.move_to_fullscreen_output(window);
+        }
+    }
+
+    /// If a window rule says `window` should be fullscreen on another output,
+    /// move it there, remembering its tags so it can be moved back.
+    fn move_to_fullscreen_output(&mut self, window: &WindowElement) {
+        let Some(fullscreen_output) = window
+            .with_state(|state| state.fullscreen_output.clone())
+            .and_then(|name| name.output(&self.pinnacle))
+        else {
+            return;
+        };
+
+        if window.output(&self.pinnacle).as_ref() == Some(&fullscreen_output) {
+            return;
+        }
+
+        let tags = window.with_state(|state| state.tags.clone());
+        self.move_window_to_output(window, &fullscreen_output);
+        window.with_state_mut(|state| state.pre_fullscreen_tags = Some(tags));
+    }
+
+    /// Move `window` back to where it was before [`Self::move_to_fullscreen_output`].
+    fn restore_from_fullscreen_output(&mut self, window: &WindowElement) {
+        let Some(tags) = window.with_state_mut(|state| state.pre_fullscreen_tags.take()) else {
+            return;
+        };
+
+        let Some(output) = tags.first().and_then(|tag| tag.output(&self.pinnacle)) else {
+            return;
+        };
+
+        self.move_window_to_output(window, &output);
+
+        // Give back the exact tags it had, not just the output's active ones
+        window.with_state_mut(|state| state.tags = tags);
+        self.pinnacle.request_layout(&output);
+    }
+
+    /// Move `window` to `output`, giving it the output's active tags.
+    ///
+    /// Floating windows keep their position relative to the output's top left corner.
+    pub fn move_window_to_output(&mut self, window: &WindowElement, output: &Output) {
+        let source_output = window.output(&self.pinnacle);
+        if source_output.as_ref() == Some(output) {
+            return;
+        }
+
+        let snapshots = source_output.as_ref().map(|source_output| {
+            self.backend.with_renderer(|renderer| {
+                capture_snapshots_on_output(
+                    &mut self.pinnacle,
+                    renderer,
+                    source_output,
+                    [window.clone()],
+                )
+            })
+        });
+
+        window.place_on_output(output);
+
+        let source_geo = source_output
+            .as_ref()
+            .and_then(|op| self.pinnacle.space.output_geometry(op));
+        let target_geo = self.pinnacle.space.output_geometry(output);
+
+        if let (Some(source_geo), Some(target_geo)) = (source_geo, target_geo) {
+            // Keep floating windows at the same relative position,
+            // scaled for outputs with different sizes
+            let floating_loc = window.with_state_mut(|state| {
+                let FloatingOrTiled::Floating(rect) = &mut state.floating_or_tiled else {
+                    return None;
+                };
+                let loc_relative_to_output = (rect.loc - source_geo.loc).to_f64();
+                let scaled_loc = Point::<f64, Logical>::from((
+                    loc_relative_to_output.x * target_geo.size.w as f64
+                        / source_geo.size.w.max(1) as f64,
+                    loc_relative_to_output.y * target_geo.size.h as f64
+                        / source_geo.size.h.max(1) as f64,
+                ))
+                .to_i32_round();
+                rect.loc = scaled_loc + target_geo.loc;
+                Some(rect.loc)
+            });
+
+            if let Some(loc) = floating_loc {
+                self.pinnacle.space.map_element(window.clone(), loc, false);
+            }
+        }
+
+        if let Some(source_output) = source_output {
+            if let Some((fs_and_up_snapshots, under_fs_snapshots)) = snapshots.flatten() {
+                source_output.with_state_mut(|op_state| {
+                    op_state.new_wait_layout_transaction(
+                        self.pinnacle.loop_handle.clone(),
+                        fs_and_up_snapshots,
+                        under_fs_snapshots,
+                    )
+                });
+            }
+
+            self.pinnacle.request_layout(&source_output);
+            self.schedule_render(&source_output);
+        }
+
+        self.pinnacle.request_layout(output);
+        self.schedule_render(output);
+
+        self.pinnacle.fixup_xwayland_window_layering();
+    }
+
+    /// Hold a client's fullscreen or maximize request so the config can allow or deny it.
+    ///
+    /// If the config doesn't resolve the request before a timeout, it is applied.
+    ///
+    /// Returns `false` if the config isn't listening for state requests, in which case
+    /// the caller should apply the request immediately.
+    pub fn defer_state_request(
+        &mut self,
+        window: &WindowElement,
+        request: StateRequest,
+        output: Option<&Output>,
+    ) -> bool {
+        if !self
+            .pinnacle
+            .signal_state
+            .window_state_request
+            .is_connected()
+        {
+            return false;
+        }
+
+        let window_id = window.with_state(|state| state.id);
+
+        let timeout_token = self
+            .pinnacle
+            .loop_handle
+            .insert_source(
+                Timer::from_duration(STATE_REQUEST_TIMEOUT),
+                move |_, _, state| {
+                    if let Some(window) = window_id.window(&state.pinnacle) {
+                        if let Some(pending) =
+                            window.with_state_mut(|state| state.pending_state_request.take())
+                        {
+                            state.apply_state_request(&window, pending);
+                        }
+                    }
+                    TimeoutAction::Drop
+                },
+            )
+            .expect("failed to insert state request timer");
+
+        let old_pending = window.with_state_mut(|state| {
+            state.pending_state_request.replace(PendingStateRequest {
+                request,
+                output: output.map(|output| OutputName(output.name())),
+                timeout_token,
+            })
+        });
+        if let Some(old_pending) = old_pending {
+            self.pinnacle.loop_handle.remove(old_pending.timeout_token);
+        }
+
+        let kind = match request {
+            StateRequest::Fullscreen => window_state_request_response::Kind::Fullscreen,
+            StateRequest::Maximized => window_state_request_response::Kind::Maximized,
+        };
+
+        self.pinnacle
+            .signal_state
+            .window_state_request
+            .signal(|buffer| {
+                buffer.push_back(WindowStateRequestResponse {
+                    window_id: Some(window_id.0),
+                    kind: Some(kind as i32),
+                    output_name: output.map(|output| output.name()),
+                })
+            });
+
+        true
+    }
+
+    /// Allow or deny the window's pending state request, if any.
+    pub fn resolve_state_request(&mut self, window: &WindowElement, allow: bool) {
+        let Some(pending) = window.with_state_mut(|state| state.pending_state_request.take())
+        else {
+            return;
+        };
+
+        self.pinnacle.loop_handle.remove(pending.timeout_token);
+
+        if allow {
+            self.apply_state_request(window, pending);
+        }
+    }
+
+    /// Drop the window's pending `request` because the client took it back.
+    pub fn cancel_state_request(&mut self, window: &WindowElement, request: StateRequest) {
+        let pending = window.with_state_mut(|state| {
+            state
+                .pending_state_request
+                .as_ref()
+                .is_some_and(|pending| pending.request == request)
+                .then(|| state.pending_state_request.take())
+                .flatten()
+        });
+
+        if let Some(pending) = pending {
+            self.pinnacle.loop_handle.remove(pending.timeout_token);
+        }
+    }
+
+    fn apply_state_request(&mut self, window: &WindowElement, pending: PendingStateRequest) {
+        match pending.request {
+            StateRequest::Fullscreen => {
+                if let Some(output) = pending
+                    .output
+                    .and_then(|output| output.output(&self.pinnacle))
+                {
+                    self.move_window_to_output(window, &output);
+                }
+                self.set_window_fullscreen(window, true);
+            }
+            StateRequest::Maximized => self.set_window_maximized(window, true),
+        }
     }
 }
@@ -27,7 +27,7 @@ use crate::{
     focus::keyboard::KeyboardFocusTarget,
     render::util::snapshot::capture_snapshots_on_output,
     state::{State, WithState},
-    window::WindowElement,
+    window::{window_state::StateRequest, WindowElement},
 };
 
 impl XdgShellHandler for State {
@@ -225,6 +225,13 @@ impl XdgShellHandler for State {
                     })
             });
 
+        if let Some(window) = self.pinnacle.window_for_surface(wl_surface) {
+            if self.defer_state_request(&window, StateRequest::Fullscreen, output.as_ref()) {
+                surface.send_configure();
+                return;
+            }
+        }
+
         if let Some(output) = output {
             let Some(geometry) = self.pinnacle.space.output_geometry(&output) else {
                 surface.send_configure();
@@ -264,6 +271,7 @@ impl XdgShellHandler for State {
             return;
         };
 
+        self.cancel_state_request(&window, StateRequest::Fullscreen);
         self.set_window_fullscreen(&window, false);
     }
 
@@ -272,6 +280,11 @@ impl XdgShellHandler for State {
             return;
         };
 
+        if self.defer_state_request(&window, StateRequest::Maximized, None) {
+            surface.send_configure();
+            return;
+        }
+
         self.set_window_maximized(&window, true);
     }
 
@@ -280,14 +293,16 @@ impl XdgShellHandler for State {
             return;
         };
 
+        self.cancel_state_request(&window, StateRequest::Maximized);
         self.set_window_maximized(&window, false);
     }
 
-    fn minimize_request(&mut self, _surface: ToplevelSurface) {
-        // TODO:
-        // if let Some(window) = self.window_for_surface(surface.wl_surface()) {
-        //     self.space.unmap_elem(&window);
-        // }
+    fn minimize_request(&mut self, surface: ToplevelSurface) {
+        let Some(window) = self.pinnacle.window_for_surface(surface.wl_surface()) else {
+            return;
+        };
+
+        self.set_window_minimized(&window, true);
     }
 
     // TODO: impl the rest of the fns in XdgShellHandler
@@ -4,6 +4,7 @@ use std::{process::Stdio, time::Duration};
 
 use smithay::{
     desktop::Window,
+    input::pointer::CursorIcon,
     utils::{Logical, Point, Rectangle, Size, SERIAL_COUNTER},
     wayland::selection::{
         data_device::{
@@ -28,7 +29,10 @@ use crate::{
     focus::keyboard::KeyboardFocusTarget,
     render::util::snapshot::capture_snapshots_on_output,
     state::{Pinnacle, State, WithState},
-    window::{window_state::FloatingOrTiled, WindowElement},
+    window::{
+        window_state::{FloatingOrTiled, StateRequest},
+        WindowElement,
+    },
 };
 
 impl XwmHandler for State {
@@ -109,7 +113,7 @@ impl XwmHandler for State {
         self.pinnacle.windows.push(window.clone());
         self.pinnacle.raise_window(window.clone(), true);
 
-        self.pinnacle.apply_window_rules(&window);
+        self.apply_window_rules(&window);
 
         if window.is_on_active_tag() {
             if let Some(output) = window.output(&self.pinnacle) {
@@ -256,6 +260,10 @@ impl XwmHandler for State {
             return;
         };
 
+        if self.defer_state_request(&window, StateRequest::Maximized, None) {
+            return;
+        }
+
         self.set_window_maximized(&window, true);
     }
 
@@ -267,6 +275,7 @@ impl XwmHandler for State {
             return;
         };
 
+        self.cancel_state_request(&window, StateRequest::Maximized);
         self.set_window_maximized(&window, false);
     }
 
@@ -278,6 +287,10 @@ impl XwmHandler for State {
             return;
         };
 
+        if self.defer_state_request(&window, StateRequest::Fullscreen, None) {
+            return;
+        }
+
         self.set_window_fullscreen(&window, true);
     }
 
@@ -289,6 +302,7 @@ impl XwmHandler for State {
             return;
         };
 
+        self.cancel_state_request(&window, StateRequest::Fullscreen);
         self.set_window_fullscreen(&window, true);
     }
 
@@ -520,8 +534,8 @@ impl Pinnacle {
                     )
                     .expect("Failed to attach x11wm");
 
-                    let cursor = Cursor::load();
-                    let image = cursor.get_image(1, Duration::ZERO);
+                    let mut cursor = Cursor::load();
+                    let image = cursor.get_image(CursorIcon::Default, 1, Duration::ZERO);
                     wm.set_cursor(
                         &image.pixels_rgba,
                         Size::from((image.width as u16, image.height as u16)),
@@ -1,6 +1,13 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod cursor_hide;
+pub mod idle;
+pub mod keybind_sequence;
 pub mod libinput;
+pub mod motion_stats;
+pub mod prediction;
+pub mod synthetic;
+pub mod tablet;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -11,11 +18,15 @@ use std::{
 use crate::{
     focus::{keyboard::KeyboardFocusTarget, pointer::PointerFocusTarget},
     state::{Pinnacle, WithState},
+    tag::TagId,
     window::WindowElement,
 };
-use pinnacle_api_defs::pinnacle::input::v0alpha1::{
-    set_libinput_setting_request::Setting, set_mousebind_request, SetKeybindResponse,
-    SetMousebindResponse,
+use pinnacle_api_defs::pinnacle::{
+    input::v0alpha1::{
+        set_libinput_setting_request::Setting, set_mousebind_request, Modifier, SetKeybindResponse,
+        SetKeybindSequenceResponse, SetMousebindResponse,
+    },
+    signal::v0alpha1::InputModifiersChangedResponse,
 };
 use smithay::{
     backend::{
@@ -30,10 +41,19 @@ use smithay::{
         keyboard::{keysyms, FilterResult, ModifiersState},
         pointer::{AxisFrame, ButtonEvent, MotionEvent, RelativeMotionEvent},
     },
-    reexports::input::{self, Led},
+    output::Output,
+    reexports::{
+        calloop::{
+            timer::{TimeoutAction, Timer},
+            RegistrationToken,
+        },
+        input::{self, Led},
+    },
     utils::{IsAlive, Logical, Point, Rectangle, SERIAL_COUNTER},
     wayland::{
         compositor::{self, RegionAttributes, SurfaceAttributes},
+        input_method::InputMethodSeat,
+        keyboard_shortcuts_inhibit::KeyboardShortcutsInhibitorSeat,
         pointer_constraints::{with_pointer_constraint, PointerConstraint},
         seat::WaylandFocus,
         shell::wlr_layer::{self, KeyboardInteractivity, LayerSurfaceCachedState},
@@ -41,10 +61,16 @@ use smithay::{
 };
 use tokio::sync::mpsc::UnboundedSender;
 use tracing::{error, info};
-use xkbcommon::xkb::Keysym;
+use xkbcommon::xkb::{self, Keysym};
 
 use crate::state::State;
 
+use self::{
+    cursor_hide::CursorHide, idle::IdleTimeouts, keybind_sequence::KeybindSequences,
+    libinput::DeviceIdentity, motion_stats::PointerMotionStats, prediction::PointerPrediction,
+    tablet::TabletMappings,
+};
+
 bitflags::bitflags! {
     #[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
     pub struct ModifierMask: u8 {
@@ -52,9 +78,46 @@ bitflags::bitflags! {
         const CTRL  = 1 << 1;
         const ALT   = 1 << 2;
         const SUPER = 1 << 3;
+        const HYPER = 1 << 4;
+        const META  = 1 << 5;
+    }
+}
+
+bitflags::bitflags! {
+    /// Modifiers held down on a specific side of the keyboard.
+    #[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
+    pub struct ModifierSides: u16 {
+        const LEFT_SHIFT  = 1;
+        const RIGHT_SHIFT = 1 << 1;
+        const LEFT_CTRL   = 1 << 2;
+        const RIGHT_CTRL  = 1 << 3;
+        const LEFT_ALT    = 1 << 4;
+        const RIGHT_ALT   = 1 << 5;
+        const LEFT_SUPER  = 1 << 6;
+        const RIGHT_SUPER = 1 << 7;
+        const LEFT_HYPER  = 1 << 8;
+        const RIGHT_HYPER = 1 << 9;
+        const LEFT_META   = 1 << 10;
+        const RIGHT_META  = 1 << 11;
     }
 }
 
+/// Modifier keysyms and the side of the keyboard they're on.
+const MODIFIER_SIDE_SYMS: [(Keysym, ModifierSides); 12] = [
+    (Keysym::Shift_L, ModifierSides::LEFT_SHIFT),
+    (Keysym::Shift_R, ModifierSides::RIGHT_SHIFT),
+    (Keysym::Control_L, ModifierSides::LEFT_CTRL),
+    (Keysym::Control_R, ModifierSides::RIGHT_CTRL),
+    (Keysym::Alt_L, ModifierSides::LEFT_ALT),
+    (Keysym::Alt_R, ModifierSides::RIGHT_ALT),
+    (Keysym::Super_L, ModifierSides::LEFT_SUPER),
+    (Keysym::Super_R, ModifierSides::RIGHT_SUPER),
+    (Keysym::Hyper_L, ModifierSides::LEFT_HYPER),
+    (Keysym::Hyper_R, ModifierSides::RIGHT_HYPER),
+    (Keysym::Meta_L, ModifierSides::LEFT_META),
+    (Keysym::Meta_R, ModifierSides::RIGHT_META),
+];
+
 impl From<ModifiersState> for ModifierMask {
     fn from(modifiers: ModifiersState) -> Self {
         let mut mask = ModifierMask::empty();
@@ -74,6 +137,24 @@ impl From<ModifiersState> for ModifierMask {
     }
 }
 
+impl ModifierMask {
+    /// Get the api modifiers this mask contains.
+    pub fn api_modifiers(&self) -> Vec<Modifier> {
+        [
+            (ModifierMask::SHIFT, Modifier::Shift),
+            (ModifierMask::CTRL, Modifier::Ctrl),
+            (ModifierMask::ALT, Modifier::Alt),
+            (ModifierMask::SUPER, Modifier::Super),
+            (ModifierMask::HYPER, Modifier::Hyper),
+            (ModifierMask::META, Modifier::Meta),
+        ]
+        .into_iter()
+        .filter(|(mask, _)| self.contains(*mask))
+        .map(|(_, modifier)| modifier)
+        .collect()
+    }
+}
+
 impl From<&ModifiersState> for ModifierMask {
     fn from(modifiers: &ModifiersState) -> Self {
         let mut mask = ModifierMask::empty();
@@ -93,24 +174,199 @@ impl From<&ModifiersState> for ModifierMask {
     }
 }
 
+/// A condition on when a keybind is active.
+///
+/// All set conditions must be met.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeybindCondition {
+    /// The tag that must be active
+    pub tag: Option<TagId>,
+    /// The layout that the first active tag on the focused output must use
+    pub layout: Option<String>,
+}
+
+impl KeybindCondition {
+    /// Returns whether or not this condition is currently met.
+    pub fn is_met(&self, pinnacle: &Pinnacle) -> bool {
+        if let Some(tag_id) = self.tag.as_ref() {
+            if !tag_id.tag(pinnacle).is_some_and(|tag| tag.active()) {
+                return false;
+            }
+        }
+
+        if let Some(layout) = self.layout.as_ref() {
+            let focused_layout = pinnacle.focused_output().and_then(|op| {
+                op.with_state(|state| state.focused_tags().next().and_then(|tag| tag.layout()))
+            });
+
+            if focused_layout.as_ref() != Some(layout) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// A keybind's callback along with when it's active.
+#[derive(Debug)]
+pub struct Keybind {
+    /// If set, the keybind is only active when this is met
+    pub condition: Option<KeybindCondition>,
+    pub sender: UnboundedSender<Result<SetKeybindResponse, tonic::Status>>,
+}
+
+/// Settings for focusing windows by clicking on them.
+#[derive(Debug)]
+pub struct ClickFocus {
+    /// Whether or not clicking on a window focuses it
+    pub enabled: bool,
+    /// The buttons that focus the window under the pointer.
+    ///
+    /// If empty, all buttons do.
+    pub buttons: Vec<u32>,
+    /// Whether to focus on press or release
+    pub edge: set_mousebind_request::MouseEdge,
+    /// Whether or not focusing a window by clicking also raises it
+    pub raise: bool,
+    /// Whether or not to keep the current focus when clicking on the background
+    /// instead of unfocusing everything
+    pub keep_focus_on_background: bool,
+}
+
+impl Default for ClickFocus {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            buttons: Vec::new(),
+            edge: set_mousebind_request::MouseEdge::Press,
+            raise: true,
+            keep_focus_on_background: false,
+        }
+    }
+}
+
+impl ClickFocus {
+    /// Returns whether or not the given button event should focus the window under the pointer.
+    fn triggers_on(&self, button: u32, edge: set_mousebind_request::MouseEdge) -> bool {
+        self.enabled
+            && self.edge == edge
+            && (self.buttons.is_empty() || self.buttons.contains(&button))
+    }
+}
+
+/// Settings for focusing windows by moving the pointer over them.
+#[derive(Debug, Default)]
+pub struct FocusFollowsMouse {
+    /// Whether or not moving the pointer over a window focuses it
+    pub enabled: bool,
+    /// How long the pointer needs to stay over a window before it's focused
+    pub delay: Duration,
+    /// When the pointer last moved focus
+    last_focus_change: Option<Duration>,
+    /// The window that will be focused once the delay passes, along with the timer's token
+    pending: Option<(WindowElement, RegistrationToken)>,
+}
+
+impl FocusFollowsMouse {
+    /// Reset settings to their defaults.
+    ///
+    /// A pending focus change is kept so its timer can still be removed; it won't
+    /// go through as focus follows mouse is disabled.
+    pub fn clear(&mut self) {
+        self.enabled = false;
+        self.delay = Duration::ZERO;
+    }
+}
+
+/// The minimum time between focus changes caused by the pointer moving.
+///
+/// This prevents sweeping the pointer across several windows from
+/// focusing every one of them along the way.
+const FOCUS_FOLLOWS_MOUSE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Settings for switching tags by scrolling.
+#[derive(Debug, Default)]
+pub struct ScrollTagSwitch {
+    /// Whether or not scrolling over the background switches tags
+    pub on_background: bool,
+    /// Regions, relative to the top left of each output, where scrolling switches tags
+    pub regions: Vec<Rectangle<i32, Logical>>,
+    /// Scroll accumulated from continuous sources like touchpads
+    accumulated: f64,
+    /// Scroll accumulated from wheels, in 120ths of a detent
+    accumulated_v120: f64,
+}
+
+/// Which scroll axes are inverted.
+///
+/// This is applied on top of libinput's natural scrolling and affects all devices.
+#[derive(Debug, Default)]
+pub struct ScrollInversion {
+    /// Whether or not horizontal scrolling is inverted
+    pub horizontal: bool,
+    /// Whether or not vertical scrolling is inverted
+    pub vertical: bool,
+}
+
+/// How far a continuous scroll needs to go to switch one tag.
+const SCROLL_TAG_SWITCH_THRESHOLD: f64 = 20.0;
+
+/// How far a wheel needs to scroll to switch one tag, one detent.
+///
+/// High-resolution wheels send fractions of this per event.
+const SCROLL_TAG_SWITCH_V120_THRESHOLD: f64 = 120.0;
+
 #[derive(Default)]
 pub struct InputState {
     // TODO: move all of these to config
     pub reload_keybind: Option<(ModifierMask, Keysym)>,
     pub kill_keybind: Option<(ModifierMask, Keysym)>,
 
-    pub keybinds:
-        HashMap<(ModifierMask, Keysym), UnboundedSender<Result<SetKeybindResponse, tonic::Status>>>,
+    /// Keybinds along with the modifier sides they require.
+    ///
+    /// Binds with empty [`ModifierSides`] match modifiers on either side.
+    /// Each key can have one bind per condition.
+    pub keybinds: HashMap<(ModifierMask, ModifierSides, Keysym), Vec<Keybind>>,
     pub mousebinds: HashMap<
-        (ModifierMask, u32, set_mousebind_request::MouseEdge),
+        (
+            ModifierMask,
+            ModifierSides,
+            u32,
+            set_mousebind_request::MouseEdge,
+        ),
         UnboundedSender<Result<SetMousebindResponse, tonic::Status>>,
     >,
     //--------------------------------------------------
     #[allow(clippy::type_complexity)]
     pub libinput_settings: HashMap<Discriminant<Setting>, Box<dyn Fn(&mut input::Device) + Send>>,
+    /// Libinput settings that only apply to one device.
+    ///
+    /// These are applied after [`Self::libinput_settings`] so they take precedence.
+    #[allow(clippy::type_complexity)]
+    pub device_libinput_settings: HashMap<
+        DeviceIdentity,
+        HashMap<Discriminant<Setting>, Box<dyn Fn(&mut input::Device) + Send>>,
+    >,
     /// All libinput devices that have been connected
     pub libinput_devices: Vec<input::Device>,
 
+    pub click_focus: ClickFocus,
+    pub focus_follows_mouse: FocusFollowsMouse,
+    pub scroll_tag_switch: ScrollTagSwitch,
+    pub scroll_inversion: ScrollInversion,
+    pub pointer_prediction: PointerPrediction,
+    pub pointer_motion_stats: PointerMotionStats,
+    pub idle_timeouts: IdleTimeouts,
+    pub cursor_hide: CursorHide,
+    pub tablet_mappings: TabletMappings,
+    pub keybind_sequences: KeybindSequences,
+    /// Whether input can be injected through the API.
+    ///
+    /// This is read from the environment once on startup, as clients can change
+    /// the compositor's environment through the API.
+    pub injection_enabled: bool,
+
     /// A keyboard focus target stack that is used when there are exclusive keyboard layer
     /// surfaces. When used, the first item is the previous focus before there were any
     /// exclusive layer surfaces.
@@ -121,16 +377,46 @@ pub struct InputState {
 
     // Keys that were used in a keybind and should not be released
     no_release_keys: HashSet<u32>,
+
+    /// Modifier keysyms that are currently held down.
+    ///
+    /// xkb has no dedicated modifiers for Hyper and Meta and doesn't tell left and right
+    /// modifiers apart, so they're tracked by key instead.
+    pressed_modifier_syms: HashSet<Keysym>,
+
+    /// The last modifier state sent to the config, used to only signal actual changes
+    last_modifiers: Option<InputModifiersChangedResponse>,
 }
 
 impl InputState {
     pub fn clear(&mut self) {
         self.reload_keybind = None;
         self.kill_keybind = None;
-        self.libinput_devices.clear();
+        // Connected devices are deliberately kept so that settings from the
+        // next config apply to them without needing to replug them.
+        self.clear_binds();
+        self.libinput_settings.clear();
+        self.device_libinput_settings.clear();
+        self.click_focus = ClickFocus::default();
+        self.focus_follows_mouse.clear();
+        self.scroll_tag_switch = ScrollTagSwitch::default();
+        self.scroll_inversion = ScrollInversion::default();
+        self.pointer_prediction = PointerPrediction::default();
+        self.idle_timeouts.clear();
+        self.cursor_hide.clear();
+        self.tablet_mappings.clear();
+    }
+
+    /// Remove all keybinds, keybind sequences, and mousebinds.
+    ///
+    /// This drops their senders, closing the config's streams. Callbacks that were already
+    /// triggered are still delivered, as the streams are drained before they end.
+    ///
+    /// Keys held down from a keybind keep having their release suppressed.
+    pub fn clear_binds(&mut self) {
         self.keybinds.clear();
         self.mousebinds.clear();
-        self.libinput_settings.clear();
+        self.keybind_sequences.clear();
     }
 }
 
@@ -143,13 +429,129 @@ impl std::fmt::Debug for InputState {
             .field("keybinds", &self.keybinds)
             .field("mousebinds", &self.mousebinds)
             .field("libinput_settings", &"...")
+            .field("device_libinput_settings", &"...")
+            .field("click_focus", &self.click_focus)
+            .field("focus_follows_mouse", &self.focus_follows_mouse)
+            .field("scroll_tag_switch", &self.scroll_tag_switch)
+            .field("scroll_inversion", &self.scroll_inversion)
+            .field("pointer_prediction", &self.pointer_prediction)
+            .field("pointer_motion_stats", &self.pointer_motion_stats)
+            .field("idle_timeouts", &self.idle_timeouts)
+            .field("cursor_hide", &self.cursor_hide)
+            .field("tablet_mappings", &self.tablet_mappings)
+            .field("keybind_sequences", &self.keybind_sequences)
+            .field("injection_enabled", &self.injection_enabled)
             .finish()
     }
 }
 
 impl InputState {
     pub fn new() -> Self {
-        Default::default()
+        Self {
+            injection_enabled: synthetic::input_injection_enabled(),
+            ..Default::default()
+        }
+    }
+
+    /// Get the [`ModifierMask`] for the given modifiers, including Hyper and Meta.
+    ///
+    /// Hyper and Meta usually share a real modifier with Super and Alt respectively.
+    /// If only Hyper or Meta is held down, the shared modifier is not included in the mask.
+    pub fn modifier_mask(&self, modifiers: &ModifiersState) -> ModifierMask {
+        let mut mask = ModifierMask::from(modifiers);
+
+        let held = |syms: [Keysym; 2]| {
+            syms.iter()
+                .any(|sym| self.pressed_modifier_syms.contains(sym))
+        };
+
+        if held([Keysym::Hyper_L, Keysym::Hyper_R]) {
+            mask |= ModifierMask::HYPER;
+            if !held([Keysym::Super_L, Keysym::Super_R]) {
+                mask.remove(ModifierMask::SUPER);
+            }
+        }
+
+        if held([Keysym::Meta_L, Keysym::Meta_R]) {
+            mask |= ModifierMask::META;
+            if !held([Keysym::Alt_L, Keysym::Alt_R]) {
+                mask.remove(ModifierMask::ALT);
+            }
+        }
+
+        mask
+    }
+
+    /// Get the sides of the keyboard the currently held modifiers are on.
+    pub fn held_modifier_sides(&self) -> ModifierSides {
+        MODIFIER_SIDE_SYMS
+            .iter()
+            .filter(|(sym, _)| self.pressed_modifier_syms.contains(sym))
+            .fold(ModifierSides::empty(), |acc, (_, side)| acc | *side)
+    }
+
+    /// Add a keybind, replacing any bind for the same key with the same condition.
+    pub fn add_keybind(&mut self, key: (ModifierMask, ModifierSides, Keysym), keybind: Keybind) {
+        let binds = self.keybinds.entry(key).or_default();
+        binds.retain(|bind| bind.condition != keybind.condition);
+        binds.push(keybind);
+    }
+
+    /// Get the keybind for the given modifiers and keysym whose condition is met.
+    ///
+    /// Binds that require modifiers on a specific side take priority over side-agnostic ones,
+    /// and conditional binds take priority over unconditional ones.
+    fn keybind(
+        &self,
+        mod_mask: ModifierMask,
+        keysym: Keysym,
+        condition_met: impl Fn(&KeybindCondition) -> bool,
+    ) -> Option<&UnboundedSender<Result<SetKeybindResponse, tonic::Status>>> {
+        let held_sides = self.held_modifier_sides();
+
+        self.keybinds
+            .iter()
+            .filter(|((mask, sides, sym), _)| {
+                *mask == mod_mask && *sym == keysym && held_sides.contains(*sides)
+            })
+            .flat_map(|((_, sides, _), binds)| binds.iter().map(move |bind| (sides, bind)))
+            .filter(|(_, bind)| bind.condition.iter().all(&condition_met))
+            .max_by_key(|(sides, bind)| (sides.bits().count_ones(), bind.condition.is_some()))
+            .map(|(_, bind)| &bind.sender)
+    }
+
+    /// Get the mousebind for the given modifiers, button, and edge.
+    ///
+    /// Binds that require modifiers on a specific side take priority over side-agnostic ones.
+    fn mousebind(
+        &self,
+        mod_mask: ModifierMask,
+        button: u32,
+        edge: set_mousebind_request::MouseEdge,
+    ) -> Option<&UnboundedSender<Result<SetMousebindResponse, tonic::Status>>> {
+        let held_sides = self.held_modifier_sides();
+
+        self.mousebinds
+            .iter()
+            .filter(|((mask, sides, btn, edg), _)| {
+                *mask == mod_mask && *btn == button && *edg == edge && held_sides.contains(*sides)
+            })
+            .max_by_key(|((_, sides, _, _), _)| sides.bits().count_ones())
+            .map(|(_, sender)| sender)
+    }
+
+    /// Keep track of held modifier keys that xkb doesn't report.
+    fn update_pressed_modifier_syms(&mut self, syms: &[Keysym], key_state: KeyState) {
+        for &sym in syms.iter().filter(|sym| {
+            MODIFIER_SIDE_SYMS
+                .iter()
+                .any(|(mod_sym, _)| mod_sym == *sym)
+        }) {
+            match key_state {
+                KeyState::Pressed => self.pressed_modifier_syms.insert(sym),
+                KeyState::Released => self.pressed_modifier_syms.remove(&sym),
+            };
+        }
     }
 }
 
@@ -157,6 +559,12 @@ impl InputState {
 enum KeyAction {
     /// Call a config callback.
     CallCallback(UnboundedSender<Result<SetKeybindResponse, tonic::Status>>),
+    /// Call a keybind sequence's config callback.
+    CallSequenceCallback(UnboundedSender<Result<SetKeybindSequenceResponse, tonic::Status>>),
+    /// Prevent the key and its release from being sent to clients.
+    Consume,
+    /// Send these keys to clients in place of the pressed one.
+    ReplayKeys(Vec<u32>),
     /// Quit the compositor.
     Quit,
     /// Switch ttys.
@@ -210,7 +618,7 @@ impl Pinnacle {
             .filter(|win| win.is_on_active_tag())
             .enumerate()
         {
-            if win.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
+            if win.is_fullscreen_on_output(output) {
                 fullscreen_and_up_split_at = i + 1;
             }
         }
@@ -290,16 +698,38 @@ impl Pinnacle {
 
 impl State {
     pub fn process_input_event<B: InputBackend>(&mut self, event: InputEvent<B>) {
+        if !matches!(
+            event,
+            InputEvent::DeviceAdded { .. } | InputEvent::DeviceRemoved { .. }
+        ) {
+            self.pinnacle.notify_activity();
+        }
+
+        match &event {
+            InputEvent::PointerMotion { .. }
+            | InputEvent::PointerMotionAbsolute { .. }
+            | InputEvent::TabletToolAxis { .. }
+            | InputEvent::TabletToolProximity { .. } => self.notify_pointer_activity(true),
+            InputEvent::PointerButton { .. }
+            | InputEvent::PointerAxis { .. }
+            | InputEvent::TabletToolTip { .. }
+            | InputEvent::TabletToolButton { .. } => self.notify_pointer_activity(false),
+            _ => (),
+        }
+
         match event {
             // TODO: rest of input events
-
-            // InputEvent::DeviceAdded { device } => todo!(),
-            // InputEvent::DeviceRemoved { device } => todo!(),
+            InputEvent::DeviceAdded { device } => self.tablet_device_added::<B>(&device),
+            InputEvent::DeviceRemoved { device } => self.tablet_device_removed::<B>(&device),
             InputEvent::Keyboard { event } => self.keyboard::<B>(event),
             InputEvent::PointerMotion { event } => self.pointer_motion::<B>(event),
             InputEvent::PointerMotionAbsolute { event } => self.pointer_motion_absolute::<B>(event),
             InputEvent::PointerButton { event } => self.pointer_button::<B>(event),
             InputEvent::PointerAxis { event } => self.pointer_axis::<B>(event),
+            InputEvent::TabletToolAxis { event } => self.tablet_tool_axis::<B>(event),
+            InputEvent::TabletToolProximity { event } => self.tablet_tool_proximity::<B>(event),
+            InputEvent::TabletToolTip { event } => self.tablet_tool_tip::<B>(event),
+            InputEvent::TabletToolButton { event } => self.tablet_tool_button::<B>(event),
 
             _ => (),
         }
@@ -380,6 +810,7 @@ impl State {
             return;
         };
         let loc: Point<f64, Logical> = loc.into();
+        self.pinnacle.input_state.pointer_prediction.reset();
         self.pinnacle.maybe_activate_pointer_constraint(loc);
         let new_under = self.pinnacle.pointer_focus_target_under(loc);
 
@@ -399,6 +830,48 @@ impl State {
         }
     }
 
+    /// Warp the cursor to `loc` in the global space, keeping it on an output.
+    ///
+    /// The output the cursor ends up on is focused. This does nothing if there are no outputs.
+    pub fn warp_cursor_clamped(&mut self, loc: Point<f64, Logical>) {
+        let output_geos = self
+            .pinnacle
+            .space
+            .outputs()
+            .flat_map(|op| self.pinnacle.space.output_geometry(op))
+            .collect::<Vec<_>>();
+
+        if output_geos.is_empty() {
+            return;
+        }
+
+        let loc = constrain_point_inside_rects(loc, output_geos);
+
+        if let Some(output) = self.pinnacle.space.output_under(loc).next().cloned() {
+            self.pinnacle.output_focus_stack.set_focus(output);
+        }
+
+        self.warp_cursor_to_global_loc(loc);
+
+        if let Some(pointer) = self.pinnacle.seat.get_pointer() {
+            pointer.frame(self);
+        }
+    }
+
+    /// Move the cursor back onto the nearest output if it's no longer on one,
+    /// e.g. when the output it was on got narrower after being rotated.
+    pub fn clamp_cursor_to_outputs(&mut self) {
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+
+        let loc = pointer.current_location();
+
+        if self.pinnacle.space.output_under(loc).next().is_none() {
+            self.warp_cursor_clamped(loc);
+        }
+    }
+
     fn keyboard<I: InputBackend>(&mut self, event: I::KeyboardKeyEvent) {
         let serial = SERIAL_COUNTER.next_serial();
         let time = event.time_msec();
@@ -514,6 +987,22 @@ impl State {
             }
         }
 
+        // Only an input method's grab counts here; popups grab the keyboard too
+        let input_method_grabbed = self.pinnacle.seat.input_method().keyboard_grabbed();
+
+        // Clients like screen lockers and remote desktops can ask for all keys,
+        // in which case only the kill keybind and vt switching are kept.
+        let shortcuts_inhibited = keyboard
+            .current_focus()
+            .and_then(|focus| {
+                focus.wl_surface().and_then(|surface| {
+                    self.pinnacle
+                        .seat
+                        .keyboard_shortcuts_inhibitor_for_surface(&surface)
+                })
+            })
+            .is_some_and(|inhibitor| inhibitor.is_active());
+
         let action = keyboard.input(
             self,
             event.key_code(),
@@ -521,6 +1010,11 @@ impl State {
             serial,
             time,
             |state, modifiers, keysym| {
+                state
+                    .pinnacle
+                    .input_state
+                    .update_pressed_modifier_syms(keysym.raw_syms(), press_state);
+
                 if press_state == KeyState::Released
                     && state
                         .pinnacle
@@ -532,54 +1026,117 @@ impl State {
                 }
 
                 if press_state == KeyState::Pressed {
-                    let mod_mask = ModifierMask::from(modifiers);
+                    let mod_mask = state.pinnacle.input_state.modifier_mask(modifiers);
 
                     let raw_sym = keysym.raw_syms().iter().next();
                     let mod_sym = keysym.modified_sym();
 
+                    // These always work, even in the middle of a keybind sequence
+                    if kill_keybind == Some((mod_mask, mod_sym)) {
+                        return FilterResult::Intercept(KeyAction::Quit);
+                    }
+
+                    if let mut vt @ keysyms::KEY_XF86Switch_VT_1..=keysyms::KEY_XF86Switch_VT_12 =
+                        keysym.modified_sym().raw()
+                    {
+                        vt = vt - keysyms::KEY_XF86Switch_VT_1 + 1;
+                        tracing::info!("Switching to vt {vt}");
+                        return FilterResult::Intercept(KeyAction::SwitchVt(vt as i32));
+                    }
+
+                    let keybinds_allowed =
+                        state.pinnacle.lock_state.is_unlocked() && !shortcuts_inhibited;
+
+                    let sequence_key = keybinds_allowed && !mod_sym.is_modifier_key();
+
+                    // A started sequence gets the next key before regular keybinds
+                    if sequence_key && state.pinnacle.input_state.keybind_sequences.is_pending() {
+                        if let Some(action) = state.press_keybind_sequence(
+                            event.key_code(),
+                            mod_mask,
+                            mod_sym,
+                            raw_sym.copied(),
+                        ) {
+                            return FilterResult::Intercept(action);
+                        }
+                    }
+
+                    // Give an input method the first chance at keys that produce text
+                    // so composing works. Keys with modifiers other than shift and keys
+                    // like F-keys and media keys still go through keybinds so they keep
+                    // working while an IME is active.
+                    if input_method_grabbed
+                        && (mod_mask - ModifierMask::SHIFT).is_empty()
+                        && xkb::keysym_to_utf32(mod_sym) != 0
+                        && state.pinnacle.lock_state.is_unlocked()
+                    {
+                        return FilterResult::Forward;
+                    }
+
+                    let condition_met = |cond: &KeybindCondition| cond.is_met(&state.pinnacle);
+
                     if let Some(sender) = state
                         .pinnacle
                         .input_state
-                        .keybinds
-                        .get(&(mod_mask, mod_sym))
+                        .keybind(mod_mask, mod_sym, condition_met)
                         .or_else(|| {
                             raw_sym.and_then(|raw_sym| {
-                                state
-                                    .pinnacle
-                                    .input_state
-                                    .keybinds
-                                    .get(&(mod_mask, *raw_sym))
+                                state.pinnacle.input_state.keybind(
+                                    mod_mask,
+                                    *raw_sym,
+                                    condition_met,
+                                )
                             })
                         })
                     {
-                        if state.pinnacle.lock_state.is_unlocked() {
+                        if keybinds_allowed {
                             return FilterResult::Intercept(KeyAction::CallCallback(
                                 sender.clone(),
                             ));
                         }
                     }
 
-                    if kill_keybind == Some((mod_mask, mod_sym)) {
-                        return FilterResult::Intercept(KeyAction::Quit);
+                    if sequence_key {
+                        if let Some(action) = state.press_keybind_sequence(
+                            event.key_code(),
+                            mod_mask,
+                            mod_sym,
+                            raw_sym.copied(),
+                        ) {
+                            return FilterResult::Intercept(action);
+                        }
                     }
 
-                    if reload_keybind == Some((mod_mask, mod_sym)) {
+                    if keybinds_allowed && reload_keybind == Some((mod_mask, mod_sym)) {
                         return FilterResult::Intercept(KeyAction::ReloadConfig);
                     }
-
-                    if let mut vt @ keysyms::KEY_XF86Switch_VT_1..=keysyms::KEY_XF86Switch_VT_12 =
-                        keysym.modified_sym().raw()
-                    {
-                        vt = vt - keysyms::KEY_XF86Switch_VT_1 + 1;
-                        tracing::info!("Switching to vt {vt}");
-                        return FilterResult::Intercept(KeyAction::SwitchVt(vt as i32));
-                    }
                 }
 
                 FilterResult::Forward
             },
         );
 
+        let new_modifiers = keyboard.modifier_state();
+        let modifiers_changed = InputModifiersChangedResponse {
+            modifiers: self
+                .pinnacle
+                .input_state
+                .modifier_mask(&new_modifiers)
+                .api_modifiers()
+                .into_iter()
+                .map(|modifier| modifier as i32)
+                .collect(),
+            caps_lock: Some(new_modifiers.caps_lock),
+            num_lock: Some(new_modifiers.num_lock),
+        };
+        if self.pinnacle.input_state.last_modifiers.as_ref() != Some(&modifiers_changed) {
+            self.pinnacle.input_state.last_modifiers = Some(modifiers_changed.clone());
+            self.pinnacle
+                .signal_state
+                .input_modifiers_changed
+                .signal(|buf| buf.push_back(modifiers_changed));
+        }
+
         if let Some(KeyAction::Suppress) = action.as_ref() {
             self.pinnacle
                 .input_state
@@ -597,6 +1154,13 @@ impl State {
                 KeyAction::CallCallback(sender) => {
                     let _ = sender.send(Ok(SetKeybindResponse {}));
                 }
+                KeyAction::CallSequenceCallback(sender) => {
+                    let _ = sender.send(Ok(SetKeybindSequenceResponse {}));
+                }
+                KeyAction::Consume => (),
+                KeyAction::ReplayKeys(keys) => {
+                    self.replay_keys(&keys);
+                }
                 KeyAction::Quit => {
                     self.pinnacle.shutdown();
                 }
@@ -631,7 +1195,10 @@ impl State {
 
         let pointer_loc = pointer.current_location();
 
-        let mod_mask = ModifierMask::from(keyboard.modifier_state());
+        let mod_mask = self
+            .pinnacle
+            .input_state
+            .modifier_mask(&keyboard.modifier_state());
 
         let mouse_edge = match button_state {
             ButtonState::Released => set_mousebind_request::MouseEdge::Release,
@@ -641,17 +1208,32 @@ impl State {
         if let Some(stream) = self
             .pinnacle
             .input_state
-            .mousebinds
-            .get(&(mod_mask, button, mouse_edge))
+            .mousebind(mod_mask, button, mouse_edge)
         {
             let _ = stream.send(Ok(SetMousebindResponse {}));
             return;
         }
 
-        if button_state == ButtonState::Pressed {
-            if let Some((focus, _)) = self.pinnacle.pointer_focus_target_under(pointer_loc) {
+        let focus_under = self.pinnacle.pointer_focus_target_under(pointer_loc);
+
+        let clicked_background = match &focus_under {
+            Some((focus, _)) => focus.is_background_layer(self),
+            None => true,
+        };
+
+        let click_focus = &self.pinnacle.input_state.click_focus;
+        let keep_focus = clicked_background && click_focus.keep_focus_on_background;
+
+        if click_focus.triggers_on(button, mouse_edge) && !keep_focus {
+            if let Some((focus, _)) = focus_under {
                 if let Some(window) = focus.window_for(self) {
-                    self.pinnacle.raise_window(window.clone(), true);
+                    if self.pinnacle.input_state.click_focus.raise {
+                        self.pinnacle.raise_window(window.clone(), true);
+                    } else {
+                        for win in self.pinnacle.space.elements() {
+                            win.set_activate(win == &window);
+                        }
+                    }
                     if let Some(output) = window.output(&self.pinnacle) {
                         output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
                     }
@@ -701,16 +1283,74 @@ impl State {
     fn pointer_axis<I: InputBackend>(&mut self, event: I::PointerAxisEvent) {
         let source = event.source();
 
+        let inversion = &self.pinnacle.input_state.scroll_inversion;
+        let horizontal_sign = if inversion.horizontal { -1.0 } else { 1.0 };
+        let vertical_sign = if inversion.vertical { -1.0 } else { 1.0 };
+
         let horizontal_amount = event
             .amount(Axis::Horizontal)
-            .unwrap_or_else(|| event.amount_v120(Axis::Horizontal).unwrap_or(0.0) * 3.0 / 120.);
+            .unwrap_or_else(|| event.amount_v120(Axis::Horizontal).unwrap_or(0.0) * 3.0 / 120.)
+            * horizontal_sign;
 
         let vertical_amount = event
             .amount(Axis::Vertical)
-            .unwrap_or_else(|| event.amount_v120(Axis::Vertical).unwrap_or(0.0) * 3.0 / 120.);
+            .unwrap_or_else(|| event.amount_v120(Axis::Vertical).unwrap_or(0.0) * 3.0 / 120.)
+            * vertical_sign;
 
-        let horizontal_amount_discrete = event.amount_v120(Axis::Horizontal);
-        let vertical_amount_discrete = event.amount_v120(Axis::Vertical);
+        let pointer = self
+            .pinnacle
+            .seat
+            .get_pointer()
+            .expect("Seat has no pointer");
+
+        if let Some(output) = self.scroll_tag_switch_output(pointer.current_location()) {
+            if vertical_amount != 0.0 {
+                let scroll = &mut self.pinnacle.input_state.scroll_tag_switch;
+
+                let offset = match source {
+                    AxisSource::Wheel | AxisSource::WheelTilt => {
+                        let v120 = event
+                            .amount_v120(Axis::Vertical)
+                            .map(|v120| v120 * vertical_sign)
+                            .unwrap_or(vertical_amount.signum() * SCROLL_TAG_SWITCH_V120_THRESHOLD);
+
+                        scroll.accumulated_v120 += v120;
+                        if scroll.accumulated_v120.abs() >= SCROLL_TAG_SWITCH_V120_THRESHOLD {
+                            let offset = scroll.accumulated_v120.signum();
+                            scroll.accumulated_v120 -= offset * SCROLL_TAG_SWITCH_V120_THRESHOLD;
+                            offset as isize
+                        } else {
+                            0
+                        }
+                    }
+                    _ => {
+                        scroll.accumulated += vertical_amount;
+                        if scroll.accumulated.abs() >= SCROLL_TAG_SWITCH_THRESHOLD {
+                            let offset = scroll.accumulated.signum() as isize;
+                            scroll.accumulated = 0.0;
+                            offset
+                        } else {
+                            0
+                        }
+                    }
+                };
+
+                if offset != 0 {
+                    if let Some(tag) = self.pinnacle.tag_relative_to_active(&output, offset) {
+                        self.switch_to_tag(&tag);
+                    }
+                }
+            }
+
+            return;
+        }
+
+        let horizontal_amount_discrete = event
+            .amount_v120(Axis::Horizontal)
+            .map(|v120| v120 * horizontal_sign);
+        let vertical_amount_discrete = event
+            .amount_v120(Axis::Vertical)
+            .map(|v120| v120 * vertical_sign);
 
         let mut frame = AxisFrame::new(event.time_msec()).source(source);
 
@@ -732,16 +1372,40 @@ impl State {
             frame = frame.stop(Axis::Vertical);
         }
 
-        let pointer = self
-            .pinnacle
-            .seat
-            .get_pointer()
-            .expect("Seat has no pointer");
-
         pointer.axis(self, frame);
         pointer.frame(self);
     }
 
+    /// If scrolling at `pointer_loc` should switch tags, get the output to switch tags on.
+    fn scroll_tag_switch_output(&self, pointer_loc: Point<f64, Logical>) -> Option<Output> {
+        let scroll = &self.pinnacle.input_state.scroll_tag_switch;
+
+        if !scroll.on_background && scroll.regions.is_empty() {
+            return None;
+        }
+
+        let output = self
+            .pinnacle
+            .space
+            .output_under(pointer_loc)
+            .next()
+            .cloned()?;
+        let output_loc = self.pinnacle.space.output_geometry(&output)?.loc;
+
+        let in_region = scroll.regions.iter().any(|region| {
+            Rectangle::from_loc_and_size(region.loc + output_loc, region.size)
+                .contains(pointer_loc.to_i32_round())
+        });
+
+        let over_background = scroll.on_background
+            && match self.pinnacle.pointer_focus_target_under(pointer_loc) {
+                Some((focus, _)) => focus.is_background_layer(self),
+                None => true,
+            };
+
+        (in_region || over_background).then_some(output)
+    }
+
     /// Handle an absolute pointer motion event.
     ///
     /// This *should* only be generated on the winit backend.
@@ -789,7 +1453,184 @@ impl State {
 
         pointer.frame(self);
 
-        if let Some(output) = self.pinnacle.focused_output().cloned() {
+        self.push_pointer_prediction_sample(pointer.current_location());
+
+        self.focus_window_under_cursor(pointer_loc);
+
+        self.schedule_render_after_pointer_motion();
+    }
+
+    /// Focus the window under the cursor if focus follows the mouse.
+    ///
+    /// Focus isn't moved while a layer surface or popup has keyboard focus, while
+    /// a fullscreen window is focused, or during a pointer grab.
+    ///
+    /// With a delay set, focus only moves once the pointer has stayed over the window
+    /// for that long. Moving off the window before then cancels the focus change.
+    ///
+    /// Without a delay, focus changes within [`FOCUS_FOLLOWS_MOUSE_DEBOUNCE`] of the last
+    /// one are deferred until it passes rather than dropped.
+    fn focus_window_under_cursor(&mut self, pointer_loc: Point<f64, Logical>) {
+        let Some((window, output)) = self.focus_follows_mouse_target(pointer_loc) else {
+            self.cancel_pending_focus_follows_mouse();
+            return;
+        };
+
+        let ffm = &self.pinnacle.input_state.focus_follows_mouse;
+
+        let delay = if ffm.delay.is_zero() {
+            let now: Duration = self.pinnacle.clock.now().into();
+            let since_last_change = ffm.last_focus_change.map(|last| now.saturating_sub(last));
+
+            match since_last_change {
+                // Wait out the rest of the debounce, focusing whatever window
+                // the pointer ends up over
+                Some(since) if since < FOCUS_FOLLOWS_MOUSE_DEBOUNCE => {
+                    FOCUS_FOLLOWS_MOUSE_DEBOUNCE - since
+                }
+                _ => {
+                    self.cancel_pending_focus_follows_mouse();
+                    self.focus_window_from_pointer(&window, &output);
+                    return;
+                }
+            }
+        } else {
+            ffm.delay
+        };
+
+        if ffm
+            .pending
+            .as_ref()
+            .is_some_and(|(pending, _)| pending == &window)
+        {
+            return;
+        }
+
+        self.cancel_pending_focus_follows_mouse();
+
+        let res =
+            self.pinnacle
+                .loop_handle
+                .insert_source(Timer::from_duration(delay), |_, _, state| {
+                    state.pending_focus_follows_mouse_elapsed();
+                    TimeoutAction::Drop
+                });
+
+        match res {
+            Ok(token) => {
+                self.pinnacle.input_state.focus_follows_mouse.pending = Some((window, token));
+            }
+            Err(_) => error!("Failed to insert focus follows mouse timer"),
+        }
+    }
+
+    /// Get the window under the cursor that focus follows mouse would focus,
+    /// along with its output.
+    fn focus_follows_mouse_target(
+        &self,
+        pointer_loc: Point<f64, Logical>,
+    ) -> Option<(WindowElement, Output)> {
+        if !self.pinnacle.input_state.focus_follows_mouse.enabled {
+            return None;
+        }
+
+        let pointer = self.pinnacle.seat.get_pointer()?;
+        let keyboard = self.pinnacle.seat.get_keyboard()?;
+
+        if pointer.is_grabbed() {
+            return None;
+        }
+
+        if matches!(
+            keyboard.current_focus(),
+            Some(KeyboardFocusTarget::LayerSurface(_) | KeyboardFocusTarget::Popup(_))
+        ) {
+            return None;
+        }
+
+        let window = self
+            .pinnacle
+            .pointer_focus_target_under(pointer_loc)
+            .and_then(|(focus, _)| focus.window_for(self))?;
+
+        if window.is_x11_override_redirect() {
+            return None;
+        }
+
+        let output = window.output(&self.pinnacle)?;
+
+        let focused = self.pinnacle.focused_window(&output);
+
+        if focused.as_ref() == Some(&window) && self.pinnacle.focused_output() == Some(&output) {
+            return None;
+        }
+
+        if focused.is_some_and(|win| {
+            win.with_state(|state| state.fullscreen_or_maximized.is_fullscreen())
+        }) {
+            return None;
+        }
+
+        Some((window, output))
+    }
+
+    fn cancel_pending_focus_follows_mouse(&mut self) {
+        if let Some((_, token)) = self.pinnacle.input_state.focus_follows_mouse.pending.take() {
+            self.pinnacle.loop_handle.remove(token);
+        }
+    }
+
+    /// Focus the pending window if the pointer is still over it.
+    fn pending_focus_follows_mouse_elapsed(&mut self) {
+        let Some((window, _)) = self.pinnacle.input_state.focus_follows_mouse.pending.take() else {
+            return;
+        };
+
+        let Some(pointer) = self.pinnacle.seat.get_pointer() else {
+            return;
+        };
+
+        // Things may have changed while waiting, so check everything again
+        let Some((target, output)) = self.focus_follows_mouse_target(pointer.current_location())
+        else {
+            return;
+        };
+
+        if target == window {
+            self.focus_window_from_pointer(&window, &output);
+        }
+    }
+
+    fn focus_window_from_pointer(&mut self, window: &WindowElement, output: &Output) {
+        self.pinnacle
+            .input_state
+            .focus_follows_mouse
+            .last_focus_change = Some(self.pinnacle.clock.now().into());
+
+        for win in self.pinnacle.space.elements() {
+            win.set_activate(false);
+        }
+
+        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+        self.pinnacle.output_focus_stack.set_focus(output.clone());
+        self.update_keyboard_focus(output);
+
+        for window in self.pinnacle.space.elements() {
+            if let Some(toplevel) = window.toplevel() {
+                toplevel.send_configure();
+            }
+        }
+    }
+
+    /// Schedule renders on the outputs affected by the pointer moving.
+    ///
+    /// While dragging, the dnd icon can straddle outputs, so every output is rerendered.
+    fn schedule_render_after_pointer_motion(&mut self) {
+        if self.pinnacle.dnd_icon.is_some() {
+            for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                self.schedule_render(&output);
+            }
+        } else if let Some(output) = self.pinnacle.focused_output().cloned() {
             self.schedule_render(&output);
         }
     }
@@ -800,6 +1641,12 @@ impl State {
             return;
         };
 
+        self.pinnacle.input_state.pointer_motion_stats.push_sample(
+            self.pinnacle.clock.now().into(),
+            event.delta_unaccel(),
+            event.delta(),
+        );
+
         let pointer_loc = pointer.current_location();
 
         let mut pointer_confined_to: Option<(
@@ -970,9 +1817,11 @@ impl State {
 
         pointer.frame(self);
 
-        if let Some(output) = self.pinnacle.focused_output().cloned() {
-            self.schedule_render(&output);
-        }
+        self.push_pointer_prediction_sample(pointer.current_location());
+
+        self.focus_window_under_cursor(new_pointer_loc);
+
+        self.schedule_render_after_pointer_motion();
     }
 }
 
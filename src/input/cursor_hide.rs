@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Hiding the cursor after pointer inactivity.
+//!
+//! When enabled, the cursor is hidden once the pointer hasn't been used for a while
+//! and shown again as soon as it moves. Only drawing is affected; the cursor image
+//! that clients set is kept so it comes back unchanged.
+
+use std::time::Duration;
+
+use smithay::{
+    input::pointer::CursorImageStatus,
+    reexports::calloop::{
+        timer::{TimeoutAction, Timer},
+        RegistrationToken,
+    },
+};
+use tracing::error;
+
+use crate::state::{Pinnacle, State};
+
+/// Cursor hiding set by the config.
+#[derive(Debug, Default)]
+pub struct CursorHide {
+    /// How long the pointer needs to be inactive for the cursor to hide,
+    /// or `None` to never hide it
+    timeout: Option<Duration>,
+    /// Whether the cursor is currently hidden
+    hidden: bool,
+    /// When the pointer was last used
+    last_activity: Duration,
+    /// The timer waiting to hide the cursor
+    timer: Option<RegistrationToken>,
+}
+
+impl Pinnacle {
+    /// Whether the cursor is hidden because of pointer inactivity.
+    pub fn cursor_hidden(&self) -> bool {
+        self.input_state.cursor_hide.hidden
+    }
+
+    /// Get the cursor image to draw, taking hiding into account.
+    pub fn cursor_status_to_draw(&self) -> CursorImageStatus {
+        if self.cursor_hidden() {
+            CursorImageStatus::Hidden
+        } else {
+            self.cursor_status.clone()
+        }
+    }
+
+    fn schedule_cursor_hide_timer(&mut self, timeout: Duration) {
+        let res = self
+            .loop_handle
+            .insert_source(Timer::from_duration(timeout), move |_, _, state| {
+                state.cursor_hide_timer_elapsed()
+            });
+
+        match res {
+            Ok(token) => self.input_state.cursor_hide.timer = Some(token),
+            Err(_) => error!("Failed to insert cursor hide timer"),
+        }
+    }
+
+    fn cancel_cursor_hide_timer(&mut self) {
+        if let Some(token) = self.input_state.cursor_hide.timer.take() {
+            self.loop_handle.remove(token);
+        }
+    }
+}
+
+impl CursorHide {
+    /// Stop hiding the cursor.
+    ///
+    /// A pending timer will stop the next time it fires.
+    pub fn clear(&mut self) {
+        self.timeout = None;
+        self.hidden = false;
+    }
+}
+
+impl State {
+    /// Hide the cursor after `timeout` passes without pointer activity,
+    /// or never hide it if `None`.
+    ///
+    /// A running timer is restarted so the new timeout takes effect immediately.
+    pub fn set_hide_cursor_after(&mut self, timeout: Option<Duration>) {
+        let now = self.pinnacle.clock.now().into();
+        let cursor_hide = &mut self.pinnacle.input_state.cursor_hide;
+
+        cursor_hide.timeout = timeout;
+        cursor_hide.last_activity = now;
+
+        self.pinnacle.cancel_cursor_hide_timer();
+
+        match timeout {
+            Some(timeout) => self.pinnacle.schedule_cursor_hide_timer(timeout),
+            None => {
+                let cursor_hide = &mut self.pinnacle.input_state.cursor_hide;
+                if cursor_hide.hidden {
+                    cursor_hide.hidden = false;
+                    for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+                        self.schedule_render(&output);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Like idle timeouts, the timer checks when it fires whether the pointer was used
+    /// in the meantime and waits for the rest of the timeout if so.
+    fn cursor_hide_timer_elapsed(&mut self) -> TimeoutAction {
+        let now: Duration = self.pinnacle.clock.now().into();
+        let cursor_hide = &mut self.pinnacle.input_state.cursor_hide;
+
+        let Some(timeout) = cursor_hide.timeout else {
+            cursor_hide.timer = None;
+            return TimeoutAction::Drop;
+        };
+
+        let inactive = now.saturating_sub(cursor_hide.last_activity);
+        if inactive < timeout {
+            return TimeoutAction::ToDuration(timeout - inactive);
+        }
+
+        // Don't hide the cursor in the middle of a drag or other pointer grab
+        if self
+            .pinnacle
+            .seat
+            .get_pointer()
+            .is_some_and(|pointer| pointer.is_grabbed())
+        {
+            return TimeoutAction::ToDuration(timeout);
+        }
+
+        let cursor_hide = &mut self.pinnacle.input_state.cursor_hide;
+        cursor_hide.hidden = true;
+        cursor_hide.timer = None;
+
+        for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.schedule_render(&output);
+        }
+
+        TimeoutAction::Drop
+    }
+
+    /// Note pointer activity, delaying hiding the cursor.
+    ///
+    /// If `moved` is true and the cursor is hidden, it is shown again.
+    pub(super) fn notify_pointer_activity(&mut self, moved: bool) {
+        let now = self.pinnacle.clock.now().into();
+        let cursor_hide = &mut self.pinnacle.input_state.cursor_hide;
+        cursor_hide.last_activity = now;
+
+        let Some(timeout) = cursor_hide.timeout else {
+            return;
+        };
+
+        if !moved || !cursor_hide.hidden {
+            return;
+        }
+
+        cursor_hide.hidden = false;
+
+        if cursor_hide.timer.is_none() {
+            self.pinnacle.schedule_cursor_hide_timer(timeout);
+        }
+
+        for output in self.pinnacle.space.outputs().cloned().collect::<Vec<_>>() {
+            self.schedule_render(&output);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Idle notifications.
+//!
+//! Clients are notified through ext-idle-notify-v1 and the config through idle timeouts
+//! it sets. Both are reset by any input activity.
+//!
+//! Timeouts are calloop timers, so they keep running while switched to another tty.
+
+use std::{collections::HashMap, time::Duration};
+
+use pinnacle_api_defs::pinnacle::{
+    input::v0alpha1::SetIdleNotificationResponse, signal::v0alpha1::InputResumedResponse,
+};
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::error;
+
+use crate::state::Pinnacle;
+
+/// An idle timeout set by the config.
+#[derive(Debug)]
+struct IdleTimeout {
+    timeout: Duration,
+    sender: UnboundedSender<Result<SetIdleNotificationResponse, tonic::Status>>,
+    /// Whether or not this timeout elapsed since the last activity
+    idle: bool,
+}
+
+/// Idle timeouts set by the config.
+#[derive(Debug, Default)]
+pub struct IdleTimeouts {
+    timeouts: HashMap<u32, IdleTimeout>,
+    /// Ids aren't reused so that timers from a previous config don't fire new timeouts
+    next_id: u32,
+    /// When there was last input activity
+    last_activity: Duration,
+}
+
+impl IdleTimeouts {
+    /// Remove all timeouts.
+    ///
+    /// Their timers will stop the next time they fire.
+    pub fn clear(&mut self) {
+        self.timeouts.clear();
+    }
+}
+
+impl Pinnacle {
+    /// Notify the config through `sender` after `timeout` passes without input activity.
+    pub fn add_idle_timeout(
+        &mut self,
+        timeout: Duration,
+        sender: UnboundedSender<Result<SetIdleNotificationResponse, tonic::Status>>,
+    ) {
+        let idle = &mut self.input_state.idle_timeouts;
+
+        let id = idle.next_id;
+        idle.next_id += 1;
+
+        idle.timeouts.insert(
+            id,
+            IdleTimeout {
+                timeout,
+                sender,
+                idle: false,
+            },
+        );
+
+        self.schedule_idle_timeout(id, timeout);
+    }
+
+    fn schedule_idle_timeout(&self, id: u32, timeout: Duration) {
+        let res = self
+            .loop_handle
+            .insert_source(Timer::from_duration(timeout), move |_, _, state| {
+                state.pinnacle.idle_timeout_elapsed(id)
+            });
+
+        if res.is_err() {
+            error!("Failed to insert idle timer");
+        }
+    }
+
+    /// Rather than resetting timers on every input event, timers check when they fire
+    /// whether there was activity in the meantime and wait for the rest of the timeout if so.
+    fn idle_timeout_elapsed(&mut self, id: u32) -> TimeoutAction {
+        let now: Duration = self.clock.now().into();
+        let idle = &mut self.input_state.idle_timeouts;
+
+        let Some(timeout) = idle.timeouts.get_mut(&id) else {
+            return TimeoutAction::Drop;
+        };
+
+        let inactive = now.saturating_sub(idle.last_activity);
+        if inactive < timeout.timeout {
+            return TimeoutAction::ToDuration(timeout.timeout - inactive);
+        }
+
+        timeout.idle = true;
+
+        if timeout
+            .sender
+            .send(Ok(SetIdleNotificationResponse {}))
+            .is_err()
+        {
+            idle.timeouts.remove(&id);
+        }
+
+        TimeoutAction::Drop
+    }
+
+    /// Reset idle timeouts because of input activity.
+    ///
+    /// If any config timeouts elapsed, this restarts them and signals the config
+    /// that activity resumed.
+    pub fn notify_activity(&mut self) {
+        self.idle_notifier_state.notify_activity(&self.seat);
+
+        let now: Duration = self.clock.now().into();
+        let idle = &mut self.input_state.idle_timeouts;
+        idle.last_activity = now;
+
+        let resumed = idle
+            .timeouts
+            .iter_mut()
+            .filter(|(_, timeout)| timeout.idle)
+            .map(|(id, timeout)| {
+                timeout.idle = false;
+                (*id, timeout.timeout)
+            })
+            .collect::<Vec<_>>();
+
+        if resumed.is_empty() {
+            return;
+        }
+
+        for (id, timeout) in resumed {
+            self.schedule_idle_timeout(id, timeout);
+        }
+
+        self.signal_state
+            .input_resumed
+            .signal(|buf| buf.push_back(InputResumedResponse {}));
+    }
+}
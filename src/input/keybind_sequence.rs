@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Keybind sequences.
+//!
+//! A sequence is a list of chords pressed one after another, like `Super+w` then `h`.
+//! Once a key starts one or more sequences, the following keys are matched against
+//! the next chord of those sequences until one completes, a key matches none of them,
+//! or no key is pressed before the timeout.
+//!
+//! Keys that are part of a started sequence are held back from clients. If a sequence
+//! forwards unmatched keys, they're replayed to the focused client when it's cancelled.
+
+use std::time::Duration;
+
+use pinnacle_api_defs::pinnacle::input::v0alpha1::SetKeybindSequenceResponse;
+use smithay::{
+    backend::input::KeyState,
+    input::keyboard::FilterResult,
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::SERIAL_COUNTER,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::error;
+use xkbcommon::xkb::Keysym;
+
+use crate::state::State;
+
+use super::{KeyAction, ModifierMask, ModifierSides};
+
+/// A key along with the modifiers that need to be held while pressing it.
+pub type Chord = (ModifierMask, ModifierSides, Keysym);
+
+/// How long to wait for the next chord if the config doesn't say.
+pub const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Debug)]
+pub struct KeybindSequence {
+    pub chords: Vec<Chord>,
+    /// How long to wait for the next chord
+    pub timeout: Duration,
+    /// Whether the held back keys are sent on to clients when this sequence is cancelled
+    pub forward_unmatched: bool,
+    pub sender: UnboundedSender<Result<SetKeybindSequenceResponse, tonic::Status>>,
+}
+
+/// All keybind sequences and the progress through them.
+#[derive(Debug, Default)]
+pub struct KeybindSequences {
+    sequences: Vec<KeybindSequence>,
+    pending: Option<PendingSequence>,
+    /// Bumped on every step so stale timeouts can be told apart
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct PendingSequence {
+    /// How many chords have been pressed
+    matched: usize,
+    /// Indices of the sequences that start with the pressed chords
+    candidates: Vec<usize>,
+    /// The key codes of the pressed chords, held back from clients
+    keys: Vec<u32>,
+}
+
+/// What pressing a key did to keybind sequences.
+#[derive(Debug)]
+enum SequenceStep {
+    /// The key isn't part of any sequence.
+    Unrelated,
+    /// The key started or continued a sequence.
+    Continued,
+    /// The key completed a sequence.
+    Completed(UnboundedSender<Result<SetKeybindSequenceResponse, tonic::Status>>),
+    /// The key didn't continue the pending sequence.
+    Cancelled {
+        /// The held back keys if they should be replayed
+        replay: Option<Vec<u32>>,
+    },
+}
+
+impl KeybindSequences {
+    /// Add a sequence, replacing one with the same chords.
+    pub fn add(&mut self, sequence: KeybindSequence) {
+        self.sequences.retain(|seq| seq.chords != sequence.chords);
+        self.sequences.push(sequence);
+        // Indices of pending candidates may have shifted
+        self.pending = None;
+    }
+
+    /// Remove all sequences.
+    pub fn clear(&mut self) {
+        self.sequences.clear();
+        self.pending = None;
+    }
+
+    /// Whether a sequence has been started and is waiting for its next chord.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn press(&mut self, key_code: u32, chord_matches: impl Fn(&Chord) -> bool) -> SequenceStep {
+        let (matched, candidates, mut keys) = match self.pending.take() {
+            Some(pending) => (pending.matched, pending.candidates, pending.keys),
+            None => (0, (0..self.sequences.len()).collect(), Vec::new()),
+        };
+
+        let next = candidates
+            .iter()
+            .copied()
+            .filter(|&i| {
+                self.sequences[i]
+                    .chords
+                    .get(matched)
+                    .is_some_and(&chord_matches)
+            })
+            .collect::<Vec<_>>();
+
+        if next.is_empty() {
+            if matched == 0 {
+                return SequenceStep::Unrelated;
+            }
+
+            return SequenceStep::Cancelled {
+                replay: self.forwards_unmatched(&candidates).then_some(keys),
+            };
+        }
+
+        let matched = matched + 1;
+        self.generation = self.generation.wrapping_add(1);
+
+        // A sequence that completes wins over longer ones starting the same way
+        if let Some(&done) = next
+            .iter()
+            .find(|&&i| self.sequences[i].chords.len() == matched)
+        {
+            return SequenceStep::Completed(self.sequences[done].sender.clone());
+        }
+
+        keys.push(key_code);
+        self.pending = Some(PendingSequence {
+            matched,
+            candidates: next,
+            keys,
+        });
+
+        SequenceStep::Continued
+    }
+
+    /// The longest timeout of the sequences that are still possible.
+    fn timeout(&self) -> Duration {
+        self.pending
+            .iter()
+            .flat_map(|pending| pending.candidates.iter())
+            .map(|&i| self.sequences[i].timeout)
+            .max()
+            .unwrap_or(DEFAULT_SEQUENCE_TIMEOUT)
+    }
+
+    /// Whether any of the given sequences forward unmatched keys.
+    fn forwards_unmatched(&self, candidates: &[usize]) -> bool {
+        candidates
+            .iter()
+            .any(|&i| self.sequences[i].forward_unmatched)
+    }
+
+    /// Cancel the pending sequence if no key was pressed since `generation`.
+    ///
+    /// Returns the held back keys if they should be replayed.
+    fn time_out(&mut self, generation: u64) -> Option<Vec<u32>> {
+        if self.generation != generation {
+            return None;
+        }
+
+        let pending = self.pending.take()?;
+        self.forwards_unmatched(&pending.candidates)
+            .then_some(pending.keys)
+    }
+}
+
+impl State {
+    /// Feed a pressed key to keybind sequences.
+    ///
+    /// Returns the action to take, or `None` if the key should be handled as usual.
+    /// A key that cancels a sequence is handled as usual unless the held back keys
+    /// are replayed, in which case it's replayed after them to keep the order.
+    pub(super) fn press_keybind_sequence(
+        &mut self,
+        key_code: u32,
+        mod_mask: ModifierMask,
+        mod_sym: Keysym,
+        raw_sym: Option<Keysym>,
+    ) -> Option<KeyAction> {
+        let input_state = &mut self.pinnacle.input_state;
+        let held_sides = input_state.held_modifier_sides();
+
+        let step = input_state
+            .keybind_sequences
+            .press(key_code, |(mask, sides, sym)| {
+                *mask == mod_mask
+                    && held_sides.contains(*sides)
+                    && (*sym == mod_sym || Some(*sym) == raw_sym)
+            });
+
+        match step {
+            SequenceStep::Unrelated | SequenceStep::Cancelled { replay: None } => None,
+            SequenceStep::Cancelled {
+                replay: Some(mut keys),
+            } => {
+                keys.push(key_code);
+                Some(KeyAction::ReplayKeys(keys))
+            }
+            SequenceStep::Completed(sender) => Some(KeyAction::CallSequenceCallback(sender)),
+            SequenceStep::Continued => {
+                let sequences = &self.pinnacle.input_state.keybind_sequences;
+                let generation = sequences.generation;
+
+                let res = self.pinnacle.loop_handle.insert_source(
+                    Timer::from_duration(sequences.timeout()),
+                    move |_, _, state| {
+                        if let Some(keys) = state
+                            .pinnacle
+                            .input_state
+                            .keybind_sequences
+                            .time_out(generation)
+                        {
+                            state.replay_keys(&keys);
+                        }
+                        TimeoutAction::Drop
+                    },
+                );
+
+                if res.is_err() {
+                    error!("Failed to insert keybind sequence timer");
+                }
+
+                Some(KeyAction::Consume)
+            }
+        }
+    }
+
+    /// Press and release `keys` for the focused client without checking keybinds.
+    ///
+    /// The keys get the modifiers that are held when they're replayed.
+    pub(super) fn replay_keys(&mut self, keys: &[u32]) {
+        let Some(keyboard) = self.pinnacle.seat.get_keyboard() else {
+            return;
+        };
+
+        let time = Duration::from(self.pinnacle.clock.now()).as_millis() as u32;
+
+        for &key in keys {
+            for key_state in [KeyState::Pressed, KeyState::Released] {
+                keyboard.input(
+                    self,
+                    key,
+                    key_state,
+                    SERIAL_COUNTER.next_serial(),
+                    time,
+                    |_, _, _| FilterResult::<()>::Forward,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xkbcommon::xkb::keysyms;
+
+    use super::*;
+
+    // KEY_W, KEY_H, KEY_X
+    const W: u32 = 17;
+    const H: u32 = 35;
+    const X: u32 = 45;
+
+    fn sequences(forward_unmatched: bool) -> KeybindSequences {
+        let (sender, _) = tokio::sync::mpsc::unbounded_channel();
+        let mut sequences = KeybindSequences::default();
+        sequences.add(KeybindSequence {
+            chords: vec![
+                (
+                    ModifierMask::SUPER,
+                    ModifierSides::empty(),
+                    Keysym::new(keysyms::KEY_w),
+                ),
+                (
+                    ModifierMask::empty(),
+                    ModifierSides::empty(),
+                    Keysym::new(keysyms::KEY_h),
+                ),
+            ],
+            timeout: DEFAULT_SEQUENCE_TIMEOUT,
+            forward_unmatched,
+            sender,
+        });
+        sequences
+    }
+
+    fn press(sequences: &mut KeybindSequences, key_code: u32, chord: Chord) -> SequenceStep {
+        sequences.press(key_code, |expected| *expected == chord)
+    }
+
+    #[test]
+    fn cancel_replays_held_back_keys_when_forwarding() {
+        let mut sequences = sequences(true);
+
+        let step = press(
+            &mut sequences,
+            W,
+            (
+                ModifierMask::SUPER,
+                ModifierSides::empty(),
+                Keysym::new(keysyms::KEY_w),
+            ),
+        );
+        assert!(matches!(step, SequenceStep::Continued));
+
+        let step = press(
+            &mut sequences,
+            X,
+            (
+                ModifierMask::empty(),
+                ModifierSides::empty(),
+                Keysym::new(keysyms::KEY_x),
+            ),
+        );
+        let SequenceStep::Cancelled { replay } = step else {
+            panic!("sequence wasn't cancelled: {step:?}");
+        };
+        assert_eq!(replay, Some(vec![W]));
+        assert!(!sequences.is_pending());
+    }
+
+    #[test]
+    fn cancel_drops_held_back_keys_when_not_forwarding() {
+        let mut sequences = sequences(false);
+
+        press(
+            &mut sequences,
+            W,
+            (
+                ModifierMask::SUPER,
+                ModifierSides::empty(),
+                Keysym::new(keysyms::KEY_w),
+            ),
+        );
+        let step = press(
+            &mut sequences,
+            X,
+            (
+                ModifierMask::empty(),
+                ModifierSides::empty(),
+                Keysym::new(keysyms::KEY_x),
+            ),
+        );
+        assert!(matches!(step, SequenceStep::Cancelled { replay: None }));
+    }
+
+    #[test]
+    fn time_out_replays_held_back_keys_when_forwarding() {
+        let mut sequences = sequences(true);
+
+        press(
+            &mut sequences,
+            W,
+            (
+                ModifierMask::SUPER,
+                ModifierSides::empty(),
+                Keysym::new(keysyms::KEY_w),
+            ),
+        );
+        let generation = sequences.generation;
+
+        // A stale timeout does nothing
+        assert_eq!(sequences.time_out(generation.wrapping_sub(1)), None);
+        assert!(sequences.is_pending());
+
+        assert_eq!(sequences.time_out(generation), Some(vec![W]));
+        assert!(!sequences.is_pending());
+    }
+
+    #[test]
+    fn completing_doesnt_replay() {
+        let mut sequences = sequences(true);
+
+        press(
+            &mut sequences,
+            W,
+            (
+                ModifierMask::SUPER,
+                ModifierSides::empty(),
+                Keysym::new(keysyms::KEY_w),
+            ),
+        );
+        let step = press(
+            &mut sequences,
+            H,
+            (
+                ModifierMask::empty(),
+                ModifierSides::empty(),
+                Keysym::new(keysyms::KEY_h),
+            ),
+        );
+        assert!(matches!(step, SequenceStep::Completed(_)));
+        assert!(!sequences.is_pending());
+    }
+}
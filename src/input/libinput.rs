@@ -1,9 +1,16 @@
-use smithay::backend::{input::InputEvent, libinput::LibinputInputBackend};
+use pinnacle_api_defs::pinnacle::{
+    input::v0alpha1::{Device, DeviceCapability},
+    signal::v0alpha1::{InputDeviceAddedResponse, InputDeviceRemovedResponse},
+};
+use smithay::{
+    backend::{input::InputEvent, libinput::LibinputInputBackend},
+    reexports::input,
+};
 
 use crate::state::Pinnacle;
 
 impl Pinnacle {
-    /// Apply current libinput settings to new devices.
+    /// Apply current libinput settings to new devices and keep track of connected ones.
     pub fn apply_libinput_settings(&mut self, event: &InputEvent<LibinputInputBackend>) {
         let mut device = match event {
             InputEvent::DeviceAdded { device } => device.clone(),
@@ -11,19 +18,139 @@ impl Pinnacle {
                 self.input_state
                     .libinput_devices
                     .retain(|dev| dev != device);
+
+                self.signal_state.input_device_removed.signal(|buf| {
+                    buf.push_back(InputDeviceRemovedResponse {
+                        device: Some(device_to_api(device)),
+                    });
+                });
                 return;
             }
             _ => return,
         };
 
+        for setting in self.input_state.libinput_settings.values() {
+            setting(&mut device);
+        }
+        if let Some(settings) = self
+            .input_state
+            .device_libinput_settings
+            .get(&DeviceIdentity::from(&device))
+        {
+            for setting in settings.values() {
+                setting(&mut device);
+            }
+        }
+        self.input_state.pointer_motion_stats.reset();
+
         if self.input_state.libinput_devices.contains(&device) {
             return;
         }
 
-        for setting in self.input_state.libinput_settings.values() {
-            setting(&mut device);
-        }
+        self.signal_state.input_device_added.signal(|buf| {
+            buf.push_back(InputDeviceAddedResponse {
+                device: Some(device_to_api(&device)),
+            });
+        });
 
         self.input_state.libinput_devices.push(device);
     }
 }
+
+/// What identifies a libinput device across replugs.
+///
+/// This can't be the device's sysname as the kernel reuses event nodes,
+/// so a replugged device may get another device's old one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceIdentity {
+    name: String,
+    vendor_id: u32,
+    product_id: u32,
+}
+
+impl From<&input::Device> for DeviceIdentity {
+    fn from(device: &input::Device) -> Self {
+        Self::from(&device_to_api(device))
+    }
+}
+
+impl From<&Device> for DeviceIdentity {
+    fn from(device: &Device) -> Self {
+        Self {
+            name: device.name.clone().unwrap_or_default(),
+            vendor_id: device.vendor_id.unwrap_or_default(),
+            product_id: device.product_id.unwrap_or_default(),
+        }
+    }
+}
+
+/// Convert a libinput device into its api representation.
+pub fn device_to_api(device: &input::Device) -> Device {
+    let capabilities = [
+        (
+            input::DeviceCapability::Keyboard,
+            DeviceCapability::Keyboard,
+        ),
+        (input::DeviceCapability::Pointer, DeviceCapability::Pointer),
+        (input::DeviceCapability::Touch, DeviceCapability::Touch),
+        (
+            input::DeviceCapability::TabletTool,
+            DeviceCapability::TabletTool,
+        ),
+        (
+            input::DeviceCapability::TabletPad,
+            DeviceCapability::TabletPad,
+        ),
+        (input::DeviceCapability::Gesture, DeviceCapability::Gesture),
+        (input::DeviceCapability::Switch, DeviceCapability::Switch),
+    ]
+    .into_iter()
+    .filter(|(cap, _)| device.has_capability(*cap))
+    .map(|(_, cap)| cap as i32)
+    .collect();
+
+    Device {
+        id: Some(device.sysname().to_string()),
+        name: Some(device.name().to_string()),
+        vendor_id: Some(device.id_vendor()),
+        product_id: Some(device.id_product()),
+        capabilities,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_device(id: &str, name: &str, vendor_id: u32, product_id: u32) -> Device {
+        Device {
+            id: Some(id.to_string()),
+            name: Some(name.to_string()),
+            vendor_id: Some(vendor_id),
+            product_id: Some(product_id),
+            capabilities: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn replugged_device_keeps_its_identity() {
+        let trackpad = api_device("event5", "Trackpad", 0x046d, 0x4024);
+        let replugged_trackpad = api_device("event7", "Trackpad", 0x046d, 0x4024);
+
+        assert_eq!(
+            DeviceIdentity::from(&trackpad),
+            DeviceIdentity::from(&replugged_trackpad)
+        );
+    }
+
+    #[test]
+    fn reused_event_node_is_another_device() {
+        let trackpad = api_device("event5", "Trackpad", 0x046d, 0x4024);
+        let mouse = api_device("event5", "Mouse", 0x046d, 0xc52b);
+
+        assert_ne!(
+            DeviceIdentity::from(&trackpad),
+            DeviceIdentity::from(&mouse)
+        );
+    }
+}
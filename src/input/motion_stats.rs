@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Pointer acceleration statistics.
+//!
+//! Recent pointer motion is recorded both before and after libinput applies acceleration
+//! so configs can see how their acceleration settings actually behave.
+
+use std::{collections::VecDeque, time::Duration};
+
+use smithay::utils::{Logical, Point};
+
+/// How far back motion is taken into account.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Raw and accelerated pointer motion over the last [`WINDOW`].
+#[derive(Debug, Default)]
+pub struct PointerMotionStats {
+    /// Motion events as (time, raw distance, accelerated distance), oldest first
+    samples: VecDeque<(Duration, f64, f64)>,
+}
+
+impl PointerMotionStats {
+    /// Record a motion event that happened at `time`.
+    pub fn push_sample(
+        &mut self,
+        time: Duration,
+        delta_unaccel: Point<f64, Logical>,
+        delta: Point<f64, Logical>,
+    ) {
+        self.samples.push_back((
+            time,
+            delta_unaccel.x.hypot(delta_unaccel.y),
+            delta.x.hypot(delta.y),
+        ));
+
+        while self
+            .samples
+            .front()
+            .is_some_and(|(sample_time, _, _)| time.saturating_sub(*sample_time) > WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Forget all recorded motion.
+    ///
+    /// This should be called when acceleration settings change so old motion
+    /// doesn't skew the numbers.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Get the total raw and accelerated distance the pointer moved, in that order.
+    pub fn totals(&self) -> (f64, f64) {
+        self.samples
+            .iter()
+            .fold((0.0, 0.0), |(raw, accel), (_, sample_raw, sample_accel)| {
+                (raw + sample_raw, accel + sample_accel)
+            })
+    }
+
+    /// Get how much the pointer was accelerated, as accelerated distance over raw distance.
+    ///
+    /// Returns `None` if the pointer hasn't moved recently.
+    pub fn ratio(&self) -> Option<f64> {
+        let (raw, accel) = self.totals();
+        (raw > 0.0).then(|| accel / raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_is_accel_over_raw() {
+        let mut stats = PointerMotionStats::default();
+        stats.push_sample(Duration::ZERO, (3.0, 4.0).into(), (6.0, 8.0).into());
+        stats.push_sample(
+            Duration::from_millis(10),
+            (0.0, 5.0).into(),
+            (0.0, 10.0).into(),
+        );
+
+        assert_eq!(stats.totals(), (10.0, 20.0));
+        assert_eq!(stats.ratio(), Some(2.0));
+    }
+
+    #[test]
+    fn old_samples_are_dropped() {
+        let mut stats = PointerMotionStats::default();
+        stats.push_sample(Duration::ZERO, (10.0, 0.0).into(), (10.0, 0.0).into());
+        stats.push_sample(WINDOW * 2, (1.0, 0.0).into(), (2.0, 0.0).into());
+
+        assert_eq!(stats.totals(), (1.0, 2.0));
+    }
+
+    #[test]
+    fn no_motion_has_no_ratio() {
+        assert_eq!(PointerMotionStats::default().ratio(), None);
+    }
+}
@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Pointer motion prediction.
+//!
+//! When enabled, the cursor is drawn slightly ahead of where the pointer actually is,
+//! extrapolated from its recent velocity. This hides some of the latency between
+//! moving the mouse and the cursor moving on screen.
+//!
+//! Prediction only affects where the cursor is drawn; input is always delivered
+//! at the real pointer location.
+//!
+//! Once the pointer stops, a timer forgets its motion and renders the cursor again
+//! so it doesn't stay drawn ahead of the real location.
+
+use std::{collections::VecDeque, time::Duration};
+
+use smithay::{
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::{Logical, Point},
+};
+use tracing::error;
+
+use crate::state::{Pinnacle, State};
+
+/// How far ahead in time the cursor is predicted.
+const LOOKAHEAD: Duration = Duration::from_millis(8);
+/// How far back motion samples are used to compute the pointer's velocity.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(32);
+/// If the pointer hasn't moved for this long, it's considered stopped and isn't predicted.
+const STOP_THRESHOLD: Duration = Duration::from_millis(24);
+/// The furthest the predicted location can be from the real one, in logical pixels.
+///
+/// This prevents the cursor from overshooting wildly on fast flicks.
+const MAX_DISTANCE: f64 = 24.0;
+
+/// Recent pointer motion used to predict where the pointer will be.
+#[derive(Debug, Default)]
+pub struct PointerPrediction {
+    /// Whether or not prediction is enabled
+    pub enabled: bool,
+    /// Pointer locations along with when they were reached, oldest first
+    samples: VecDeque<(Duration, Point<f64, Logical>)>,
+    /// Whether a timer is waiting for the pointer to stop
+    stop_timer_armed: bool,
+}
+
+impl PointerPrediction {
+    /// Record that the pointer moved to `location` at `time`.
+    pub fn push_sample(&mut self, time: Duration, location: Point<f64, Logical>) {
+        if !self.enabled {
+            return;
+        }
+
+        self.samples.push_back((time, location));
+
+        while self
+            .samples
+            .front()
+            .is_some_and(|(sample_time, _)| time.saturating_sub(*sample_time) > VELOCITY_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Whether there is recorded motion to predict from.
+    pub fn is_moving(&self) -> bool {
+        !self.samples.is_empty()
+    }
+
+    /// How long until the pointer counts as stopped at `now`, or `None` if it already has.
+    fn time_until_stopped(&self, now: Duration) -> Option<Duration> {
+        let (last_time, _) = self.samples.back()?;
+        let stopped_at = *last_time + STOP_THRESHOLD;
+        (stopped_at > now).then(|| stopped_at - now)
+    }
+
+    /// Forget all recorded motion.
+    ///
+    /// This should be called when the pointer jumps, e.g. when it's warped.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Get the location the cursor should be drawn at `now`, given its real `location`.
+    pub fn predict(&self, now: Duration, location: Point<f64, Logical>) -> Point<f64, Logical> {
+        if !self.enabled {
+            return location;
+        }
+
+        let (Some((first_time, first_loc)), Some((last_time, last_loc))) =
+            (self.samples.front(), self.samples.back())
+        else {
+            return location;
+        };
+
+        if now.saturating_sub(*last_time) > STOP_THRESHOLD {
+            return location;
+        }
+
+        let elapsed = last_time.saturating_sub(*first_time).as_secs_f64();
+        if elapsed <= 0.0 {
+            return location;
+        }
+
+        let velocity = (*last_loc - *first_loc).downscale(elapsed);
+        let mut offset = velocity.upscale(LOOKAHEAD.as_secs_f64());
+
+        let distance = offset.x.hypot(offset.y);
+        if distance > MAX_DISTANCE {
+            offset = offset.downscale(distance / MAX_DISTANCE);
+        }
+
+        location + offset
+    }
+}
+
+impl State {
+    /// Record pointer motion to `location` for prediction.
+    ///
+    /// This also makes sure the cursor is rendered at its real location once the pointer stops.
+    pub fn push_pointer_prediction_sample(&mut self, location: Point<f64, Logical>) {
+        let prediction = &mut self.pinnacle.input_state.pointer_prediction;
+        if !prediction.enabled {
+            return;
+        }
+
+        prediction.push_sample(self.pinnacle.clock.now().into(), location);
+
+        if prediction.stop_timer_armed {
+            return;
+        }
+
+        let res = self.pinnacle.loop_handle.insert_source(
+            Timer::from_duration(STOP_THRESHOLD),
+            |_, _, state| {
+                let now = state.pinnacle.clock.now().into();
+                let prediction = &mut state.pinnacle.input_state.pointer_prediction;
+
+                // Newer motion came in, so wait for that to stop instead
+                if let Some(remaining) = prediction.time_until_stopped(now) {
+                    return TimeoutAction::ToDuration(remaining);
+                }
+
+                prediction.stop_timer_armed = false;
+                prediction.reset();
+                state.schedule_render_after_pointer_motion();
+
+                TimeoutAction::Drop
+            },
+        );
+
+        match res {
+            Ok(_) => {
+                self.pinnacle
+                    .input_state
+                    .pointer_prediction
+                    .stop_timer_armed = true
+            }
+            Err(err) => error!("Failed to insert pointer prediction timer: {err}"),
+        }
+    }
+}
+
+impl Pinnacle {
+    /// Get the location the cursor should be drawn at, taking prediction into account.
+    pub fn cursor_render_location(&self) -> Point<f64, Logical> {
+        let location = self
+            .seat
+            .get_pointer()
+            .map(|ptr| ptr.current_location())
+            .unwrap_or((0.0, 0.0).into());
+
+        self.input_state
+            .pointer_prediction
+            .predict(self.clock.now().into(), location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn disabled_prediction_returns_real_location() {
+        let mut prediction = PointerPrediction::default();
+        prediction.push_sample(ms(0), (0.0, 0.0).into());
+        prediction.push_sample(ms(8), (8.0, 0.0).into());
+
+        assert_eq!(
+            prediction.predict(ms(8), (8.0, 0.0).into()),
+            (8.0, 0.0).into()
+        );
+    }
+
+    #[test]
+    fn prediction_extrapolates_velocity() {
+        let mut prediction = PointerPrediction {
+            enabled: true,
+            ..Default::default()
+        };
+        // 1 pixel per millisecond
+        prediction.push_sample(ms(0), (0.0, 0.0).into());
+        prediction.push_sample(ms(8), (8.0, 0.0).into());
+
+        assert_eq!(
+            prediction.predict(ms(8), (8.0, 0.0).into()),
+            (16.0, 0.0).into()
+        );
+    }
+
+    #[test]
+    fn prediction_is_bounded() {
+        let mut prediction = PointerPrediction {
+            enabled: true,
+            ..Default::default()
+        };
+        prediction.push_sample(ms(0), (0.0, 0.0).into());
+        prediction.push_sample(ms(1), (0.0, 1000.0).into());
+
+        let predicted = prediction.predict(ms(1), (0.0, 1000.0).into());
+        assert!((predicted.y - 1000.0 - MAX_DISTANCE).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn time_until_stopped_counts_from_last_sample() {
+        let mut prediction = PointerPrediction {
+            enabled: true,
+            ..Default::default()
+        };
+        assert_eq!(prediction.time_until_stopped(ms(0)), None);
+
+        prediction.push_sample(ms(0), (0.0, 0.0).into());
+        prediction.push_sample(ms(8), (8.0, 0.0).into());
+
+        assert_eq!(
+            prediction.time_until_stopped(ms(10)),
+            Some(ms(8) + STOP_THRESHOLD - ms(10))
+        );
+        assert_eq!(prediction.time_until_stopped(ms(8) + STOP_THRESHOLD), None);
+    }
+
+    #[test]
+    fn stopped_pointer_is_not_predicted() {
+        let mut prediction = PointerPrediction {
+            enabled: true,
+            ..Default::default()
+        };
+        prediction.push_sample(ms(0), (0.0, 0.0).into());
+        prediction.push_sample(ms(8), (8.0, 0.0).into());
+
+        assert_eq!(
+            prediction.predict(ms(100), (8.0, 0.0).into()),
+            (8.0, 0.0).into()
+        );
+    }
+}
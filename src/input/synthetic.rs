@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Synthetic input.
+//!
+//! Input can be injected through the API so configs can exercise keybinds and focus
+//! in headless tests. Injected events go through [`State::process_input_event`] like
+//! real ones, but come from a [`SyntheticInput`] backend instead of libinput or winit.
+//!
+//! Because any client with access to the socket could then control the session,
+//! injection is only allowed when [`INPUT_INJECTION_ENV`] is set.
+
+use std::{path::PathBuf, time::Duration};
+
+use smithay::{
+    backend::input::{
+        AbsolutePositionEvent, ButtonState, Device, DeviceCapability, Event, InputBackend,
+        InputEvent, KeyState, KeyboardKeyEvent, PointerButtonEvent, PointerMotionAbsoluteEvent,
+        UnusedEvent,
+    },
+    utils::{Logical, Point},
+};
+
+use crate::state::State;
+
+/// The environment variable that allows injecting input when set.
+pub const INPUT_INJECTION_ENV: &str = "PINNACLE_ENABLE_INPUT_INJECTION";
+
+/// Whether the environment allows injecting input.
+///
+/// This should only be checked on startup; see [`InputState::injection_enabled`].
+///
+/// [`InputState::injection_enabled`]: crate::input::InputState::injection_enabled
+pub fn input_injection_enabled() -> bool {
+    std::env::var_os(INPUT_INJECTION_ENV).is_some()
+}
+
+/// The input backend for injected events.
+#[derive(Debug)]
+pub struct SyntheticInput;
+
+/// The device all injected events come from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntheticDevice;
+
+impl Device for SyntheticDevice {
+    fn id(&self) -> String {
+        "pinnacle-synthetic-input".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Pinnacle Synthetic Input".to_string()
+    }
+
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        matches!(
+            capability,
+            DeviceCapability::Keyboard | DeviceCapability::Pointer
+        )
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn syspath(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// An injected key press or release.
+#[derive(Debug)]
+pub struct SyntheticKeyEvent {
+    time: Duration,
+    key_code: u32,
+    state: KeyState,
+}
+
+impl Event<SyntheticInput> for SyntheticKeyEvent {
+    fn time(&self) -> u64 {
+        self.time.as_micros() as u64
+    }
+
+    fn device(&self) -> SyntheticDevice {
+        SyntheticDevice
+    }
+}
+
+impl KeyboardKeyEvent<SyntheticInput> for SyntheticKeyEvent {
+    fn key_code(&self) -> u32 {
+        self.key_code
+    }
+
+    fn state(&self) -> KeyState {
+        self.state
+    }
+
+    fn count(&self) -> u32 {
+        match self.state {
+            KeyState::Pressed => 1,
+            KeyState::Released => 0,
+        }
+    }
+}
+
+/// An injected pointer button press or release.
+#[derive(Debug)]
+pub struct SyntheticButtonEvent {
+    time: Duration,
+    button: u32,
+    state: ButtonState,
+}
+
+impl Event<SyntheticInput> for SyntheticButtonEvent {
+    fn time(&self) -> u64 {
+        self.time.as_micros() as u64
+    }
+
+    fn device(&self) -> SyntheticDevice {
+        SyntheticDevice
+    }
+}
+
+impl PointerButtonEvent<SyntheticInput> for SyntheticButtonEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.state
+    }
+}
+
+/// An injected pointer motion to a location relative to the first output,
+/// which is where absolute motion is mapped.
+#[derive(Debug)]
+pub struct SyntheticMotionAbsoluteEvent {
+    time: Duration,
+    location: Point<f64, Logical>,
+}
+
+impl Event<SyntheticInput> for SyntheticMotionAbsoluteEvent {
+    fn time(&self) -> u64 {
+        self.time.as_micros() as u64
+    }
+
+    fn device(&self) -> SyntheticDevice {
+        SyntheticDevice
+    }
+}
+
+impl AbsolutePositionEvent<SyntheticInput> for SyntheticMotionAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.location.x
+    }
+
+    fn y(&self) -> f64 {
+        self.location.y
+    }
+
+    // The location is already in logical coordinates
+    fn x_transformed(&self, _width: i32) -> f64 {
+        self.location.x
+    }
+
+    fn y_transformed(&self, _height: i32) -> f64 {
+        self.location.y
+    }
+}
+
+impl PointerMotionAbsoluteEvent<SyntheticInput> for SyntheticMotionAbsoluteEvent {}
+
+impl InputBackend for SyntheticInput {
+    type Device = SyntheticDevice;
+    type KeyboardKeyEvent = SyntheticKeyEvent;
+    type PointerAxisEvent = UnusedEvent;
+    type PointerButtonEvent = SyntheticButtonEvent;
+    type PointerMotionEvent = UnusedEvent;
+    type PointerMotionAbsoluteEvent = SyntheticMotionAbsoluteEvent;
+    type GestureSwipeBeginEvent = UnusedEvent;
+    type GestureSwipeUpdateEvent = UnusedEvent;
+    type GestureSwipeEndEvent = UnusedEvent;
+    type GesturePinchBeginEvent = UnusedEvent;
+    type GesturePinchUpdateEvent = UnusedEvent;
+    type GesturePinchEndEvent = UnusedEvent;
+    type GestureHoldBeginEvent = UnusedEvent;
+    type GestureHoldEndEvent = UnusedEvent;
+    type TouchDownEvent = UnusedEvent;
+    type TouchUpEvent = UnusedEvent;
+    type TouchMotionEvent = UnusedEvent;
+    type TouchCancelEvent = UnusedEvent;
+    type TouchFrameEvent = UnusedEvent;
+    type TabletToolAxisEvent = UnusedEvent;
+    type TabletToolProximityEvent = UnusedEvent;
+    type TabletToolTipEvent = UnusedEvent;
+    type TabletToolButtonEvent = UnusedEvent;
+    type SwitchToggleEvent = UnusedEvent;
+    type SpecialEvent = UnusedEvent;
+}
+
+impl State {
+    /// Inject a press or release of the key with the given evdev keycode.
+    pub fn inject_key(&mut self, key_code: u32, pressed: bool) {
+        let event = SyntheticKeyEvent {
+            time: self.pinnacle.clock.now().into(),
+            key_code,
+            state: if pressed { KeyState::Pressed } else { KeyState::Released },
+        };
+
+        self.process_input_event(InputEvent::<SyntheticInput>::Keyboard { event });
+    }
+
+    /// Inject a press or release of the pointer button with the given evdev code.
+    pub fn inject_pointer_button(&mut self, button: u32, pressed: bool) {
+        let event = SyntheticButtonEvent {
+            time: self.pinnacle.clock.now().into(),
+            button,
+            state: if pressed {
+                ButtonState::Pressed
+            } else {
+                ButtonState::Released
+            },
+        };
+
+        self.process_input_event(InputEvent::<SyntheticInput>::PointerButton { event });
+    }
+
+    /// Inject pointer motion to `location` in the global space.
+    pub fn inject_pointer_motion(&mut self, location: Point<f64, Logical>) {
+        let Some(first_output_loc) = self
+            .pinnacle
+            .space
+            .outputs()
+            .next()
+            .and_then(|output| self.pinnacle.space.output_geometry(output))
+            .map(|geo| geo.loc)
+        else {
+            return;
+        };
+
+        let event = SyntheticMotionAbsoluteEvent {
+            time: self.pinnacle.clock.now().into(),
+            location: location - first_output_loc.to_f64(),
+        };
+
+        self.process_input_event(InputEvent::<SyntheticInput>::PointerMotionAbsolute { event });
+    }
+}
@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Tablet tool input.
+//!
+//! Tablet tools move the pointer like an absolute pointing device. Clients that bind
+//! the tablet protocol additionally receive proximity, tip, pressure, tilt, and the
+//! rest of the tool's axes.
+//!
+//! By default a tablet's input area maps onto the first output, the same as absolute
+//! pointer motion. The config can map it onto a region of a specific output instead.
+
+use std::collections::HashMap;
+
+use smithay::{
+    backend::input::{
+        Device, DeviceCapability, Event, InputBackend, ProximityState, TabletToolButtonEvent,
+        TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState,
+    },
+    input::pointer::MotionEvent,
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
+    wayland::{
+        seat::WaylandFocus,
+        tablet_manager::{TabletDescriptor, TabletSeatTrait},
+    },
+};
+
+use crate::{
+    output::OutputName,
+    state::{Pinnacle, State, WithState},
+};
+
+/// Where a tablet's input area is mapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabletMapping {
+    pub output_name: OutputName,
+    /// The mapped region relative to the output, or the whole output if `None`
+    pub region: Option<Rectangle<i32, Logical>>,
+}
+
+/// Tablet mappings keyed by device id, with `None` applying to all other tablets.
+pub type TabletMappings = HashMap<Option<String>, TabletMapping>;
+
+impl Pinnacle {
+    /// Get the area in the global space that the tablet with the given device id maps onto.
+    fn tablet_area(&self, device_id: &str) -> Option<Rectangle<i32, Logical>> {
+        let mappings = &self.input_state.tablet_mappings;
+        let mapping = mappings
+            .get(&Some(device_id.to_string()))
+            .or_else(|| mappings.get(&None));
+
+        let mapped_area = mapping.and_then(|mapping| {
+            let output = mapping.output_name.output(self)?;
+            let output_geo = self.space.output_geometry(&output)?;
+            Some(match mapping.region {
+                Some(region) => {
+                    Rectangle::from_loc_and_size(output_geo.loc + region.loc, region.size)
+                }
+                None => output_geo,
+            })
+        });
+
+        mapped_area.or_else(|| {
+            let output = self.space.outputs().next()?;
+            self.space.output_geometry(output)
+        })
+    }
+}
+
+impl State {
+    pub(super) fn tablet_device_added<I: InputBackend>(&mut self, device: &I::Device) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            self.pinnacle.seat.tablet_seat().add_tablet::<State>(
+                &self.pinnacle.display_handle,
+                &TabletDescriptor::from(device),
+            );
+        }
+    }
+
+    pub(super) fn tablet_device_removed<I: InputBackend>(&mut self, device: &I::Device) {
+        if device.has_capability(DeviceCapability::TabletTool) {
+            let tablet_seat = self.pinnacle.seat.tablet_seat();
+            tablet_seat.remove_tablet(&TabletDescriptor::from(device));
+
+            // Tools can't be used without a tablet
+            if tablet_seat.count_tablets() == 0 {
+                tablet_seat.clear_tools();
+            }
+        }
+    }
+
+    /// Move the pointer to where the tool is.
+    ///
+    /// Returns the new location along with the surface under it.
+    fn tablet_tool_motion<I: InputBackend>(
+        &mut self,
+        event: &(impl TabletToolEvent<I> + Event<I>),
+    ) -> Option<(
+        Point<f64, Logical>,
+        Option<(WlSurface, Point<f64, Logical>)>,
+    )> {
+        let pointer = self.pinnacle.seat.get_pointer()?;
+        let area = self.pinnacle.tablet_area(&event.device().id())?;
+
+        let location = event.position_transformed(area.size) + area.loc.to_f64();
+
+        if let Some(output) = self.pinnacle.space.output_under(location).next().cloned() {
+            self.pinnacle.output_focus_stack.set_focus(output);
+        }
+
+        let focus = self.pinnacle.pointer_focus_target_under(location);
+
+        pointer.motion(
+            self,
+            focus.clone(),
+            &MotionEvent {
+                location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time: event.time_msec(),
+            },
+        );
+        pointer.frame(self);
+
+        self.schedule_render_after_pointer_motion();
+
+        let surface_under =
+            focus.and_then(|(target, loc)| Some((target.wl_surface()?.into_owned(), loc.to_f64())));
+
+        Some((location, surface_under))
+    }
+
+    pub(super) fn tablet_tool_axis<I: InputBackend>(&mut self, event: I::TabletToolAxisEvent) {
+        let Some((location, surface_under)) = self.tablet_tool_motion(&event) else {
+            return;
+        };
+
+        let tablet_seat = self.pinnacle.seat.tablet_seat();
+        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&event.device()));
+        let tool = tablet_seat.get_tool(&event.tool());
+
+        let (Some(tablet), Some(tool)) = (tablet, tool) else {
+            return;
+        };
+
+        if event.pressure_has_changed() {
+            tool.pressure(event.pressure());
+        }
+        if event.distance_has_changed() {
+            tool.distance(event.distance());
+        }
+        if event.tilt_has_changed() {
+            tool.tilt(event.tilt());
+        }
+        if event.slider_has_changed() {
+            tool.slider_position(event.slider_position());
+        }
+        if event.rotation_has_changed() {
+            tool.rotation(event.rotation());
+        }
+        if event.wheel_has_changed() {
+            tool.wheel(event.wheel_delta(), event.wheel_delta_discrete());
+        }
+
+        tool.motion(
+            location,
+            surface_under,
+            &tablet,
+            SERIAL_COUNTER.next_serial(),
+            event.time_msec(),
+        );
+    }
+
+    pub(super) fn tablet_tool_proximity<I: InputBackend>(
+        &mut self,
+        event: I::TabletToolProximityEvent,
+    ) {
+        let tablet_seat = self.pinnacle.seat.tablet_seat();
+        tablet_seat.add_tool::<State>(&self.pinnacle.display_handle, &event.tool());
+
+        let Some((location, surface_under)) = self.tablet_tool_motion(&event) else {
+            return;
+        };
+
+        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&event.device()));
+        let tool = tablet_seat.get_tool(&event.tool());
+
+        let (Some(tablet), Some(tool)) = (tablet, tool) else {
+            return;
+        };
+
+        match event.state() {
+            ProximityState::In => {
+                if let Some(surface_under) = surface_under {
+                    tool.proximity_in(
+                        location,
+                        surface_under,
+                        &tablet,
+                        SERIAL_COUNTER.next_serial(),
+                        event.time_msec(),
+                    );
+                }
+            }
+            ProximityState::Out => tool.proximity_out(event.time_msec()),
+        }
+    }
+
+    pub(super) fn tablet_tool_tip<I: InputBackend>(&mut self, event: I::TabletToolTipEvent) {
+        let Some(tool) = self.pinnacle.seat.tablet_seat().get_tool(&event.tool()) else {
+            return;
+        };
+
+        match event.tip_state() {
+            TabletToolTipState::Down => {
+                let serial = SERIAL_COUNTER.next_serial();
+                tool.tip_down(serial, event.time_msec());
+                self.focus_window_under_tablet_tool();
+            }
+            TabletToolTipState::Up => tool.tip_up(event.time_msec()),
+        }
+    }
+
+    pub(super) fn tablet_tool_button<I: InputBackend>(&mut self, event: I::TabletToolButtonEvent) {
+        let Some(tool) = self.pinnacle.seat.tablet_seat().get_tool(&event.tool()) else {
+            return;
+        };
+
+        tool.button(
+            event.button(),
+            event.button_state(),
+            SERIAL_COUNTER.next_serial(),
+            event.time_msec(),
+        );
+    }
+
+    /// Raise and focus the window under the pointer, like clicking on it would.
+    fn focus_window_under_tablet_tool(&mut self) {
+        let (Some(pointer), Some(keyboard)) = (
+            self.pinnacle.seat.get_pointer(),
+            self.pinnacle.seat.get_keyboard(),
+        ) else {
+            return;
+        };
+
+        let Some((focus, _)) = self
+            .pinnacle
+            .pointer_focus_target_under(pointer.current_location())
+        else {
+            return;
+        };
+
+        let Some(window) = focus.window_for(self) else {
+            return;
+        };
+
+        if window.is_x11_override_redirect() || focus.popup_for(self).is_some() {
+            return;
+        }
+
+        self.pinnacle.raise_window(window.clone(), true);
+        if let Some(output) = window.output(&self.pinnacle) {
+            output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+        }
+
+        keyboard.set_focus(
+            self,
+            focus.to_keyboard_focus_target(self),
+            SERIAL_COUNTER.next_serial(),
+        );
+
+        for window in self.pinnacle.space.elements() {
+            if let Some(toplevel) = window.toplevel() {
+                toplevel.send_configure();
+            }
+        }
+    }
+}
@@ -8,7 +8,7 @@ use pinnacle_api_defs::pinnacle::layout::v0alpha1::{layout_request::Geometries,
 use smithay::{
     desktop::{layer_map_for_output, WindowSurface},
     output::Output,
-    utils::{Logical, Rectangle, Serial},
+    utils::{Logical, Rectangle, Serial, Size},
 };
 use tokio::sync::mpsc::UnboundedSender;
 use tonic::Status;
@@ -26,7 +26,42 @@ use crate::{
 
 use self::transaction::LayoutTransaction;
 
+/// Gaps applied around tiled windows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Gaps {
+    /// The gap between adjacent tiled windows.
+    pub inner: u32,
+    /// The gap between tiled windows and the edges of the output's usable area.
+    pub outer: u32,
+}
+
 impl Pinnacle {
+    /// Get the gaps for the given output, which are those of its first active tag.
+    fn output_gaps(&self, output: &Output) -> Gaps {
+        output.with_state(|state| {
+            state
+                .focused_tags()
+                .next()
+                .map(|tag| tag.gaps())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Get the area tiled windows are laid out in, relative to the output.
+    ///
+    /// This is the area not covered by exclusive zones, inset by the outer gap.
+    fn tiling_area(&self, output: &Output) -> Rectangle<i32, Logical> {
+        let mut zone = layer_map_for_output(output).non_exclusive_zone();
+
+        let outer = self.output_gaps(output).outer as i32;
+        let inset_w = i32::min(outer, (zone.size.w - 1) / 2).max(0);
+        let inset_h = i32::min(outer, (zone.size.h - 1) / 2).max(0);
+
+        zone.loc += (inset_w, inset_h).into();
+        zone.size -= (inset_w * 2, inset_h * 2).into();
+        zone
+    }
+
     fn update_windows_with_geometries(
         &mut self,
         output: &Output,
@@ -38,7 +73,9 @@ impl Pinnacle {
                 .iter()
                 .filter(|win| !win.is_x11_override_redirect())
                 .filter(|win| {
-                    win.with_state(|state| state.tags.iter().any(|tg| focused_tags.contains(&tg)))
+                    win.with_state(|state| {
+                        !state.minimized && state.tags.iter().any(|tg| focused_tags.contains(&tg))
+                    })
                 })
                 .cloned()
                 .collect::<Vec<_>>()
@@ -60,8 +97,12 @@ impl Pinnacle {
             map.non_exclusive_zone()
         };
 
-        let mut zipped = tiled_windows.zip(geometries.into_iter().map(|mut geo| {
-            geo.loc += output_geo.loc + non_exclusive_geo.loc;
+        let tiling_area = self.tiling_area(output);
+        let inner_gap = self.output_gaps(output).inner as i32;
+
+        let mut zipped = tiled_windows.zip(geometries.into_iter().map(|geo| {
+            let mut geo = inset_by_inner_gap(geo, tiling_area.size, inner_gap);
+            geo.loc += output_geo.loc + tiling_area.loc;
             geo
         }));
 
@@ -82,15 +123,22 @@ impl Pinnacle {
                     window.change_geometry(output_geo);
                 }
                 FullscreenOrMaximized::Maximized => {
-                    window.change_geometry(Rectangle::from_loc_and_size(
-                        output_geo.loc + non_exclusive_geo.loc,
-                        non_exclusive_geo.size,
+                    let covers_exclusive_zones =
+                        window.with_state(|state| state.maximize_covers_exclusive_zones);
+                    window.change_geometry(maximized_geometry(
+                        output_geo,
+                        non_exclusive_geo,
+                        covers_exclusive_zones,
                     ));
                 }
                 FullscreenOrMaximized::Neither => {
                     if let FloatingOrTiled::Floating(rect) =
                         window.with_state(|state| state.floating_or_tiled)
                     {
+                        let rect = self.clamp_floating_rect(rect);
+                        window.with_state_mut(|state| {
+                            state.floating_or_tiled = FloatingOrTiled::Floating(rect);
+                        });
                         window.change_geometry(rect);
                     }
                 }
@@ -133,6 +181,57 @@ impl Pinnacle {
     }
 }
 
+/// Get the geometry of a maximized window on an output at `output_geo`.
+///
+/// Maximized windows fill `non_exclusive_zone`, which is relative to the output, so they
+/// don't cover panels. With `covers_exclusive_zones` they fill the whole output instead.
+fn maximized_geometry(
+    output_geo: Rectangle<i32, Logical>,
+    non_exclusive_zone: Rectangle<i32, Logical>,
+    covers_exclusive_zones: bool,
+) -> Rectangle<i32, Logical> {
+    if covers_exclusive_zones {
+        output_geo
+    } else {
+        Rectangle::from_loc_and_size(
+            output_geo.loc + non_exclusive_zone.loc,
+            non_exclusive_zone.size,
+        )
+    }
+}
+
+/// Inset a tiled geometry by half the inner gap on every side that doesn't touch
+/// the edge of the tiling area, so adjacent windows end up `inner_gap` apart.
+fn inset_by_inner_gap(
+    geo: Rectangle<i32, Logical>,
+    area_size: Size<i32, Logical>,
+    inner_gap: i32,
+) -> Rectangle<i32, Logical> {
+    let leading = inner_gap / 2;
+    let trailing = inner_gap - leading;
+
+    let left = if geo.loc.x > 0 { leading } else { 0 };
+    let top = if geo.loc.y > 0 { leading } else { 0 };
+    let right = if geo.loc.x + geo.size.w < area_size.w {
+        trailing
+    } else {
+        0
+    };
+    let bottom = if geo.loc.y + geo.size.h < area_size.h {
+        trailing
+    } else {
+        0
+    };
+
+    Rectangle::from_loc_and_size(
+        (geo.loc.x + left, geo.loc.y + top),
+        (
+            i32::max(geo.size.w - left - right, 1),
+            i32::max(geo.size.h - top - bottom, 1),
+        ),
+    )
+}
+
 /// A monotonically increasing identifier for layout requests.
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct LayoutRequestId(u32);
@@ -167,7 +266,9 @@ impl Pinnacle {
                 .iter()
                 .filter(|win| !win.is_x11_override_redirect())
                 .filter(|win| {
-                    win.with_state(|state| state.tags.iter().any(|tg| focused_tags.contains(&tg)))
+                    win.with_state(|state| {
+                        !state.minimized && state.tags.iter().any(|tg| focused_tags.contains(&tg))
+                    })
                 })
                 .cloned()
                 .collect::<Vec<_>>()
@@ -184,9 +285,8 @@ impl Pinnacle {
             .collect::<Vec<_>>();
 
         let (output_width, output_height) = {
-            let map = layer_map_for_output(output);
-            let zone = map.non_exclusive_zone();
-            (zone.size.w, zone.size.h)
+            let area = self.tiling_area(output);
+            (area.size.w, area.size.h)
         };
 
         let window_ids = windows
@@ -194,8 +294,14 @@ impl Pinnacle {
             .map(|win| win.with_state(|state| state.id.0))
             .collect::<Vec<_>>();
 
-        let tag_ids =
-            output.with_state(|state| state.focused_tags().map(|tag| tag.id().0).collect());
+        let (tag_ids, master_factor, layout) = output.with_state(|state| {
+            let first_tag = state.focused_tags().next();
+            (
+                state.focused_tags().map(|tag| tag.id().0).collect(),
+                first_tag.and_then(|tag| tag.master_factor()),
+                first_tag.and_then(|tag| tag.layout()),
+            )
+        });
 
         self.layout_state
             .pending_requests
@@ -208,6 +314,8 @@ impl Pinnacle {
             tag_ids,
             output_width: Some(output_width as u32),
             output_height: Some(output_height as u32),
+            master_factor,
+            layout,
         }));
 
         Some(id)
@@ -220,6 +328,7 @@ impl State {
             request_id: Some(request_id),
             output_name: Some(output_name),
             geometries,
+            master_factor: layout_master_factor,
         } = geometries
         else {
             anyhow::bail!("One or more `geometries` fields were None");
@@ -261,6 +370,10 @@ impl State {
             anyhow::bail!("Attempted to layout but one or more dimensions were null");
         };
 
+        if let Some(tag) = output.with_state(|state| state.focused_tags().next().cloned()) {
+            tag.set_layout_master_factor(layout_master_factor);
+        }
+
         self.pinnacle.layout_state.pending_requests.remove(&output);
         self.pinnacle
             .layout_state
@@ -295,3 +408,24 @@ impl State {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maximized_windows_leave_exclusive_zones_uncovered() {
+        let output_geo = Rectangle::from_loc_and_size((1920, 0), (1920, 1080));
+        // A top panel reserving 30px
+        let non_exclusive_zone = Rectangle::from_loc_and_size((0, 30), (1920, 1050));
+
+        assert_eq!(
+            maximized_geometry(output_geo, non_exclusive_zone, false),
+            Rectangle::from_loc_and_size((1920, 30), (1920, 1050))
+        );
+        assert_eq!(
+            maximized_geometry(output_geo, non_exclusive_zone, true),
+            output_geo
+        );
+    }
+}
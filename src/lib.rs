@@ -9,6 +9,7 @@ pub mod handlers;
 pub mod input;
 pub mod layout;
 pub mod output;
+pub mod process;
 pub mod protocol;
 pub mod render;
 pub mod state;
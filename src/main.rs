@@ -17,6 +17,7 @@ use anyhow::Context;
 use pinnacle::{
     cli::{self, Cli},
     config::{get_config_dir, parse_metaconfig, Metaconfig},
+    process,
     state::State,
 };
 use smithay::reexports::{calloop::EventLoop, rustix::process::geteuid};
@@ -25,6 +26,9 @@ use tracing_appender::rolling::Rotation;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 use xdg::BaseDirectories;
 
+/// The soft open file limit Pinnacle tries to raise to if `--max-open-files` isn't passed.
+const DEFAULT_MAX_OPEN_FILES: u64 = 65536;
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let base_dirs = BaseDirectories::with_prefix("pinnacle")?;
@@ -89,6 +93,8 @@ async fn main() -> anyhow::Result<()> {
         warn!("You may see LOTS of file descriptors open under Pinnacle.");
     }
 
+    process::raise_fd_limit(cli.max_open_files.unwrap_or(DEFAULT_MAX_OPEN_FILES));
+
     let backend: cli::Backend = match (cli.backend, cli.force) {
         (None, _) => {
             if in_graphical_env {
@@ -10,13 +10,14 @@ use smithay::{
     utils::{Logical, Point, Transform},
     wayland::session_lock::LockSurface,
 };
+use tracing::warn;
 
 use crate::{
     focus::WindowKeyboardFocusStack,
     layout::transaction::{LayoutTransaction, SnapshotTarget},
     protocol::screencopy::Screencopy,
     state::{Pinnacle, State, WithState},
-    tag::Tag,
+    tag::{switch_animation::TagSwitchAnimation, Tag},
     window::window_state::FloatingOrTiled,
 };
 
@@ -38,6 +39,31 @@ impl OutputName {
     }
 }
 
+/// A way to identify the primary output, which is anchored at (0, 0).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrimaryOutput {
+    /// Identify the output by its connector name.
+    Name(OutputName),
+    /// Identify the output by its EDID serial number.
+    Serial(NonZeroU32),
+}
+
+impl PrimaryOutput {
+    /// Get the connected output this identifies.
+    pub fn output(&self, pinnacle: &Pinnacle) -> Option<Output> {
+        pinnacle
+            .space
+            .outputs()
+            .find(|output| match self {
+                PrimaryOutput::Name(name) => output.name() == name.0,
+                PrimaryOutput::Serial(serial) => {
+                    output.with_state(|state| state.serial) == Some(*serial)
+                }
+            })
+            .cloned()
+    }
+}
+
 /// State of an output's blanking status for session lock.
 #[derive(Debug, Default, Copy, Clone)]
 pub enum BlankingState {
@@ -50,18 +76,44 @@ pub enum BlankingState {
     Blanked,
 }
 
+/// What to do when a frame misses its vblank and another render is pending.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum VblankMissPolicy {
+    /// Render again immediately after the late frame is presented.
+    #[default]
+    CatchUp,
+    /// Wait and render as close to the next vblank as possible so the frame
+    /// has the newest state. This reduces latency when the GPU is under load.
+    Skip,
+}
+
 /// The state of an output
 #[derive(Default, Debug)]
 pub struct OutputState {
     pub tags: Vec<Tag>,
+    /// The tags that were active before the last change in active tags.
+    pub previous_active_tags: Vec<Tag>,
     pub focus_stack: WindowKeyboardFocusStack,
     pub screencopy: Option<Screencopy>,
     pub serial: Option<NonZeroU32>,
+    /// The raw EDID of the monitor, if it provides one.
+    pub edid: Option<Vec<u8>>,
     pub modes: Vec<Mode>,
     pub lock_surface: Option<LockSurface>,
     pub blanking_state: BlankingState,
     /// A pending layout transaction.
     pub layout_transaction: Option<LayoutTransaction>,
+    /// A running tag switch animation.
+    pub tag_switch_animation: Option<TagSwitchAnimation>,
+    pub vblank_miss_policy: VblankMissPolicy,
+    /// The color drawn behind everything while unlocked, or `None` for [`CLEAR_COLOR`].
+    ///
+    /// [`CLEAR_COLOR`]: crate::render::CLEAR_COLOR
+    pub clear_color: Option<[f32; 4]>,
+    /// The color temperature set by the config.
+    ///
+    /// This gives way to gamma control clients and is reapplied when they let go.
+    pub color_temperature: Option<u16>,
 }
 
 impl WithState for Output {
@@ -127,6 +179,7 @@ impl Pinnacle {
         let old_scale = output.current_scale().fractional_scale();
 
         output.change_current_state(mode, transform, scale, location);
+        self.output_management_manager_state.schedule_refresh();
         if let Some(location) = location {
             self.space.map_output(output, location);
             self.signal_state.output_move.signal(|buf| {
@@ -149,8 +202,11 @@ impl Pinnacle {
             });
         }
         if let Some(mode) = mode {
-            output.set_preferred(mode);
-            output.with_state_mut(|state| state.modes.push(mode));
+            output.with_state_mut(|state| {
+                if !state.modes.contains(&mode) {
+                    state.modes.push(mode);
+                }
+            });
         }
 
         if let Some(scale) = scale {
@@ -188,6 +244,10 @@ impl Pinnacle {
             }
         }
 
+        if location.is_some() || mode.is_some() || transform.is_some() || scale.is_some() {
+            self.keep_floating_windows_in_bounds();
+        }
+
         if let Some(lock_surface) = output.with_state(|state| state.lock_surface.clone()) {
             lock_surface.with_pending_state(|state| {
                 let Some(new_geo) = self.space.output_geometry(output) else {
@@ -199,4 +259,76 @@ impl Pinnacle {
             lock_surface.send_configure();
         }
     }
+
+    /// Log a warning if `output` overlaps any other output.
+    ///
+    /// Overlapping outputs are allowed, but they're usually a mistake in the config.
+    pub fn warn_on_output_overlap(&self, output: &Output) {
+        let Some(output_geo) = self.space.output_geometry(output) else {
+            return;
+        };
+
+        for other in self.space.outputs().filter(|op| *op != output) {
+            let Some(other_geo) = self.space.output_geometry(other) else {
+                continue;
+            };
+
+            if output_geo.overlaps(other_geo) {
+                warn!(
+                    "Output {} at {:?} overlaps output {} at {:?}",
+                    output.name(),
+                    output_geo,
+                    other.name(),
+                    other_geo
+                );
+            }
+        }
+    }
+
+    /// Get the output `offset` places away from `output` in the order outputs were
+    /// connected, wrapping around.
+    pub fn output_relative_to(&self, output: &Output, offset: isize) -> Option<Output> {
+        let outputs = self.space.outputs().collect::<Vec<_>>();
+        let idx = outputs.iter().position(|op| *op == output)?;
+        let idx = (idx as isize + offset).rem_euclid(outputs.len() as isize);
+
+        outputs.get(idx as usize).map(|op| (*op).clone())
+    }
+
+    /// Place the primary output at (0, 0) and lay out all other outputs to its right.
+    ///
+    /// Other outputs keep their left-to-right order and vertical positions, and are placed
+    /// edge to edge like newly connected outputs are.
+    pub fn anchor_primary_output(&mut self) {
+        let Some(primary) = self
+            .config
+            .primary_output
+            .as_ref()
+            .and_then(|primary| primary.output(self))
+        else {
+            return;
+        };
+
+        let Some(primary_geo) = self.space.output_geometry(&primary) else {
+            return;
+        };
+
+        let mut others = self
+            .space
+            .outputs()
+            .filter(|op| *op != &primary)
+            .filter_map(|op| Some((op.clone(), self.space.output_geometry(op)?)))
+            .collect::<Vec<_>>();
+        others.sort_by_key(|(_, geo)| geo.loc.x);
+
+        self.change_output_state(&primary, None, None, None, Some((0, 0).into()));
+        self.request_layout(&primary);
+
+        let mut x = primary_geo.size.w;
+        for (output, geo) in others {
+            self.change_output_state(&output, None, None, None, Some((x, geo.loc.y).into()));
+            self.request_layout(&output);
+            x += geo.size.w;
+        }
+    }
 }
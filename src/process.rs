@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Process limits and spawning.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use smithay::reexports::rustix::process::{getrlimit, setrlimit, Resource, Rlimit};
+use tracing::{info, warn};
+
+/// Whether [`raise_fd_limit`] changed the open file limit.
+static FD_LIMIT_RAISED: AtomicBool = AtomicBool::new(false);
+/// The soft open file limit from before [`raise_fd_limit`].
+static ORIGINAL_FD_LIMIT_CURRENT: AtomicU64 = AtomicU64::new(0);
+/// The hard open file limit from before [`raise_fd_limit`], or `u64::MAX` if unlimited.
+static ORIGINAL_FD_LIMIT_MAXIMUM: AtomicU64 = AtomicU64::new(0);
+
+/// Raise the soft limit on open file descriptors to `target`.
+///
+/// Every client, dmabuf, syncobj, and DRM lease holds at least one fd, and the
+/// default soft limit of 1024 is easy to blow through with a lot of windows open, at
+/// which point things start failing with "too many open files".
+///
+/// If `target` is above the hard limit and the hard limit can't be raised (e.g. because
+/// we're unprivileged or in a constrained container), the soft limit is raised to the
+/// hard limit instead.
+///
+/// The raised limit is inherited by spawned processes, so commands should be spawned
+/// with [`restore_fd_limit_on_spawn`].
+pub fn raise_fd_limit(target: u64) {
+    let limit = getrlimit(Resource::Nofile);
+
+    // `None` means unlimited
+    let Some(current) = limit.current else {
+        return;
+    };
+
+    if current >= target {
+        return;
+    }
+
+    ORIGINAL_FD_LIMIT_CURRENT.store(current, Ordering::SeqCst);
+    ORIGINAL_FD_LIMIT_MAXIMUM.store(limit.maximum.unwrap_or(u64::MAX), Ordering::SeqCst);
+    FD_LIMIT_RAISED.store(true, Ordering::SeqCst);
+
+    let desired = Rlimit {
+        current: Some(target),
+        maximum: limit.maximum.map(|max| max.max(target)),
+    };
+
+    match setrlimit(Resource::Nofile, desired) {
+        Ok(()) => info!("Raised open file limit from {current} to {target}"),
+        Err(err) => {
+            let Some(hard) = limit.maximum.filter(|&hard| hard > current) else {
+                warn!("Failed to raise open file limit from {current} to {target}: {err}");
+                return;
+            };
+
+            warn!(
+                "Failed to raise open file limit to {target}: {err}; \
+                falling back to the hard limit of {hard}"
+            );
+
+            let fallback = Rlimit {
+                current: Some(hard),
+                maximum: Some(hard),
+            };
+
+            match setrlimit(Resource::Nofile, fallback) {
+                Ok(()) => info!("Raised open file limit from {current} to {hard}"),
+                Err(err) => warn!("Failed to raise open file limit to {hard}: {err}"),
+            }
+        }
+    }
+}
+
+/// Make `command` restore the open file limit from before [`raise_fd_limit`]
+/// in the spawned process.
+///
+/// Processes that use `select()` break with file descriptors above `FD_SETSIZE` (1024),
+/// so they shouldn't inherit the raised limit.
+pub fn restore_fd_limit_on_spawn(command: &mut tokio::process::Command) {
+    if !FD_LIMIT_RAISED.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let original = Rlimit {
+        current: Some(ORIGINAL_FD_LIMIT_CURRENT.load(Ordering::SeqCst)),
+        maximum: Some(ORIGINAL_FD_LIMIT_MAXIMUM.load(Ordering::SeqCst))
+            .filter(|&max| max != u64::MAX),
+    };
+
+    // SAFETY: `setrlimit` is a plain syscall, so it's safe to call between fork and exec.
+    unsafe {
+        command.pre_exec(move || {
+            // Not fatal, the process just gets the raised limit
+            let _ = setrlimit(Resource::Nofile, original.clone());
+            Ok(())
+        });
+    }
+}
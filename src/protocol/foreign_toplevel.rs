@@ -206,7 +206,8 @@ fn pending_toplevel_data_for(
             title: Some(x11_surface.title()),
             app_id: Some(x11_surface.class()),
             maximized: x11_surface.is_maximized(),
-            minimized: x11_surface.is_minimized(),
+            // Minimizing through this protocol only sets Pinnacle's state, not the X11 one
+            minimized: win.with_state(|state| state.minimized),
             fullscreen: x11_surface.is_fullscreen(),
             _activated: x11_surface.is_activated(),
             focused,
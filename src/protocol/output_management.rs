@@ -0,0 +1,865 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! wlr-output-management, which lets tools like kanshi and wlr-randr configure outputs.
+//!
+//! Clients are sent a head for every output along with its modes. Whenever an output changes,
+//! the changed properties are resent followed by a `done` event with a new serial.
+//! Configurations created against an older serial are cancelled.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use smithay::{
+    output::{Mode, Output},
+    reexports::{
+        wayland_protocols_wlr::output_management::v1::server::{
+            zwlr_output_configuration_head_v1::{self, ZwlrOutputConfigurationHeadV1},
+            zwlr_output_configuration_v1::{self, ZwlrOutputConfigurationV1},
+            zwlr_output_head_v1::{self, ZwlrOutputHeadV1},
+            zwlr_output_manager_v1::{self, ZwlrOutputManagerV1},
+            zwlr_output_mode_v1::{self, ZwlrOutputModeV1},
+        },
+        wayland_server::{
+            self, backend::ClientId, protocol::wl_output, Client, DataInit, Dispatch,
+            DisplayHandle, GlobalDispatch, Resource,
+        },
+    },
+    utils::{Logical, Physical, Point, Size, Transform},
+};
+
+use crate::state::{State, WithState};
+
+const VERSION: u32 = 3;
+
+pub struct OutputManagementManagerState {
+    display: DisplayHandle,
+    managers: HashMap<ZwlrOutputManagerV1, ManagerData>,
+    /// The output state last sent to clients
+    outputs: HashMap<Output, OutputSnapshot>,
+    serial: u32,
+    /// Whether outputs changed since they were last sent to clients
+    refresh_scheduled: bool,
+}
+
+pub struct OutputManagementGlobalData {
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
+}
+
+/// The heads and modes sent to a single manager.
+#[derive(Default)]
+struct ManagerData {
+    heads: HashMap<Output, HeadInstance>,
+}
+
+struct HeadInstance {
+    head: ZwlrOutputHeadV1,
+    modes: Vec<(Mode, ZwlrOutputModeV1)>,
+}
+
+/// The state of an output as advertised to clients.
+#[derive(Debug, Clone, PartialEq)]
+struct OutputSnapshot {
+    name: String,
+    description: String,
+    physical_size: (i32, i32),
+    make: String,
+    model: String,
+    serial_number: Option<String>,
+    modes: Vec<Mode>,
+    preferred_mode: Option<Mode>,
+    current_mode: Option<Mode>,
+    enabled: bool,
+    position: Point<i32, Logical>,
+    transform: Transform,
+    scale: f64,
+}
+
+impl OutputSnapshot {
+    fn new(output: &Output, enabled: bool) -> Self {
+        let physical_props = output.physical_properties();
+        let current_mode = output.current_mode();
+
+        // Modes can be added more than once
+        let mut modes = Vec::<Mode>::new();
+        for mode in output
+            .with_state(|state| state.modes.clone())
+            .into_iter()
+            .chain(current_mode)
+        {
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+
+        Self {
+            name: output.name(),
+            description: output.description(),
+            physical_size: (physical_props.size.w, physical_props.size.h),
+            make: physical_props.make,
+            model: physical_props.model,
+            serial_number: output.with_state(|state| state.serial.map(|serial| serial.to_string())),
+            modes,
+            preferred_mode: output.preferred_mode(),
+            current_mode,
+            enabled,
+            position: output.current_location(),
+            transform: output.current_transform(),
+            scale: output.current_scale().fractional_scale(),
+        }
+    }
+}
+
+/// A mode requested by a client.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModeConfiguration {
+    /// One of the modes advertised for the output
+    Mode(Mode),
+    /// A mode with the given size and refresh rate in millihertz,
+    /// or any refresh rate if `None`
+    Custom {
+        size: Size<i32, Physical>,
+        refresh: Option<i32>,
+    },
+}
+
+/// How a client wants an output to be configured.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputConfiguration {
+    Disabled,
+    /// Properties that are `None` should be left as they are.
+    Enabled {
+        mode: Option<ModeConfiguration>,
+        position: Option<Point<i32, Logical>>,
+        transform: Option<Transform>,
+        scale: Option<f64>,
+    },
+}
+
+pub trait OutputManagementHandler {
+    fn output_management_manager_state(&mut self) -> &mut OutputManagementManagerState;
+    /// A client wants to apply the given configuration, or just check if it could be applied
+    /// if `test_only` is true.
+    ///
+    /// Returns whether or not the configuration was applied successfully (or would be).
+    fn apply_output_configuration(
+        &mut self,
+        config: HashMap<Output, OutputConfiguration>,
+        test_only: bool,
+    ) -> bool;
+}
+
+/// The data of a configuration object.
+pub struct ConfigurationData {
+    serial: u32,
+    /// Whether this configuration was already applied or tested
+    used: bool,
+    /// Configured heads, where disabled heads have no configuration head
+    heads: HashMap<Output, Option<ZwlrOutputConfigurationHeadV1>>,
+}
+
+/// The data of a configuration head object.
+pub struct ConfigurationHeadData {
+    output: Output,
+    pending: Mutex<PendingHeadConfiguration>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct PendingHeadConfiguration {
+    mode: Option<ModeConfiguration>,
+    position: Option<Point<i32, Logical>>,
+    transform: Option<Transform>,
+    scale: Option<f64>,
+}
+
+impl OutputManagementManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ZwlrOutputManagerV1, OutputManagementGlobalData>
+            + Dispatch<ZwlrOutputManagerV1, ()>
+            + 'static,
+        F: Fn(&Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = OutputManagementGlobalData {
+            filter: Box::new(filter),
+        };
+
+        display.create_global::<D, ZwlrOutputManagerV1, _>(VERSION, global_data);
+
+        Self {
+            display: display.clone(),
+            managers: HashMap::new(),
+            outputs: HashMap::new(),
+            serial: 0,
+            refresh_scheduled: true,
+        }
+    }
+
+    /// Send clients the current output configuration at the end of this event loop cycle.
+    ///
+    /// This should be called whenever an output is added, removed, or changed.
+    pub fn schedule_refresh(&mut self) {
+        self.refresh_scheduled = true;
+    }
+
+    /// Send any changes in `outputs` to clients.
+    fn update<D>(&mut self, outputs: HashMap<Output, OutputSnapshot>)
+    where
+        D: Dispatch<ZwlrOutputHeadV1, Output> + Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+    {
+        if outputs == self.outputs {
+            return;
+        }
+
+        for (manager, data) in self.managers.iter_mut() {
+            data.heads.retain(|output, instance| {
+                if outputs.contains_key(output) {
+                    return true;
+                }
+
+                for (_, mode) in instance.modes.drain(..) {
+                    mode.finished();
+                }
+                instance.head.finished();
+                false
+            });
+
+            for (output, snapshot) in outputs.iter() {
+                match (data.heads.get_mut(output), self.outputs.get(output)) {
+                    (Some(instance), Some(old)) => {
+                        instance.send_changes::<D>(&self.display, old, snapshot);
+                    }
+                    _ => {
+                        let Some(client) = manager.client() else {
+                            continue;
+                        };
+                        if let Some(instance) = HeadInstance::new::<D>(
+                            &self.display,
+                            &client,
+                            manager,
+                            output,
+                            snapshot,
+                        ) {
+                            data.heads.insert(output.clone(), instance);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.outputs = outputs;
+        self.serial = self.serial.wrapping_add(1);
+
+        for manager in self.managers.keys() {
+            manager.done(self.serial);
+        }
+    }
+}
+
+/// Send clients the current output configuration if a refresh was scheduled
+/// with [`OutputManagementManagerState::schedule_refresh`].
+pub fn refresh(state: &mut State) {
+    if !std::mem::take(
+        &mut state
+            .pinnacle
+            .output_management_manager_state
+            .refresh_scheduled,
+    ) {
+        return;
+    }
+
+    let outputs = state
+        .pinnacle
+        .space
+        .outputs()
+        .map(|output| {
+            let enabled = state.backend.output_powered(output);
+            (output.clone(), OutputSnapshot::new(output, enabled))
+        })
+        .collect();
+
+    state
+        .pinnacle
+        .output_management_manager_state
+        .update::<State>(outputs);
+}
+
+impl HeadInstance {
+    fn new<D>(
+        display: &DisplayHandle,
+        client: &Client,
+        manager: &ZwlrOutputManagerV1,
+        output: &Output,
+        snapshot: &OutputSnapshot,
+    ) -> Option<Self>
+    where
+        D: Dispatch<ZwlrOutputHeadV1, Output> + Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+    {
+        let head = client
+            .create_resource::<ZwlrOutputHeadV1, _, D>(display, manager.version(), output.clone())
+            .ok()?;
+        manager.head(&head);
+
+        head.name(snapshot.name.clone());
+        head.description(snapshot.description.clone());
+        head.physical_size(snapshot.physical_size.0, snapshot.physical_size.1);
+
+        let mut instance = Self {
+            head,
+            modes: Vec::new(),
+        };
+
+        for mode in snapshot.modes.iter() {
+            instance.add_mode::<D>(display, client, *mode, snapshot.preferred_mode);
+        }
+
+        instance.head.enabled(snapshot.enabled as i32);
+        if snapshot.enabled {
+            instance.send_current_mode(snapshot.current_mode);
+            instance
+                .head
+                .position(snapshot.position.x, snapshot.position.y);
+            instance.head.transform(to_wl_transform(snapshot.transform));
+            instance.head.scale(snapshot.scale);
+        }
+
+        if instance.head.version() >= zwlr_output_head_v1::EVT_MAKE_SINCE {
+            instance.head.make(snapshot.make.clone());
+            instance.head.model(snapshot.model.clone());
+            if let Some(serial_number) = snapshot.serial_number.clone() {
+                instance.head.serial_number(serial_number);
+            }
+        }
+
+        Some(instance)
+    }
+
+    fn add_mode<D>(
+        &mut self,
+        display: &DisplayHandle,
+        client: &Client,
+        mode: Mode,
+        preferred_mode: Option<Mode>,
+    ) where
+        D: Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+    {
+        let Ok(wlr_mode) =
+            client.create_resource::<ZwlrOutputModeV1, _, D>(display, self.head.version(), mode)
+        else {
+            return;
+        };
+
+        self.head.mode(&wlr_mode);
+        wlr_mode.size(mode.size.w, mode.size.h);
+        if mode.refresh > 0 {
+            wlr_mode.refresh(mode.refresh);
+        }
+        if preferred_mode == Some(mode) {
+            wlr_mode.preferred();
+        }
+
+        self.modes.push((mode, wlr_mode));
+    }
+
+    fn send_current_mode(&self, current_mode: Option<Mode>) {
+        let wlr_mode = self
+            .modes
+            .iter()
+            .find(|(mode, _)| Some(*mode) == current_mode)
+            .map(|(_, wlr_mode)| wlr_mode);
+
+        if let Some(wlr_mode) = wlr_mode {
+            self.head.current_mode(wlr_mode);
+        }
+    }
+
+    fn send_changes<D>(
+        &mut self,
+        display: &DisplayHandle,
+        old: &OutputSnapshot,
+        new: &OutputSnapshot,
+    ) where
+        D: Dispatch<ZwlrOutputModeV1, Mode> + 'static,
+    {
+        if old.description != new.description {
+            self.head.description(new.description.clone());
+        }
+
+        if old.modes != new.modes {
+            self.modes.retain(|(mode, wlr_mode)| {
+                let keep = new.modes.contains(mode);
+                if !keep {
+                    wlr_mode.finished();
+                }
+                keep
+            });
+
+            if let Some(client) = self.head.client() {
+                for mode in new.modes.iter() {
+                    if !self.modes.iter().any(|(m, _)| m == mode) {
+                        self.add_mode::<D>(display, &client, *mode, new.preferred_mode);
+                    }
+                }
+            }
+        }
+
+        let newly_enabled = new.enabled && !old.enabled;
+
+        if old.enabled != new.enabled {
+            self.head.enabled(new.enabled as i32);
+        }
+
+        if !new.enabled {
+            return;
+        }
+
+        if newly_enabled || old.current_mode != new.current_mode || old.modes != new.modes {
+            self.send_current_mode(new.current_mode);
+        }
+        if newly_enabled || old.position != new.position {
+            self.head.position(new.position.x, new.position.y);
+        }
+        if newly_enabled || old.transform != new.transform {
+            self.head.transform(to_wl_transform(new.transform));
+        }
+        if newly_enabled || old.scale != new.scale {
+            self.head.scale(new.scale);
+        }
+    }
+}
+
+fn to_wl_transform(transform: Transform) -> wl_output::Transform {
+    match transform {
+        Transform::Normal => wl_output::Transform::Normal,
+        Transform::_90 => wl_output::Transform::_90,
+        Transform::_180 => wl_output::Transform::_180,
+        Transform::_270 => wl_output::Transform::_270,
+        Transform::Flipped => wl_output::Transform::Flipped,
+        Transform::Flipped90 => wl_output::Transform::Flipped90,
+        Transform::Flipped180 => wl_output::Transform::Flipped180,
+        Transform::Flipped270 => wl_output::Transform::Flipped270,
+    }
+}
+
+fn from_wl_transform(transform: wl_output::Transform) -> Option<Transform> {
+    Some(match transform {
+        wl_output::Transform::Normal => Transform::Normal,
+        wl_output::Transform::_90 => Transform::_90,
+        wl_output::Transform::_180 => Transform::_180,
+        wl_output::Transform::_270 => Transform::_270,
+        wl_output::Transform::Flipped => Transform::Flipped,
+        wl_output::Transform::Flipped90 => Transform::Flipped90,
+        wl_output::Transform::Flipped180 => Transform::Flipped180,
+        wl_output::Transform::Flipped270 => Transform::Flipped270,
+        _ => return None,
+    })
+}
+
+impl<D> GlobalDispatch<ZwlrOutputManagerV1, OutputManagementGlobalData, D>
+    for OutputManagementManagerState
+where
+    D: GlobalDispatch<ZwlrOutputManagerV1, OutputManagementGlobalData>
+        + Dispatch<ZwlrOutputManagerV1, ()>
+        + Dispatch<ZwlrOutputHeadV1, Output>
+        + Dispatch<ZwlrOutputModeV1, Mode>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn bind(
+        state: &mut D,
+        handle: &DisplayHandle,
+        client: &Client,
+        resource: wayland_server::New<ZwlrOutputManagerV1>,
+        _global_data: &OutputManagementGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let manager = data_init.init(resource, ());
+
+        let state = state.output_management_manager_state();
+
+        let mut data = ManagerData::default();
+        for (output, snapshot) in state.outputs.iter() {
+            if let Some(instance) =
+                HeadInstance::new::<D>(handle, client, &manager, output, snapshot)
+            {
+                data.heads.insert(output.clone(), instance);
+            }
+        }
+
+        manager.done(state.serial);
+
+        state.managers.insert(manager, data);
+    }
+
+    fn can_view(client: Client, global_data: &OutputManagementGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputManagerV1, (), D> for OutputManagementManagerState
+where
+    D: Dispatch<ZwlrOutputManagerV1, ()>
+        + Dispatch<ZwlrOutputConfigurationV1, Mutex<ConfigurationData>>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ZwlrOutputManagerV1,
+        request: <ZwlrOutputManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_manager_v1::Request::CreateConfiguration { id, serial } => {
+                data_init.init(
+                    id,
+                    Mutex::new(ConfigurationData {
+                        serial,
+                        used: false,
+                        heads: HashMap::new(),
+                    }),
+                );
+            }
+            zwlr_output_manager_v1::Request::Stop => {
+                resource.finished();
+
+                state
+                    .output_management_manager_state()
+                    .managers
+                    .remove(resource);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ZwlrOutputManagerV1, _data: &()) {
+        state
+            .output_management_manager_state()
+            .managers
+            .remove(resource);
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputHeadV1, Output, D> for OutputManagementManagerState
+where
+    D: Dispatch<ZwlrOutputHeadV1, Output> + OutputManagementHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ZwlrOutputHeadV1,
+        request: <ZwlrOutputHeadV1 as Resource>::Request,
+        _data: &Output,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_head_v1::Request::Release => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ZwlrOutputHeadV1, _data: &Output) {
+        for data in state
+            .output_management_manager_state()
+            .managers
+            .values_mut()
+        {
+            data.heads.retain(|_, instance| &instance.head != resource);
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputModeV1, Mode, D> for OutputManagementManagerState
+where
+    D: Dispatch<ZwlrOutputModeV1, Mode> + OutputManagementHandler + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ZwlrOutputModeV1,
+        request: <ZwlrOutputModeV1 as Resource>::Request,
+        _data: &Mode,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_mode_v1::Request::Release => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ZwlrOutputModeV1, _data: &Mode) {
+        for data in state
+            .output_management_manager_state()
+            .managers
+            .values_mut()
+        {
+            for instance in data.heads.values_mut() {
+                instance.modes.retain(|(_, wlr_mode)| wlr_mode != resource);
+            }
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputConfigurationV1, Mutex<ConfigurationData>, D>
+    for OutputManagementManagerState
+where
+    D: Dispatch<ZwlrOutputConfigurationV1, Mutex<ConfigurationData>>
+        + Dispatch<ZwlrOutputConfigurationHeadV1, ConfigurationHeadData>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ZwlrOutputConfigurationV1,
+        request: <ZwlrOutputConfigurationV1 as Resource>::Request,
+        data: &Mutex<ConfigurationData>,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let mut data = data.lock().unwrap();
+
+        match request {
+            zwlr_output_configuration_v1::Request::EnableHead { id, head } => {
+                let Some(output) = head.data::<Output>().cloned() else {
+                    return;
+                };
+
+                let conf_head = data_init.init(
+                    id,
+                    ConfigurationHeadData {
+                        output: output.clone(),
+                        pending: Mutex::new(PendingHeadConfiguration::default()),
+                    },
+                );
+
+                if data.heads.contains_key(&output) {
+                    resource.post_error(
+                        zwlr_output_configuration_v1::Error::AlreadyConfiguredHead,
+                        "head was already configured",
+                    );
+                    return;
+                }
+
+                data.heads.insert(output, Some(conf_head));
+            }
+            zwlr_output_configuration_v1::Request::DisableHead { head } => {
+                let Some(output) = head.data::<Output>().cloned() else {
+                    return;
+                };
+
+                if data.heads.contains_key(&output) {
+                    resource.post_error(
+                        zwlr_output_configuration_v1::Error::AlreadyConfiguredHead,
+                        "head was already configured",
+                    );
+                    return;
+                }
+
+                data.heads.insert(output, None);
+            }
+            request @ (zwlr_output_configuration_v1::Request::Apply
+            | zwlr_output_configuration_v1::Request::Test) => {
+                let test_only = matches!(request, zwlr_output_configuration_v1::Request::Test);
+
+                if data.used {
+                    resource.post_error(
+                        zwlr_output_configuration_v1::Error::AlreadyUsed,
+                        "configuration was already applied or tested",
+                    );
+                    return;
+                }
+                data.used = true;
+
+                let protocol_state = state.output_management_manager_state();
+
+                // The outputs changed since the client created this configuration
+                if data.serial != protocol_state.serial {
+                    resource.cancelled();
+                    return;
+                }
+
+                if protocol_state
+                    .outputs
+                    .keys()
+                    .any(|output| !data.heads.contains_key(output))
+                {
+                    resource.post_error(
+                        zwlr_output_configuration_v1::Error::UnconfiguredHead,
+                        "not all heads were configured",
+                    );
+                    return;
+                }
+
+                let config = data
+                    .heads
+                    .iter()
+                    .map(|(output, conf_head)| {
+                        let conf = match conf_head
+                            .as_ref()
+                            .and_then(|conf_head| conf_head.data::<ConfigurationHeadData>())
+                        {
+                            Some(conf_head_data) => {
+                                let pending = conf_head_data.pending.lock().unwrap().clone();
+                                OutputConfiguration::Enabled {
+                                    mode: pending.mode,
+                                    position: pending.position,
+                                    transform: pending.transform,
+                                    scale: pending.scale,
+                                }
+                            }
+                            None => OutputConfiguration::Disabled,
+                        };
+                        (output.clone(), conf)
+                    })
+                    .collect();
+
+                if state.apply_output_configuration(config, test_only) {
+                    resource.succeeded();
+                } else {
+                    resource.failed();
+                }
+            }
+            zwlr_output_configuration_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputConfigurationHeadV1, ConfigurationHeadData, D>
+    for OutputManagementManagerState
+where
+    D: Dispatch<ZwlrOutputConfigurationHeadV1, ConfigurationHeadData>
+        + OutputManagementHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ZwlrOutputConfigurationHeadV1,
+        request: <ZwlrOutputConfigurationHeadV1 as Resource>::Request,
+        data: &ConfigurationHeadData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let mut pending = data.pending.lock().unwrap();
+
+        let already_set = match &request {
+            zwlr_output_configuration_head_v1::Request::SetMode { .. }
+            | zwlr_output_configuration_head_v1::Request::SetCustomMode { .. } => {
+                pending.mode.is_some()
+            }
+            zwlr_output_configuration_head_v1::Request::SetPosition { .. } => {
+                pending.position.is_some()
+            }
+            zwlr_output_configuration_head_v1::Request::SetTransform { .. } => {
+                pending.transform.is_some()
+            }
+            zwlr_output_configuration_head_v1::Request::SetScale { .. } => pending.scale.is_some(),
+            _ => false,
+        };
+
+        if already_set {
+            resource.post_error(
+                zwlr_output_configuration_head_v1::Error::AlreadySet,
+                "property was already set",
+            );
+            return;
+        }
+
+        match request {
+            zwlr_output_configuration_head_v1::Request::SetMode { mode } => {
+                let advertised = mode.data::<Mode>().copied().filter(|mode| {
+                    state
+                        .output_management_manager_state()
+                        .outputs
+                        .get(&data.output)
+                        .is_some_and(|snapshot| snapshot.modes.contains(mode))
+                });
+
+                let Some(mode) = advertised else {
+                    resource.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidMode,
+                        "mode doesn't belong to this head",
+                    );
+                    return;
+                };
+
+                pending.mode = Some(ModeConfiguration::Mode(mode));
+            }
+            zwlr_output_configuration_head_v1::Request::SetCustomMode {
+                width,
+                height,
+                refresh,
+            } => {
+                if width <= 0 || height <= 0 || refresh < 0 {
+                    resource.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidCustomMode,
+                        "invalid custom mode",
+                    );
+                    return;
+                }
+
+                pending.mode = Some(ModeConfiguration::Custom {
+                    size: (width, height).into(),
+                    // Zero means any refresh rate
+                    refresh: (refresh > 0).then_some(refresh),
+                });
+            }
+            zwlr_output_configuration_head_v1::Request::SetPosition { x, y } => {
+                pending.position = Some((x, y).into());
+            }
+            zwlr_output_configuration_head_v1::Request::SetTransform { transform } => {
+                let Some(transform) = transform.into_result().ok().and_then(from_wl_transform)
+                else {
+                    resource.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidTransform,
+                        "invalid transform",
+                    );
+                    return;
+                };
+
+                pending.transform = Some(transform);
+            }
+            zwlr_output_configuration_head_v1::Request::SetScale { scale } => {
+                if !scale.is_finite() || scale <= 0.0 {
+                    resource.post_error(
+                        zwlr_output_configuration_head_v1::Error::InvalidScale,
+                        "scale must be positive",
+                    );
+                    return;
+                }
+
+                pending.scale = Some(scale);
+            }
+            _ => (),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_output_management {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_manager_v1::ZwlrOutputManagerV1: $crate::protocol::output_management::OutputManagementGlobalData
+        ] => $crate::protocol::output_management::OutputManagementManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_manager_v1::ZwlrOutputManagerV1: ()
+        ] => $crate::protocol::output_management::OutputManagementManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_head_v1::ZwlrOutputHeadV1: smithay::output::Output
+        ] => $crate::protocol::output_management::OutputManagementManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_mode_v1::ZwlrOutputModeV1: smithay::output::Mode
+        ] => $crate::protocol::output_management::OutputManagementManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_configuration_v1::ZwlrOutputConfigurationV1: std::sync::Mutex<$crate::protocol::output_management::ConfigurationData>
+        ] => $crate::protocol::output_management::OutputManagementManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_management::v1::server::zwlr_output_configuration_head_v1::ZwlrOutputConfigurationHeadV1: $crate::protocol::output_management::ConfigurationHeadData
+        ] => $crate::protocol::output_management::OutputManagementManagerState);
+    };
+}
@@ -7,25 +7,31 @@ pub mod util;
 
 use std::{ops::Deref, sync::Mutex};
 
+use anyhow::{bail, Context};
 use smithay::{
-    backend::renderer::{
-        element::{surface::WaylandSurfaceRenderElement, AsRenderElements, RenderElementStates},
-        gles::GlesRenderer,
-        ImportAll, ImportMem, Renderer, Texture,
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            element::{
+                surface::WaylandSurfaceRenderElement, AsRenderElements, RenderElementStates,
+            },
+            gles::{GlesRenderer, GlesTexture},
+            ExportMem, ImportAll, ImportMem, Renderer, Texture,
+        },
     },
     desktop::{
         layer_map_for_output,
         space::SpaceElement,
         utils::{
-            surface_presentation_feedback_flags_from_states, surface_primary_scanout_output,
-            OutputPresentationFeedback,
+            output_update, surface_presentation_feedback_flags_from_states,
+            surface_primary_scanout_output, OutputPresentationFeedback,
         },
         PopupManager, Space, WindowSurface,
     },
     input::pointer::{CursorImageAttributes, CursorImageStatus},
     output::Output,
     reexports::wayland_server::protocol::wl_surface::WlSurface,
-    utils::{Logical, Point, Scale},
+    utils::{Logical, Point, Rectangle, Scale, Transform},
     wayland::{compositor, shell::wlr_layer},
 };
 
@@ -40,7 +46,7 @@ use crate::{
 use self::{
     pointer::{PointerElement, PointerRenderElement},
     texture::CommonTextureRenderElement,
-    util::surface::texture_render_elements_from_surface_tree,
+    util::{render_to_texture, surface::texture_render_elements_from_surface_tree},
 };
 
 pub const CLEAR_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
@@ -249,13 +255,13 @@ fn window_render_elements<R: PRenderer>(
         .rev() // rev because I treat the focus stack backwards vs how the renderer orders it
         .enumerate()
         .map(|(i, win)| {
-            if win.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()) {
+            if win.is_fullscreen_on_output(output) {
                 last_fullscreen_split_at = i + 1;
             }
 
             let loc = space.element_location(win).unwrap_or_default() - output.current_location();
 
-            win.render_elements(renderer, loc, scale, 1.0)
+            win.render_elements(renderer, loc, scale, win.render_alpha())
                 .into_iter()
                 .map(OutputRenderElement::from)
         }).collect::<Vec<_>>();
@@ -296,7 +302,7 @@ pub fn pointer_render_elements<R: PRenderer>(
                     .hotspot
             })
         } else {
-            (0, 0).into()
+            pointer_element.hotspot()
         };
 
         let cursor_pos = pointer_location - output_geometry.loc.to_f64() - cursor_hotspot.to_f64();
@@ -308,16 +314,27 @@ pub fn pointer_render_elements<R: PRenderer>(
             scale,
             1.0,
         ));
+    }
 
-        if let Some(dnd_icon) = dnd_icon {
-            output_render_elements.extend(AsRenderElements::render_elements(
-                &smithay::desktop::space::SurfaceTree::from_surface(dnd_icon),
-                renderer,
-                cursor_pos_scaled,
-                scale,
-                1.0,
-            ));
-        }
+    // The dnd icon is rendered on every output it overlaps so it doesn't get cut off
+    // when dragged across an output boundary, and it tracks which outputs it's on
+    // so the client can render it at the correct scale.
+    if let Some(dnd_icon) = dnd_icon {
+        let icon_loc = pointer_location.to_i32_round();
+        let output_overlap =
+            Rectangle::from_loc_and_size(output_geometry.loc - icon_loc, output_geometry.size);
+        output_update(output, Some(output_overlap), dnd_icon);
+
+        let icon_pos_scaled =
+            (pointer_location - output_geometry.loc.to_f64()).to_physical_precise_round(scale);
+
+        output_render_elements.extend(AsRenderElements::render_elements(
+            &smithay::desktop::space::SurfaceTree::from_surface(dnd_icon),
+            renderer,
+            icon_pos_scaled,
+            scale,
+            1.0,
+        ));
     }
 
     output_render_elements
@@ -336,7 +353,7 @@ fn layout_transaction_render_elements<R: PRenderer + AsGlesRenderer>(
     let mut flat_map = |target: &SnapshotTarget| match target {
         SnapshotTarget::Window(win) => {
             let loc = space.element_location(win).unwrap_or_default() - output_loc;
-            win.render_elements(renderer, loc, scale, 1.0)
+            win.render_elements(renderer, loc, scale, win.render_alpha())
                 .into_iter()
                 .map(SnapshotRenderElement::from)
                 .collect::<Vec<_>>()
@@ -385,22 +402,6 @@ pub fn output_render_elements<R: PRenderer + AsGlesRenderer>(
         .filter(|win| win.is_on_active_tag())
         .collect::<Vec<_>>();
 
-    // // draw input method surface if any
-    // let rectangle = input_method.coordinates();
-    // let position = Point::from((
-    //     rectangle.loc.x + rectangle.size.w,
-    //     rectangle.loc.y + rectangle.size.h,
-    // ));
-    // input_method.with_surface(|surface| {
-    //     custom_render_elements.extend(AsRenderElements::<R>::render_elements(
-    //         &SurfaceTree::from_surface(surface),
-    //         renderer,
-    //         position.to_physical_precise_round(scale),
-    //         scale,
-    //         1.0,
-    //     ));
-    // });
-
     let output_loc = output.current_location();
 
     let o_r_elements = override_redirect_windows
@@ -449,11 +450,32 @@ pub fn output_render_elements<R: PRenderer + AsGlesRenderer>(
             window_render_elements::<R>(output, &windows, space, renderer, scale);
     }
 
+    // The outgoing tag's windows fade out over the incoming ones
+    let (tag_switch_fs_and_up_elements, tag_switch_under_fs_elements) = output
+        .with_state_mut(|state| {
+            let transaction_pending = state.layout_transaction.is_some();
+            state
+                .tag_switch_animation
+                .as_mut()
+                .map(|anim| anim.render_elements(renderer, scale, transaction_pending))
+        })
+        .unwrap_or_default();
+
     // Elements render from top to bottom
 
     output_render_elements.extend(overlay.into_iter().map(OutputRenderElement::from));
+    output_render_elements.extend(
+        tag_switch_fs_and_up_elements
+            .into_iter()
+            .map(OutputRenderElement::from),
+    );
     output_render_elements.extend(fullscreen_and_up_elements);
     output_render_elements.extend(top.into_iter().map(OutputRenderElement::from));
+    output_render_elements.extend(
+        tag_switch_under_fs_elements
+            .into_iter()
+            .map(OutputRenderElement::from),
+    );
     output_render_elements.extend(rest_of_window_elements);
     output_render_elements.extend(bottom.into_iter().map(OutputRenderElement::from));
     output_render_elements.extend(background.into_iter().map(OutputRenderElement::from));
@@ -495,7 +517,122 @@ pub fn take_presentation_feedback(
     output_presentation_feedback
 }
 
+/// The contents of an output, from [`State::capture_output`].
+#[derive(Debug)]
+pub struct OutputCapture {
+    /// The width of the capture in physical pixels
+    pub width: u32,
+    /// The height of the capture in physical pixels
+    pub height: u32,
+    /// The number of bytes per row
+    pub stride: u32,
+    /// The pixels in RGBA order, top row first
+    pub data: Vec<u8>,
+}
+
 impl State {
+    /// Render `output` offscreen and read back its contents.
+    ///
+    /// The capture is the size of the output's current mode, transformed the same way
+    /// the output is.
+    pub fn capture_output(
+        &mut self,
+        output: &Output,
+        include_cursor: bool,
+    ) -> anyhow::Result<OutputCapture> {
+        let mode = output
+            .current_mode()
+            .context("output has no current mode")?;
+
+        if !self.pinnacle.lock_state.is_unlocked() {
+            bail!("the session is locked");
+        }
+
+        let size = output.current_transform().transform_size(mode.size);
+        let scale = Scale::from(output.current_scale().fractional_scale());
+        let windows = self.pinnacle.space.elements().cloned().collect::<Vec<_>>();
+        let pinnacle = &mut self.pinnacle;
+
+        self.backend
+            .with_renderer(|renderer| {
+                let mut elements = Vec::new();
+
+                if include_cursor {
+                    let mut pointer_element = PointerElement::<GlesTexture>::new();
+                    pointer_element.set_status(pinnacle.cursor_status_to_draw());
+
+                    let pointer_location = pinnacle.cursor_render_location();
+
+                    elements.extend(pointer_render_elements(
+                        output,
+                        renderer,
+                        &pinnacle.space,
+                        pointer_location,
+                        &mut pinnacle.cursor_status,
+                        pinnacle.dnd_icon.as_ref(),
+                        &pointer_element,
+                    ));
+                }
+
+                elements.extend(output_render_elements(
+                    output,
+                    renderer,
+                    &pinnacle.space,
+                    &windows,
+                ));
+
+                // Elements are top to bottom but are drawn bottom to top
+                render_to_texture(
+                    renderer,
+                    elements.into_iter().rev(),
+                    size,
+                    scale,
+                    Transform::Normal,
+                    Fourcc::Abgr8888,
+                )?;
+
+                let buffer_size = size.to_logical(1).to_buffer(1, Transform::Normal);
+
+                // The texture is still bound from above
+                let mapping = renderer
+                    .copy_framebuffer(
+                        Rectangle::from_loc_and_size((0, 0), buffer_size),
+                        Fourcc::Abgr8888,
+                    )
+                    .context("failed to copy framebuffer")?;
+
+                let data = renderer
+                    .map_texture(&mapping)
+                    .context("failed to map texture")?
+                    .to_vec();
+
+                Ok(OutputCapture {
+                    width: size.w as u32,
+                    height: size.h as u32,
+                    stride: size.w as u32 * 4,
+                    data,
+                })
+            })
+            .context("no renderer available")?
+    }
+
+    /// Generate the render elements that would be drawn on `output` this frame,
+    /// from top to bottom.
+    ///
+    /// This is for debugging; nothing is actually rendered.
+    pub fn inspect_render_elements(
+        &mut self,
+        output: &Output,
+    ) -> Vec<OutputRenderElement<GlesRenderer>> {
+        let windows = self.pinnacle.space.elements().cloned().collect::<Vec<_>>();
+
+        self.backend
+            .with_renderer(|renderer| {
+                output_render_elements(output, renderer, &self.pinnacle.space, &windows)
+            })
+            .unwrap_or_default()
+    }
+
     /// Schedule a new render.
     pub fn schedule_render(&mut self, output: &Output) {
         match &mut self.backend {
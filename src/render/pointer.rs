@@ -12,13 +12,15 @@ use smithay::{
     },
     input::pointer::CursorImageStatus,
     render_elements,
-    utils::{Physical, Point, Scale},
+    utils::{Logical, Physical, Point, Scale},
 };
 
 use super::PRenderer;
 
 pub struct PointerElement<T: Texture> {
     texture: Option<TextureBuffer<T>>,
+    /// The hotspot of the texture
+    hotspot: Point<i32, Logical>,
     status: CursorImageStatus,
     kind: element::Kind,
 }
@@ -27,6 +29,7 @@ impl<T: Texture> Default for PointerElement<T> {
     fn default() -> Self {
         Self {
             texture: Default::default(),
+            hotspot: Default::default(),
             status: CursorImageStatus::default_named(),
             kind: element::Kind::Cursor,
         }
@@ -42,8 +45,14 @@ impl<T: Texture> PointerElement<T> {
         self.status = status;
     }
 
-    pub fn set_texture(&mut self, texture: TextureBuffer<T>) {
+    pub fn set_texture(&mut self, texture: TextureBuffer<T>, hotspot: Point<i32, Logical>) {
         self.texture = Some(texture);
+        self.hotspot = hotspot;
+    }
+
+    /// The hotspot of the texture drawn for named cursors.
+    pub fn hotspot(&self) -> Point<i32, Logical> {
+        self.hotspot
     }
 
     pub fn set_element_kind(&mut self, kind: element::Kind) {
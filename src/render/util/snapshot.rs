@@ -169,7 +169,7 @@ pub fn capture_snapshots_on_output(
                 || (win.is_on_active_tag()
                     && win.with_state(|state| state.floating_or_tiled.is_floating()))
         })
-        .position(|win| win.with_state(|state| state.fullscreen_or_maximized.is_fullscreen()));
+        .position(|win| win.is_fullscreen_on_output(output));
 
     let mut under_fullscreen = pinnacle
         .space
@@ -195,7 +195,7 @@ pub fn capture_snapshots_on_output(
                 renderer,
                 loc,
                 output.current_scale().fractional_scale().into(),
-                1.0,
+                win.render_alpha(),
             );
 
             Some(SnapshotTarget::Snapshot(snapshot))
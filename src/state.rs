@@ -12,9 +12,10 @@ use crate::{
     protocol::{
         foreign_toplevel::{self, ForeignToplevelManagerState},
         gamma_control::GammaControlManagerState,
+        output_management::{self, OutputManagementManagerState},
         screencopy::ScreencopyManagerState,
     },
-    window::WindowElement,
+    window::{window_state::WindowId, WindowElement},
 };
 use anyhow::Context;
 use pinnacle_api_defs::pinnacle::v0alpha1::ShutdownWatchResponse;
@@ -32,8 +33,12 @@ use smithay::{
     utils::{Clock, Monotonic},
     wayland::{
         compositor::{self, CompositorClientState, CompositorState},
+        cursor_shape::CursorShapeManagerState,
         dmabuf::DmabufFeedback,
         fractional_scale::FractionalScaleManagerState,
+        idle_notify::IdleNotifierState,
+        input_method::InputMethodManagerState,
+        keyboard_shortcuts_inhibit::KeyboardShortcutsInhibitState,
         output::OutputManagerState,
         pointer_constraints::PointerConstraintsState,
         relative_pointer::RelativePointerManagerState,
@@ -45,14 +50,18 @@ use smithay::{
         session_lock::SessionLockManagerState,
         shell::{wlr_layer::WlrLayerShellState, xdg::XdgShellState},
         shm::ShmState,
+        single_pixel_buffer::SinglePixelBufferState,
         socket::ListeningSocketSource,
+        tablet_manager::TabletManagerState,
+        text_input::TextInputManagerState,
         viewporter::ViewporterState,
+        virtual_keyboard::VirtualKeyboardManagerState,
         xwayland_shell::XWaylandShellState,
     },
     xwayland::{X11Wm, XWaylandClientData},
 };
 use std::{cell::RefCell, collections::HashMap, path::PathBuf, sync::Arc};
-use sysinfo::{ProcessRefreshKind, RefreshKind};
+use sysinfo::{ProcessRefreshKind, ProcessStatus, RefreshKind};
 use tracing::{info, warn};
 use xdg::BaseDirectories;
 
@@ -87,18 +96,27 @@ pub struct Pinnacle {
     pub output_manager_state: OutputManagerState,
     pub xdg_shell_state: XdgShellState,
     pub viewporter_state: ViewporterState,
+    pub single_pixel_buffer_state: SinglePixelBufferState,
+    pub keyboard_shortcuts_inhibit_state: KeyboardShortcutsInhibitState,
     pub fractional_scale_manager_state: FractionalScaleManagerState,
     pub primary_selection_state: PrimarySelectionState,
     pub layer_shell_state: WlrLayerShellState,
     pub data_control_state: DataControlState,
     pub screencopy_manager_state: ScreencopyManagerState,
     pub gamma_control_manager_state: GammaControlManagerState,
+    pub output_management_manager_state: OutputManagementManagerState,
     pub security_context_state: SecurityContextState,
     pub relative_pointer_manager_state: RelativePointerManagerState,
     pub pointer_constraints_state: PointerConstraintsState,
     pub foreign_toplevel_manager_state: ForeignToplevelManagerState,
     pub session_lock_manager_state: SessionLockManagerState,
     pub xwayland_shell_state: XWaylandShellState,
+    pub idle_notifier_state: IdleNotifierState<State>,
+    pub text_input_manager_state: TextInputManagerState,
+    pub input_method_manager_state: InputMethodManagerState,
+    pub virtual_keyboard_manager_state: VirtualKeyboardManagerState,
+    pub cursor_shape_manager_state: CursorShapeManagerState,
+    pub tablet_manager_state: TabletManagerState,
 
     pub lock_state: LockState,
 
@@ -125,6 +143,10 @@ pub struct Pinnacle {
     pub xdisplay: Option<u32>,
 
     pub system_processes: sysinfo::System,
+    /// Processes spawned with `once` keyed by their exact command line.
+    ///
+    /// This outlives configs so reloading doesn't spawn them again.
+    pub spawned_once: HashMap<Vec<String>, sysinfo::Pid>,
 
     // Currently only used to keep track of if the server has started
     pub grpc_server_join_handle: Option<tokio::task::JoinHandle<()>>,
@@ -132,6 +154,8 @@ pub struct Pinnacle {
     pub xdg_base_dirs: BaseDirectories,
 
     pub signal_state: SignalState,
+    /// The window that last had keyboard focus, used to only signal actual focus changes.
+    pub last_focused_window: Option<WindowId>,
 
     pub layout_state: LayoutState,
 
@@ -146,6 +170,8 @@ impl State {
         self.pinnacle.popup_manager.cleanup();
         self.update_pointer_focus();
         foreign_toplevel::refresh(self);
+        output_management::refresh(self);
+        self.pinnacle.signal_window_title_and_class_changes();
 
         if let Backend::Winit(winit) = &mut self.backend {
             winit.render_if_scheduled(&mut self.pinnacle);
@@ -191,7 +217,10 @@ impl Pinnacle {
         config_dir: PathBuf,
         cli: Option<Cli>,
     ) -> anyhow::Result<Self> {
-        let socket = ListeningSocketSource::new_auto()?;
+        let socket = match cli.as_ref().and_then(|cli| cli.wayland_socket.as_ref()) {
+            Some(name) => ListeningSocketSource::with_name(name)?,
+            None => ListeningSocketSource::new_auto()?,
+        };
         let socket_name = socket.socket_name().to_os_string();
 
         info!(
@@ -239,6 +268,8 @@ impl Pinnacle {
             filter_restricted_client,
         );
 
+        let idle_notifier_state = IdleNotifierState::new(&display_handle, loop_handle.clone());
+
         let pinnacle = Pinnacle {
             loop_signal,
             loop_handle,
@@ -253,6 +284,10 @@ impl Pinnacle {
             output_manager_state: OutputManagerState::new_with_xdg_output::<State>(&display_handle),
             xdg_shell_state: XdgShellState::new::<State>(&display_handle),
             viewporter_state: ViewporterState::new::<State>(&display_handle),
+            single_pixel_buffer_state: SinglePixelBufferState::new::<State>(&display_handle),
+            keyboard_shortcuts_inhibit_state: KeyboardShortcutsInhibitState::new::<State>(
+                &display_handle,
+            ),
             fractional_scale_manager_state: FractionalScaleManagerState::new::<State>(
                 &display_handle,
             ),
@@ -270,6 +305,10 @@ impl Pinnacle {
                 &display_handle,
                 filter_restricted_client,
             ),
+            output_management_manager_state: OutputManagementManagerState::new::<State, _>(
+                &display_handle,
+                filter_restricted_client,
+            ),
             security_context_state: SecurityContextState::new::<State, _>(
                 &display_handle,
                 filter_restricted_client,
@@ -287,6 +326,18 @@ impl Pinnacle {
                 filter_restricted_client,
             ),
             xwayland_shell_state: XWaylandShellState::new::<State>(&display_handle),
+            idle_notifier_state,
+            text_input_manager_state: TextInputManagerState::new::<State>(&display_handle),
+            input_method_manager_state: InputMethodManagerState::new::<State, _>(
+                &display_handle,
+                filter_restricted_client,
+            ),
+            virtual_keyboard_manager_state: VirtualKeyboardManagerState::new::<State, _>(
+                &display_handle,
+                filter_restricted_client,
+            ),
+            cursor_shape_manager_state: CursorShapeManagerState::new::<State>(&display_handle),
+            tablet_manager_state: TabletManagerState::new::<State>(&display_handle),
 
             lock_state: LockState::default(),
 
@@ -313,12 +364,15 @@ impl Pinnacle {
                 RefreshKind::new().with_processes(ProcessRefreshKind::new()),
             ),
 
+            spawned_once: HashMap::new(),
+
             grpc_server_join_handle: None,
 
             xdg_base_dirs: BaseDirectories::with_prefix("pinnacle")
                 .context("couldn't create xdg BaseDirectories")?,
 
             signal_state: SignalState::default(),
+            last_focused_window: None,
 
             layout_state: LayoutState::default(),
 
@@ -345,6 +399,30 @@ impl Pinnacle {
         });
     }
 
+    /// Returns whether or not a process spawned with `once` and the exact command line `args`
+    /// is still running.
+    pub fn spawned_once_is_running(&mut self, args: &[String]) -> bool {
+        let Some(pid) = self.spawned_once.get(args).copied() else {
+            return false;
+        };
+
+        self.system_processes
+            .refresh_processes_specifics(ProcessRefreshKind::new());
+
+        let compositor_pid = sysinfo::Pid::from_u32(std::process::id());
+
+        // Check the parent in case the pid was reused by an unrelated process
+        let running = self.system_processes.process(pid).is_some_and(|proc| {
+            proc.parent() == Some(compositor_pid) && proc.status() != ProcessStatus::Zombie
+        });
+
+        if !running {
+            self.spawned_once.remove(args);
+        }
+
+        running
+    }
+
     pub fn shutdown(&mut self) {
         info!("Shutting down Pinnacle");
         self.loop_signal.stop();
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod switch_animation;
+
 use std::{
     cell::RefCell,
     hash::Hash,
@@ -9,10 +11,23 @@ use std::{
 
 use smithay::output::Output;
 
-use crate::state::{Pinnacle, WithState};
+use crate::{
+    layout::Gaps,
+    render::util::snapshot::capture_snapshots_on_output,
+    state::{Pinnacle, State, WithState},
+};
+
+use self::switch_animation::TagSwitchAnimation;
 
 static TAG_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
 
+/// The smallest master factor a tag can have.
+pub const MIN_MASTER_FACTOR: f32 = 0.1;
+/// The largest master factor a tag can have.
+pub const MAX_MASTER_FACTOR: f32 = 0.9;
+/// The master factor relative changes start from when a tag doesn't have one.
+pub const DEFAULT_MASTER_FACTOR: f32 = 0.5;
+
 /// A unique id for a [`Tag`].
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub struct TagId(pub u32);
@@ -47,6 +62,18 @@ struct TagInner {
     name: String,
     /// Whether this tag is active or not.
     active: bool,
+    /// The name of the layout this tag uses.
+    ///
+    /// Layouts are generated by the config, so this is only used for bookkeeping.
+    layout: Option<String>,
+    /// The gaps around tiled windows while this tag is the output's first active tag.
+    gaps: Gaps,
+    /// The proportion of the layout area master-stack layouts give the master area.
+    ///
+    /// Layouts are generated by the config, which uses its own default if this isn't set.
+    master_factor: Option<f32>,
+    /// The master factor the config's layout last used when this tag didn't have one.
+    layout_master_factor: Option<f32>,
 }
 
 /// A marker for windows.
@@ -88,6 +115,41 @@ impl Tag {
         self.inner.borrow().active
     }
 
+    pub fn layout(&self) -> Option<String> {
+        self.inner.borrow().layout.clone()
+    }
+
+    pub fn set_layout(&self, layout: Option<String>) {
+        self.inner.borrow_mut().layout = layout;
+    }
+
+    pub fn gaps(&self) -> Gaps {
+        self.inner.borrow().gaps
+    }
+
+    pub fn set_gaps(&self, gaps: Gaps) {
+        self.inner.borrow_mut().gaps = gaps;
+    }
+
+    pub fn master_factor(&self) -> Option<f32> {
+        self.inner.borrow().master_factor
+    }
+
+    /// Set the master factor, clamping it between [`MIN_MASTER_FACTOR`] and
+    /// [`MAX_MASTER_FACTOR`].
+    pub fn set_master_factor(&self, master_factor: f32) {
+        self.inner.borrow_mut().master_factor =
+            Some(master_factor.clamp(MIN_MASTER_FACTOR, MAX_MASTER_FACTOR));
+    }
+
+    pub fn layout_master_factor(&self) -> Option<f32> {
+        self.inner.borrow().layout_master_factor
+    }
+
+    pub fn set_layout_master_factor(&self, master_factor: Option<f32>) {
+        self.inner.borrow_mut().layout_master_factor = master_factor;
+    }
+
     pub fn set_active(&self, active: bool, pinnacle: &mut Pinnacle) {
         self.inner.borrow_mut().active = active;
 
@@ -109,6 +171,10 @@ impl Tag {
             inner: Rc::new(RefCell::new(TagInner {
                 name,
                 active: false,
+                layout: None,
+                gaps: Gaps::default(),
+                master_factor: None,
+                layout_master_factor: None,
             })),
         }
     }
@@ -124,3 +190,171 @@ impl Tag {
             .cloned()
     }
 }
+
+impl Pinnacle {
+    /// Get the tag `offset` places away from the first active tag on `output`, wrapping around.
+    ///
+    /// If no tag is active, this is relative to the first tag.
+    pub fn tag_relative_to_active(&self, output: &Output, offset: isize) -> Option<Tag> {
+        output.with_state(|state| {
+            if state.tags.is_empty() {
+                return None;
+            }
+
+            let active_idx = state.tags.iter().position(|tag| tag.active()).unwrap_or(0);
+            let idx = (active_idx as isize + offset).rem_euclid(state.tags.len() as isize);
+
+            state.tags.get(idx as usize).cloned()
+        })
+    }
+
+    /// Get the `offset`th tag with windows on it in the direction of `offset` from the first
+    /// active tag on `output`, wrapping around.
+    ///
+    /// Tags are only gone around once, so this returns `None` if there aren't enough
+    /// occupied tags other than the active one.
+    pub fn occupied_tag_relative_to_active(&self, output: &Output, offset: isize) -> Option<Tag> {
+        if offset == 0 {
+            return None;
+        }
+
+        let tags = output.with_state(|state| state.tags.clone());
+        let len = tags.len() as isize;
+        let active_idx = tags.iter().position(|tag| tag.active()).unwrap_or(0) as isize;
+
+        let is_occupied = |tag: &Tag| {
+            self.windows
+                .iter()
+                .any(|win| win.with_state(|state| state.tags.contains(tag)))
+        };
+
+        (1..len)
+            .map(|i| &tags[(active_idx + offset.signum() * i).rem_euclid(len) as usize])
+            .filter(|tag| is_occupied(tag))
+            .nth(offset.unsigned_abs() - 1)
+            .cloned()
+    }
+
+    /// Give `output` the config's default tags and activate the first one.
+    ///
+    /// This does nothing if `output` already has tags.
+    pub fn add_default_tags(&mut self, output: &Output) {
+        if output.with_state(|state| !state.tags.is_empty()) {
+            return;
+        }
+
+        let tags = self
+            .config
+            .default_tags
+            .iter()
+            .cloned()
+            .map(Tag::new)
+            .collect::<Vec<_>>();
+
+        if let Some(first) = tags.first() {
+            first.set_active(true, self);
+        }
+
+        output.with_state_mut(|state| state.tags = tags);
+    }
+}
+
+impl State {
+    /// Change which tags are active on `output` through `change`.
+    ///
+    /// This snapshots the output for the layout transaction, remembers the previously
+    /// active tags, and relayouts and refocuses the output.
+    pub fn change_active_tags(&mut self, output: &Output, change: impl FnOnce(&mut Pinnacle)) {
+        let animation_duration = self.pinnacle.config.tag_switch_animation_duration;
+        let animate = self.pinnacle.config.tag_switch_animation && !animation_duration.is_zero();
+
+        let snapshots = self.backend.with_renderer(|renderer| {
+            // When animating, snapshot floating windows too so everything fades out
+            let also_include = if animate {
+                self.pinnacle.windows.clone()
+            } else {
+                Vec::new()
+            };
+            capture_snapshots_on_output(&mut self.pinnacle, renderer, output, also_include)
+        });
+
+        let animation = snapshots.as_ref().filter(|_| animate).map(
+            |(fs_and_up_snapshots, under_fs_snapshots)| {
+                TagSwitchAnimation::new(fs_and_up_snapshots, under_fs_snapshots, animation_duration)
+            },
+        );
+
+        let previously_active =
+            output.with_state(|state| state.focused_tags().cloned().collect::<Vec<_>>());
+
+        change(&mut self.pinnacle);
+
+        output.with_state_mut(|state| {
+            if !state.focused_tags().eq(previously_active.iter()) {
+                state.previous_active_tags = previously_active;
+            }
+        });
+
+        self.pinnacle.fixup_xwayland_window_layering();
+
+        if let Some(animation) = animation {
+            output.with_state_mut(|state| state.tag_switch_animation = Some(animation));
+        }
+
+        if let Some((fs_and_up_snapshots, under_fs_snapshots)) = snapshots {
+            output.with_state_mut(|op_state| {
+                op_state.new_wait_layout_transaction(
+                    self.pinnacle.loop_handle.clone(),
+                    fs_and_up_snapshots,
+                    under_fs_snapshots,
+                )
+            });
+        }
+
+        self.pinnacle.request_layout(output);
+        self.update_keyboard_focus(output);
+        self.schedule_render(output);
+    }
+
+    /// Switch to `tag`, deactivating all other tags on its output.
+    pub fn switch_to_tag(&mut self, tag: &Tag) {
+        let Some(output) = tag.output(&self.pinnacle) else {
+            return;
+        };
+
+        self.change_active_tags(&output, |pinnacle| {
+            output.with_state(|op_state| {
+                for op_tag in op_state.tags.iter() {
+                    op_tag.set_active(false, pinnacle);
+                }
+                tag.set_active(true, pinnacle);
+            });
+        });
+    }
+
+    /// Switch `output` back to the tags that were active before the last change.
+    ///
+    /// Calling this repeatedly toggles between the two most recent sets of active tags.
+    pub fn switch_to_previous_tags(&mut self, output: &Output) {
+        let previous = output.with_state(|state| {
+            state
+                .previous_active_tags
+                .iter()
+                .filter(|tag| state.tags.contains(tag))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+
+        if previous.is_empty() {
+            return;
+        }
+
+        self.change_active_tags(output, |pinnacle| {
+            output.with_state(|op_state| {
+                for op_tag in op_state.tags.iter() {
+                    op_tag.set_active(previous.contains(op_tag), pinnacle);
+                }
+            });
+        });
+    }
+}
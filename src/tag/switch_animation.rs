@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Crossfading between tags.
+
+use std::time::{Duration, Instant};
+
+use smithay::utils::Scale;
+
+use crate::{
+    layout::transaction::{LayoutSnapshot, SnapshotRenderElement, SnapshotTarget},
+    render::{AsGlesRenderer, PRenderer},
+};
+
+/// The default duration of tag switch animations.
+pub const DEFAULT_TAG_SWITCH_ANIMATION_DURATION: Duration = Duration::from_millis(200);
+
+/// An animation that fades out the windows of the previously active tags
+/// over the newly active ones.
+#[derive(Debug)]
+pub struct TagSwitchAnimation {
+    /// Snapshots of the outgoing fullscreen windows and the ones above them.
+    fullscreen_and_up_snapshots: Vec<LayoutSnapshot>,
+    /// Snapshots of the outgoing windows under fullscreen ones.
+    under_fullscreen_snapshots: Vec<LayoutSnapshot>,
+    duration: Duration,
+    /// The instant the animation started.
+    ///
+    /// The animation waits for any layout transaction to finish so it fades to the new layout.
+    start_time: Option<Instant>,
+}
+
+impl TagSwitchAnimation {
+    /// Creates a new animation from the snapshots of the outgoing windows
+    /// taken for the tag switch's layout transaction.
+    ///
+    /// Window targets are skipped as they can't be snapshotted after the switch.
+    pub fn new(
+        fullscreen_and_up_snapshots: &[SnapshotTarget],
+        under_fullscreen_snapshots: &[SnapshotTarget],
+        duration: Duration,
+    ) -> Self {
+        let snapshots = |targets: &[SnapshotTarget]| -> Vec<LayoutSnapshot> {
+            targets
+                .iter()
+                .filter_map(|target| match target {
+                    SnapshotTarget::Snapshot(snapshot) => Some(snapshot.clone()),
+                    SnapshotTarget::Window(_) => None,
+                })
+                .collect()
+        };
+
+        Self {
+            fullscreen_and_up_snapshots: snapshots(fullscreen_and_up_snapshots),
+            under_fullscreen_snapshots: snapshots(under_fullscreen_snapshots),
+            duration,
+            start_time: None,
+        }
+    }
+
+    /// Returns whether this animation has run for its whole duration.
+    pub fn is_done(&self) -> bool {
+        self.start_time
+            .is_some_and(|start_time| start_time.elapsed() >= self.duration)
+    }
+
+    /// Render elements for this animation, split into ones for windows fullscreen and up
+    /// and the rest.
+    ///
+    /// Nothing is rendered while `transaction_pending`, as the transaction
+    /// is already drawing the outgoing windows.
+    pub fn render_elements<R: PRenderer + AsGlesRenderer>(
+        &mut self,
+        renderer: &mut R,
+        scale: Scale<f64>,
+        transaction_pending: bool,
+    ) -> (Vec<SnapshotRenderElement<R>>, Vec<SnapshotRenderElement<R>>) {
+        if transaction_pending {
+            return Default::default();
+        }
+
+        let start_time = *self.start_time.get_or_insert_with(Instant::now);
+        let progress = start_time.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+        let alpha = (1.0 - progress).clamp(0.0, 1.0);
+
+        let mut render = |snapshots: &[LayoutSnapshot]| {
+            snapshots
+                .iter()
+                .flat_map(|snapshot| snapshot.render_elements(renderer, scale, alpha))
+                .collect::<Vec<_>>()
+        };
+
+        (
+            render(&self.fullscreen_and_up_snapshots),
+            render(&self.under_fullscreen_snapshots),
+        )
+    }
+}
@@ -1,9 +1,13 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 pub mod rules;
+pub mod scratchpad;
 
 use std::{cell::RefCell, collections::HashSet, ops::Deref};
 
+use pinnacle_api_defs::pinnacle::signal::v0alpha1::{
+    WindowClassChangedResponse, WindowTitleChangedResponse,
+};
 use smithay::{
     desktop::{space::SpaceElement, Window, WindowSurface},
     output::Output,
@@ -15,7 +19,7 @@ use tracing::{error, warn};
 
 use crate::state::{Pinnacle, WithState};
 
-use self::window_state::WindowElementState;
+use self::window_state::{FloatingOrTiled, WindowElementState};
 
 pub mod window_state;
 
@@ -138,12 +142,21 @@ impl WindowElement {
 
     /// Returns whether or not this window has an active tag.
     ///
+    /// Minimized windows are treated as not being on an active tag.
+    ///
     /// RefCell Safety: This calls `with_state` on `self`.
     pub fn is_on_active_tag(&self) -> bool {
-        self.with_state(|state| state.tags.iter().any(|tag| tag.active()))
+        self.with_state(|state| !state.minimized && state.tags.iter().any(|tag| tag.active()))
     }
 
+    /// Returns whether or not this window has one of `output`'s active tags.
+    ///
+    /// Minimized windows are treated as not being on an active tag.
     pub fn is_on_active_tag_on_output(&self, output: &Output) -> bool {
+        if self.with_state(|state| state.minimized) {
+            return false;
+        }
+
         // PERF: dear god benchmark this
         let win_tags = self
             .with_state(|state| state.tags.clone())
@@ -160,6 +173,27 @@ impl WindowElement {
         })
     }
 
+    /// Returns whether this window is fullscreen on one of `output`'s active tags.
+    ///
+    /// Fullscreen windows on inactive tags or other outputs shouldn't cover anything on `output`.
+    pub fn is_fullscreen_on_output(&self, output: &Output) -> bool {
+        self.with_state(|state| state.fullscreen_or_maximized.is_fullscreen())
+            && self.is_on_active_tag_on_output(output)
+    }
+
+    /// Returns the alpha this window should be rendered with.
+    ///
+    /// Fullscreen windows are always opaque so the clear color doesn't show through.
+    pub fn render_alpha(&self) -> f32 {
+        self.with_state(|state| {
+            if state.fullscreen_or_maximized.is_fullscreen() {
+                1.0
+            } else {
+                state.opacity
+            }
+        })
+    }
+
     /// Place this window on the given output, giving it the output's focused tags.
     ///
     /// RefCell Safety: Uses `with_state_mut` on the window and `with_state` on the output
@@ -280,6 +314,149 @@ impl WithState for WindowElement {
 }
 
 impl Pinnacle {
+    /// Signal the config about any windows whose title or class changed
+    /// since this was last called.
+    ///
+    /// This covers both xdg toplevels and X11 windows' `WM_NAME` and `WM_CLASS`.
+    pub fn signal_window_title_and_class_changes(&mut self) {
+        for win in self
+            .windows
+            .iter()
+            .filter(|win| !win.is_x11_override_redirect())
+        {
+            let title = win.title();
+            let class = win.class();
+
+            let (window_id, title_changed, class_changed) = win.with_state_mut(|state| {
+                let title_changed = state.last_title != title;
+                let class_changed = state.last_class != class;
+                state.last_title.clone_from(&title);
+                state.last_class.clone_from(&class);
+                (state.id.0, title_changed, class_changed)
+            });
+
+            if let (true, Some(title)) = (title_changed, title) {
+                self.signal_state.window_title_changed.signal(|buf| {
+                    buf.push_back(WindowTitleChangedResponse {
+                        window_id: Some(window_id),
+                        title: Some(title),
+                    });
+                });
+            }
+
+            if let (true, Some(class)) = (class_changed, class) {
+                self.signal_state.window_class_changed.signal(|buf| {
+                    buf.push_back(WindowClassChangedResponse {
+                        window_id: Some(window_id),
+                        class: Some(class),
+                    });
+                });
+            }
+        }
+    }
+
+    /// Clamp a floating window's `rect` so that at least the configured margin of it
+    /// stays on some output, moving it onto the nearest output if it doesn't.
+    ///
+    /// If floating windows aren't being kept in bounds, `rect` is returned unchanged.
+    pub fn clamp_floating_rect(&self, rect: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        if !self.config.keep_floating_in_bounds {
+            return rect;
+        }
+
+        let margin = self.config.floating_bounds_margin.max(1) as i32;
+        let margin_w = margin.min(rect.size.w.max(1));
+        let margin_h = margin.min(rect.size.h.max(1));
+
+        let output_geos = self
+            .space
+            .outputs()
+            .filter_map(|op| self.space.output_geometry(op))
+            .collect::<Vec<_>>();
+
+        let in_bounds = output_geos.iter().any(|geo| {
+            rect.intersection(*geo).is_some_and(|overlap| {
+                overlap.size.w >= margin_w.min(geo.size.w)
+                    && overlap.size.h >= margin_h.min(geo.size.h)
+            })
+        });
+
+        if in_bounds {
+            return rect;
+        }
+
+        let center = rect.loc + rect.size.downscale(2).to_point();
+
+        let Some(nearest) = output_geos.into_iter().min_by_key(|geo| {
+            let x = center.x.clamp(geo.loc.x, geo.loc.x + geo.size.w);
+            let y = center.y.clamp(geo.loc.y, geo.loc.y + geo.size.h);
+            (i64::from(center.x - x)).pow(2) + (i64::from(center.y - y)).pow(2)
+        }) else {
+            return rect;
+        };
+
+        let margin_w = margin_w.min(nearest.size.w);
+        let margin_h = margin_h.min(nearest.size.h);
+
+        let mut clamped = rect;
+        clamped.loc.x = rect
+            .loc
+            .x
+            .max(nearest.loc.x - rect.size.w + margin_w)
+            .min(nearest.loc.x + nearest.size.w - margin_w);
+        clamped.loc.y = rect
+            .loc
+            .y
+            .max(nearest.loc.y - rect.size.h + margin_h)
+            .min(nearest.loc.y + nearest.size.h - margin_h);
+
+        clamped
+    }
+
+    /// Move `window` back within output bounds if it's floating and floating windows
+    /// are being kept in bounds.
+    pub fn keep_floating_window_in_bounds(&mut self, window: &WindowElement) {
+        let FloatingOrTiled::Floating(rect) = window.with_state(|state| state.floating_or_tiled)
+        else {
+            return;
+        };
+
+        let clamped = self.clamp_floating_rect(rect);
+        if clamped == rect {
+            return;
+        }
+
+        window.with_state_mut(|state| {
+            state.floating_or_tiled = FloatingOrTiled::Floating(clamped);
+        });
+
+        if self.space.element_location(window).is_some() {
+            window.change_geometry(clamped);
+            self.space.map_element(window.clone(), clamped.loc, false);
+        }
+    }
+
+    /// Move all floating windows back within output bounds if floating windows
+    /// are being kept in bounds.
+    ///
+    /// This should be called when outputs are moved, resized, or removed so windows
+    /// don't get stranded offscreen.
+    pub fn keep_floating_windows_in_bounds(&mut self) {
+        if !self.config.keep_floating_in_bounds {
+            return;
+        }
+
+        for window in self
+            .windows
+            .iter()
+            .filter(|win| !win.is_x11_override_redirect())
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            self.keep_floating_window_in_bounds(&window);
+        }
+    }
+
     /// Returns the [Window] associated with a given [WlSurface].
     pub fn window_for_surface(&self, surface: &WlSurface) -> Option<WindowElement> {
         self.windows
@@ -28,6 +28,12 @@ pub struct WindowRuleCondition {
     /// This condition is met when the title matches.
     #[serde(default)]
     pub title: Option<Vec<String>>,
+    /// This condition is met when the class matches the regex.
+    #[serde(default)]
+    pub class_regex: Option<Vec<RulePattern>>,
+    /// This condition is met when the title matches the regex.
+    #[serde(default)]
+    pub title_regex: Option<Vec<RulePattern>>,
     /// This condition is met when the tag matches.
     #[serde(default)]
     pub tag: Option<Vec<TagId>>,
@@ -56,6 +62,8 @@ impl WindowRuleCondition {
             cond_all,
             class,
             title,
+            class_regex,
+            title_regex,
             tag,
         } = self;
 
@@ -89,6 +97,20 @@ impl WindowRuleCondition {
                 } else {
                     true
                 };
+                let class_regexes = if let Some(patterns) = class_regex {
+                    patterns
+                        .iter()
+                        .all(|pattern| pattern.is_match(window.class().as_deref()))
+                } else {
+                    true
+                };
+                let title_regexes = if let Some(patterns) = title_regex {
+                    patterns
+                        .iter()
+                        .all(|pattern| pattern.is_match(window.title().as_deref()))
+                } else {
+                    true
+                };
                 let tags = if let Some(tag_ids) = tag {
                     let mut tags = tag_ids.iter().filter_map(|tag_id| tag_id.tag(pinnacle));
                     tags.all(|tag| window.with_state(|state| state.tags.contains(&tag)))
@@ -96,7 +118,7 @@ impl WindowRuleCondition {
                     true
                 };
 
-                cond_all && cond_any && classes && titles && tags
+                cond_all && cond_any && classes && titles && class_regexes && title_regexes && tags
             }
             AllOrAny::Any => {
                 let cond_any = if let Some(cond_any) = cond_any {
@@ -127,19 +149,69 @@ impl WindowRuleCondition {
                 } else {
                     false
                 };
+                let class_regexes = if let Some(patterns) = class_regex {
+                    patterns
+                        .iter()
+                        .any(|pattern| pattern.is_match(window.class().as_deref()))
+                } else {
+                    false
+                };
+                let title_regexes = if let Some(patterns) = title_regex {
+                    patterns
+                        .iter()
+                        .any(|pattern| pattern.is_match(window.title().as_deref()))
+                } else {
+                    false
+                };
                 let tags = if let Some(tag_ids) = tag {
                     let mut tags = tag_ids.iter().filter_map(|tag_id| tag_id.tag(pinnacle));
                     tags.any(|tag| window.with_state(|state| state.tags.contains(&tag)))
                 } else {
                     false
                 };
-                cond_all || cond_any || classes || titles || tags
+                cond_all || cond_any || classes || titles || class_regexes || title_regexes || tags
             }
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+/// A regex in a window rule condition, compiled once when the rule is added.
+#[derive(Debug, Clone)]
+pub struct RulePattern(regex::Regex);
+
+impl RulePattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        regex::Regex::new(pattern).map(Self)
+    }
+
+    /// Whether `haystack` matches this pattern. A missing haystack never matches.
+    fn is_match(&self, haystack: Option<&str>) -> bool {
+        haystack.is_some_and(|haystack| self.0.is_match(haystack))
+    }
+}
+
+impl PartialEq for RulePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for RulePattern {}
+
+impl serde::Serialize for RulePattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RulePattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Self::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
 pub struct WindowRule {
     /// Set the output the window will open on.
     #[serde(default)]
@@ -160,6 +232,15 @@ pub struct WindowRule {
     /// when set to floating.
     #[serde(default)]
     pub location: Option<(i32, i32)>,
+    /// Move the window to this output whenever it becomes fullscreen.
+    #[serde(default)]
+    pub fullscreen_output: Option<OutputName>,
+    /// Set the window's opacity.
+    #[serde(default)]
+    pub opacity: Option<f32>,
+    /// Set whether the window covers layer surface exclusive zones when maximized.
+    #[serde(default)]
+    pub maximize_covers_exclusive_zones: Option<bool>,
 }
 
 // TODO: just skip serializing fields on the other FloatingOrTiled
@@ -181,6 +262,9 @@ impl Pinnacle {
                     fullscreen_or_maximized,
                     size,
                     location,
+                    fullscreen_output,
+                    opacity,
+                    maximize_covers_exclusive_zones,
                 } = rule;
 
                 // TODO: If both `output` and `tags` are specified, `tags` will apply over
@@ -219,6 +303,20 @@ impl Pinnacle {
                     }
                 }
 
+                if let Some(fullscreen_output) = fullscreen_output {
+                    window.with_state_mut(|state| {
+                        state.fullscreen_output = Some(fullscreen_output.clone())
+                    });
+                }
+
+                if let Some(opacity) = opacity {
+                    window.with_state_mut(|state| state.opacity = opacity.clamp(0.0, 1.0));
+                }
+
+                if let Some(covers) = maximize_covers_exclusive_zones {
+                    window.with_state_mut(|state| state.maximize_covers_exclusive_zones = *covers);
+                }
+
                 if let Some(fs_or_max) = fullscreen_or_maximized {
                     window.with_state_mut(|state| state.fullscreen_or_maximized = *fs_or_max);
                 }
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The scratchpad.
+//!
+//! Windows in the scratchpad are floating and hidden by taking away all of their tags,
+//! so they don't show up on any output or in tag cycling and aren't affected when outputs
+//! disconnect. Summoning them gives them the focused output's active tags and centers them.
+
+use smithay::{output::Output, utils::Rectangle};
+
+use crate::{
+    render::util::snapshot::capture_snapshots_on_output,
+    state::{State, WithState},
+    window::{window_state::FloatingOrTiled, WindowElement},
+};
+
+impl State {
+    /// Add `window` to the scratchpad and hide it, or remove it from the scratchpad.
+    ///
+    /// Windows removed while hidden are put back on the focused output.
+    pub fn toggle_window_scratchpad(&mut self, window: &WindowElement) {
+        if window.with_state(|state| state.scratchpad) {
+            window.with_state_mut(|state| state.scratchpad = false);
+
+            if window.with_state(|state| state.tags.is_empty()) {
+                if let Some(output) = self.pinnacle.focused_output().cloned() {
+                    self.show_scratchpad_window(window, &output);
+                }
+            }
+        } else {
+            if window.with_state(|state| state.floating_or_tiled.is_tiled()) {
+                window.toggle_floating();
+            }
+            window.with_state_mut(|state| state.scratchpad = true);
+            self.hide_scratchpad_window(window);
+        }
+    }
+
+    /// Summon the scratchpad on the focused output, or dismiss it if any of its windows
+    /// are already shown there.
+    pub fn toggle_scratchpad_visible(&mut self) {
+        let Some(output) = self.pinnacle.focused_output().cloned() else {
+            return;
+        };
+
+        let scratchpad = self
+            .pinnacle
+            .windows
+            .iter()
+            .filter(|win| win.with_state(|state| state.scratchpad))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let shown = scratchpad
+            .iter()
+            .filter(|win| win.is_on_active_tag_on_output(&output))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !shown.is_empty() {
+            for win in shown {
+                self.hide_scratchpad_window(&win);
+            }
+            return;
+        }
+
+        for win in scratchpad {
+            self.show_scratchpad_window(&win, &output);
+        }
+    }
+
+    fn hide_scratchpad_window(&mut self, window: &WindowElement) {
+        let Some(output) = window.output(&self.pinnacle) else {
+            window.with_state_mut(|state| state.tags.clear());
+            return;
+        };
+
+        let snapshots = self.backend.with_renderer(|renderer| {
+            capture_snapshots_on_output(&mut self.pinnacle, renderer, &output, [window.clone()])
+        });
+
+        window.with_state_mut(|state| state.tags.clear());
+
+        if let Some((fs_and_up_snapshots, under_fs_snapshots)) = snapshots {
+            output.with_state_mut(|op_state| {
+                op_state.new_wait_layout_transaction(
+                    self.pinnacle.loop_handle.clone(),
+                    fs_and_up_snapshots,
+                    under_fs_snapshots,
+                )
+            });
+        }
+
+        self.pinnacle.request_layout(&output);
+        self.update_keyboard_focus(&output);
+        self.schedule_render(&output);
+    }
+
+    /// Put `window` on `output`'s active tags, centered and focused.
+    fn show_scratchpad_window(&mut self, window: &WindowElement, output: &Output) {
+        let Some(output_geo) = self.pinnacle.space.output_geometry(output) else {
+            return;
+        };
+
+        window.place_on_output(output);
+
+        window.with_state_mut(|state| {
+            if let FloatingOrTiled::Floating(rect) = &mut state.floating_or_tiled {
+                let size = rect.size;
+                let loc = output_geo.loc
+                    + (
+                        (output_geo.size.w - size.w) / 2,
+                        (output_geo.size.h - size.h) / 2,
+                    )
+                        .into();
+                *rect = Rectangle::from_loc_and_size(loc, size);
+            }
+        });
+
+        self.pinnacle.raise_window(window.clone(), true);
+        output.with_state_mut(|state| state.focus_stack.set_focus(window.clone()));
+
+        self.pinnacle.request_layout(output);
+        self.update_keyboard_focus(output);
+        self.schedule_render(output);
+    }
+}
@@ -4,13 +4,14 @@ use std::sync::atomic::{AtomicU32, Ordering};
 
 use smithay::{
     desktop::{space::SpaceElement, WindowSurface},
-    reexports::wayland_protocols::xdg::shell::server::xdg_toplevel,
+    reexports::{calloop::RegistrationToken, wayland_protocols::xdg::shell::server::xdg_toplevel},
     utils::{Logical, Point, Rectangle, Serial},
     wayland::compositor::HookId,
 };
 
 use crate::{
     layout::transaction::LayoutSnapshot,
+    output::OutputName,
     state::{Pinnacle, WithState},
     tag::Tag,
 };
@@ -60,6 +61,41 @@ pub struct WindowElementState {
     pub committed_serial: Option<Serial>,
     pub snapshot: Option<LayoutSnapshot>,
     pub snapshot_hook_id: Option<HookId>,
+    /// The output this window is moved to when it becomes fullscreen, set by a window rule.
+    pub fullscreen_output: Option<OutputName>,
+    /// The tags this window had before being moved to its fullscreen output.
+    pub pre_fullscreen_tags: Option<Vec<Tag>>,
+    /// A fullscreen or maximize request from the client that the config hasn't resolved yet.
+    pub pending_state_request: Option<PendingStateRequest>,
+    /// How opaque this window is drawn, from 0.0 to 1.0.
+    pub opacity: f32,
+    /// Whether this window covers layer surface exclusive zones when maximized.
+    pub maximize_covers_exclusive_zones: bool,
+    /// The title from the last time title changes were checked.
+    pub last_title: Option<String>,
+    /// The class from the last time class changes were checked.
+    pub last_class: Option<String>,
+    /// Whether this window is in the scratchpad.
+    ///
+    /// Scratchpad windows without tags are hidden.
+    pub scratchpad: bool,
+}
+
+/// A state that a client can request, which the config can allow or deny.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateRequest {
+    Fullscreen,
+    Maximized,
+}
+
+/// A client's state request that is waiting on the config.
+#[derive(Debug)]
+pub struct PendingStateRequest {
+    pub request: StateRequest,
+    /// The output the client asked to be fullscreen on
+    pub output: Option<OutputName>,
+    /// The timer that applies the request if the config doesn't resolve it in time
+    pub timeout_token: RegistrationToken,
 }
 
 impl WindowElement {
@@ -312,6 +348,14 @@ impl WindowElementState {
             committed_serial: None,
             snapshot: None,
             snapshot_hook_id: None,
+            fullscreen_output: None,
+            pre_fullscreen_tags: None,
+            pending_state_request: None,
+            opacity: 1.0,
+            maximize_covers_exclusive_zones: false,
+            last_title: None,
+            last_class: None,
+            scratchpad: false,
         }
     }
 }
@@ -79,3 +79,14 @@ pub fn output_for_name(state: &State, name: &str) -> Output {
         .unwrap()
         .clone()
 }
+
+/// The client the window tests spawn. CI installs it alongside the other test dependencies.
+#[allow(dead_code)]
+pub const TEST_CLIENT: &str = "foot";
+
+/// Whether [`TEST_CLIENT`] is on the `PATH`, so window tests can be skipped when it isn't.
+#[allow(dead_code)]
+pub fn test_client_available() -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(TEST_CLIENT).is_file()))
+}
@@ -8,7 +8,7 @@ use pinnacle_api::ApiModules;
 use test_log::test;
 
 use crate::common::output_for_name;
-use crate::common::{sleep_secs, test_api, with_state};
+use crate::common::{sleep_secs, test_api, test_client_available, with_state, TEST_CLIENT};
 
 #[tokio::main]
 async fn run_rust_inner(run: impl FnOnce(ApiModules) + Send + 'static) {
@@ -41,8 +41,16 @@ fn setup_rust(run: impl FnOnce(ApiModules) + Send + 'static) -> JoinHandle<()> {
 }
 
 mod output {
-    use pinnacle::state::WithState;
-    use pinnacle_api::output::{Alignment, OutputId, OutputLoc, OutputSetup, UpdateLocsOn};
+    use std::collections::HashMap;
+
+    use pinnacle::{
+        output::{OutputName, PrimaryOutput},
+        protocol::output_management::{
+            ModeConfiguration, OutputConfiguration, OutputManagementHandler,
+        },
+        state::WithState,
+    };
+    use pinnacle_api::output::{Alignment, Mode, OutputId, OutputLoc, OutputSetup, UpdateLocsOn};
     use smithay::{output::Output, utils::Rectangle};
 
     use super::*;
@@ -122,6 +130,512 @@ mod output {
         })
     }
 
+    #[tokio::main]
+    #[self::test]
+    async fn on_connect_and_disconnect() -> anyhow::Result<()> {
+        static DISCONNECTED: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+        test_api(|sender| {
+            setup_rust(|api| {
+                let tag = api.tag;
+                api.output.on_connect(move |op| {
+                    let tags = tag.add(op, ["1", "2"]);
+                    tags[0].set_active(true);
+                });
+                api.output.on_disconnect(|op| {
+                    DISCONNECTED.lock().unwrap().push(op.name());
+                });
+            });
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Second", (300, 200).into());
+            });
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let second = output_for_name(state, "Second");
+                let tags = second.with_state(|state| {
+                    state.tags.iter().map(|tag| tag.name()).collect::<Vec<_>>()
+                });
+                assert_eq!(tags, vec!["1", "2"]);
+
+                let focused = second.with_state(|state| {
+                    state
+                        .focused_tags()
+                        .map(|tag| tag.name())
+                        .collect::<Vec<_>>()
+                });
+                assert_eq!(focused, vec!["1"]);
+
+                state.pinnacle.remove_output(&second);
+            });
+
+            sleep_secs(1);
+
+            assert_eq!(*DISCONNECTED.lock().unwrap(), vec!["Second".to_string()]);
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn get_and_set_focused() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Second", (300, 200).into());
+            });
+
+            run_rust(|api| {
+                let second = api.output.get_by_name("Second").unwrap();
+                api.output.set_focused(&second);
+                assert_eq!(api.output.get_focused(), Some(second));
+            })?;
+
+            with_state(&sender, |state| {
+                let focused = state.pinnacle.focused_output().unwrap();
+                assert_eq!(focused.name(), "Second");
+            });
+
+            run_rust(|api| {
+                let first = api.output.get_by_name(DUMMY_OUTPUT_NAME).unwrap();
+                api.output.set_focused(&first);
+                assert_eq!(api.output.get_focused(), Some(first));
+            })?;
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_focused_ignores_disconnected_output() -> anyhow::Result<()> {
+        static SECOND: std::sync::Mutex<Option<pinnacle_api::output::OutputHandle>> =
+            std::sync::Mutex::new(None);
+
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Second", (300, 200).into());
+            });
+
+            run_rust(|api| {
+                *SECOND.lock().unwrap() = api.output.get_by_name("Second");
+            })?;
+
+            with_state(&sender, |state| {
+                let second = output_for_name(state, "Second");
+                state.pinnacle.remove_output(&second);
+            });
+
+            // This errors with NOT_FOUND instead of focusing nothing
+            run_rust(|api| {
+                let second = SECOND.lock().unwrap().take().unwrap();
+                api.output.set_focused(&second);
+            })?;
+
+            with_state(&sender, |state| {
+                let focused = state.pinnacle.focused_output().unwrap();
+                assert_eq!(focused.name(), DUMMY_OUTPUT_NAME);
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn capture_errors() -> anyhow::Result<()> {
+        static SECOND: std::sync::Mutex<Option<pinnacle_api::output::OutputHandle>> =
+            std::sync::Mutex::new(None);
+
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Second", (300, 200).into());
+            });
+
+            // The dummy backend has no renderer to capture with
+            run_rust(|api| {
+                let status = api
+                    .output
+                    .get_focused()
+                    .unwrap()
+                    .capture(false)
+                    .unwrap_err();
+                assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+                *SECOND.lock().unwrap() = api.output.get_by_name("Second");
+            })?;
+
+            with_state(&sender, |state| {
+                let second = output_for_name(state, "Second");
+                state.pinnacle.remove_output(&second);
+            });
+
+            run_rust(|api| {
+                let second = SECOND.lock().unwrap().take().unwrap();
+                let status = second.capture(true).unwrap_err();
+                assert_eq!(status.code(), tonic::Code::NotFound);
+            })?;
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn primary_output_places_others_edge_to_edge() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Middle", (1920, 1080).into());
+                state.pinnacle.new_output("Right", (1920, 1080).into());
+
+                let left = output_for_name(state, DUMMY_OUTPUT_NAME);
+                let middle = output_for_name(state, "Middle");
+                let right = output_for_name(state, "Right");
+
+                state.pinnacle.change_output_state(
+                    &middle,
+                    None,
+                    None,
+                    None,
+                    Some((1920, 0).into()),
+                );
+                state.pinnacle.change_output_state(
+                    &right,
+                    None,
+                    None,
+                    None,
+                    Some((3840, 0).into()),
+                );
+
+                state.pinnacle.config.primary_output =
+                    Some(PrimaryOutput::Name(OutputName("Middle".to_string())));
+                state.pinnacle.anchor_primary_output();
+
+                assert_eq!(middle.current_location(), (0, 0).into());
+                assert_eq!(left.current_location(), (1920, 0).into());
+                assert_eq!(right.current_location(), (3840, 0).into());
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn output_management_rejects_unsupported_modes() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                let current = op.current_mode().unwrap();
+
+                let config = |mode| {
+                    HashMap::from([(
+                        op.clone(),
+                        OutputConfiguration::Enabled {
+                            mode: Some(mode),
+                            position: None,
+                            transform: None,
+                            scale: None,
+                        },
+                    )])
+                };
+
+                let unsupported = ModeConfiguration::Mode(smithay::output::Mode {
+                    size: (1234, 567).into(),
+                    refresh: 60_000,
+                });
+                assert!(!state.apply_output_configuration(config(unsupported), true));
+                assert!(!state.apply_output_configuration(config(unsupported), false));
+                assert_eq!(op.current_mode(), Some(current));
+
+                // Custom refresh rates only need to be close to a supported one
+                let close = ModeConfiguration::Custom {
+                    size: current.size,
+                    refresh: Some(current.refresh - 100),
+                };
+                let far = ModeConfiguration::Custom {
+                    size: current.size,
+                    refresh: Some(current.refresh - 10_000),
+                };
+                assert!(state.apply_output_configuration(config(close), true));
+                assert!(!state.apply_output_configuration(config(far), true));
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn output_management_test_only_changes_nothing() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                let loc = op.current_location();
+
+                let config = HashMap::from([(
+                    op.clone(),
+                    OutputConfiguration::Enabled {
+                        mode: Some(ModeConfiguration::Mode(op.current_mode().unwrap())),
+                        position: Some(loc + (100, 0).into()),
+                        transform: None,
+                        scale: None,
+                    },
+                )]);
+
+                assert!(state.apply_output_configuration(config.clone(), true));
+                assert_eq!(op.current_location(), loc);
+
+                assert!(state.apply_output_configuration(config, false));
+                assert_eq!(op.current_location(), loc + (100, 0).into());
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn output_management_failure_changes_nothing() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Second", (300, 200).into());
+
+                let first = output_for_name(state, DUMMY_OUTPUT_NAME);
+                let second = output_for_name(state, "Second");
+                let first_loc = first.current_location();
+                let second_scale = second.current_scale().fractional_scale();
+
+                let enabled = |position, scale| OutputConfiguration::Enabled {
+                    mode: None,
+                    position,
+                    transform: None,
+                    scale,
+                };
+
+                // Scales too small to use are rejected, not clamped
+                let config = HashMap::from([
+                    (
+                        first.clone(),
+                        enabled(Some(first_loc + (0, 100).into()), None),
+                    ),
+                    (second.clone(), enabled(None, Some(0.1))),
+                ]);
+
+                assert!(!state.apply_output_configuration(config.clone(), true));
+                assert!(!state.apply_output_configuration(config, false));
+                assert_eq!(first.current_location(), first_loc);
+                assert_eq!(second.current_scale().fractional_scale(), second_scale);
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_color_temperature_is_remembered() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                api.output
+                    .get_focused()
+                    .unwrap()
+                    .set_color_temperature(3500);
+            })?;
+
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                assert_eq!(op.with_state(|state| state.color_temperature), Some(3500));
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_fractional_scale() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                api.output.get_focused().unwrap().set_scale(1.25);
+            })?;
+
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                assert_eq!(op.current_scale().fractional_scale(), 1.25);
+            });
+
+            // Non-finite scales and scale changes are rejected
+            run_rust(|api| {
+                let op = api.output.get_focused().unwrap();
+                op.set_scale(f32::NAN);
+                op.set_scale(f32::INFINITY);
+                op.increase_scale(f32::INFINITY);
+                op.decrease_scale(f32::INFINITY);
+                op.increase_scale(f32::NAN);
+            })?;
+
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                assert_eq!(op.current_scale().fractional_scale(), 1.25);
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_transform_keeps_pointer_on_output() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                api.input.warp_cursor(1900.0, 10.0);
+                api.output
+                    .get_focused()
+                    .unwrap()
+                    .set_transform(pinnacle_api::output::Transform::_90);
+            })?;
+
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                assert_eq!(op.current_transform(), smithay::utils::Transform::_90);
+
+                let op_geo = state.pinnacle.space.output_geometry(&op).unwrap();
+                assert_eq!(op_geo.size, (1080, 1920).into());
+
+                let pointer_loc = state
+                    .pinnacle
+                    .seat
+                    .get_pointer()
+                    .unwrap()
+                    .current_location();
+                assert!(op_geo.to_f64().contains(pointer_loc));
+                assert_eq!(pointer_loc.y, 10.0);
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_loc_adj_to_and_overlap() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("First", (300, 200).into());
+            });
+
+            run_rust(|api| {
+                let original = api.output.get_by_name(DUMMY_OUTPUT_NAME).unwrap();
+                let first = api.output.get_by_name("First").unwrap();
+
+                first.set_loc_adj_to(&original, Alignment::BottomAlignCenter);
+            })?;
+
+            with_state(&sender, |state| {
+                let first_op = output_for_name(state, "First");
+                let first_geo = state.pinnacle.space.output_geometry(&first_op).unwrap();
+                assert_eq!(
+                    first_geo,
+                    Rectangle::from_loc_and_size((810, 1080), (300, 200))
+                );
+            });
+
+            run_rust(|api| {
+                // Overlapping outputs are allowed
+                api.output
+                    .get_by_name("First")
+                    .unwrap()
+                    .set_location(100, 100);
+            })?;
+
+            with_state(&sender, |state| {
+                let first_op = output_for_name(state, "First");
+                let first_geo = state.pinnacle.space.output_geometry(&first_op).unwrap();
+                assert_eq!(first_geo.loc, (100, 100).into());
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_mode_by_refresh() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                op.with_state_mut(|state| {
+                    state.modes.extend([
+                        smithay::output::Mode {
+                            size: (1920, 1080).into(),
+                            refresh: 59940,
+                        },
+                        smithay::output::Mode {
+                            size: (1920, 1080).into(),
+                            refresh: 60000,
+                        },
+                        smithay::output::Mode {
+                            size: (1280, 720).into(),
+                            refresh: 59000,
+                        },
+                    ])
+                });
+            });
+
+            run_rust(|api| {
+                let op = api.output.get_by_name(DUMMY_OUTPUT_NAME).unwrap();
+                assert_eq!(op.modes().len(), 4);
+
+                op.set_mode_by_refresh(59000);
+
+                let mode_1080p_59940 = Mode {
+                    pixel_width: 1920,
+                    pixel_height: 1080,
+                    refresh_rate_millihertz: 59940,
+                };
+
+                assert_eq!(op.current_mode(), Some(mode_1080p_59940));
+                assert_eq!(op.preferred_mode().unwrap().refresh_rate_millihertz, 144000);
+                assert_eq!(op.modes().len(), 4);
+            })?;
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_custom_mode() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let op = api.output.get_by_name(DUMMY_OUTPUT_NAME).unwrap();
+                op.set_custom_mode(1600, 900, None);
+
+                let custom_mode = Mode {
+                    pixel_width: 1600,
+                    pixel_height: 900,
+                    refresh_rate_millihertz: 60000,
+                };
+
+                assert_eq!(op.current_mode(), Some(custom_mode.clone()));
+                assert!(op.modes().contains(&custom_mode));
+            })?;
+
+            with_state(&sender, |state| {
+                let op = output_for_name(state, DUMMY_OUTPUT_NAME);
+                let geo = state.pinnacle.space.output_geometry(&op).unwrap();
+                assert_eq!(geo.size, (1600, 900).into());
+            });
+
+            Ok(())
+        })
+    }
+
     #[tokio::main]
     #[self::test]
     async fn setup_loc_with_cyclic_relative_locs_works() -> anyhow::Result<()> {
@@ -364,29 +878,137 @@ mod output {
 
         #[tokio::main]
         #[self::test]
-        async fn keyboard_focus_stack() -> anyhow::Result<()> {
-            test_api(|_sender| {
-                setup_rust(|api| {
-                    api.output.setup([
-                        OutputSetup::new_with_matcher(|_| true).with_tags(["1", "2", "3"])
-                    ]);
-                });
-
-                sleep_secs(1);
-
+        async fn set_clear_color() -> anyhow::Result<()> {
+            test_api(|sender| {
                 run_rust(|api| {
-                    api.process.spawn(["foot"]);
-                    api.process.spawn(["foot"]);
-                    api.process.spawn(["foot"]);
+                    api.output
+                        .get_focused()
+                        .unwrap()
+                        .set_clear_color(0.25, 0.5, 2.0, 1.0);
                 })?;
 
                 sleep_secs(1);
 
-                run_rust(|api| {
-                    api.tag.get("2").unwrap().switch_to();
-                    api.process.spawn(["foot"]);
-                    api.process.spawn(["foot"]);
-                })?;
+                with_state(&sender, |state| {
+                    let op = state.pinnacle.focused_output().unwrap();
+                    assert_eq!(
+                        op.with_state(|state| state.clear_color),
+                        Some([0.25, 0.5, 1.0, 1.0])
+                    );
+                });
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn props_make_model_serial() -> anyhow::Result<()> {
+            test_api(|sender| {
+                with_state(&sender, |state| {
+                    // Outputs without EDID get "Unknown" as their make and model
+                    let output = Output::new(
+                        "No EDID".to_string(),
+                        smithay::output::PhysicalProperties {
+                            size: (0, 0).into(),
+                            subpixel: smithay::output::Subpixel::Unknown,
+                            make: "Unknown".to_string(),
+                            model: "Unknown".to_string(),
+                        },
+                    );
+                    output.change_current_state(
+                        Some(smithay::output::Mode {
+                            size: (300, 200).into(),
+                            refresh: 60_000,
+                        }),
+                        None,
+                        None,
+                        Some((1920, 0).into()),
+                    );
+                    state.pinnacle.space.map_output(&output, (1920, 0));
+                });
+
+                run_rust(|api| {
+                    let props = api.output.get_focused().unwrap().props();
+                    assert_eq!(props.make.as_deref(), Some("Pinnacle"));
+                    assert_eq!(props.model.as_deref(), Some("Dummy Window"));
+                    assert_eq!(props.serial, None);
+
+                    let props = api.output.get_by_name("No EDID").unwrap().props();
+                    assert_eq!(props.make.as_deref(), Some("Unknown"));
+                    assert_eq!(props.model.as_deref(), Some("Unknown"));
+                    assert_eq!(props.serial, None);
+                })?;
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn props_of_all_outputs() -> anyhow::Result<()> {
+            test_api(|sender| {
+                with_state(&sender, |state| {
+                    state.pinnacle.new_output("Second", (300, 200).into());
+                });
+
+                run_rust(|api| {
+                    let outputs = api.output.get_all();
+                    assert_eq!(outputs.len(), 2);
+
+                    let dummy = outputs
+                        .iter()
+                        .find(|op| op.name() == DUMMY_OUTPUT_NAME)
+                        .unwrap()
+                        .props();
+                    assert_eq!(dummy.focused, Some(true));
+                    assert_eq!(dummy.powered, Some(true));
+                    assert_eq!(
+                        dummy.current_mode.map(|mode| mode.refresh_rate_millihertz),
+                        Some(144000)
+                    );
+
+                    let second = outputs
+                        .iter()
+                        .find(|op| op.name() == "Second")
+                        .unwrap()
+                        .props();
+                    assert_eq!(second.focused, Some(false));
+                    assert_eq!(second.powered, Some(true));
+                    assert_eq!(second.logical_width, Some(300));
+                    assert_eq!(second.logical_height, Some(200));
+                    assert_eq!(second.vrr, Some(false));
+                })?;
+
+                Ok(())
+            })
+        }
+
+        #[tokio::main]
+        #[self::test]
+        async fn keyboard_focus_stack() -> anyhow::Result<()> {
+            test_api(|_sender| {
+                setup_rust(|api| {
+                    api.output.setup([
+                        OutputSetup::new_with_matcher(|_| true).with_tags(["1", "2", "3"])
+                    ]);
+                });
+
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    api.process.spawn(["foot"]);
+                    api.process.spawn(["foot"]);
+                    api.process.spawn(["foot"]);
+                })?;
+
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    api.tag.get("2").unwrap().switch_to();
+                    api.process.spawn(["foot"]);
+                    api.process.spawn(["foot"]);
+                })?;
 
                 sleep_secs(1);
 
@@ -497,3 +1119,1514 @@ mod output {
         }
     }
 }
+
+mod window {
+    use pinnacle::{
+        state::WithState,
+        window::window_state::{FloatingOrTiled, WindowId},
+    };
+    use pinnacle_api::{
+        output::OutputSetup,
+        window::{
+            rules::{WindowRule, WindowRuleCondition},
+            FullscreenOrMaximized,
+        },
+    };
+    use smithay::utils::Rectangle;
+
+    use super::*;
+
+    #[tokio::main]
+    #[self::test]
+    async fn close() -> anyhow::Result<()> {
+        if !test_client_available() {
+            eprintln!("skipping: `{TEST_CLIENT}` is not installed");
+            return Ok(());
+        }
+
+        test_api(|sender| {
+            run_rust(|api| {
+                api.process.spawn([TEST_CLIENT]);
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert_eq!(state.pinnacle.windows.len(), 1);
+            });
+
+            run_rust(|api| {
+                api.window.get_all()[0].close();
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert_eq!(state.pinnacle.windows.len(), 0);
+            });
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_geometry_floats_tiled_window() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let win = &api.window.get_all()[0];
+                // Rejected
+                win.set_geometry(0, 0, 0, 300);
+                assert_eq!(win.floating(), Some(false));
+
+                win.set_geometry(100, 200, 400, 300);
+                assert_eq!(win.floating(), Some(true));
+            })?;
+
+            with_state(&sender, |state| {
+                let win = &state.pinnacle.windows[0];
+                let FloatingOrTiled::Floating(rect) =
+                    win.with_state(|state| state.floating_or_tiled)
+                else {
+                    panic!("window wasn't floating");
+                };
+                assert_eq!(rect, Rectangle::from_loc_and_size((100, 200), (400, 300)));
+            });
+
+            run_rust(|api| {
+                api.window.get_all()[0].close();
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn toggle_fullscreen_restores_floating_rect() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let win = &api.window.get_all()[0];
+                win.set_geometry(100, 200, 400, 300);
+
+                win.toggle_fullscreen();
+                assert_eq!(
+                    win.fullscreen_or_maximized(),
+                    Some(FullscreenOrMaximized::Fullscreen)
+                );
+
+                win.set_maximized(true);
+                assert_eq!(
+                    win.fullscreen_or_maximized(),
+                    Some(FullscreenOrMaximized::Maximized)
+                );
+
+                win.toggle_maximized();
+                assert_eq!(
+                    win.fullscreen_or_maximized(),
+                    Some(FullscreenOrMaximized::Neither)
+                );
+                assert_eq!(win.floating(), Some(true));
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let win = &state.pinnacle.windows[0];
+                assert_eq!(
+                    state
+                        .pinnacle
+                        .space
+                        .element_geometry(win)
+                        .map(|geo| geo.loc),
+                    Some((100, 200).into())
+                );
+            });
+
+            run_rust(|api| {
+                api.window.get_all()[0].close();
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn scratchpad() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let win = &api.window.get_all()[0];
+                win.toggle_scratchpad();
+                assert_eq!(win.scratchpad(), Some(true));
+                assert!(win.tags().is_empty());
+
+                api.window.toggle_scratchpad_visible();
+                assert_eq!(win.tags().len(), 1);
+                assert_eq!(win.floating(), Some(true));
+            })?;
+
+            with_state(&sender, |state| {
+                let win = &state.pinnacle.windows[0];
+                let FloatingOrTiled::Floating(rect) =
+                    win.with_state(|state| state.floating_or_tiled)
+                else {
+                    panic!("window wasn't floating");
+                };
+                let output_geo = state
+                    .pinnacle
+                    .space
+                    .output_geometry(state.pinnacle.focused_output().unwrap())
+                    .unwrap();
+                assert!((rect.loc.x * 2 + rect.size.w - output_geo.size.w).abs() <= 1);
+                assert!((rect.loc.y * 2 + rect.size.h - output_geo.size.h).abs() <= 1);
+            });
+
+            run_rust(|api| {
+                let win = &api.window.get_all()[0];
+                api.window.toggle_scratchpad_visible();
+                assert!(win.tags().is_empty());
+
+                // Removing a hidden window from the scratchpad brings it back
+                win.toggle_scratchpad();
+                assert_eq!(win.scratchpad(), Some(false));
+                assert_eq!(win.tags().len(), 1);
+
+                win.close();
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_minimized() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                assert_eq!(wins.len(), 2);
+
+                wins[1].set_focused(true);
+                wins[1].set_minimized(true);
+                assert_eq!(wins[1].minimized(), Some(true));
+                assert_eq!(wins[0].focused(), Some(true));
+                assert_eq!(api.window.get_all().len(), 2);
+            })?;
+
+            with_state(&sender, |state| {
+                let output = state.pinnacle.focused_output().unwrap();
+                let win = &state.pinnacle.windows[1];
+                assert!(!win.is_on_active_tag());
+                assert!(!win.is_on_active_tag_on_output(output));
+            });
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                wins[1].set_minimized(false);
+                assert_eq!(wins[1].minimized(), Some(false));
+                assert_eq!(wins[1].focused(), Some(true));
+
+                for win in wins {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn cycle_focus() -> anyhow::Result<()> {
+        test_api(|_sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1", "2"]);
+                tags[0].set_active(true);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                assert_eq!(wins.len(), 2);
+
+                let focused = api.window.get_focused().unwrap();
+                let other = wins.iter().find(|win| **win != focused).unwrap();
+
+                api.window.cycle_focus_next(false);
+                assert_eq!(api.window.get_focused().as_ref(), Some(other));
+                api.window.cycle_focus_next(false);
+                assert_eq!(api.window.get_focused(), Some(focused.clone()));
+                api.window.cycle_focus_prev(false);
+                assert_eq!(api.window.get_focused().as_ref(), Some(other));
+
+                // Windows on inactive tags are only cycled to with `all_tags`
+                other.move_to_tag(&api.tag.get("2").unwrap());
+                api.window.cycle_focus_next(false);
+                assert_eq!(api.window.get_focused(), Some(focused.clone()));
+
+                api.window.cycle_focus_next(true);
+                assert_eq!(api.window.get_focused().as_ref(), Some(other));
+                assert_eq!(api.tag.get("2").unwrap().active(), Some(true));
+
+                for win in wins {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn raise_and_lower() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+
+                api.process.spawn(["foot"]);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                for win in api.window.get_all() {
+                    win.set_floating(true);
+                    win.set_geometry(100, 100, 400, 400);
+                }
+            })?;
+
+            sleep_secs(1);
+
+            let assert_top = |id: u32| {
+                with_state(&sender, move |state| {
+                    let top = state.pinnacle.space.elements().last().unwrap();
+                    assert_eq!(top.with_state(|state| state.id), WindowId(id));
+                });
+            };
+
+            // The last spawned window was raised when it was mapped
+            assert_top(1);
+
+            run_rust(|api| api.window.get_all()[0].raise())?;
+            assert_top(0);
+
+            run_rust(|api| api.window.get_all()[0].lower())?;
+            assert_top(1);
+
+            run_rust(|api| {
+                for win in api.window.get_all() {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn focus_follows_mouse_delay() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+
+                api.input.set_focus_follows_mouse(true);
+                api.input.set_focus_follows_mouse_delay(500);
+
+                api.process.spawn(["foot"]);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                assert_eq!(wins.len(), 2);
+
+                for (i, win) in wins.iter().enumerate() {
+                    win.set_floating(true);
+                    win.set_geometry(i as i32 * 800, 0, 400, 400);
+                }
+            })?;
+
+            sleep_secs(1);
+
+            // The last window spawned is focused, so move over the first one
+            with_state(&sender, |state| {
+                state.inject_pointer_motion((200.0, 200.0).into());
+            });
+
+            run_rust(|api| {
+                // Focus doesn't move before the delay passes
+                let wins = api.window.get_all();
+                assert_eq!(api.window.get_focused().as_ref(), Some(&wins[1]));
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                assert_eq!(api.window.get_focused().as_ref(), Some(&wins[0]));
+            })?;
+
+            // Leaving the window before the delay passes cancels the focus change
+            with_state(&sender, |state| {
+                state.inject_pointer_motion((1000.0, 200.0).into());
+                state.inject_pointer_motion((600.0, 800.0).into());
+            });
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                assert_eq!(api.window.get_focused().as_ref(), Some(&wins[0]));
+            })?;
+
+            run_rust(|api| {
+                for win in api.window.get_all() {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn focus_follows_mouse_debounce_defers_trailing_change() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+
+                api.input.set_focus_follows_mouse(true);
+
+                api.process.spawn(["foot"]);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                assert_eq!(wins.len(), 2);
+
+                for (i, win) in wins.iter().enumerate() {
+                    win.set_floating(true);
+                    win.set_geometry(i as i32 * 800, 0, 400, 400);
+                }
+            })?;
+
+            sleep_secs(1);
+
+            // The second move lands within the debounce of the first focus change
+            with_state(&sender, |state| {
+                state.inject_pointer_motion((200.0, 200.0).into());
+                state.inject_pointer_motion((1000.0, 200.0).into());
+            });
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let wins = api.window.get_all();
+                assert_eq!(api.window.get_focused().as_ref(), Some(&wins[1]));
+            })?;
+
+            run_rust(|api| {
+                for win in api.window.get_all() {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn window_rule_class_regex_places_window() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Second", (1280, 720).into());
+            });
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let second = api.output.get_by_name("Second").unwrap();
+                let tags = api.tag.add(&second, ["1"]);
+                tags[0].set_active(true);
+
+                api.window.add_window_rule(
+                    WindowRuleCondition::new().class_regexes(["^fo+t$"]),
+                    WindowRule::new()
+                        .output(&second)
+                        .floating(true)
+                        .x(100)
+                        .y(50)
+                        .width(500)
+                        .height(300),
+                );
+
+                // Invalid regexes are rejected
+                api.window.add_window_rule(
+                    WindowRuleCondition::new().title_regexes(["("]),
+                    WindowRule::new().floating(true),
+                );
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert_eq!(state.pinnacle.config.window_rules.len(), 1);
+            });
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let win = &state.pinnacle.windows[0];
+                assert_eq!(
+                    win.output(&state.pinnacle),
+                    Some(output_for_name(state, "Second"))
+                );
+
+                let FloatingOrTiled::Floating(rect) =
+                    win.with_state(|state| state.floating_or_tiled)
+                else {
+                    panic!("window wasn't floating");
+                };
+                assert_eq!(rect, Rectangle::from_loc_and_size((100, 50), (500, 300)));
+            });
+
+            run_rust(|api| {
+                api.window.get_all()[0].close();
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn keep_floating_in_bounds() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+                api.window.keep_floating_in_bounds(true, 50);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.window.get_all()[0].set_geometry(5000, -5000, 400, 300);
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let win = &state.pinnacle.windows[0];
+                let FloatingOrTiled::Floating(rect) =
+                    win.with_state(|state| state.floating_or_tiled)
+                else {
+                    panic!("window wasn't floating");
+                };
+                assert_eq!(
+                    rect,
+                    Rectangle::from_loc_and_size((1920 - 50, 50 - 300), (400, 300))
+                );
+            });
+
+            run_rust(|api| {
+                api.window.get_all()[0].close();
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn focus_switches_to_hidden_window() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1", "2"]);
+                tags[0].set_active(true);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.tag.get("2").unwrap().switch_to();
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let tag_1 = api.tag.get("1").unwrap();
+                let hidden = api
+                    .window
+                    .get_all()
+                    .into_iter()
+                    .find(|win| win.tags().contains(&tag_1))
+                    .unwrap();
+                hidden.focus();
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let output = state.pinnacle.focused_output().unwrap().clone();
+                let first = state.pinnacle.windows[0].clone();
+                assert!(first.is_on_active_tag());
+                assert_eq!(state.pinnacle.focused_window(&output), Some(first));
+            });
+
+            run_rust(|api| {
+                for win in api.window.get_all() {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn focus_if_visible_ignores_hidden_window() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1", "2"]);
+                tags[0].set_active(true);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.tag.get("2").unwrap().switch_to();
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let tag_1 = api.tag.get("1").unwrap();
+                let hidden = api
+                    .window
+                    .get_all()
+                    .into_iter()
+                    .find(|win| win.tags().contains(&tag_1))
+                    .unwrap();
+                hidden.focus_if_visible();
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let output = state.pinnacle.focused_output().unwrap().clone();
+                let first = state.pinnacle.windows[0].clone();
+                let second = state.pinnacle.windows[1].clone();
+                assert!(!first.is_on_active_tag());
+                assert_eq!(state.pinnacle.focused_window(&output), Some(second));
+            });
+
+            run_rust(|api| {
+                for win in api.window.get_all() {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn move_to_output() -> anyhow::Result<()> {
+        test_api(|sender| {
+            setup_rust(|api| {
+                api.output
+                    .setup([OutputSetup::new_with_matcher(|_| true).with_tags(["1"])]);
+            });
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                state.pinnacle.new_output("Second", (300, 200).into());
+            });
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let second = api.output.get_by_name("Second").unwrap();
+                api.window.get_all()[0].move_to_output(&second);
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let win = &state.pinnacle.windows[0];
+                assert_eq!(
+                    win.output(&state.pinnacle).map(|op| op.name()),
+                    Some("Second".to_string())
+                );
+            });
+
+            run_rust(|api| {
+                api.window.get_all()[0].close();
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn get_all_with_props() -> anyhow::Result<()> {
+        test_api(|_sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                let windows = api.window.get_all_with_props();
+                assert_eq!(windows.len(), 1);
+
+                let (win, props) = &windows[0];
+                assert_eq!(props.is_x11_override_redirect, Some(false));
+                assert_eq!(props.tags.len(), 1);
+                assert_eq!(props.title, win.title());
+
+                win.close();
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+}
+
+mod input {
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    use pinnacle::input::synthetic::INPUT_INJECTION_ENV;
+    use pinnacle_api::input::{KeybindSequenceOptions, Mod};
+
+    use super::*;
+
+    #[tokio::main]
+    #[self::test]
+    async fn inject_input() -> anyhow::Result<()> {
+        static KEYBIND_RAN: AtomicBool = AtomicBool::new(false);
+
+        temp_env::with_var(INPUT_INJECTION_ENV, Some("1"), || {
+            test_api(|sender| {
+                setup_rust(|api| {
+                    api.input.keybind([] as [Mod; 0], 'a', || {
+                        KEYBIND_RAN.store(true, Ordering::SeqCst);
+                    });
+                });
+
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    // KEY_A
+                    api.input.inject_key(30, true);
+                    api.input.inject_key(30, false);
+                    api.input.inject_pointer_motion(500.0, 300.0);
+                })?;
+
+                sleep_secs(1);
+
+                assert!(KEYBIND_RAN.load(Ordering::SeqCst));
+
+                with_state(&sender, |state| {
+                    let pointer = state.pinnacle.seat.get_pointer().unwrap();
+                    assert_eq!(pointer.current_location(), (500.0, 300.0).into());
+                });
+
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn keyboard_grab_suppresses_keybinds() -> anyhow::Result<()> {
+        use pinnacle::handlers::session_lock::LockState;
+
+        static KEYBIND_RAN: AtomicU32 = AtomicU32::new(0);
+
+        // KEY_A
+        const A: u32 = 30;
+
+        temp_env::with_var(INPUT_INJECTION_ENV, Some("1"), || {
+            test_api(|sender| {
+                setup_rust(|api| {
+                    api.input.keybind([] as [Mod; 0], 'a', || {
+                        KEYBIND_RAN.fetch_add(1, Ordering::SeqCst);
+                    });
+                });
+
+                sleep_secs(1);
+
+                // A session lock grabs the keyboard the same way an active
+                // keyboard shortcuts inhibitor does
+                with_state(&sender, |state| {
+                    state.pinnacle.lock_state = LockState::Locked;
+                });
+
+                run_rust(|api| {
+                    api.input.inject_key(A, true);
+                    api.input.inject_key(A, false);
+                })?;
+
+                sleep_secs(1);
+
+                assert_eq!(KEYBIND_RAN.load(Ordering::SeqCst), 0);
+
+                // Releasing the grab restores keybinds
+                with_state(&sender, |state| {
+                    state.pinnacle.lock_state = LockState::Unlocked;
+                });
+
+                run_rust(|api| {
+                    api.input.inject_key(A, true);
+                    api.input.inject_key(A, false);
+                })?;
+
+                sleep_secs(1);
+
+                assert_eq!(KEYBIND_RAN.load(Ordering::SeqCst), 1);
+
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn inject_input_disabled_by_default() -> anyhow::Result<()> {
+        temp_env::with_var_unset(INPUT_INJECTION_ENV, || {
+            test_api(|sender| {
+                run_rust(|api| {
+                    api.input.inject_pointer_motion(500.0, 300.0);
+                })?;
+
+                with_state(&sender, |state| {
+                    let pointer = state.pinnacle.seat.get_pointer().unwrap();
+                    assert_ne!(pointer.current_location(), (500.0, 300.0).into());
+                });
+
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn pointer_prediction_resets_when_pointer_stops() -> anyhow::Result<()> {
+        temp_env::with_var(INPUT_INJECTION_ENV, Some("1"), || {
+            test_api(|sender| {
+                run_rust(|api| {
+                    api.input.set_pointer_prediction(true);
+                    api.input.inject_pointer_motion(100.0, 100.0);
+                    api.input.inject_pointer_motion(110.0, 100.0);
+                })?;
+
+                sleep_secs(1);
+
+                with_state(&sender, |state| {
+                    assert!(!state.pinnacle.input_state.pointer_prediction.is_moving());
+                    assert_eq!(
+                        state.pinnacle.cursor_render_location(),
+                        (110.0, 100.0).into()
+                    );
+                });
+
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_env_cannot_enable_input_injection() -> anyhow::Result<()> {
+        temp_env::with_var_unset(INPUT_INJECTION_ENV, || {
+            test_api(|sender| {
+                run_rust(|api| {
+                    api.process.set_env(INPUT_INJECTION_ENV, "1");
+                    api.input.inject_pointer_motion(500.0, 300.0);
+                })?;
+
+                with_state(&sender, |state| {
+                    assert!(!state.pinnacle.input_state.injection_enabled);
+                    let pointer = state.pinnacle.seat.get_pointer().unwrap();
+                    assert_ne!(pointer.current_location(), (500.0, 300.0).into());
+                });
+
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn keybind_sequence() -> anyhow::Result<()> {
+        static SEQUENCE_RAN: AtomicU32 = AtomicU32::new(0);
+
+        // KEY_LEFTMETA, KEY_W, KEY_H
+        const SUPER: u32 = 125;
+        const W: u32 = 17;
+        const H: u32 = 35;
+
+        temp_env::with_var(INPUT_INJECTION_ENV, Some("1"), || {
+            test_api(|_sender| {
+                setup_rust(|api| {
+                    api.input.keybind_sequence_with(
+                        [(vec![Mod::Super], 'w'), (vec![], 'h')],
+                        KeybindSequenceOptions {
+                            timeout_ms: Some(500),
+                            ..Default::default()
+                        },
+                        || {
+                            SEQUENCE_RAN.fetch_add(1, Ordering::SeqCst);
+                        },
+                    );
+                });
+
+                sleep_secs(1);
+
+                fn press_start(api: &ApiModules) {
+                    api.input.inject_key(SUPER, true);
+                    api.input.inject_key(W, true);
+                    api.input.inject_key(W, false);
+                    api.input.inject_key(SUPER, false);
+                }
+
+                run_rust(|api| {
+                    press_start(&api);
+                    api.input.inject_key(H, true);
+                    api.input.inject_key(H, false);
+                })?;
+
+                sleep_secs(1);
+
+                assert_eq!(SEQUENCE_RAN.load(Ordering::SeqCst), 1);
+
+                run_rust(|api| press_start(&api))?;
+
+                // Let the sequence time out
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    api.input.inject_key(H, true);
+                    api.input.inject_key(H, false);
+                })?;
+
+                sleep_secs(1);
+
+                assert_eq!(SEQUENCE_RAN.load(Ordering::SeqCst), 1);
+
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn clear_binds() -> anyhow::Result<()> {
+        static OLD_BIND_RAN: AtomicU32 = AtomicU32::new(0);
+        static NEW_BIND_RAN: AtomicU32 = AtomicU32::new(0);
+
+        // KEY_LEFTMETA, KEY_W
+        const SUPER: u32 = 125;
+        const W: u32 = 17;
+
+        temp_env::with_var(INPUT_INJECTION_ENV, Some("1"), || {
+            test_api(|sender| {
+                setup_rust(|api| {
+                    api.input.keybind([Mod::Super], 'w', || {
+                        OLD_BIND_RAN.fetch_add(1, Ordering::SeqCst);
+                    });
+                });
+
+                sleep_secs(1);
+
+                run_rust(|api| api.input.clear_binds())?;
+
+                with_state(&sender, |state| {
+                    assert!(state.pinnacle.input_state.keybinds.is_empty());
+                });
+
+                setup_rust(|api| {
+                    api.input.keybind([Mod::Super], 'w', || {
+                        NEW_BIND_RAN.fetch_add(1, Ordering::SeqCst);
+                    });
+                });
+
+                sleep_secs(1);
+
+                run_rust(|api| {
+                    api.input.inject_key(SUPER, true);
+                    api.input.inject_key(W, true);
+                    api.input.inject_key(W, false);
+                    api.input.inject_key(SUPER, false);
+                })?;
+
+                sleep_secs(1);
+
+                assert_eq!(OLD_BIND_RAN.load(Ordering::SeqCst), 0);
+                assert_eq!(NEW_BIND_RAN.load(Ordering::SeqCst), 1);
+
+                Ok(())
+            })
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn hide_cursor_after() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                api.input.set_hide_cursor_after(200);
+            })?;
+
+            with_state(&sender, |state| {
+                assert!(!state.pinnacle.cursor_hidden());
+            });
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert!(state.pinnacle.cursor_hidden());
+            });
+
+            run_rust(|api| {
+                api.input.set_hide_cursor_after(None);
+            })?;
+
+            with_state(&sender, |state| {
+                assert!(!state.pinnacle.cursor_hidden());
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn hide_cursor_after_restarts_running_timer() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                api.input.set_hide_cursor_after(10000);
+                api.input.set_hide_cursor_after(200);
+            })?;
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                assert!(state.pinnacle.cursor_hidden());
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn map_tablet() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let op = api.output.get_focused().unwrap();
+                api.input.map_tablet(
+                    None,
+                    &op,
+                    Some(pinnacle_api::util::Geometry {
+                        x: 100,
+                        y: 50,
+                        width: 800,
+                        height: 600,
+                    }),
+                );
+            })?;
+
+            with_state(&sender, |state| {
+                let mapping = state
+                    .pinnacle
+                    .input_state
+                    .tablet_mappings
+                    .get(&None)
+                    .unwrap();
+                assert_eq!(mapping.output_name.0, DUMMY_OUTPUT_NAME);
+                assert_eq!(
+                    mapping.region,
+                    Some(smithay::utils::Rectangle::from_loc_and_size(
+                        (100, 50),
+                        (800, 600)
+                    ))
+                );
+            });
+
+            run_rust(|api| {
+                api.input.unmap_tablet(None);
+            })?;
+
+            with_state(&sender, |state| {
+                assert!(state.pinnacle.input_state.tablet_mappings.is_empty());
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_repeat_rate() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                api.input.set_repeat_rate(30, 250);
+                assert_eq!(api.input.repeat_rate(), (30, 250));
+
+                // A rate of 0 disables repeat
+                api.input.set_repeat_rate(0, 250);
+                assert_eq!(api.input.repeat_rate(), (0, 250));
+            })?;
+
+            with_state(&sender, |state| {
+                let kb = state.pinnacle.seat.get_keyboard().unwrap();
+                assert_eq!(kb.repeat_rate(), 0);
+                assert_eq!(kb.repeat_delay(), 250);
+            });
+
+            Ok(())
+        })
+    }
+}
+
+mod tag {
+    use pinnacle::{layout::Gaps, state::WithState, window::window_state::WindowId};
+
+    use super::*;
+
+    #[tokio::main]
+    #[self::test]
+    async fn switch_to_next_occupied() -> anyhow::Result<()> {
+        test_api(|_sender| {
+            run_rust(|api| {
+                let tags = api
+                    .tag
+                    .add(&api.output.get_focused().unwrap(), ["1", "2", "3"]);
+                tags[0].set_active(true);
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                // No other tag has windows
+                api.tag.switch_to_next_occupied(None);
+                assert_eq!(api.tag.get("1").unwrap().active(), Some(true));
+
+                api.tag.get("3").unwrap().switch_to();
+                api.process.spawn(["foot"]);
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                api.tag.get("1").unwrap().switch_to();
+                api.tag.switch_to_next_occupied(None);
+                assert_eq!(api.tag.get("3").unwrap().active(), Some(true));
+
+                api.tag.switch_to_prev_occupied(None);
+                assert_eq!(api.tag.get("1").unwrap().active(), Some(true));
+
+                for win in api.window.get_all() {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_gaps() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                assert_eq!(tags[0].gaps(), Some((0, 0)));
+
+                tags[0].set_gaps(8, 16);
+            })?;
+
+            with_state(&sender, |state| {
+                let tag = state
+                    .pinnacle
+                    .space
+                    .outputs()
+                    .flat_map(|op| op.with_state(|state| state.tags.clone()))
+                    .next()
+                    .unwrap();
+                assert_eq!(
+                    tag.gaps(),
+                    Gaps {
+                        inner: 8,
+                        outer: 16
+                    }
+                );
+            });
+
+            run_rust(|api| {
+                assert_eq!(api.tag.get("1").unwrap().gaps(), Some((8, 16)));
+            })?;
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_master_factor() -> anyhow::Result<()> {
+        test_api(|sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                assert_eq!(tags[0].master_factor(), None);
+
+                tags[0].adjust_master_factor(0.1);
+                assert!((tags[0].master_factor().unwrap() - 0.6).abs() < 0.001);
+
+                tags[0].set_master_factor(1.5);
+                assert_eq!(tags[0].master_factor(), Some(0.9));
+
+                tags[0].adjust_master_factor(-0.2);
+
+                // Non-finite factors are rejected
+                tags[0].set_master_factor(f32::NAN);
+                tags[0].adjust_master_factor(f32::INFINITY);
+            })?;
+
+            with_state(&sender, |state| {
+                let tag = state
+                    .pinnacle
+                    .space
+                    .outputs()
+                    .flat_map(|op| op.with_state(|state| state.tags.clone()))
+                    .next()
+                    .unwrap();
+                assert!((tag.master_factor().unwrap() - 0.7).abs() < 0.001);
+            });
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn set_switch_animation() -> anyhow::Result<()> {
+        test_api(|sender| {
+            with_state(&sender, |state| {
+                assert!(!state.pinnacle.config.tag_switch_animation);
+            });
+
+            run_rust(|api| {
+                api.tag.set_switch_animation(true);
+                api.tag.set_switch_animation_duration(150);
+
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1", "2"]);
+                tags[0].switch_to();
+                tags[1].switch_to();
+            })?;
+
+            with_state(&sender, |state| {
+                assert!(state.pinnacle.config.tag_switch_animation);
+                assert_eq!(
+                    state.pinnacle.config.tag_switch_animation_duration,
+                    std::time::Duration::from_millis(150)
+                );
+            });
+
+            Ok(())
+        })
+    }
+}
+
+mod process {
+    use pinnacle::window::window_state::WindowId;
+    use pinnacle_api::process::SpawnCallbacks;
+
+    use super::*;
+
+    #[tokio::main]
+    #[self::test]
+    async fn spawn_once() -> anyhow::Result<()> {
+        test_api(|_sender| {
+            run_rust(|api| {
+                api.tag.add(&api.output.get_focused().unwrap(), ["1"])[0].set_active(true);
+
+                assert!(api.process.spawn_once(["foot"]));
+                assert!(!api.process.spawn_once(["foot"]));
+                // Only the exact command line is deduplicated
+                assert!(api.process.spawn_once(["foot", "--title", "other"]));
+            })?;
+
+            sleep_secs(1);
+
+            run_rust(|api| {
+                for win in api.window.get_all() {
+                    win.close();
+                }
+            })?;
+
+            WindowId::reset();
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn spawn_with_callbacks() -> anyhow::Result<()> {
+        test_api(|_sender| {
+            let (send, recv) = std::sync::mpsc::channel::<String>();
+
+            setup_rust(move |api| {
+                let stdout = send.clone();
+                let stderr = send.clone();
+                let exit = send;
+
+                api.process.spawn_with_callbacks(
+                    ["sh", "-c", "echo out; echo err >&2; printf partial; exit 3"],
+                    SpawnCallbacks {
+                        stdout: Some(Box::new(move |line| {
+                            stdout.send(format!("stdout: {line}")).unwrap()
+                        })),
+                        stderr: Some(Box::new(move |line| {
+                            stderr.send(format!("stderr: {line}")).unwrap()
+                        })),
+                        exit: Some(Box::new(move |code, _msg| {
+                            exit.send(format!("exit: {code:?}")).unwrap()
+                        })),
+                    },
+                );
+            });
+
+            let mut messages = Vec::new();
+            while let Ok(msg) = recv.recv_timeout(std::time::Duration::from_secs(5)) {
+                let done = msg.starts_with("exit");
+                messages.push(msg);
+                if done {
+                    break;
+                }
+            }
+
+            assert_eq!(messages.last().map(String::as_str), Some("exit: Some(3)"));
+            assert!(messages.contains(&"stdout: out".to_string()));
+            assert!(messages.contains(&"stdout: partial".to_string()));
+            assert!(messages.contains(&"stderr: err".to_string()));
+
+            Ok(())
+        })
+    }
+}
+
+mod layout {
+    use pinnacle_api::layout::{CyclingLayoutManager, DwindleLayout, MasterStackLayout};
+
+    use super::*;
+
+    #[tokio::main]
+    #[self::test]
+    async fn cycling_layout_manager_follows_tag_layout() -> anyhow::Result<()> {
+        test_api(|_sender| {
+            run_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                let tag = &tags[0];
+
+                let mut manager = CyclingLayoutManager::new([
+                    Box::<MasterStackLayout>::default() as _,
+                    Box::<DwindleLayout>::default() as _,
+                ]);
+
+                manager.cycle_layout_forward(tag);
+                assert_eq!(tag.layout().as_deref(), Some("dwindle"));
+
+                // Changing the layout outside of the manager doesn't desync it
+                tag.set_layout(Some("master_stack"));
+                manager.cycle_layout_forward(tag);
+                assert_eq!(tag.layout().as_deref(), Some("dwindle"));
+
+                manager.cycle_layout_backward(tag);
+                assert_eq!(tag.layout().as_deref(), Some("master_stack"));
+            })?;
+
+            Ok(())
+        })
+    }
+
+    #[tokio::main]
+    #[self::test]
+    async fn adjust_master_factor_starts_from_layout_factor() -> anyhow::Result<()> {
+        test_api(|sender| {
+            setup_rust(|api| {
+                let tags = api.tag.add(&api.output.get_focused().unwrap(), ["1"]);
+                tags[0].set_active(true);
+
+                let requester =
+                    api.layout.set_manager(CyclingLayoutManager::new([
+                        Box::new(MasterStackLayout {
+                            master_factor: 0.6,
+                            ..Default::default()
+                        }) as _,
+                    ]));
+                requester.request_layout();
+            });
+
+            sleep_secs(1);
+
+            with_state(&sender, |state| {
+                let output = output_for_name(state, DUMMY_OUTPUT_NAME);
+                let tag = output.with_state(|state| state.focused_tags().next().cloned());
+                assert_eq!(tag.unwrap().layout_master_factor(), Some(0.6));
+            });
+
+            run_rust(|api| {
+                let tag = api.tag.get("1").unwrap();
+                tag.adjust_master_factor(0.05);
+                let master_factor = tag.master_factor().unwrap();
+                assert!((master_factor - 0.65).abs() < f32::EPSILON * 4.0);
+            })?;
+
+            Ok(())
+        })
+    }
+}